@@ -59,15 +59,18 @@ impl ProcessedEvent {
                         ProcessedEvent::Nothing
                     }
                 }
-                SimulatorEvent::KeyDown { keycode, .. } if keycode == Keycode::H => {
-                    ProcessedEvent::NextHorizontal
-                }
-                SimulatorEvent::KeyDown { keycode, .. } if keycode == Keycode::V => {
-                    ProcessedEvent::NextVertical
-                }
-                SimulatorEvent::KeyDown { keycode, .. } if keycode == Keycode::M => {
-                    ProcessedEvent::NextMode
-                }
+                SimulatorEvent::KeyDown {
+                    keycode: Keycode::H,
+                    ..
+                } => ProcessedEvent::NextHorizontal,
+                SimulatorEvent::KeyDown {
+                    keycode: Keycode::V,
+                    ..
+                } => ProcessedEvent::NextVertical,
+                SimulatorEvent::KeyDown {
+                    keycode: Keycode::M,
+                    ..
+                } => ProcessedEvent::NextMode,
                 SimulatorEvent::Quit => ProcessedEvent::Quit,
                 _ => ProcessedEvent::Nothing,
             }
@@ -165,7 +168,8 @@ fn main() -> Result<(), Infallible> {
                         HeightMode::Exact(VerticalOverdraw::Visible) => {
                             HeightMode::Exact(VerticalOverdraw::Hidden)
                         }
-                        HeightMode::Exact(VerticalOverdraw::Hidden) => {
+                        HeightMode::Exact(VerticalOverdraw::Hidden)
+                        | HeightMode::Exact(VerticalOverdraw::Masked(_)) => {
                             HeightMode::ShrinkToText(VerticalOverdraw::FullRowsOnly)
                         }
                         HeightMode::ShrinkToText(VerticalOverdraw::FullRowsOnly) => {
@@ -174,8 +178,17 @@ fn main() -> Result<(), Infallible> {
                         HeightMode::ShrinkToText(VerticalOverdraw::Visible) => {
                             HeightMode::ShrinkToText(VerticalOverdraw::Hidden)
                         }
-                        HeightMode::ShrinkToText(VerticalOverdraw::Hidden) => HeightMode::FitToText,
-                        HeightMode::FitToText => HeightMode::Exact(VerticalOverdraw::FullRowsOnly),
+                        HeightMode::ShrinkToText(VerticalOverdraw::Hidden)
+                        | HeightMode::ShrinkToText(VerticalOverdraw::Masked(_)) => {
+                            HeightMode::FitToText
+                        }
+                        HeightMode::FitToText => HeightMode::FitToTextLimited {
+                            max_height: 100,
+                            overdraw: VerticalOverdraw::FullRowsOnly,
+                        },
+                        HeightMode::FitToTextLimited { .. } => {
+                            HeightMode::Exact(VerticalOverdraw::FullRowsOnly)
+                        }
                     }
                 }
                 ProcessedEvent::Quit => break 'demo,
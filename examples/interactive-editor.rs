@@ -15,18 +15,18 @@ use embedded_graphics::{
 use embedded_graphics_simulator::{
     BinaryColorTheme, OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
 };
-use embedded_text::{alignment::VerticalAlignment, plugin::Plugin, style::TextBoxStyle, TextBox};
+use embedded_text::{plugin::Plugin, TextBox};
 use sdl2::keyboard::{Keycode, Mod};
 use std::{collections::HashMap, convert::Infallible, thread, time::Duration};
 
 trait StrExt {
-    fn first_n_chars<'a>(&'a self, n: usize) -> &'a str;
+    fn first_n_chars(&self, n: usize) -> &str;
 }
 
 impl StrExt for str {
-    fn first_n_chars<'a>(&'a self, n: usize) -> &'a str {
+    fn first_n_chars(&self, n: usize) -> &str {
         if let Some((i, (idx, _))) = self.char_indices().enumerate().take(n + 1).last() {
-            if i < n as usize {
+            if i < n {
                 self
             } else {
                 &self[0..idx]
@@ -31,7 +31,7 @@ fn main() -> Result<(), Infallible> {
         Rectangle::new(Point::zero(), Size::new(64, 96)),
         character_style,
     )
-    .add_plugin(Tail)
+    .add_plugin(Tail::new())
     .draw(&mut display)?;
 
     TextBox::with_textbox_style(
@@ -43,7 +43,7 @@ fn main() -> Result<(), Infallible> {
             .height_mode(HeightMode::Exact(VerticalOverdraw::Hidden))
             .build(),
     )
-    .add_plugin(Tail)
+    .add_plugin(Tail::new())
     .draw(&mut display)?;
 
     // Set up the window and show the display's contents.
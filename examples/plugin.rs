@@ -16,18 +16,18 @@ use embedded_graphics_simulator::{
     BinaryColorTheme, OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
 };
 use embedded_text::{
-    alignment::{HorizontalAlignment, VerticalAlignment},
-    plugin::{tail::Tail, Plugin},
+    alignment::HorizontalAlignment,
+    plugin::Plugin,
     style::{HeightMode, TextBoxStyleBuilder, VerticalOverdraw},
     TextBox, Token,
 };
 
 trait StrExt {
-    fn first_n_chars<'a>(&'a self, n: u32) -> &'a str;
+    fn first_n_chars(&self, n: u32) -> &str;
 }
 
 impl StrExt for str {
-    fn first_n_chars<'a>(&'a self, n: u32) -> &'a str {
+    fn first_n_chars(&self, n: u32) -> &str {
         if let Some((i, (idx, _))) = self.char_indices().enumerate().take(n as usize + 1).last() {
             if i < n as usize {
                 self
@@ -0,0 +1,216 @@
+//! Draw target adapters.
+//!
+//! Framework code often fixes the error type of the draw targets it works with. The adapters in
+//! this module help drawing text boxes into such systems without having to convert errors by hand.
+use core::{convert::Infallible, marker::PhantomData};
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Dimensions,
+    prelude::{Pixel, PixelColor},
+    primitives::Rectangle,
+    Drawable,
+};
+
+/// Draw target adapter that converts errors of the wrapped draw target.
+///
+/// This struct is created by [`DrawTargetErrorExt::map_err`].
+///
+/// [`DrawTargetErrorExt::map_err`]: trait.DrawTargetErrorExt.html#method.map_err
+#[derive(Debug)]
+pub struct MapErr<'d, D, E> {
+    target: &'d mut D,
+    _marker: PhantomData<E>,
+}
+
+impl<'d, D, E> Dimensions for MapErr<'d, D, E>
+where
+    D: DrawTarget,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<'d, D, E> DrawTarget for MapErr<'d, D, E>
+where
+    D: DrawTarget,
+    E: From<D::Error>,
+{
+    type Color = D::Color;
+    type Error = E;
+
+    #[inline]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.target.draw_iter(pixels).map_err(E::from)
+    }
+
+    #[inline]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.target.fill_contiguous(area, colors).map_err(E::from)
+    }
+
+    #[inline]
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(area, color).map_err(E::from)
+    }
+
+    #[inline]
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color).map_err(E::from)
+    }
+}
+
+/// Extension trait to convert the error type of draw targets.
+pub trait DrawTargetErrorExt: DrawTarget + Sized {
+    /// Wraps the draw target so that its errors are converted into `E`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::convert::Infallible;
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{adapter::DrawTargetErrorExt, TextBox};
+    ///
+    /// #[derive(Debug)]
+    /// enum AppError {
+    ///     Display,
+    /// }
+    ///
+    /// impl From<Infallible> for AppError {
+    ///     fn from(e: Infallible) -> Self {
+    ///         match e {}
+    ///     }
+    /// }
+    ///
+    /// fn draw_ui<D>(display: &mut D) -> Result<(), AppError>
+    /// where
+    ///     D: DrawTarget<Color = BinaryColor, Error = AppError>,
+    /// {
+    ///     let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    ///     let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+    ///
+    ///     TextBox::new("Hello", bounds, character_style).draw(display)?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut display = MockDisplay::new();
+    /// draw_ui(&mut display.map_err()).unwrap();
+    /// ```
+    #[inline]
+    fn map_err<E>(&mut self) -> MapErr<'_, Self, E>
+    where
+        E: From<Self::Error>,
+    {
+        MapErr {
+            target: self,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<D> DrawTargetErrorExt for D where D: DrawTarget {}
+
+/// Extension trait to draw objects into draw targets that can't fail.
+pub trait DrawInfallible: Drawable {
+    /// Draws the object into a draw target whose error type is `Infallible`.
+    ///
+    /// Unlike `draw`, this method returns the output directly, without wrapping it in a `Result`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{adapter::DrawInfallible, TextBox};
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+    ///
+    /// let mut display = MockDisplay::new();
+    /// let remaining = TextBox::new("Hello", bounds, character_style).draw_infallible(&mut display);
+    ///
+    /// assert_eq!(remaining, "");
+    /// ```
+    #[inline]
+    fn draw_infallible<D>(&self, target: &mut D) -> Self::Output
+    where
+        D: DrawTarget<Color = Self::Color, Error = Infallible>,
+    {
+        match self.draw(target) {
+            Ok(output) => output,
+            Err(e) => match e {},
+        }
+    }
+}
+
+impl<T> DrawInfallible for T
+where
+    T: Drawable,
+    T::Color: PixelColor,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{adapter::DrawTargetErrorExt, TextBox};
+
+    #[derive(Debug, PartialEq)]
+    struct TestError;
+
+    impl From<Infallible> for TestError {
+        fn from(e: Infallible) -> Self {
+            match e {}
+        }
+    }
+
+    #[test]
+    fn map_err_draws_into_wrapped_target() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), Size::new(12, 9));
+
+        let mut display = MockDisplay::new();
+        let result: Result<&str, TestError> =
+            TextBox::new("ab", bounds, character_style).draw(&mut display.map_err());
+
+        assert_eq!(result, Ok(""));
+        display.assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+        ]);
+    }
+}
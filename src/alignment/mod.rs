@@ -26,28 +26,37 @@ pub enum HorizontalAlignment {
 }
 
 impl HorizontalAlignment {
+    /// Returns whether the placement of a line depends on its measurement.
+    pub(crate) fn needs_measurement(self) -> bool {
+        self != HorizontalAlignment::Left
+    }
+
     /// Calculate offset from the left side and whitespace information.
     pub(crate) fn place_line(
         self,
         renderer: &impl TextRenderer,
+        word_spacing: i32,
         measurement: LineMeasurement,
     ) -> (u32, SpaceConfig) {
-        match self {
+        let (left, space_config) = match self {
             HorizontalAlignment::Left => (0, SpaceConfig::new_from_renderer(renderer)),
-            HorizontalAlignment::Center => (
-                (measurement.max_line_width - measurement.width + 1) / 2,
-                SpaceConfig::new_from_renderer(renderer),
-            ),
+            HorizontalAlignment::Center => {
+                let space = measurement.max_line_width.saturating_sub(measurement.width);
+                (
+                    space / 2 + space % 2,
+                    SpaceConfig::new_from_renderer(renderer),
+                )
+            }
             HorizontalAlignment::Right => (
-                measurement.max_line_width - measurement.width,
+                measurement.max_line_width.saturating_sub(measurement.width),
                 SpaceConfig::new_from_renderer(renderer),
             ),
             HorizontalAlignment::Justified => {
                 let space_width = str_width(renderer, " ");
                 let space_count = measurement.space_count;
                 let space_info = if !measurement.last_line && space_count != 0 {
-                    let space =
-                        measurement.max_line_width - measurement.width + space_count * space_width;
+                    let space = (measurement.max_line_width + space_count * space_width)
+                        .saturating_sub(measurement.width);
                     let space_width = space / space_count;
                     let extra_pixels = space % space_count;
                     SpaceConfig::new(space_width, Some(extra_pixels))
@@ -56,7 +65,9 @@ impl HorizontalAlignment {
                 };
                 (0, space_info)
             }
-        }
+        };
+
+        (left, space_config.with_word_spacing(word_spacing))
     }
 }
 
@@ -80,6 +91,53 @@ pub enum VerticalAlignment {
 }
 
 impl VerticalAlignment {
+    /// Returns the vertical offset of the first line of text, relative to the top of the text box.
+    ///
+    /// This is the same calculation the text box uses to position its text. `text_height` is the
+    /// height of the text, as returned by [`TextBoxStyle::measure_text_height`], and `box_height`
+    /// is the height of the text box. The offset is negative if the text is taller than the text
+    /// box. Vertical overdraw settings don't change the position of the text, only which lines are
+    /// displayed.
+    ///
+    /// # Example
+    ///
+    /// Draw an icon next to the first line of vertically centered text:
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{alignment::VerticalAlignment, style::TextBoxStyleBuilder};
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .vertical_alignment(VerticalAlignment::Middle)
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::new(10, 0), Size::new(60, 40));
+    ///
+    /// let text_height = style.measure_text_height(&character_style, "Hello, World!", 60);
+    /// let offset = style
+    ///     .vertical_alignment
+    ///     .vertical_offset(text_height as i32, bounds.size.height as i32);
+    ///
+    /// let icon_position = Point::new(0, bounds.top_left.y + offset);
+    /// assert_eq!(icon_position, Point::new(0, 11));
+    /// ```
+    ///
+    /// [`TextBoxStyle::measure_text_height`]: ../style/struct.TextBoxStyle.html#method.measure_text_height
+    #[inline]
+    #[must_use]
+    pub const fn vertical_offset(self, text_height: i32, box_height: i32) -> i32 {
+        match self {
+            VerticalAlignment::Top => 0,
+            VerticalAlignment::Middle => (box_height - text_height) / 2,
+            VerticalAlignment::Bottom => box_height - text_height,
+        }
+    }
+
     /// Set the cursor's initial vertical position
     pub(crate) fn apply_vertical_alignment(
         self,
@@ -87,22 +145,6 @@ impl VerticalAlignment {
         text_height: i32,
         box_height: i32,
     ) {
-        match self {
-            VerticalAlignment::Top => {
-                // nothing to do here
-            }
-
-            VerticalAlignment::Middle => {
-                let offset = (box_height - text_height) / 2;
-
-                cursor.y += offset;
-            }
-
-            VerticalAlignment::Bottom => {
-                let offset = box_height - text_height;
-
-                cursor.y += offset;
-            }
-        }
+        cursor.y += self.vertical_offset(text_height, box_height);
     }
 }
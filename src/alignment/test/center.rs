@@ -184,3 +184,30 @@ fn soft_hyphen_centering() {
         ],
     );
 }
+
+#[test]
+fn line_with_many_words() {
+    let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    let text = "a a a a a a a a a a a a a a a a a a a a";
+
+    let mut display = MockDisplay::new();
+    TextBox::with_textbox_style(
+        text,
+        Rectangle::new(Point::zero(), size_for(&FONT_6X9, 41, 1)),
+        character_style,
+        TextBoxStyle::with_alignment(HorizontalAlignment::Center),
+    )
+    .draw(&mut display)
+    .unwrap();
+
+    let mut expected = MockDisplay::new();
+    TextBox::new(
+        text,
+        Rectangle::new(Point::new(6, 0), size_for(&FONT_6X9, 39, 1)),
+        character_style,
+    )
+    .draw(&mut expected)
+    .unwrap();
+
+    display.assert_eq(&expected);
+}
@@ -259,15 +259,15 @@ fn wrapped_soft_hyphen_rendering() {
             ".###....##....#......#..",
             "........................",
             "........................",
-            "........................",
-            ".......#................",
-            ".......#................",
-            ".......###...#..#..###..",
-            "#####..#..#..#..#..#..#.",
-            ".......#..#..#..#..#..#.",
-            ".......#..#...###..###..",
-            ".............#..#..#....",
-            "..............##...#....",
+            "..................      ",
+            ".#................      ",
+            ".#................      ",
+            ".###...#..#..###..      ",
+            ".#..#..#..#..#..#.      ",
+            ".#..#..#..#..#..#.      ",
+            ".#..#...###..###..      ",
+            ".......#..#..#....      ",
+            "........##...#....      ",
         ],
     );
 }
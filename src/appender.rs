@@ -0,0 +1,524 @@
+//! Incremental rendering of text that is appended to a text box.
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    alignment::{HorizontalAlignment, VerticalAlignment},
+    rendering::cursor::Cursor,
+    style::{HeightMode, TextBoxStyle},
+    TextBox, TextSource,
+};
+
+/// Draws text that is appended to previously drawn text, without redrawing it.
+///
+/// Log consoles and terminals usually only add text to the end of what they display. Drawing
+/// the whole log as a [`TextBox`] every time a message is added lays out and redraws all of the
+/// previous messages, which is slow on displays that are connected through slow buses, like SPI.
+/// `TextBoxAppender` keeps the position where the previous text ended, and the character style
+/// that was in effect there, and only draws the new text.
+///
+/// The appended text continues the last line of the previous text. Words that don't fit into
+/// the rest of that line are moved to the next line. Text starts at the top of the bounding box,
+/// and the bounding box is not changed by the height mode of the style. The appender only draws
+/// text: the background fill and the border of the style are not drawn, they are expected to be
+/// drawn once, before the first text is appended.
+///
+/// Text can only be appended to left aligned lines that start at the top, because the position
+/// of the previous lines depends on the whole text otherwise. Creating an appender with any other
+/// alignment panics in debug builds.
+///
+/// *Note:* text style changes that are not part of the character style, like the emulated ANSI
+/// intensity, are not carried over between appended pieces of text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{style::TextBoxStyle, TextBoxAppender};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(96, 18));
+///
+/// let mut console = TextBoxAppender::new(bounds, character_style, TextBoxStyle::default());
+///
+/// let mut display = MockDisplay::new();
+/// assert_eq!(console.append("Booting... ", &mut display).unwrap(), "");
+/// assert_eq!(console.append("done\n", &mut display).unwrap(), "");
+///
+/// // The text that doesn't fit into the bounding box is returned.
+/// assert_eq!(console.append("Ready\nIdle", &mut display).unwrap(), "Idle");
+/// ```
+///
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Clone, Debug)]
+pub struct TextBoxAppender<S> {
+    bounds: Rectangle,
+    character_style: S,
+    style: TextBoxStyle,
+
+    /// The position where the next text starts.
+    cursor: Cursor,
+
+    /// The character style at the end of the previous text.
+    current_style: S,
+}
+
+impl<S> TextBoxAppender<S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Creates a new appender that starts drawing text at the top left corner of `bounds`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the text is not left aligned, or is not aligned to the top.
+    #[inline]
+    pub fn new(bounds: Rectangle, character_style: S, mut style: TextBoxStyle) -> Self {
+        debug_assert!(
+            style.alignment == HorizontalAlignment::Left
+                && style.vertical_alignment == VerticalAlignment::Top,
+            "TextBoxAppender only supports left aligned text at the top of the bounding box"
+        );
+
+        style.height_mode = HeightMode::Exact(style.height_mode.overdraw());
+
+        let cursor = Self::start_cursor(bounds, &character_style, &style);
+
+        Self {
+            bounds,
+            current_style: character_style.clone(),
+            character_style,
+            style,
+            cursor,
+        }
+    }
+
+    /// Returns the cursor at the top left corner of `bounds`.
+    fn start_cursor(bounds: Rectangle, character_style: &S, style: &TextBoxStyle) -> Cursor {
+        let mut cursor = Cursor::new(
+            bounds,
+            character_style.line_height(),
+            style.line_height,
+            style.tab_size.into_pixels(character_style),
+        );
+        cursor.snap_to_grid(style.baseline_grid);
+
+        #[cfg(feature = "ansi")]
+        cursor.mark_first_line();
+
+        cursor
+    }
+
+    /// Returns the character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+
+    /// Returns the text box style.
+    #[inline]
+    pub fn style(&self) -> &TextBoxStyle {
+        &self.style
+    }
+
+    /// Draws `text` after the previously appended text.
+    ///
+    /// Returns the part of `text` that doesn't fit into the bounding box. Once the bounding box
+    /// is full, every further call returns the whole text, until the appender is [`reset`].
+    ///
+    /// [`reset`]: #method.reset
+    #[inline]
+    pub fn append<'t, D>(&mut self, text: &'t str, display: &mut D) -> Result<&'t str, D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+        S: 't,
+    {
+        let mut text_box = TextBox::with_textbox_style(
+            text,
+            self.bounds,
+            self.character_style.clone(),
+            self.style,
+        );
+        text_box.bounds = self.bounds;
+
+        if !self.cursor.at_line_start() && !text_box.fits_at(&self.cursor, &self.current_style) {
+            // The text is wrapped to the next line, where leading spaces are not displayed.
+            self.cursor.new_line();
+            text_box.text = text.trim_start_matches(' ');
+        }
+
+        text_box.draw_appended(display, &mut self.cursor, &mut self.current_style)
+    }
+
+    /// Draws the text read from `source` after the previously appended text.
+    ///
+    /// Returns the number of bytes of text that were drawn. Chunks are appended until the source
+    /// runs out of text, until the text doesn't fit into the bounding box, or until an invalid
+    /// UTF-8 sequence is read. The source is not read any further than the chunk that didn't fit.
+    ///
+    /// Words and characters that are split between chunks are put together before they are
+    /// drawn, so the text is wrapped the same way as if it was appended as a whole. Only the last
+    /// 64 bytes of a word are carried over to the next chunk, longer words may be broken
+    /// differently.
+    #[inline]
+    pub fn append_source<T, D>(
+        &mut self,
+        source: &mut T,
+        display: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        T: TextSource + ?Sized,
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        self.append_chunks(source, display, false)
+    }
+
+    /// Draws the text read from `source`, like `append_source`. If `lossy` is set, invalid UTF-8
+    /// sequences are replaced with the replacement character, instead of ending the text.
+    pub(crate) fn append_chunks<T, D>(
+        &mut self,
+        source: &mut T,
+        display: &mut D,
+        lossy: bool,
+    ) -> Result<usize, D::Error>
+    where
+        T: TextSource + ?Sized,
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        let mut writer = SourceWriter {
+            appender: self,
+            carried: [0; CARRIED_LENGTH],
+            carried_len: 0,
+            drawn: 0,
+            finished: false,
+            lossy,
+        };
+
+        while !writer.finished {
+            match source.next_chunk() {
+                Some(chunk) => writer.write(chunk, display)?,
+                None => {
+                    writer.flush(display)?;
+                    break;
+                }
+            }
+        }
+
+        Ok(writer.drawn)
+    }
+
+    /// Moves the position of the next text back to the top left corner of the bounding box, and
+    /// restores the character style.
+    ///
+    /// The display is not cleared.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.cursor = Self::start_cursor(self.bounds, &self.character_style, &self.style);
+        self.current_style = self.character_style.clone();
+    }
+}
+
+/// The number of bytes at the end of a chunk that can be carried over to the next chunk.
+const CARRIED_LENGTH: usize = 64;
+
+/// The character that replaces invalid UTF-8 sequences.
+const REPLACEMENT: &str = "\u{fffd}";
+
+/// Returns `true` if words can't continue over `byte`.
+fn is_word_break(byte: &u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Splits `bytes` into valid UTF-8 text and the invalid sequences after it, like
+/// `String::from_utf8_lossy` does.
+///
+/// `on_part` is called with every valid part, and the length of the invalid sequence that follows
+/// it. An incomplete character at the end of `bytes` is reported as an invalid sequence.
+fn split_lossy<'t>(mut bytes: &'t [u8], mut on_part: impl FnMut(&'t str, usize) -> bool) {
+    while !bytes.is_empty() {
+        let (valid, invalid) = match core::str::from_utf8(bytes) {
+            Ok(text) => (text, 0),
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                let invalid = error.error_len().unwrap_or(rest.len());
+
+                (core::str::from_utf8(valid).unwrap_or_default(), invalid)
+            }
+        };
+
+        if !on_part(valid, invalid) {
+            return;
+        }
+        bytes = &bytes[valid.len() + invalid..];
+    }
+}
+
+/// Returns the length of `bytes` without an incomplete character at its end.
+fn complete_len(bytes: &[u8]) -> usize {
+    let mut start = 0;
+    loop {
+        match core::str::from_utf8(&bytes[start..]) {
+            Ok(_) => return bytes.len(),
+            Err(error) => match error.error_len() {
+                Some(invalid) => start += error.valid_up_to() + invalid,
+                None => return start + error.valid_up_to(),
+            },
+        }
+    }
+}
+
+/// Appends the chunks of a text source, keeping the words that are split between chunks together.
+struct SourceWriter<'s, S> {
+    appender: &'s mut TextBoxAppender<S>,
+
+    /// The end of the previous chunk that may continue in the next chunk: the start of an
+    /// unfinished word, which may end in an incomplete character.
+    carried: [u8; CARRIED_LENGTH],
+    carried_len: usize,
+
+    /// The number of bytes drawn.
+    drawn: usize,
+
+    /// Set when the text doesn't fit, or is not valid UTF-8.
+    finished: bool,
+
+    /// Replace invalid UTF-8 sequences instead of ending the text.
+    lossy: bool,
+}
+
+impl<S> SourceWriter<'_, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Draws the complete words of `chunk`, and carries the unfinished word at its end over.
+    fn write<D>(&mut self, chunk: &[u8], display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        if self.lossy && core::str::from_utf8(chunk).is_err() {
+            // Every word is carried, so invalid sequences are replaced within their words.
+            let mut rest = chunk;
+            while let Some(end) = rest.iter().position(is_word_break) {
+                self.carry(&rest[..end], display)?;
+                self.flush(display)?;
+                self.draw(&rest[end..=end], display)?;
+                rest = &rest[end + 1..];
+            }
+
+            return self.carry(rest, display);
+        }
+
+        let last = match chunk.iter().rposition(is_word_break) {
+            Some(last) => last,
+            None => return self.carry(chunk, display),
+        };
+        let first = chunk.iter().position(is_word_break).unwrap_or(last);
+
+        // The start of the chunk finishes the carried word.
+        self.carry(&chunk[..first], display)?;
+        self.flush(display)?;
+
+        self.draw(&chunk[first..=last], display)?;
+        self.carry(&chunk[last + 1..], display)
+    }
+
+    /// Adds `bytes` to the carried word. If the word gets too long, its start is drawn.
+    fn carry<D>(&mut self, mut bytes: &[u8], display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        while !bytes.is_empty() && !self.finished {
+            if self.carried_len == CARRIED_LENGTH {
+                // Keep an incomplete character at the end.
+                let complete = match core::str::from_utf8(&self.carried) {
+                    Err(error) if error.error_len().is_none() => error.valid_up_to(),
+                    _ if self.lossy => complete_len(&self.carried),
+                    _ => CARRIED_LENGTH,
+                };
+
+                let carried = self.carried;
+                self.draw_word(&carried[..complete], display)?;
+                self.carried.copy_within(complete.., 0);
+                self.carried_len -= complete;
+            }
+
+            let len = bytes.len().min(CARRIED_LENGTH - self.carried_len);
+            let (start, rest) = bytes.split_at(len);
+            self.carried[self.carried_len..self.carried_len + len].copy_from_slice(start);
+            self.carried_len += len;
+            bytes = rest;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the carried word.
+    fn flush<D>(&mut self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        let carried = self.carried;
+        let len = self.carried_len;
+        self.carried_len = 0;
+
+        self.draw_word(&carried[..len], display)
+    }
+
+    /// Appends a carried word, replacing its invalid UTF-8 sequences if the writer is lossy.
+    fn draw_word<D>(&mut self, bytes: &[u8], display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        if !self.lossy || self.finished || core::str::from_utf8(bytes).is_ok() {
+            return self.draw(bytes, display);
+        }
+
+        // Every byte is replaced by at most 3 bytes, the length of the replacement character.
+        let mut buffer = [0; 3 * CARRIED_LENGTH];
+        let mut len = 0;
+        split_lossy(bytes, |valid, invalid| {
+            buffer[len..len + valid.len()].copy_from_slice(valid.as_bytes());
+            len += valid.len();
+            if invalid > 0 {
+                buffer[len..len + REPLACEMENT.len()].copy_from_slice(REPLACEMENT.as_bytes());
+                len += REPLACEMENT.len();
+            }
+            true
+        });
+
+        let text = core::str::from_utf8(&buffer[..len]).unwrap_or_default();
+        let rest = self.appender.append(text, display)?;
+        self.finished |= !rest.is_empty();
+
+        // Count the bytes of the input that belong to the drawn text.
+        let mut remaining = text.len() - rest.len();
+        split_lossy(bytes, |valid, invalid| {
+            let valid_drawn = valid.len().min(remaining);
+            self.drawn += valid_drawn;
+            remaining -= valid_drawn;
+
+            if invalid > 0 && remaining >= REPLACEMENT.len() {
+                self.drawn += invalid;
+                remaining -= REPLACEMENT.len();
+            }
+
+            remaining > 0
+        });
+
+        Ok(())
+    }
+
+    /// Appends `bytes`, up to the first invalid UTF-8 sequence.
+    fn draw<D>(&mut self, bytes: &[u8], display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        if self.finished || bytes.is_empty() {
+            return Ok(());
+        }
+
+        let text = match core::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(error) => {
+                self.finished = true;
+                core::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default()
+            }
+        };
+
+        let rest = self.appender.append(text, display)?;
+        self.drawn += text.len() - rest.len();
+        self.finished |= !rest.is_empty();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::HorizontalAlignment,
+        style::{TextBoxStyle, TextBoxStyleBuilder},
+        utils::test::size_for,
+        TextBox, TextBoxAppender,
+    };
+
+    #[track_caller]
+    fn assert_appended(parts: &[&str], text: &str) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        let mut appender = TextBoxAppender::new(bounds, character_style, TextBoxStyle::default());
+        let mut display = MockDisplay::new();
+        for part in parts {
+            assert_eq!(appender.append(part, &mut display).unwrap(), "");
+        }
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn appended_text_continues_the_line() {
+        assert_appended(&["ab", "cd ef"], "abcd ef");
+        assert_appended(&["ab\n", "cd"], "ab\ncd");
+    }
+
+    #[test]
+    fn words_that_dont_fit_are_moved_to_the_next_line() {
+        assert_appended(&["ab ", "cdef", " g"], "ab cdef g");
+    }
+
+    #[test]
+    fn text_that_doesnt_fit_is_returned() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let mut appender = TextBoxAppender::new(bounds, character_style, TextBoxStyle::default());
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        assert_eq!(appender.append("ab\ncd", &mut display).unwrap(), "cd");
+        assert_eq!(appender.append("ef", &mut display).unwrap(), "ef");
+
+        appender.reset();
+        assert_eq!(appender.append("ef", &mut display).unwrap(), "");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "TextBoxAppender only supports left aligned text")]
+    fn other_alignments_are_rejected() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+        let style = TextBoxStyleBuilder::new()
+            .alignment(HorizontalAlignment::Center)
+            .build();
+
+        TextBoxAppender::new(bounds, character_style, style);
+    }
+}
@@ -0,0 +1,139 @@
+//! Rendering of many text boxes with the same styling.
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{rendering::SharedMeasurements, style::TextBoxStyle, TextBox};
+
+/// Draws a number of text boxes that share the same styling.
+///
+/// Screens like settings menus or grids are often composed of dozens of small labels. Drawing
+/// them as separate [`TextBox`] objects repeats the measurements that only depend on the styling
+/// for every label. `TextBoxBatch` performs these measurements once, and reuses them for every
+/// text box it draws.
+///
+/// Text boxes drawn by a batch can't use plugins.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{style::TextBoxStyle, TextBoxBatch};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let batch = TextBoxBatch::new(character_style, TextBoxStyle::default());
+///
+/// let labels = [
+///     ("On", Rectangle::new(Point::new(0, 0), Size::new(30, 9))),
+///     ("Off", Rectangle::new(Point::new(0, 10), Size::new(30, 9))),
+/// ];
+///
+/// let mut display = MockDisplay::new();
+/// batch.draw(&labels, &mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Clone, Debug)]
+pub struct TextBoxBatch<S> {
+    character_style: S,
+    style: TextBoxStyle,
+    shared: SharedMeasurements,
+}
+
+impl<S> TextBoxBatch<S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle + Clone,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Creates a new batch with the given styling.
+    #[inline]
+    pub fn new(character_style: S, style: TextBoxStyle) -> Self {
+        let shared = SharedMeasurements::new(&character_style, &style, false);
+
+        Self {
+            character_style,
+            style,
+            shared,
+        }
+    }
+
+    /// Returns the character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+
+    /// Returns the text box style.
+    #[inline]
+    pub fn style(&self) -> &TextBoxStyle {
+        &self.style
+    }
+
+    /// Draws a text box for every `(text, bounds)` pair.
+    #[inline]
+    pub fn draw<D>(&self, items: &[(&str, Rectangle)], display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        for &(text, bounds) in items {
+            TextBox::with_textbox_style(text, bounds, self.character_style.clone(), self.style)
+                .draw_with(display, &self.shared)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::VerticalAlignment, style::TextBoxStyleBuilder, utils::test::size_for, TextBox,
+        TextBoxBatch,
+    };
+
+    #[test]
+    fn batch_draws_like_text_boxes() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Bottom)
+            .build();
+        let size = size_for(&FONT_6X9, 3, 2);
+
+        let items = [
+            ("ab", Rectangle::new(Point::zero(), size)),
+            ("a b c", Rectangle::new(Point::new(20, 0), size)),
+        ];
+
+        let mut expected = MockDisplay::new();
+        for &(text, bounds) in &items {
+            TextBox::with_textbox_style(text, bounds, character_style, style)
+                .draw(&mut expected)
+                .unwrap();
+        }
+
+        let mut display = MockDisplay::new();
+        TextBoxBatch::new(character_style, style)
+            .draw(&items, &mut display)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
@@ -0,0 +1,292 @@
+//! Characters that occupy more than one cell.
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+/// The number of cells a character occupies.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum CharWidth {
+    /// The character doesn't advance the position, like combining marks.
+    Zero,
+
+    /// The character occupies a single cell.
+    Single,
+
+    /// The character occupies two cells, like fullwidth CJK characters and most emoji.
+    Double,
+}
+
+impl CharWidth {
+    /// Returns the width category of `c`.
+    ///
+    /// Fullwidth and wide characters of the common CJK, Hangul and emoji blocks are double width,
+    /// combining marks and zero-width characters are zero width, and every other character is
+    /// single width. This is a simplified version of the East Asian Width property of Unicode.
+    #[inline]
+    pub fn of(c: char) -> Self {
+        match c {
+            '\u{0300}'..='\u{036f}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{fe20}'..='\u{fe2f}' => Self::Zero,
+
+            '\u{1100}'..='\u{115f}'
+            | '\u{2e80}'..='\u{303e}'
+            | '\u{3041}'..='\u{33ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{a000}'..='\u{a4cf}'
+            | '\u{ac00}'..='\u{d7a3}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{fe30}'..='\u{fe4f}'
+            | '\u{ff00}'..='\u{ff60}'
+            | '\u{ffe0}'..='\u{ffe6}'
+            | '\u{1f300}'..='\u{1f64f}'
+            | '\u{1f900}'..='\u{1f9ff}'
+            | '\u{20000}'..='\u{3fffd}' => Self::Double,
+
+            _ => Self::Single,
+        }
+    }
+
+    /// Returns the number of cells.
+    fn cells(self) -> u32 {
+        match self {
+            Self::Zero => 0,
+            Self::Single => 1,
+            Self::Double => 2,
+        }
+    }
+}
+
+/// A character style that draws characters in one or two cells.
+///
+/// Mono fonts that contain CJK characters or emoji often draw them as double width glyphs, but
+/// the font still reports every character as wide as a single cell. `CellStyle` measures and
+/// draws the characters using the width category returned by a hook function, so wrapping and
+/// justification stay correct for mixed Latin and CJK text. Double width characters are drawn at
+/// the start of their two cells, and zero width characters don't advance the position.
+///
+/// The width of a cell is the width the wrapped character style measures for the character.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{CellStyle, TextBox};
+///
+/// let character_style = CellStyle::new(MonoTextStyle::new(&FONT_6X9, BinaryColor::On));
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("Hello, World!", bounds, character_style)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct CellStyle<S> {
+    character_style: S,
+    char_width: fn(char) -> CharWidth,
+}
+
+impl<S> CellStyle<S> {
+    /// Wraps a character style, using [`CharWidth::of`] to categorize the characters.
+    ///
+    /// [`CharWidth::of`]: enum.CharWidth.html#method.of
+    #[inline]
+    pub fn new(character_style: S) -> Self {
+        Self::with_char_width(character_style, CharWidth::of)
+    }
+
+    /// Wraps a character style, using `char_width` to categorize the characters.
+    #[inline]
+    pub fn with_char_width(character_style: S, char_width: fn(char) -> CharWidth) -> Self {
+        Self {
+            character_style,
+            char_width,
+        }
+    }
+
+    /// Returns the wrapped character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+}
+
+impl<S> CellStyle<S>
+where
+    S: TextRenderer,
+{
+    /// Returns the width of `c`, measured by the wrapped style.
+    fn cell_width(&self, c: char) -> u32 {
+        let mut buffer = [0; 4];
+        self.character_style
+            .measure_string(c.encode_utf8(&mut buffer), Point::zero(), Baseline::Top)
+            .next_position
+            .x
+            .saturating_as()
+    }
+}
+
+impl<S> TextRenderer for CellStyle<S>
+where
+    S: TextRenderer,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        mut position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        // Runs of single width characters are drawn with a single call.
+        let mut run_start = 0;
+        for (idx, c) in text.char_indices() {
+            let width = (self.char_width)(c);
+            if width == CharWidth::Single {
+                continue;
+            }
+
+            position = self.character_style.draw_string(
+                &text[run_start..idx],
+                position,
+                baseline,
+                target,
+            )?;
+
+            let end = idx + c.len_utf8();
+            self.character_style
+                .draw_string(&text[idx..end], position, baseline, target)?;
+            position.x += (width.cells() * self.cell_width(c)).saturating_as::<i32>();
+            run_start = end;
+        }
+
+        self.character_style
+            .draw_string(&text[run_start..], position, baseline, target)
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.character_style
+            .draw_whitespace(width, position, baseline, target)
+    }
+
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width = text.chars().fold(0_u32, |width, c| {
+            width.saturating_add((self.char_width)(c).cells() * self.cell_width(c))
+        });
+
+        let empty = self.character_style.measure_string("", position, baseline);
+
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                empty.bounding_box.top_left,
+                Size::new(width, empty.bounding_box.size.height),
+            ),
+            next_position: position + Point::new(width.saturating_as(), 0),
+        }
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.character_style.line_height()
+    }
+}
+
+impl<S> CharacterStyle for CellStyle<S>
+where
+    S: CharacterStyle,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.character_style.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.character_style.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.character_style.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.character_style
+            .set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, CellStyle, CharWidth, TextBox};
+
+    #[test]
+    fn double_width_characters_take_two_cells() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let cell_style = CellStyle::with_char_width(character_style, |c| match c {
+            'W' => CharWidth::Double,
+            _ => CharWidth::Single,
+        });
+
+        for &(columns, rows, expected) in &[(4, 1, "aW b"), (3, 2, "aW\nb")] {
+            let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, columns, rows));
+
+            let mut display = MockDisplay::new();
+            TextBox::new("aWb", bounds, cell_style)
+                .draw(&mut display)
+                .unwrap();
+
+            let mut expected_display = MockDisplay::new();
+            TextBox::new(expected, bounds, character_style)
+                .draw(&mut expected_display)
+                .unwrap();
+
+            display.assert_eq(&expected_display);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+//! Text diffing helpers.
+//!
+//! When the text of a [`TextBox`] changes, usually only a small part of the display needs to be
+//! updated. The helpers in this module compare two texts laid out using the same text box and
+//! report the areas that differ, so that applications can redraw or animate only those areas.
+//!
+//! [`TextBox`]: ../struct.TextBox.html
+use crate::{
+    parser::{Parser, SPEC_CHAR_ESCAPE},
+    plugin::{NoPlugin, PluginMarker as Plugin, PluginWrapper},
+    rendering::layout::{LaidOutLine, LineLayout},
+    TextBox,
+};
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Point, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+/// Iterator over the areas that differ between two texts.
+///
+/// This struct is created by [`TextBox::changed_regions`]. Each item covers the changed part of a
+/// single line. Lines that are identical in both texts are skipped.
+///
+/// [`TextBox::changed_regions`]: ../struct.TextBox.html#method.changed_regions
+pub struct ChangedRegions<'a, 'b, S, M>
+where
+    S: TextRenderer,
+{
+    text_box: &'b TextBox<'a, S, M>,
+    previous: LineLayout<'a, 'b, S, M>,
+    current: LineLayout<'a, 'b, S, M>,
+    style_changed: bool,
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Returns the areas of the text box that change when `previous` is replaced by the current
+    /// text.
+    ///
+    /// For each line, the common prefix and suffix of the two versions are skipped, so the
+    /// returned rectangles only cover the part of the line that actually changed. If a line
+    /// moves, changes alignment, or has its whitespace stretched differently, the whole line is
+    /// reported.
+    ///
+    /// *Note:* prefixes are measured without plugins, and any change to an escape sequence marks
+    /// every following line as changed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// let text_box = TextBox::new("Time\n12:31", bounds, character_style);
+    ///
+    /// let mut changes = text_box.changed_regions("Time\n12:30");
+    ///
+    /// // Only the last digit of the second line needs to be redrawn.
+    /// assert_eq!(
+    ///     changes.next(),
+    ///     Some(Rectangle::new(Point::new(24, 9), Size::new(6, 9)))
+    /// );
+    /// assert_eq!(changes.next(), None);
+    /// ```
+    #[inline]
+    pub fn changed_regions<'b>(&'b self, previous: &'a str) -> ChangedRegions<'a, 'b, S, M> {
+        ChangedRegions {
+            text_box: self,
+            previous: LineLayout::new(self, previous),
+            current: LineLayout::new(self, self.text),
+            style_changed: false,
+        }
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color> + Plugin<'a, <S as CharacterStyle>::Color>,
+{
+    /// Redraws the parts of the display that change when `previous` is replaced by the current
+    /// text.
+    ///
+    /// The display is expected to show `previous`, drawn using the same text box. Every area
+    /// returned by [`changed_regions`] is filled with `background`, and the text box is drawn
+    /// clipped to that area, so the rest of the display is not touched. This reduces the number
+    /// of pixels that are sent to displays with slow buses, or that need to be refreshed on
+    /// e-paper displays, when only a small part of the text changes.
+    ///
+    /// Returns the number of redrawn areas.
+    ///
+    /// *Note:* the text is laid out again for every changed area, so this is slower than drawing
+    /// the whole text box if many lines change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyleBuilder::new()
+    ///     .font(&FONT_6X9)
+    ///     .text_color(BinaryColor::On)
+    ///     .background_color(BinaryColor::Off)
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// let mut display = MockDisplay::new();
+    /// display.set_allow_overdraw(true);
+    ///
+    /// TextBox::new("Time\n12:30", bounds, character_style)
+    ///     .draw(&mut display)
+    ///     .unwrap();
+    ///
+    /// // Only the last digit is redrawn.
+    /// let redrawn = TextBox::new("Time\n12:31", bounds, character_style)
+    ///     .draw_changes("Time\n12:30", BinaryColor::Off, &mut display)
+    ///     .unwrap();
+    /// assert_eq!(redrawn, 1);
+    /// ```
+    ///
+    /// [`changed_regions`]: #method.changed_regions
+    #[inline]
+    pub fn draw_changes<D>(
+        &self,
+        previous: &'a str,
+        background: <S as CharacterStyle>::Color,
+        display: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = <S as CharacterStyle>::Color>,
+    {
+        let mut redrawn = 0;
+        for area in self.changed_regions(previous) {
+            display.fill_solid(&area, background)?;
+            self.draw(&mut display.clipped(&area))?;
+            redrawn += 1;
+        }
+
+        Ok(redrawn)
+    }
+}
+
+impl<'a, 'b, S, M> ChangedRegions<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    fn measure(&self, text: &str) -> u32 {
+        let plugin = PluginWrapper::new(NoPlugin::new());
+
+        self.text_box
+            .style
+            .measure_line(
+                &plugin,
+                &self.text_box.character_style,
+                &mut Parser::parse(text).with_style(&self.text_box.style),
+                self.text_box.text_bounds().size.width,
+            )
+            .width
+    }
+
+    fn compare(&mut self, previous: &LaidOutLine<'_>, current: &LaidOutLine<'_>) -> Rectangle {
+        let moved = previous.position != current.position
+            || previous.displayed_rows != current.displayed_rows
+            || previous.stretched
+            || current.stretched;
+
+        if self.style_changed || moved {
+            return union(
+                previous.area(0, previous.width),
+                current.area(0, current.width),
+            );
+        }
+
+        let prefix = common_prefix_len(previous.text, current.text);
+        let max_suffix = previous.text.len().min(current.text.len()) - prefix;
+        let suffix = common_suffix_len(previous.text, current.text, max_suffix);
+
+        let previous_changed = &previous.text[prefix..previous.text.len() - suffix];
+        let current_changed = &current.text[prefix..current.text.len() - suffix];
+        if previous_changed.contains(SPEC_CHAR_ESCAPE) || current_changed.contains(SPEC_CHAR_ESCAPE)
+        {
+            self.style_changed = true;
+        }
+
+        let from = self.measure(&current.text[..prefix]);
+        let previous_to = self.measure(&previous.text[..previous.text.len() - suffix]);
+        let current_to = self.measure(&current.text[..current.text.len() - suffix]);
+
+        let to = if previous_to == current_to {
+            current_to
+        } else {
+            // The unchanged suffix moved, so it needs to be redrawn as well.
+            previous.width.max(current.width)
+        };
+
+        current.area(from, to)
+    }
+}
+
+impl<'a, 'b, S, M> Iterator for ChangedRegions<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Item = Rectangle;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let area = match (self.previous.next(), self.current.next()) {
+                (None, None) => return None,
+                (Some(line), None) | (None, Some(line)) => line.area(0, line.width),
+                (Some(previous), Some(current)) => {
+                    if previous.text == current.text
+                        && previous.position == current.position
+                        && previous.displayed_rows == current.displayed_rows
+                        && !self.style_changed
+                    {
+                        continue;
+                    }
+
+                    self.compare(&previous, &current)
+                }
+            };
+
+            if !is_empty(&area) {
+                return Some(area);
+            }
+        }
+    }
+}
+
+fn is_empty(rect: &Rectangle) -> bool {
+    rect.size.width == 0 || rect.size.height == 0
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    match (a.bottom_right(), b.bottom_right()) {
+        _ if is_empty(&a) => b,
+        _ if is_empty(&b) => a,
+        (Some(a_br), Some(b_br)) => Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a_br.component_max(b_br),
+        ),
+        _ => Rectangle::new(Point::zero(), Size::zero()),
+    }
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map_or(a.len().min(b.len()), |((idx, _), _)| idx)
+}
+
+fn common_suffix_len(a: &str, b: &str, max: usize) -> usize {
+    a.char_indices()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|((idx, ca), cb)| ca == cb && a.len() - idx <= max)
+        .last()
+        .map_or(0, |((idx, _), _)| a.len() - idx)
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::HorizontalAlignment, style::TextBoxStyle, utils::test::size_for, TextBox,
+    };
+
+    #[track_caller]
+    fn assert_changes(
+        alignment: HorizontalAlignment,
+        previous: &str,
+        current: &str,
+        expected: &[Rectangle],
+    ) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let text_box = TextBox::with_textbox_style(
+            current,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3)),
+            character_style,
+            TextBoxStyle::with_alignment(alignment),
+        );
+
+        let changes = text_box.changed_regions(previous).collect::<Vec<_>>();
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn identical_text_has_no_changes() {
+        assert_changes(HorizontalAlignment::Left, "foo bar", "foo bar", &[]);
+    }
+
+    #[test]
+    fn only_changed_middle_is_reported() {
+        assert_changes(
+            HorizontalAlignment::Left,
+            "12:30",
+            "12:45",
+            &[Rectangle::new(Point::new(18, 0), Size::new(12, 9))],
+        );
+        assert_changes(
+            HorizontalAlignment::Left,
+            "12:30",
+            "19:30",
+            &[Rectangle::new(Point::new(6, 0), Size::new(6, 9))],
+        );
+    }
+
+    #[test]
+    fn moved_suffix_is_reported() {
+        assert_changes(
+            HorizontalAlignment::Left,
+            "1:30",
+            "12:30",
+            &[Rectangle::new(Point::new(6, 0), Size::new(24, 9))],
+        );
+    }
+
+    #[test]
+    fn unchanged_lines_are_skipped() {
+        assert_changes(
+            HorizontalAlignment::Left,
+            "foo\nbar\nbaz",
+            "foo\nbat\nbaz",
+            &[Rectangle::new(Point::new(12, 9), Size::new(6, 9))],
+        );
+    }
+
+    #[test]
+    fn added_and_removed_lines_are_reported() {
+        assert_changes(
+            HorizontalAlignment::Left,
+            "foo",
+            "foo\nbar",
+            &[Rectangle::new(Point::new(0, 9), Size::new(18, 9))],
+        );
+        assert_changes(
+            HorizontalAlignment::Left,
+            "foo\nbar",
+            "foo",
+            &[Rectangle::new(Point::new(0, 9), Size::new(18, 9))],
+        );
+    }
+
+    #[test]
+    fn changed_areas_are_redrawn() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        TextBox::new("foo\nbar\nbaz", bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        let text_box = TextBox::new("foo\nbat\nbiz", bounds, character_style);
+        let redrawn = text_box
+            .draw_changes("foo\nbar\nbaz", BinaryColor::Off, &mut display)
+            .unwrap();
+        assert_eq!(redrawn, 2);
+
+        let mut expected = MockDisplay::new();
+        text_box.draw(&mut expected).unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn realigned_line_is_reported_whole() {
+        assert_changes(
+            HorizontalAlignment::Right,
+            "foo",
+            "fo",
+            &[Rectangle::new(Point::new(12, 0), Size::new(18, 9))],
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Rich text documents built from styled spans.
+//!
+//! Text that uses multiple styles is usually written using escape sequences, like ANSI sequences
+//! or the [`EscapeDialect`] plugin. A document describes the same content as a list of
+//! [`Paragraph`]s, which consist of [`Span`]s of text and their [`SpanStyle`], so the styles are
+//! checked by the compiler instead of being parsed from the text. Documents are drawn by
+//! [`DocumentBox`], which lays out the spans the same way a [`TextBox`] lays out its text.
+//!
+//! [`EscapeDialect`]: ../plugin/escapes/struct.EscapeDialect.html
+//! [`Paragraph`]: struct.Paragraph.html
+//! [`Span`]: struct.Span.html
+//! [`SpanStyle`]: struct.SpanStyle.html
+//! [`DocumentBox`]: struct.DocumentBox.html
+//! [`TextBox`]: ../struct.TextBox.html
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Dimensions,
+    pixelcolor::Rgb888,
+    prelude::PixelColor,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        DecorationColor,
+    },
+    Drawable,
+};
+
+use crate::{
+    parser::{ChangeTextStyle, Parser, Token},
+    plugin::Plugin,
+    style::{HeightMode, TextBoxStyle},
+    TextBox,
+};
+
+/// The style of a [`Span`].
+///
+/// Every span starts from the character style of the [`DocumentBox`], or from the selected font,
+/// and the colors and decorations of the span style are applied on top of it. Styles don't carry
+/// over from one span to the next.
+///
+/// [`Span`]: struct.Span.html
+/// [`DocumentBox`]: struct.DocumentBox.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SpanStyle<C> {
+    font: Option<usize>,
+    text_color: Option<C>,
+    background_color: Option<C>,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl<C> SpanStyle<C>
+where
+    C: PixelColor,
+{
+    /// Creates a span style that draws the text using the character style of the document box.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            font: None,
+            text_color: None,
+            background_color: None,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    /// Draws the text using one of the fonts of the document box, selected by its index.
+    ///
+    /// See [`DocumentBox::with_fonts`].
+    ///
+    /// [`DocumentBox::with_fonts`]: struct.DocumentBox.html#method.with_fonts
+    #[inline]
+    pub const fn font(mut self, index: usize) -> Self {
+        self.font = Some(index);
+        self
+    }
+
+    /// Sets the text color.
+    #[inline]
+    pub const fn text_color(mut self, color: C) -> Self {
+        self.text_color = Some(color);
+        self
+    }
+
+    /// Sets the background color.
+    #[inline]
+    pub const fn background_color(mut self, color: C) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    /// Underlines the text.
+    #[inline]
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Strikes the text through.
+    #[inline]
+    pub const fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Returns the style changes that start a span.
+    fn changes(&self) -> [Option<ChangeTextStyle<C>>; 5] {
+        let decoration = |enabled: bool| {
+            if enabled {
+                Some(DecorationColor::TextColor)
+            } else {
+                None
+            }
+        };
+
+        [
+            Some(match self.font {
+                Some(index) => ChangeTextStyle::Font(index),
+                None => ChangeTextStyle::Reset,
+            }),
+            self.text_color
+                .map(|color| ChangeTextStyle::TextColor(Some(color))),
+            self.background_color
+                .map(|color| ChangeTextStyle::BackgroundColor(Some(color))),
+            decoration(self.underline).map(ChangeTextStyle::Underline),
+            decoration(self.strikethrough).map(ChangeTextStyle::Strikethrough),
+        ]
+    }
+}
+
+impl<C> Default for SpanStyle<C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A piece of text drawn in a single style.
+///
+/// The text is wrapped and broken into lines like the text of a [`TextBox`]. Newline characters
+/// start a new line without starting a new paragraph.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Span<'a, C> {
+    /// The text of the span.
+    pub text: &'a str,
+
+    /// The style of the span.
+    pub style: SpanStyle<C>,
+}
+
+impl<'a, C> Span<'a, C>
+where
+    C: PixelColor,
+{
+    /// Creates a span that is drawn using the character style of the document box.
+    #[inline]
+    pub const fn new(text: &'a str) -> Self {
+        Self::styled(text, SpanStyle::new())
+    }
+
+    /// Creates a span that is drawn using the given style.
+    #[inline]
+    pub const fn styled(text: &'a str, style: SpanStyle<C>) -> Self {
+        Self { text, style }
+    }
+}
+
+/// A paragraph of a document.
+///
+/// The spans of a paragraph are drawn one after the other, and every paragraph starts on a new
+/// line. The paragraph spacing of the text box style is inserted between paragraphs.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Paragraph<'a, C> {
+    /// The spans of the paragraph.
+    pub spans: &'a [Span<'a, C>],
+}
+
+impl<'a, C> Paragraph<'a, C> {
+    /// Creates a new paragraph.
+    #[inline]
+    pub const fn new(spans: &'a [Span<'a, C>]) -> Self {
+        Self { spans }
+    }
+}
+
+/// Turns the spans of a document into tokens.
+#[derive(Clone, Debug)]
+struct DocumentTokens<'a, C>
+where
+    C: PixelColor,
+{
+    paragraphs: &'a [Paragraph<'a, C>],
+    spans: &'a [Span<'a, C>],
+    changes: [Option<ChangeTextStyle<C>>; 5],
+    parser: Option<Parser<'a, C>>,
+    started: bool,
+}
+
+impl<'a, C> DocumentTokens<'a, C>
+where
+    C: PixelColor,
+{
+    fn new(paragraphs: &'a [Paragraph<'a, C>]) -> Self {
+        Self {
+            paragraphs,
+            spans: &[],
+            changes: [None; 5],
+            parser: None,
+            started: false,
+        }
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for DocumentTokens<'a, C>
+where
+    C: PixelColor,
+{
+    fn next_token(
+        &mut self,
+        _next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        loop {
+            if let Some(change) = self.changes.iter_mut().find_map(Option::take) {
+                return Some(Token::ChangeTextStyle(change));
+            }
+
+            if let Some(token) = self.parser.as_mut().and_then(Iterator::next) {
+                return Some(token);
+            }
+
+            if let Some((span, rest)) = self.spans.split_first() {
+                self.spans = rest;
+                self.changes = span.style.changes();
+                self.parser = Some(Parser::parse(span.text));
+                continue;
+            }
+
+            let (paragraph, rest) = self.paragraphs.split_first()?;
+            self.paragraphs = rest;
+            self.spans = paragraph.spans;
+            self.parser = None;
+
+            if self.started {
+                return Some(Token::NewLine);
+            }
+            self.started = true;
+        }
+    }
+}
+
+/// A box that draws a document.
+///
+/// The paragraphs are laid out like the text of a [`TextBox`] that has the same bounds and
+/// styles, except that the height of the box is not changed by the height mode of the style.
+///
+/// *Note:* the text of the spans is still parsed, so escape sequences in the text are
+/// interpreted if the `ansi` feature is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::document::{DocumentBox, Paragraph, Span, SpanStyle};
+///
+/// const WARNING: SpanStyle<BinaryColor> = SpanStyle::new().underline();
+///
+/// const STATUS: &[Span<'static, BinaryColor>] = &[Span::new("Status")];
+/// const DISK: &[Span<'static, BinaryColor>] = &[Span::new("Disk "), Span::styled("full", WARNING)];
+///
+/// let document = [Paragraph::new(STATUS), Paragraph::new(DISK)];
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut display = MockDisplay::new();
+/// DocumentBox::new(&document, bounds, character_style)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct DocumentBox<'a, S>
+where
+    S: TextRenderer,
+{
+    /// The paragraphs of the document.
+    pub paragraphs: &'a [Paragraph<'a, S::Color>],
+
+    /// The bounding box.
+    pub bounds: Rectangle,
+
+    /// The character style.
+    pub character_style: S,
+
+    /// The text box style.
+    pub style: TextBoxStyle,
+
+    /// The character styles that can be selected by [`SpanStyle::font`].
+    ///
+    /// [`SpanStyle::font`]: struct.SpanStyle.html#method.font
+    pub fonts: &'a [S],
+}
+
+impl<'a, S> DocumentBox<'a, S>
+where
+    S: TextRenderer,
+{
+    /// Creates a new document box.
+    #[inline]
+    pub fn new(
+        paragraphs: &'a [Paragraph<'a, S::Color>],
+        bounds: Rectangle,
+        character_style: S,
+    ) -> Self {
+        Self::with_textbox_style(paragraphs, bounds, character_style, TextBoxStyle::default())
+    }
+
+    /// Creates a new document box with the given text box style.
+    #[inline]
+    pub fn with_textbox_style(
+        paragraphs: &'a [Paragraph<'a, S::Color>],
+        bounds: Rectangle,
+        character_style: S,
+        style: TextBoxStyle,
+    ) -> Self {
+        Self {
+            paragraphs,
+            bounds,
+            character_style,
+            style,
+            fonts: &[],
+        }
+    }
+
+    /// Sets the character styles that can be selected by [`SpanStyle::font`].
+    ///
+    /// [`SpanStyle::font`]: struct.SpanStyle.html#method.font
+    #[inline]
+    pub fn with_fonts(mut self, fonts: &'a [S]) -> Self {
+        self.fonts = fonts;
+        self
+    }
+}
+
+impl<S> Dimensions for DocumentBox<'_, S>
+where
+    S: TextRenderer,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl<'a, S> Drawable for DocumentBox<'a, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    type Color = <S as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut style = self.style;
+        style.height_mode = HeightMode::Exact(style.height_mode.overdraw());
+
+        let mut text_box =
+            TextBox::with_textbox_style("", self.bounds, self.character_style.clone(), style)
+                .add_plugin(DocumentTokens::new(self.paragraphs));
+        text_box.set_fonts(self.fonts);
+        text_box.draw(display)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        document::{DocumentBox, Paragraph, Span, SpanStyle},
+        parser::ChangeTextStyle,
+        plugin::escapes::EscapeDialect,
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn parse(params: &str) -> Option<(usize, ChangeTextStyle<BinaryColor>)> {
+        let change = match params.as_bytes().first()? {
+            b'o' => ChangeTextStyle::TextColor(Some(BinaryColor::Off)),
+            b'/' => ChangeTextStyle::Reset,
+            _ => return None,
+        };
+
+        Some((1, change))
+    }
+
+    #[test]
+    fn spans_are_drawn_like_styled_text() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        let off = SpanStyle::new().text_color(BinaryColor::Off);
+        let first = [Span::new("a "), Span::styled("bcd", off), Span::new("e")];
+        let second = [Span::new("fg")];
+        let document = [Paragraph::new(&first), Paragraph::new(&second)];
+
+        let mut display = MockDisplay::new();
+        DocumentBox::new(&document, bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("a $obcd$/e\nfg", bounds, character_style)
+            .add_plugin(EscapeDialect::new('$', parse))
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
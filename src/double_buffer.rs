@@ -0,0 +1,298 @@
+//! Drawing into a packed frame buffer, and updating the changed pixels of the display.
+
+use core::marker::PhantomData;
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::{raw::RawData, PixelColor},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// A packed frame buffer that updates only the changed pixels of the display.
+///
+/// Text that is redrawn on a display that can't be read back usually has to be cleared first,
+/// which makes the display flicker. `DoubleBuffer` is a draw target that stores the pixels of an
+/// area in a packed buffer provided by the application, using 1, 2, 4 or 8 bits per pixel,
+/// depending on the color type. [`flush`] compares the buffer with a second buffer that holds
+/// the pixels that are displayed, and only draws the pixels that differ.
+///
+/// Rows start at a byte boundary, and pixels are stored starting at the most significant bits
+/// of a byte. Use [`buffer_len`] to calculate the required size of the buffers.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{DoubleBuffer, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// // 60 pixels wide rows take 8 bytes.
+/// let mut frame = [0; 8 * 9];
+/// let mut shown = [0; 8 * 9];
+/// let mut buffer = DoubleBuffer::<BinaryColor>::new(bounds, &mut frame, &mut shown).unwrap();
+///
+/// let mut display = MockDisplay::new();
+/// display.set_allow_overdraw(true);
+///
+/// for text in &["12:30", "12:31"] {
+///     buffer.clear(BinaryColor::Off).unwrap();
+///     TextBox::new(text, bounds, character_style)
+///         .draw(&mut buffer)
+///         .unwrap();
+///     buffer.flush(&mut display).unwrap();
+/// }
+/// ```
+///
+/// [`flush`]: #method.flush
+/// [`buffer_len`]: #method.buffer_len
+#[derive(Debug)]
+pub struct DoubleBuffer<'b, C> {
+    area: Rectangle,
+    frame: &'b mut [u8],
+    shown: &'b mut [u8],
+
+    /// Whether `shown` holds the pixels that are displayed.
+    valid: bool,
+    color: PhantomData<C>,
+}
+
+impl<'b, C> DoubleBuffer<'b, C>
+where
+    C: PixelColor + From<C::Raw>,
+    C::Raw: From<C> + RawData<Storage = u8>,
+{
+    /// Creates a new double buffer for `area`.
+    ///
+    /// `frame` holds the pixels that are drawn, and `shown` the pixels that are displayed.
+    /// Returns `None` if the buffers are smaller than [`buffer_len`] bytes.
+    ///
+    /// [`buffer_len`]: #method.buffer_len
+    #[inline]
+    pub fn new(area: Rectangle, frame: &'b mut [u8], shown: &'b mut [u8]) -> Option<Self> {
+        let len = Self::buffer_len(area.size);
+        if frame.len() < len || shown.len() < len {
+            return None;
+        }
+
+        Some(Self {
+            area,
+            frame: &mut frame[..len],
+            shown: &mut shown[..len],
+            valid: false,
+            color: PhantomData,
+        })
+    }
+
+    /// Returns the number of bytes a buffer needs to hold an area of the given size.
+    #[inline]
+    pub fn buffer_len(size: Size) -> usize {
+        Self::row_len(size.width) * size.height as usize
+    }
+
+    /// Returns the number of bytes of a row.
+    // `usize::div_ceil` is not available in the minimum supported Rust version.
+    #[allow(clippy::manual_div_ceil)]
+    fn row_len(width: u32) -> usize {
+        (width as usize * C::Raw::BITS_PER_PIXEL + 7) / 8
+    }
+
+    /// Marks every pixel as changed, so the next [`flush`] draws the whole area.
+    ///
+    /// Call this if the display was modified by other code.
+    ///
+    /// [`flush`]: #method.flush
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Returns the byte index and the bit offset of the pixel at `x`, `y` relative to the area.
+    fn position(&self, x: u32, y: u32) -> (usize, u32) {
+        let bits = C::Raw::BITS_PER_PIXEL as u32;
+        let bit = x * bits;
+        let index = y as usize * Self::row_len(self.area.size.width) + (bit / 8) as usize;
+
+        (index, 8 - bits - bit % 8)
+    }
+
+    /// Returns the raw value of the pixel at `x`, `y` in `buffer`.
+    fn get(&self, buffer: &[u8], x: u32, y: u32) -> u8 {
+        let (index, shift) = self.position(x, y);
+        let mask = (0xFF_u16 >> (8 - C::Raw::BITS_PER_PIXEL)) as u8;
+
+        (buffer[index] >> shift) & mask
+    }
+
+    fn set(&mut self, x: u32, y: u32, value: u8) {
+        let (index, shift) = self.position(x, y);
+        let mask = (0xFF_u16 >> (8 - C::Raw::BITS_PER_PIXEL)) as u8;
+
+        let byte = &mut self.frame[index];
+        *byte = (*byte & !(mask << shift)) | ((value & mask) << shift);
+    }
+
+    /// Draws the pixels that changed since the previous call into `display`.
+    ///
+    /// Changed pixels are drawn in horizontal runs. The first call after creating the buffer, or
+    /// after [`invalidate`], draws every pixel. Returns the number of drawn pixels.
+    ///
+    /// [`invalidate`]: #method.invalidate
+    #[inline]
+    pub fn flush<D>(&mut self, display: &mut D) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let Size { width, height } = self.area.size;
+
+        let mut drawn = 0;
+        for y in 0..height {
+            let mut x = 0;
+            while x < width {
+                if self.valid && self.get(self.frame, x, y) == self.get(self.shown, x, y) {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                while x < width
+                    && !(self.valid && self.get(self.frame, x, y) == self.get(self.shown, x, y))
+                {
+                    x += 1;
+                }
+
+                let run = Rectangle::new(
+                    self.area.top_left + Point::new(start.saturating_as(), y.saturating_as()),
+                    Size::new(x - start, 1),
+                );
+                let frame = &*self.frame;
+                display.fill_contiguous(
+                    &run,
+                    (start..x).map(|x| C::from(C::Raw::from(self.get(frame, x, y)))),
+                )?;
+                drawn += (x - start) as usize;
+            }
+        }
+
+        self.shown.copy_from_slice(self.frame);
+        self.valid = true;
+
+        Ok(drawn)
+    }
+}
+
+impl<C> Dimensions for DoubleBuffer<'_, C> {
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<C> DrawTarget for DoubleBuffer<'_, C>
+where
+    C: PixelColor + From<C::Raw>,
+    C::Raw: From<C> + RawData<Storage = u8>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let Point { x, y } = point - self.area.top_left;
+            if x >= 0
+                && y >= 0
+                && (x as u32) < self.area.size.width
+                && (y as u32) < self.area.size.height
+            {
+                self.set(x as u32, y as u32, C::Raw::from(color).into_inner());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Gray4},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, DoubleBuffer, TextBox};
+
+    #[test]
+    fn only_changed_pixels_are_drawn() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let bounds = Rectangle::new(Point::new(1, 2), size_for(&FONT_6X9, 3, 1));
+
+        let mut frame = [0; 3 * 9];
+        let mut shown = [0; 3 * 9];
+        assert!(DoubleBuffer::<BinaryColor>::new(bounds, &mut frame, &mut shown[..20]).is_none());
+
+        let mut buffer = DoubleBuffer::<BinaryColor>::new(bounds, &mut frame, &mut shown).unwrap();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        TextBox::new("abc", bounds, character_style)
+            .draw(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer.flush(&mut display).unwrap(), 18 * 9);
+        assert_eq!(buffer.flush(&mut display).unwrap(), 0);
+
+        TextBox::new("abd", bounds, character_style)
+            .draw(&mut buffer)
+            .unwrap();
+        assert!(buffer.flush(&mut display).unwrap() < 6 * 9);
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("abd", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn pixels_are_packed() {
+        let area = Rectangle::new(Point::zero(), Size::new(3, 2));
+        assert_eq!(DoubleBuffer::<Gray4>::buffer_len(area.size), 4);
+
+        let mut frame = [0; 4];
+        let mut shown = [0; 4];
+        let mut buffer = DoubleBuffer::<Gray4>::new(area, &mut frame, &mut shown).unwrap();
+
+        let pixels = [
+            Pixel(Point::new(0, 0), Gray4::new(0xA)),
+            Pixel(Point::new(2, 0), Gray4::new(0x5)),
+            Pixel(Point::new(1, 1), Gray4::new(0xF)),
+            Pixel(Point::new(3, 1), Gray4::WHITE),
+        ];
+        buffer.draw_iter(pixels.iter().copied()).unwrap();
+        buffer.flush(&mut MockDisplay::new()).unwrap();
+
+        assert_eq!(frame, [0xA0, 0x50, 0x0F, 0x00]);
+    }
+}
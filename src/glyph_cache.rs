@@ -0,0 +1,353 @@
+//! Memoization of glyph widths.
+
+use core::{cell::Cell, fmt};
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+/// The number of glyph widths stored by a cache.
+const CACHE_SIZE: usize = 128;
+
+/// A cached glyph width.
+type Entry = Option<(char, u32)>;
+
+/// Storage for the widths of recently measured glyphs.
+///
+/// Laying out a long text measures the same characters many times. Monospaced fonts measure
+/// characters quickly, but proportional [`TextRenderer`] implementations may have to look up
+/// every glyph in their font data. Character styles wrapped by [`GlyphWidthCache::wrap`] only ask
+/// the wrapped style to measure characters that are not in the cache.
+///
+/// The cache has room for 128 characters, and stores the width of a character in the slot
+/// selected by its code point, so ASCII text never evicts its own glyphs. A cache must only be
+/// used with a single font, because glyphs are only identified by their characters.
+///
+/// *Note:* the widths of strings are calculated by adding the widths of their characters, so
+/// kerning between characters is not applied when measuring text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{GlyphWidthCache, TextBox};
+///
+/// let cache = GlyphWidthCache::new();
+/// let character_style = cache.wrap(MonoTextStyle::new(&FONT_6X9, BinaryColor::On));
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("Hello, World!", bounds, character_style)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`TextRenderer`]: https://docs.rs/embedded-graphics/0.7/embedded_graphics/text/renderer/trait.TextRenderer.html
+/// [`GlyphWidthCache::wrap`]: #method.wrap
+#[derive(Clone)]
+pub struct GlyphWidthCache {
+    entries: Cell<[Entry; CACHE_SIZE]>,
+}
+
+impl fmt::Debug for GlyphWidthCache {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlyphWidthCache").finish()
+    }
+}
+
+impl Default for GlyphWidthCache {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlyphWidthCache {
+    /// Creates an empty cache.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: Cell::new([None; CACHE_SIZE]),
+        }
+    }
+
+    /// Wraps a character style, so it uses the cache to measure text.
+    #[inline]
+    pub fn wrap<S>(&self, character_style: S) -> CachedWidths<'_, S> {
+        CachedWidths {
+            character_style,
+            cache: self,
+        }
+    }
+
+    /// Removes every width from the cache.
+    #[inline]
+    pub fn clear(&self) {
+        self.entries.set([None; CACHE_SIZE]);
+    }
+
+    /// Returns the width of `c`, measuring it with `renderer` if it's not in the cache.
+    fn width(&self, renderer: &impl TextRenderer, c: char) -> u32 {
+        let entries: &Cell<[Entry]> = &self.entries;
+        let entry = &entries.as_slice_of_cells()[c as usize % CACHE_SIZE];
+
+        match entry.get() {
+            Some((cached, width)) if cached == c => width,
+            _ => {
+                let mut buffer = [0; 4];
+                let width = renderer
+                    .measure_string(c.encode_utf8(&mut buffer), Point::zero(), Baseline::Top)
+                    .next_position
+                    .x
+                    .saturating_as();
+                entry.set(Some((c, width)));
+
+                width
+            }
+        }
+    }
+}
+
+/// A character style that measures text using a [`GlyphWidthCache`].
+///
+/// This struct is created by [`GlyphWidthCache::wrap`]. Copies of the character style share the
+/// same cache.
+///
+/// [`GlyphWidthCache`]: struct.GlyphWidthCache.html
+/// [`GlyphWidthCache::wrap`]: struct.GlyphWidthCache.html#method.wrap
+#[derive(Clone, Debug)]
+pub struct CachedWidths<'c, S> {
+    character_style: S,
+    cache: &'c GlyphWidthCache,
+}
+
+impl<'c, S> CachedWidths<'c, S> {
+    /// Returns the wrapped character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+}
+
+impl<'c, S> TextRenderer for CachedWidths<'c, S>
+where
+    S: TextRenderer,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.character_style
+            .draw_string(text, position, baseline, target)
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.character_style
+            .draw_whitespace(width, position, baseline, target)
+    }
+
+    /// Measures `text` by adding the cached widths of its characters.
+    ///
+    /// The returned bounding box is as wide as the text advances the position.
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width = text.chars().fold(0_u32, |width, c| {
+            width.saturating_add(self.cache.width(&self.character_style, c))
+        });
+
+        let empty = self.character_style.measure_string("", position, baseline);
+
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                empty.bounding_box.top_left,
+                Size::new(width, empty.bounding_box.size.height),
+            ),
+            next_position: position + Point::new(width.saturating_as(), 0),
+        }
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.character_style.line_height()
+    }
+}
+
+impl<'c, S> CharacterStyle for CachedWidths<'c, S>
+where
+    S: CharacterStyle,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.character_style.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.character_style.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.character_style.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.character_style
+            .set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::cell::Cell;
+
+    use embedded_graphics::{
+        draw_target::DrawTarget,
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+        text::{
+            renderer::{CharacterStyle, TextMetrics, TextRenderer},
+            Baseline,
+        },
+    };
+
+    use crate::{utils::test::size_for, GlyphWidthCache, TextBox};
+
+    /// Character style that counts the measured characters.
+    #[derive(Clone)]
+    struct CountingStyle<'a> {
+        style: MonoTextStyle<'static, BinaryColor>,
+        measured: &'a Cell<usize>,
+    }
+
+    impl CharacterStyle for CountingStyle<'_> {
+        type Color = BinaryColor;
+    }
+
+    impl TextRenderer for CountingStyle<'_> {
+        type Color = BinaryColor;
+
+        fn draw_string<D>(
+            &self,
+            text: &str,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.style.draw_string(text, position, baseline, target)
+        }
+
+        fn draw_whitespace<D>(
+            &self,
+            width: u32,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.style
+                .draw_whitespace(width, position, baseline, target)
+        }
+
+        fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+            self.measured
+                .set(self.measured.get() + text.chars().count());
+            self.style.measure_string(text, position, baseline)
+        }
+
+        fn line_height(&self) -> u32 {
+            self.style.line_height()
+        }
+    }
+
+    #[test]
+    fn characters_are_measured_once() {
+        let measured = Cell::new(0);
+        let cache = GlyphWidthCache::new();
+        let style = cache.wrap(CountingStyle {
+            style: MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+            measured: &measured,
+        });
+
+        let width = |text| {
+            style
+                .measure_string(text, Point::zero(), Baseline::Top)
+                .next_position
+                .x
+        };
+
+        assert_eq!(width("abba"), 24);
+        assert_eq!(measured.get(), 2);
+
+        assert_eq!(width("ab c"), 24);
+        assert_eq!(measured.get(), 4);
+
+        cache.clear();
+        assert_eq!(width("a"), 6);
+        assert_eq!(measured.get(), 5);
+    }
+
+    #[test]
+    fn cached_style_renders_the_same() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let cache = GlyphWidthCache::new();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+        let text = "The quick brown fox";
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, cache.wrap(character_style))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
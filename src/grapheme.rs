@@ -0,0 +1,127 @@
+//! Splitting text into user-perceived characters.
+//!
+//! A user-perceived character, or grapheme cluster, may consist of multiple `char`s, for example a
+//! letter followed by combining accents, or an emoji with a skin tone modifier. Lines are never
+//! broken inside a cluster, and plugins that count characters count clusters.
+//!
+//! Only the most common cluster forms are recognized, which doesn't need the Unicode property
+//! tables of a full segmentation algorithm:
+//!  * combining marks, variation selectors, emoji modifiers and tag characters extend the
+//!    previous character,
+//!  * characters joined by a zero-width joiner form a single cluster,
+//!  * pairs of regional indicators, which make up flags, form a single cluster.
+use az::SaturatingAs;
+
+/// Zero-width joiner.
+const ZWJ: char = '\u{200d}';
+
+/// Returns whether `c` extends the preceding character.
+fn is_extending(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036f}' // Combining Diacritical Marks
+        | '\u{0483}'..='\u{0489}' // Combining Cyrillic
+        | '\u{1ab0}'..='\u{1aff}' // Combining Diacritical Marks Extended
+        | '\u{1dc0}'..='\u{1dff}' // Combining Diacritical Marks Supplement
+        | '\u{20d0}'..='\u{20ff}' // Combining Diacritical Marks for Symbols
+        | '\u{fe00}'..='\u{fe0f}' // Variation Selectors
+        | '\u{fe20}'..='\u{fe2f}' // Combining Half Marks
+        | ZWJ
+        | '\u{1f3fb}'..='\u{1f3ff}' // Emoji modifiers
+        | '\u{e0020}'..='\u{e007f}' // Tags
+        | '\u{e0100}'..='\u{e01ef}' // Variation Selectors Supplement
+    )
+}
+
+/// Returns whether `c` is a regional indicator symbol.
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1f1e6}'..='\u{1f1ff}')
+}
+
+/// Iterator over the grapheme clusters of a string, and their byte offsets.
+#[derive(Clone, Debug)]
+pub struct Graphemes<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.text[self.offset..];
+        let mut chars = rest.char_indices();
+
+        let (_, mut prev) = chars.next()?;
+        let mut regional_indicators = is_regional_indicator(prev) as u8;
+
+        let mut len = rest.len();
+        for (idx, c) in chars {
+            let joined = is_extending(c)
+                || prev == ZWJ
+                || (regional_indicators == 1 && is_regional_indicator(c));
+
+            if !joined {
+                len = idx;
+                break;
+            }
+
+            if is_regional_indicator(c) {
+                regional_indicators += 1;
+            }
+            prev = c;
+        }
+
+        let start = self.offset;
+        self.offset += len;
+
+        Some((start, &rest[..len]))
+    }
+}
+
+/// Returns an iterator over the grapheme clusters of `s`.
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { text: s, offset: 0 }
+}
+
+/// Returns the number of grapheme clusters in `s`.
+pub fn grapheme_count(s: &str) -> u32 {
+    graphemes(s).count().saturating_as()
+}
+
+/// Returns the byte offset of the `n`th grapheme cluster of `s`, or the length of `s` if `s` has
+/// `n` or fewer clusters.
+#[cfg(any(feature = "plugin-marquee", feature = "plugin-typewriter", test))]
+pub fn grapheme_offset(s: &str, n: u32) -> usize {
+    match graphemes(s).nth(n as usize) {
+        Some((idx, _)) => idx,
+        None => s.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{grapheme_count, grapheme_offset, graphemes};
+
+    #[test]
+    fn clusters_are_not_split() {
+        let text =
+            "e\u{301}a\u{1f44d}\u{1f3fd}\u{1f1ed}\u{1f1fa}\u{1f1e9}\u{1f468}\u{200d}\u{1f4bb}";
+
+        assert_eq!(
+            graphemes(text)
+                .map(|(_, g)| g)
+                .collect::<std::vec::Vec<_>>(),
+            [
+                "e\u{301}",
+                "a",
+                "\u{1f44d}\u{1f3fd}",
+                "\u{1f1ed}\u{1f1fa}",
+                "\u{1f1e9}",
+                "\u{1f468}\u{200d}\u{1f4bb}",
+            ]
+        );
+        assert_eq!(grapheme_count(text), 6);
+        assert_eq!(grapheme_offset(text, 1), 3);
+        assert_eq!(grapheme_offset(text, 6), text.len());
+    }
+}
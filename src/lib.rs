@@ -89,48 +89,152 @@
 //! }
 //! ```
 //!
+//! ## Rendering from restricted contexts
+//!
+//! The rendering code doesn't use any `static` state and doesn't allocate, so independent text
+//! boxes can be drawn from interrupt handlers or other contexts that can preempt each other. The
+//! only mutable state used while drawing lives in the `TextBox` object itself. The crate denies
+//! `unsafe_code`, which keeps `static mut` items out of the rendering code.
+//!
+//! Plugins and callbacks are user code and may not uphold these guarantees. Use
+//! [`TextBox::render_no_alloc_no_static`], which is only available for text boxes without
+//! plugins, and checks that no callbacks are set in debug builds, where this matters.
+//!
 //! ## Cargo features
 //!
 //! * `ansi`: enables ANSI sequence support. This feature is enabled by default.
 //! * `plugin` (*experimental*): allows the user to implement plugins.
+//! * `alloc`: enables types that use heap allocation, like [`OwnedTextBox`] and boxed plugins.
+//! * `buffered`: enables [`TextBox::draw_buffered`] to render lines into a buffer before drawing
+//!   them, and [`DoubleBuffer`] to draw into a packed frame buffer and only update the changed
+//!   pixels of the display.
+//! * `glyph-cache`: enables [`GlyphWidthCache`] to remember the widths of glyphs measured by
+//!   proportional fonts.
+//! * `document`: enables the [`document`] module, which builds multi-style text from typed spans
+//!   instead of escape sequences.
+//! * `snapshot`: enables [`TextBox::render_snapshot`] to write golden-file tests of plugins and
+//!   styles. Implies `alloc`.
+//! * `plugins`: enables every built-in plugin below, and the `ansi` feature. The plugins can also
+//!   be enabled one by one:
+//!   * `plugin-gutter`: [`Gutter`] displays line numbers or labels next to the text.
+//!   * `plugin-keywords`: [`Keywords`] and [`Highlight`] color words using a keyword table or a
+//!     function.
+//!   * `plugin-marquee`: [`Marquee`] scrolls paragraphs horizontally.
+//!   * `plugin-masking`: [`Masking`] hides the text behind mask characters, like password fields.
+//!   * `plugin-typewriter`: [`Typewriter`] reveals the text character by character.
+//!   * `plugin-whitespace`: [`VisibleWhitespace`] marks spaces and tabs.
+//!
+//! Other built-in plugins, like markup and hyphenation, are always available
+//! in the [`plugin`] module.
 //!
 //! [embedded-graphics]: https://github.com/embedded-graphics/embedded-graphics/
 //! [the embedded-graphics simulator]: https://github.com/embedded-graphics/embedded-graphics/tree/master/simulator
 //! [simulator README]: https://github.com/embedded-graphics/embedded-graphics/tree/master/simulator#usage-without-sdl2
 //! [`TextBox`]: ./struct.TextBox.html
+//! [`OwnedTextBox`]: ./struct.OwnedTextBox.html
+//! [`TextBox::render_no_alloc_no_static`]: ./struct.TextBox.html#method.render_no_alloc_no_static
+//! [`TextBox::render_snapshot`]: ./struct.TextBox.html#method.render_snapshot
+//! [`TextBox::draw_buffered`]: ./struct.TextBox.html#method.draw_buffered
+//! [`GlyphWidthCache`]: ./struct.GlyphWidthCache.html
+//! [`DoubleBuffer`]: ./struct.DoubleBuffer.html
+//! [`document`]: ./document/index.html
+//! [`Gutter`]: ./plugin/gutter/struct.Gutter.html
+//! [`Keywords`]: ./plugin/keywords/struct.Keywords.html
+//! [`Highlight`]: ./plugin/keywords/struct.Highlight.html
+//! [`Marquee`]: ./plugin/marquee/struct.Marquee.html
+//! [`Masking`]: ./plugin/masking/struct.Masking.html
+//! [`Typewriter`]: ./plugin/typewriter/struct.Typewriter.html
+//! [`VisibleWhitespace`]: ./plugin/whitespace/struct.VisibleWhitespace.html
+//! [`plugin`]: ./plugin/index.html
 //! [`Horizontal`]: ./alignment/enum.HorizontalAlignment.html
 //! [`Vertical`]: ./alignment/enum.VerticalAlignment.html
 
 #![cfg_attr(not(test), no_std)]
 #![deny(clippy::missing_inline_in_public_items)]
 #![deny(clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
 #![deny(missing_docs)]
+#![deny(unsafe_code)]
 #![warn(clippy::all)]
 #![allow(clippy::needless_doctest_main)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod adapter;
 pub mod alignment;
+mod appender;
+mod batch;
+mod cells;
+pub mod diff;
+#[cfg(feature = "document")]
+pub mod document;
+#[cfg(feature = "buffered")]
+mod double_buffer;
+#[cfg(feature = "glyph-cache")]
+mod glyph_cache;
+mod grapheme;
+mod minimal;
+mod overlay;
+#[cfg(feature = "alloc")]
+mod owned;
+pub mod panic;
 mod parser;
 pub mod plugin;
 mod rendering;
+mod rotated;
+mod source;
+mod stored;
 pub mod style;
+mod vertical;
+pub mod widget;
+mod writer;
 
 mod utils;
 
+use core::ops::Range;
+
 use crate::{
     alignment::{HorizontalAlignment, VerticalAlignment},
     plugin::{NoPlugin, PluginMarker as Plugin, PluginWrapper},
-    style::TextBoxStyle,
+    style::{Insets, StyledRanges, TextBoxStyle, Theme},
 };
+pub use appender::TextBoxAppender;
+pub use batch::TextBoxBatch;
+pub use cells::{CellStyle, CharWidth};
+#[cfg(feature = "buffered")]
+pub use double_buffer::DoubleBuffer;
 use embedded_graphics::{
     geometry::{Dimensions, Point},
     pixelcolor::Rgb888,
     primitives::Rectangle,
-    text::renderer::{CharacterStyle, TextRenderer},
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        DecorationColor,
+    },
     transform::Transform,
 };
+#[cfg(feature = "glyph-cache")]
+pub use glyph_cache::{CachedWidths, GlyphWidthCache};
+pub use minimal::MinimalTextBox;
 use object_chain::{Chain, ChainElement, Link};
+pub use overlay::Overlay;
+#[cfg(feature = "alloc")]
+pub use owned::OwnedTextBox;
 pub use parser::{ChangeTextStyle, Token};
-pub use rendering::TextBoxProperties;
+use rendering::WordColorFn;
+pub use rendering::{
+    hit_test::{LineGap, TextPosition},
+    layout::{LineMeasurementIter, LineMetrics, LineMetricsIter, Overflow, ScrolledOff},
+    regions::{Region, Regions},
+    session::RenderSession,
+    DrawProgress, TextBoxProperties,
+};
+pub use rotated::{Mirror, Mirrored, Rotated, Rotation};
+pub use source::{LossyTextBox, TextSource};
+pub use stored::StoredTextBox;
+pub use vertical::{ColumnOrder, VerticalTextBox};
+pub use writer::TextBoxWriter;
 
 /// A text box object.
 ///
@@ -167,6 +271,20 @@ where
     pub vertical_offset: i32,
 
     plugin: PluginWrapper<'a, M, S::Color>,
+
+    word_color: Option<WordColorFn<S::Color>>,
+
+    fonts: &'a [S],
+
+    styled_ranges: Option<StyledRanges<'a, S::Color>>,
+
+    padding: Insets,
+
+    theme: Option<&'a Theme>,
+
+    exclusions: &'a [Rectangle],
+
+    line_span: Option<fn(u32, i32) -> Range<i32>>,
 }
 
 impl<'a, S> TextBox<'a, S, NoPlugin<<S as TextRenderer>::Color>>
@@ -194,7 +312,14 @@ where
             character_style,
             style: textbox_style,
             vertical_offset: 0,
-            plugin: PluginWrapper::new(NoPlugin::new()),
+            plugin: PluginWrapper::without_plugin(),
+            word_color: None,
+            fonts: &[],
+            styled_ranges: None,
+            padding: Insets::default(),
+            theme: None,
+            exclusions: &[],
+            line_span: None,
         };
 
         styled.style.height_mode.apply(&mut styled);
@@ -254,6 +379,13 @@ where
             style: self.style,
             vertical_offset: self.vertical_offset,
             plugin: PluginWrapper::new(Chain::new(plugin)),
+            word_color: self.word_color,
+            fonts: self.fonts,
+            styled_ranges: self.styled_ranges,
+            padding: self.padding,
+            theme: self.theme,
+            exclusions: self.exclusions,
+            line_span: self.line_span,
         };
         textbox.style.height_mode.apply(&mut textbox);
 
@@ -268,6 +400,8 @@ where
     P: Plugin<'a, <S as TextRenderer>::Color> + ChainElement,
 {
     /// Adds a new plugin to the `TextBox`.
+    ///
+    /// The new plugin reads the tokens produced by the plugins that were added before it.
     #[inline]
     pub fn add_plugin<M>(self, plugin: M) -> TextBox<'a, S, Link<M, P>>
     where
@@ -282,6 +416,13 @@ where
             style: self.style,
             vertical_offset: self.vertical_offset,
             plugin: PluginWrapper::new(parent.plugin.append(plugin)),
+            word_color: self.word_color,
+            fonts: self.fonts,
+            styled_ranges: self.styled_ranges,
+            padding: self.padding,
+            theme: self.theme,
+            exclusions: self.exclusions,
+            line_span: self.line_span,
         };
         textbox.style.height_mode.apply(&mut textbox);
 
@@ -310,6 +451,16 @@ where
     }
 }
 
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer,
+{
+    /// Returns the area the text is laid out in.
+    pub(crate) fn text_bounds(&self) -> Rectangle {
+        self.padding.shrink(self.bounds)
+    }
+}
+
 impl<'a, S, M> Dimensions for TextBox<'a, S, M>
 where
     S: TextRenderer,
@@ -321,16 +472,99 @@ where
     }
 }
 
+/// Changing the character style of a `TextBox` changes the default style of the text. ANSI
+/// sequences that reset the text style (`\x1b[0m`) restore the changed style.
+impl<'a, S, M> CharacterStyle for TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Color = <S as CharacterStyle>::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.character_style.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.character_style.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.character_style.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.character_style
+            .set_strikethrough_color(strikethrough_color);
+    }
+}
+
 impl<'a, S, M> TextBox<'a, S, M>
 where
     S: TextRenderer,
     M: Plugin<'a, S::Color>,
     S::Color: From<Rgb888>,
 {
+    /// Sets the padding between the bounding box and the text.
+    ///
+    /// The text is laid out and drawn inside the bounding box shrunk by `padding`, while the
+    /// bounding box itself is kept, so it can still be used to draw backgrounds and borders
+    /// around the text. The [`BackgroundFill`] style fills the outer bounding box.
+    ///
+    /// The height mode of the style is applied again: text boxes that fit their height to the
+    /// text include the top and bottom padding in their height. [`HeightMode::ShrinkToText`]
+    /// doesn't make the text box taller than it is.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::{PrimitiveStyle, Rectangle},
+    /// };
+    /// use embedded_text::{style::Insets, TextBox};
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 20));
+    ///
+    /// let text_box = TextBox::new("Hello", bounds, character_style).with_padding(Insets::uniform(2));
+    ///
+    /// let mut display = MockDisplay::new();
+    /// text_box
+    ///     .bounding_box()
+    ///     .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+    ///     .draw(&mut display)
+    ///     .unwrap();
+    /// text_box.draw(&mut display).unwrap();
+    /// ```
+    ///
+    /// [`BackgroundFill`]: style/enum.BackgroundFill.html
+    /// [`HeightMode::ShrinkToText`]: style/enum.HeightMode.html#variant.ShrinkToText
+    #[inline]
+    pub fn with_padding(mut self, padding: Insets) -> Self {
+        self.padding = padding;
+        self.style.height_mode.apply(&mut self);
+
+        self
+    }
+
+    /// Returns the padding between the bounding box and the text.
+    #[inline]
+    pub fn padding(&self) -> Insets {
+        self.padding
+    }
+
     /// Sets the height of the [`TextBox`] to the height of the text.
     #[inline]
     pub fn fit_height(&mut self) -> &mut Self {
-        self.fit_height_limited(u32::max_value())
+        self.fit_height_limited(u32::MAX)
     }
 
     /// Sets the height of the [`TextBox`] to the height of the text, limited to `max_height`.
@@ -339,20 +573,249 @@ where
     /// `max_height` pixel vertical space.
     #[inline]
     pub fn fit_height_limited(&mut self, max_height: u32) -> &mut Self {
-        // Measure text given the width of the textbox
+        // Measure text given the width of the lines of the textbox
         let text_height = self
-            .style
-            .measure_text_height(
-                &self.character_style,
+            .measure_lines(
+                PluginWrapper::new(NoPlugin::new()),
+                &[],
                 self.text,
-                self.bounding_box().size.width,
+                &self.top_cursor(),
             )
+            .height
+            .saturating_add(self.padding.vertical())
             .min(max_height)
-            .min(i32::max_value() as u32);
+            .min(i32::MAX as u32);
 
         // Apply height
         self.bounds.size.height = text_height;
 
         self
     }
+
+    /// Sets the width of the [`TextBox`] to the width of the widest line of the text.
+    ///
+    /// Lines are only broken at line break characters when they are measured. The width is not
+    /// fitted automatically, so text boxes that are created with a width of 0 must call this
+    /// method before they are drawn or measured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(0, 9));
+    ///
+    /// let mut text_box = TextBox::new("Badge", bounds, character_style);
+    /// text_box.fit_width();
+    ///
+    /// assert_eq!(text_box.bounding_box().size, Size::new(30, 9));
+    /// ```
+    #[inline]
+    pub fn fit_width(&mut self) -> &mut Self {
+        self.fit_width_limited(u32::MAX)
+    }
+
+    /// Sets the width of the [`TextBox`] to the width of the widest line of the text, limited to
+    /// `max_width`.
+    ///
+    /// Lines that are wider than the limit are wrapped. The height mode of the text box is
+    /// applied again using the new width.
+    #[inline]
+    pub fn fit_width_limited(&mut self, max_width: u32) -> &mut Self {
+        // Measure text without wrapping
+        let text_width = self
+            .style
+            .measure_text(&self.character_style, self.text, i32::MAX as u32)
+            .width
+            .saturating_add(self.padding.horizontal())
+            .min(max_width)
+            .min(i32::MAX as u32);
+
+        // Apply width
+        self.bounds.size.width = text_width;
+        self.style.height_mode.apply(self);
+
+        self
+    }
+
+    /// Sets a callback that selects the text color of individual words.
+    ///
+    /// The callback is called with the index of the word in the text and the word itself. If it
+    /// returns `Some(color)`, the word is drawn using that text color, otherwise the current
+    /// character style is used. Words are separated by whitespace and line breaks. Words that are
+    /// broken up, for example by wrapping, are passed to the callback using their first part, and
+    /// all parts are drawn using the selected color.
+    ///
+    /// This is a lightweight alternative to implementing a plugin, for example to highlight
+    /// log levels:
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::Rgb888,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// fn log_level_color(_index: usize, word: &str) -> Option<Rgb888> {
+    ///     match word {
+    ///         "ERROR" => Some(Rgb888::RED),
+    ///         "WARN" => Some(Rgb888::YELLOW),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// let mut text_box = TextBox::new("ERROR disk full", bounds, character_style);
+    /// text_box.set_word_color(log_level_color);
+    ///
+    /// let mut display = MockDisplay::new();
+    /// text_box.draw(&mut display).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_word_color(&mut self, callback: fn(usize, &str) -> Option<S::Color>) -> &mut Self {
+        self.word_color = Some(WordColorFn(callback));
+        self
+    }
+
+    /// Sets the theme that provides the text and background colors when the text box is drawn.
+    ///
+    /// The colors of the theme are read on every draw, so changing them changes the colors of
+    /// every text box that uses the theme. See [`Theme`] for more information.
+    ///
+    /// [`Theme`]: style/struct.Theme.html
+    #[inline]
+    pub fn set_theme(&mut self, theme: &'a Theme) -> &mut Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Sets the areas that the text flows around, like an icon in the corner of the text box.
+    ///
+    /// The areas are relative to the top left corner of the bounding box. Lines that overlap an
+    /// area are shortened: if there is more room to the right of the area, the line starts after
+    /// it, otherwise the line ends before it. The areas are not drawn.
+    ///
+    /// The shortened lines are also used to measure the text, by the height mode, the vertical
+    /// alignment, the background fill and [`line_metrics`]. The height of the text is measured
+    /// with its first line at the top of the text area, so text that is moved down by the vertical
+    /// alignment may overlap different areas when it is drawn.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 27));
+    ///
+    /// // Room for a 16x16 icon in the top left corner.
+    /// let icon = [Rectangle::new(Point::zero(), Size::new(16, 16))];
+    ///
+    /// let mut text_box = TextBox::new("The text flows around the icon.", bounds, character_style);
+    /// text_box.set_exclusions(&icon);
+    ///
+    /// let mut display = MockDisplay::new();
+    /// text_box.draw(&mut display).unwrap();
+    /// ```
+    ///
+    /// [`line_metrics`]: #method.line_metrics
+    #[inline]
+    pub fn set_exclusions(&mut self, exclusions: &'a [Rectangle]) -> &mut Self {
+        self.exclusions = exclusions;
+        self.style.height_mode.apply(self);
+        self
+    }
+
+    /// Sets the callback that returns the horizontal span of every line, for text boxes that are
+    /// not rectangular, like text on a round display.
+    ///
+    /// The callback receives the index of the line and its vertical position, and returns the
+    /// range of horizontal positions the line may occupy. Positions are relative to the top left
+    /// corner of the bounding box. Lines are also limited by the bounding box. Like the excluded
+    /// areas, the spans are also used to measure the text, with its first line at the top of the
+    /// text area.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::ops::Range;
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// /// Returns the span of a line inside a circle with a diameter of 60 pixels.
+    /// fn circle(_line: u32, y: i32) -> Range<i32> {
+    ///     let y = y + 4 - 30;
+    ///     let mut half_width = 0;
+    ///     while (half_width + 1) * (half_width + 1) + y * y <= 30 * 30 {
+    ///         half_width += 1;
+    ///     }
+    ///
+    ///     30 - half_width..30 + half_width
+    /// }
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 60));
+    ///
+    /// let mut text_box = TextBox::new("Text inside a round display", bounds, character_style);
+    /// text_box.set_line_span(circle);
+    ///
+    /// let mut display = MockDisplay::new();
+    /// text_box.draw(&mut display).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_line_span(&mut self, callback: fn(u32, i32) -> Range<i32>) -> &mut Self {
+        self.line_span = Some(callback);
+        self.style.height_mode.apply(self);
+        self
+    }
+
+    /// Sets the character styles that can be selected using [`ChangeTextStyle::Font`] tokens.
+    ///
+    /// Plugins can emit a `ChangeTextStyle::Font(index)` token to draw the following text using
+    /// `fonts[index]`, for example to display headings using a larger font than the body text.
+    /// `ChangeTextStyle::Reset` switches back to the character style of the text box. Lines that
+    /// contain text in multiple fonts are as tall as their tallest font.
+    ///
+    /// [`ChangeTextStyle::Font`]: enum.ChangeTextStyle.html#variant.Font
+    #[inline]
+    pub fn set_fonts(&mut self, fonts: &'a [S]) -> &mut Self {
+        self.fonts = fonts;
+        self
+    }
+
+    /// Sets the styles applied to ranges of the text.
+    ///
+    /// See [`StyledRanges`] for more information.
+    ///
+    /// [`StyledRanges`]: style/struct.StyledRanges.html
+    #[inline]
+    pub fn set_styled_ranges(&mut self, ranges: StyledRanges<'a, S::Color>) -> &mut Self {
+        self.styled_ranges = Some(ranges);
+        self
+    }
 }
@@ -0,0 +1,375 @@
+//! Text box with a minimal renderer.
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Dimensions, Point},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+    Drawable,
+};
+
+use crate::utils::str_width;
+
+/// A text box with a minimal renderer.
+///
+/// `MinimalTextBox` is meant for screens that must be displayed when resources are scarce, like
+/// bootloader or panic handler screens. It doesn't support plugins, ANSI escape sequences or
+/// text box styles. The text is always left and top aligned, and lines that don't completely fit
+/// into the bounding box are not drawn, like in a [`TextBox`] with the default style.
+///
+/// Words are wrapped at spaces and newline characters, and words that are wider than the text box
+/// are broken at any character. Spaces at the start of wrapped lines and at the end of lines are
+/// not drawn. Other characters, including tabs and escape characters are drawn as they are by the
+/// character style.
+///
+/// The renderer doesn't recurse, and it doesn't allocate. Apart from the text box, it only keeps
+/// a reference to the character style, a cursor position and a few string slices on the stack,
+/// so its stack usage doesn't depend on the text.
+///
+/// # Stack usage
+///
+/// The worst case stack usage of drawing a text box with a `MonoTextStyle` using `FONT_6X10` and
+/// `BinaryColor` into a draw target that doesn't use the stack was measured to be:
+///
+/// | Text box         | Stack usage |
+/// |------------------|-------------|
+/// | `MinimalTextBox` | 504 bytes   |
+/// | [`TextBox`]      | 2576 bytes  |
+///
+/// The numbers include the frame of the calling function. They were measured for the
+/// `thumbv7em-none-eabihf` target, built with `opt-level = "s"`, LTO, `panic = "abort"` and
+/// default features disabled, using rustc 1.97.0-nightly. The frame sizes were emitted using
+/// `-Z emit-stack-sizes`, and the worst case was found by walking the call graph of the
+/// disassembled object code, ignoring panic paths. The results depend on the compiler version,
+/// the target and the optimization settings, so measure your own configuration if the margin is
+/// small.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::MinimalTextBox;
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut display = MockDisplay::new();
+/// MinimalTextBox::new("PANIC at main.rs:12", bounds, character_style)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Clone, Debug, Hash)]
+#[must_use]
+pub struct MinimalTextBox<'a, S> {
+    /// The text to be displayed.
+    pub text: &'a str,
+
+    /// The bounding box.
+    pub bounds: Rectangle,
+
+    /// The character style.
+    pub character_style: S,
+}
+
+impl<'a, S> MinimalTextBox<'a, S>
+where
+    S: TextRenderer,
+{
+    /// Creates a new text box.
+    #[inline]
+    pub const fn new(text: &'a str, bounds: Rectangle, character_style: S) -> Self {
+        Self {
+            text,
+            bounds,
+            character_style,
+        }
+    }
+}
+
+impl<S> Dimensions for MinimalTextBox<'_, S> {
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+/// Tracks the position of the next character.
+struct LineWriter<'s, S> {
+    style: &'s S,
+    bounds: Rectangle,
+    line_height: i32,
+    position: Point,
+}
+
+impl<S> LineWriter<'_, S>
+where
+    S: TextRenderer,
+{
+    /// Returns the horizontal space left in the current line.
+    fn space(&self) -> u32 {
+        (self.bounds.top_left.x + self.bounds.size.width.saturating_as::<i32>() - self.position.x)
+            .saturating_as()
+    }
+
+    /// Returns whether nothing was drawn in the current line.
+    fn is_empty(&self) -> bool {
+        self.position.x == self.bounds.top_left.x
+    }
+
+    /// Moves to the next line. Returns `false` if the line doesn't fit into the bounding box.
+    fn new_line(&mut self) -> bool {
+        self.position = Point::new(self.bounds.top_left.x, self.position.y + self.line_height);
+        self.line_fits()
+    }
+
+    /// Returns whether the current line fits into the bounding box.
+    fn line_fits(&self) -> bool {
+        self.position.y + self.line_height
+            <= self.bounds.top_left.y + self.bounds.size.height.saturating_as::<i32>()
+    }
+
+    fn draw_spaces<D>(&mut self, spaces: &str, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = S::Color>,
+    {
+        let width = str_width(self.style, spaces);
+        self.position = self
+            .style
+            .draw_whitespace(width, self.position, Baseline::Top, display)?;
+        Ok(())
+    }
+
+    /// Draws a word, breaking it into multiple lines if necessary.
+    ///
+    /// Returns `false` if the word didn't fit into the bounding box.
+    fn draw_word<D>(&mut self, mut word: &str, display: &mut D) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = S::Color>,
+    {
+        while !word.is_empty() {
+            let space = self.space();
+
+            // Find the longest part that fits, but draw at least one character in empty lines.
+            let mut split = 0;
+            let mut width = 0;
+            for (idx, c) in word.char_indices() {
+                let end = idx + c.len_utf8();
+                width += str_width(self.style, &word[idx..end]);
+                if width > space && (split > 0 || !self.is_empty()) {
+                    break;
+                }
+                split = end;
+            }
+
+            if split == 0 {
+                if !self.new_line() {
+                    return Ok(false);
+                }
+                continue;
+            }
+
+            self.position =
+                self.style
+                    .draw_string(&word[..split], self.position, Baseline::Top, display)?;
+            word = &word[split..];
+        }
+
+        Ok(true)
+    }
+}
+
+impl<'a, S> Drawable for MinimalTextBox<'a, S>
+where
+    S: TextRenderer,
+{
+    type Color = S::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut display = display.clipped(&self.bounds);
+        let mut writer = LineWriter {
+            style: &self.character_style,
+            bounds: self.bounds,
+            line_height: self.character_style.line_height().saturating_as(),
+            position: self.bounds.top_left,
+        };
+
+        if !writer.line_fits() {
+            return Ok(());
+        }
+
+        for (index, paragraph) in self.text.split('\n').enumerate() {
+            if index > 0 && !writer.new_line() {
+                return Ok(());
+            }
+
+            let mut rest = paragraph;
+            while !rest.is_empty() {
+                let word_start = rest.find(|c| c != ' ').unwrap_or(rest.len());
+                let (spaces, after_spaces) = rest.split_at(word_start);
+                let word_end = after_spaces.find(' ').unwrap_or(after_spaces.len());
+                let (word, next) = after_spaces.split_at(word_end);
+                rest = next;
+
+                if word.is_empty() {
+                    // Spaces at the end of lines are not drawn.
+                    break;
+                }
+
+                let width = str_width(&self.character_style, spaces)
+                    + str_width(&self.character_style, word);
+                if width <= writer.space() || writer.is_empty() {
+                    writer.draw_spaces(spaces, &mut display)?;
+                } else if !writer.new_line() {
+                    return Ok(());
+                }
+
+                if !writer.draw_word(word, &mut display)? {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, MinimalTextBox};
+
+    fn draw(text: &str, size: Size) -> MockDisplay<BinaryColor> {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size);
+
+        let mut display = MockDisplay::new();
+        MinimalTextBox::new(text, bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn wraps_words() {
+        draw("ab cd", size_for(&FONT_6X9, 3, 2)).assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+            "           ",
+            "           ",
+            "           ",
+            "          #",
+            "          #",
+            "  ###   ###",
+            " #     #  #",
+            " #     #  #",
+            "  ###   ###",
+        ]);
+    }
+
+    #[test]
+    fn skips_spaces_at_line_ends() {
+        draw("a  b\nc  ", size_for(&FONT_6X9, 2, 3)).assert_pattern(&[
+            "     ", "     ", "     ", "  ###", " #  #", " #  #", "  ###", "     ", "     ",
+            "     ", " #   ", " #   ", " ### ", " #  #", " #  #", " ### ", "     ", "     ",
+            "     ", "     ", "     ", "  ###", " #   ", " #   ", "  ###",
+        ]);
+    }
+
+    #[test]
+    fn breaks_long_words() {
+        draw("abcde", size_for(&FONT_6X9, 2, 3)).assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+            "           ",
+            "           ",
+            "           ",
+            "          #",
+            "          #",
+            "  ###   ###",
+            " #     #  #",
+            " #     #  #",
+            "  ###   ###",
+            "           ",
+            "           ",
+            "           ",
+            "           ",
+            "           ",
+            "  ##       ",
+            " # ##      ",
+            " ##        ",
+            "  ###      ",
+        ]);
+        draw("a bcd", size_for(&FONT_6X9, 2, 3)).assert_pattern(&[
+            "           ",
+            "           ",
+            "           ",
+            "  ###      ",
+            " #  #      ",
+            " #  #      ",
+            "  ###      ",
+            "           ",
+            "           ",
+            "           ",
+            " #         ",
+            " #         ",
+            " ###    ###",
+            " #  #  #   ",
+            " #  #  #   ",
+            " ###    ###",
+            "           ",
+            "           ",
+            "           ",
+            "    #      ",
+            "    #      ",
+            "  ###      ",
+            " #  #      ",
+            " #  #      ",
+            "  ###      ",
+        ]);
+    }
+
+    #[test]
+    fn draws_full_rows_only() {
+        draw("ab cd", size_for(&FONT_6X9, 2, 1) + Size::new(0, 5)).assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+        ]);
+    }
+}
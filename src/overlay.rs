@@ -0,0 +1,183 @@
+//! Rendering of a text box over an other one.
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Dimensions,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+use crate::{plugin::PluginMarker as Plugin, style::BackgroundFill, TextBox};
+
+/// Draws a text box over an other text box, in the same bounds.
+///
+/// The base text box is drawn as usual. The overlay is laid out in the bounding box of the base,
+/// and only draws the foreground of its glyphs and decorations: its background color, background
+/// fill and border are disabled, so the base text remains visible everywhere else. This makes it
+/// possible to draw highlights or annotations over a text, and to redraw them without touching
+/// the base text box.
+///
+/// *Note:* text style changes in the text of the overlay, like ANSI sequences, can still set a
+/// background color.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{Overlay, TextBox};
+///
+/// let bounds = Rectangle::new(Point::zero(), Size::new(66, 9));
+/// let base_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let highlight_style = MonoTextStyle::new(&FONT_6X9, Rgb888::YELLOW);
+///
+/// // Highlights "World" by drawing it again in a different color.
+/// let overlay = Overlay::new(
+///     TextBox::new("Hello World", bounds, base_style),
+///     TextBox::new("      World", bounds, highlight_style),
+/// );
+///
+/// let mut display = MockDisplay::new();
+/// display.set_allow_overdraw(true);
+/// overlay.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Overlay<'a, S, M, O, N>
+where
+    S: TextRenderer,
+    O: TextRenderer,
+{
+    base: TextBox<'a, S, M>,
+    overlay: TextBox<'a, O, N>,
+}
+
+impl<'a, S, M, O, N> Overlay<'a, S, M, O, N>
+where
+    S: TextRenderer,
+    O: TextRenderer + CharacterStyle,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+    N: Plugin<'a, <O as TextRenderer>::Color>,
+{
+    /// Creates a new object that draws `overlay` over `base`.
+    ///
+    /// The bounding box of `overlay` is replaced by the bounding box of `base`.
+    #[inline]
+    pub fn new(base: TextBox<'a, S, M>, mut overlay: TextBox<'a, O, N>) -> Self {
+        overlay.bounds = base.bounds;
+        overlay.character_style.set_background_color(None);
+        overlay.style.background_fill = BackgroundFill::None;
+        overlay.style.border = None;
+
+        Self { base, overlay }
+    }
+
+    /// Returns the base text box.
+    #[inline]
+    pub fn base(&self) -> &TextBox<'a, S, M> {
+        &self.base
+    }
+
+    /// Returns the overlay text box.
+    #[inline]
+    pub fn overlay(&self) -> &TextBox<'a, O, N> {
+        &self.overlay
+    }
+}
+
+impl<'a, S, M, O, N> Dimensions for Overlay<'a, S, M, O, N>
+where
+    S: TextRenderer,
+    O: TextRenderer,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.base.bounding_box()
+    }
+}
+
+impl<'a, S, M, O, N> Drawable for Overlay<'a, S, M, O, N>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color> + Plugin<'a, <S as CharacterStyle>::Color>,
+    O: TextRenderer<Color = <S as CharacterStyle>::Color>
+        + CharacterStyle<Color = <S as CharacterStyle>::Color>,
+    N: Plugin<'a, <S as CharacterStyle>::Color>,
+{
+    type Color = <S as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(&self, display: &mut D) -> Result<(), D::Error> {
+        self.base.draw(display)?;
+        self.overlay.draw(display)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        style::{BackgroundFill, TextBoxStyleBuilder},
+        utils::test::size_for,
+        Overlay, TextBox,
+    };
+
+    #[test]
+    fn overlay_only_draws_glyphs() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1));
+        let base_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let overlay_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::Off)
+            .background_color(BinaryColor::On)
+            .build();
+        let filled = TextBoxStyleBuilder::new()
+            .background_fill(BackgroundFill::Bounds(Rgb888::WHITE))
+            .build();
+
+        let overlay = Overlay::new(
+            TextBox::new("a", bounds, base_style),
+            TextBox::with_textbox_style(
+                "  b",
+                Rectangle::new(Point::new(5, 5), Size::new(1, 1)),
+                overlay_style,
+                filled,
+            ),
+        );
+
+        let mut display = MockDisplay::new();
+        overlay.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "                  ",
+            "             .    ",
+            "             .    ",
+            "  ###        ...  ",
+            " #  #        .  . ",
+            " #  #        .  . ",
+            "  ###        ...  ",
+        ]);
+        assert_eq!(overlay.bounding_box(), bounds);
+    }
+}
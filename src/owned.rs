@@ -0,0 +1,300 @@
+//! Text box that owns its text.
+
+use alloc::{string::String, vec::Vec};
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::Transform,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{alignment::VerticalAlignment, style::TextBoxStyle, LineMetrics, TextBox};
+
+/// A text box that owns its text.
+///
+/// `OwnedTextBox` stores its text in a `String`, so it can be kept in long living UI structures
+/// without borrowing the text from somewhere else. The [`line_metrics`] of the text are measured
+/// when they are first needed, and kept until the text or the styling changes.
+///
+/// Use [`as_text_box`] to draw the text, or to add plugins. The cached line metrics are not used
+/// for drawing, the text is laid out again every time it is drawn.
+///
+/// This type is only available with the `alloc` crate feature.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::OwnedTextBox;
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut text_box = OwnedTextBox::new(format!("{} items", 3), bounds, character_style);
+/// assert_eq!(text_box.line_metrics().len(), 1);
+///
+/// text_box.set_text("Lorem ipsum dolor sit amet");
+/// assert_eq!(text_box.line_metrics().len(), 4);
+///
+/// let mut display = MockDisplay::new();
+/// text_box.as_text_box().draw(&mut display).unwrap();
+/// ```
+///
+/// [`as_text_box`]: #method.as_text_box
+/// [`line_metrics`]: #method.line_metrics
+#[derive(Clone, Debug)]
+pub struct OwnedTextBox<S> {
+    text: String,
+    bounds: Rectangle,
+    character_style: S,
+    style: TextBoxStyle,
+    layout: Option<Vec<LineMetrics>>,
+}
+
+impl<S> OwnedTextBox<S>
+where
+    S: TextRenderer + CharacterStyle + Clone,
+    <S as TextRenderer>::Color: From<Rgb888>,
+{
+    /// Creates a new `OwnedTextBox` instance with a given bounding `Rectangle`.
+    #[inline]
+    pub fn new(text: impl Into<String>, bounds: Rectangle, character_style: S) -> Self {
+        Self::with_textbox_style(text, bounds, character_style, TextBoxStyle::default())
+    }
+
+    /// Creates a new `OwnedTextBox` instance with a given bounding `Rectangle` and a given
+    /// `TextBoxStyle`.
+    #[inline]
+    pub fn with_textbox_style(
+        text: impl Into<String>,
+        bounds: Rectangle,
+        character_style: S,
+        style: TextBoxStyle,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            bounds,
+            character_style,
+            style,
+            layout: None,
+        }
+    }
+
+    /// Returns the text.
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text.
+    #[inline]
+    pub fn set_text(&mut self, text: impl Into<String>) -> &mut Self {
+        self.text = text.into();
+        self.layout = None;
+        self
+    }
+
+    /// Appends a string to the end of the text.
+    #[inline]
+    pub fn push_str(&mut self, text: &str) -> &mut Self {
+        self.text.push_str(text);
+        self.layout = None;
+        self
+    }
+
+    /// Returns the bounding box the text box was created with.
+    #[inline]
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Changes the bounding box.
+    #[inline]
+    pub fn set_bounds(&mut self, bounds: Rectangle) -> &mut Self {
+        self.bounds = bounds;
+        self.layout = None;
+        self
+    }
+
+    /// Changes the bounding box, keeping the measured line metrics if possible.
+    ///
+    /// Lines are broken based on the width of the bounding box only. If the width of `bounds` is
+    /// the same as the current width, the cached [`line_metrics`] are not measured again, only
+    /// moved to the new position. Otherwise, or if the placement of the lines depends on the height
+    /// of the text box, this method works like [`set_bounds`]. Drawing the text box lays out the
+    /// text again in both cases.
+    ///
+    /// [`line_metrics`]: #method.line_metrics
+    /// [`set_bounds`]: #method.set_bounds
+    #[inline]
+    pub fn relayout_height(&mut self, bounds: Rectangle) -> &mut Self {
+        let offset = bounds.top_left - self.bounds.top_left;
+
+        // Lines are only placed independently of the height if they start at the top, and the
+        // baseline grid, which is aligned to the display, is not shifted.
+        let keep_layout = bounds.size.width == self.bounds.size.width
+            && self.style.vertical_alignment == VerticalAlignment::Top
+            && match self.style.baseline_grid {
+                Some(grid) if grid > 1 => offset.y.rem_euclid(grid.saturating_as()) == 0,
+                _ => true,
+            };
+
+        match self.layout.as_mut() {
+            Some(layout) if keep_layout => {
+                for line in layout.iter_mut() {
+                    line.bounds = line.bounds.translate(offset);
+                }
+            }
+            _ => self.layout = None,
+        }
+
+        self.bounds = bounds;
+        self
+    }
+
+    /// Returns the character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+
+    /// Changes the character style.
+    #[inline]
+    pub fn set_character_style(&mut self, character_style: S) -> &mut Self {
+        self.character_style = character_style;
+        self.layout = None;
+        self
+    }
+
+    /// Returns the text box style.
+    #[inline]
+    pub fn style(&self) -> &TextBoxStyle {
+        &self.style
+    }
+
+    /// Changes the text box style.
+    #[inline]
+    pub fn set_style(&mut self, style: TextBoxStyle) -> &mut Self {
+        self.style = style;
+        self.layout = None;
+        self
+    }
+
+    /// Returns a `TextBox` that borrows the text.
+    #[inline]
+    pub fn as_text_box(&self) -> TextBox<'_, S> {
+        TextBox::with_textbox_style(
+            &self.text,
+            self.bounds,
+            self.character_style.clone(),
+            self.style,
+        )
+    }
+
+    /// Returns the measurements of every line of the text.
+    ///
+    /// The lines are measured on the first call, and the result is reused until the text or the
+    /// styling is changed.
+    #[inline]
+    pub fn line_metrics(&mut self) -> &[LineMetrics] {
+        if self.layout.is_none() {
+            let layout = self.as_text_box().line_metrics().collect();
+            self.layout = Some(layout);
+        }
+
+        self.layout.as_deref().unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::VerticalAlignment, style::TextBoxStyleBuilder, utils::test::size_for,
+        OwnedTextBox,
+    };
+
+    #[test]
+    fn layout_is_updated_after_changes() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let mut text_box = OwnedTextBox::new("word", bounds, character_style);
+        assert_eq!(text_box.line_metrics().len(), 1);
+
+        text_box.push_str(" word");
+        assert_eq!(text_box.line_metrics().len(), 2);
+
+        text_box.set_bounds(Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 3)));
+        assert_eq!(text_box.line_metrics().len(), 1);
+        assert_eq!(text_box.line_metrics()[0].width, 54);
+    }
+
+    #[test]
+    fn relayout_height_keeps_the_layout() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let mut text_box = OwnedTextBox::new("word word", bounds, character_style);
+        assert_eq!(text_box.line_metrics().len(), 2);
+
+        text_box.relayout_height(Rectangle::new(Point::new(0, 5), size_for(&FONT_6X9, 5, 1)));
+        assert!(text_box.layout.is_some());
+        assert_eq!(text_box.line_metrics().len(), 2);
+        assert_eq!(
+            text_box.line_metrics()[1].bounds.top_left,
+            Point::new(0, 14)
+        );
+
+        text_box.relayout_height(Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1)));
+        assert!(text_box.layout.is_none());
+        assert_eq!(text_box.line_metrics().len(), 1);
+    }
+
+    #[test]
+    fn relayout_height_moves_the_layout() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let mut text_box = OwnedTextBox::new("word", bounds, character_style);
+        let expected = text_box.line_metrics()[0].bounds;
+
+        let moved = Rectangle::new(Point::new(3, 7), size_for(&FONT_6X9, 5, 2));
+        text_box.relayout_height(moved);
+        assert!(text_box.layout.is_some());
+        assert_eq!(text_box.line_metrics()[0].bounds.top_left, Point::new(3, 7));
+        assert_eq!(text_box.line_metrics()[0].bounds.size, expected.size);
+
+        // Centered lines depend on the height of the text box.
+        text_box.set_style(
+            TextBoxStyleBuilder::new()
+                .vertical_alignment(VerticalAlignment::Middle)
+                .build(),
+        );
+        assert_eq!(
+            text_box.line_metrics()[0].bounds.top_left,
+            Point::new(3, 11)
+        );
+
+        text_box.relayout_height(Rectangle::new(Point::new(3, 7), size_for(&FONT_6X9, 5, 4)));
+        assert!(text_box.layout.is_none());
+        assert_eq!(
+            text_box.line_metrics()[0].bounds.top_left,
+            Point::new(3, 20)
+        );
+    }
+}
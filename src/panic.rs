@@ -0,0 +1,156 @@
+//! Display panic messages.
+//!
+//! Panic handlers run in a restricted context: the heap may not be usable, and the state of the
+//! program is unknown. [`draw_panic_message`] formats a message into a fixed size buffer on the
+//! stack, and draws it using a [`MinimalTextBox`], so it can be called from a panic handler.
+//!
+//! [`draw_panic_message`]: fn.draw_panic_message.html
+//! [`MinimalTextBox`]: ../struct.MinimalTextBox.html
+
+use core::fmt::{self, Write};
+
+use embedded_graphics::{draw_target::DrawTarget, text::renderer::TextRenderer, Drawable};
+
+use crate::{utils::copy_truncated, MinimalTextBox};
+
+/// The maximum length of a panic message in bytes. Longer messages are truncated.
+pub const MESSAGE_BUFFER_SIZE: usize = 256;
+
+/// Fixed size buffer that silently drops the text that doesn't fit.
+struct MessageBuffer {
+    bytes: [u8; MESSAGE_BUFFER_SIZE],
+    len: usize,
+    truncated: bool,
+}
+
+impl MessageBuffer {
+    fn new() -> Self {
+        Self {
+            bytes: [0; MESSAGE_BUFFER_SIZE],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // Only whole characters are copied into the buffer.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Ok(());
+        }
+
+        let len = copy_truncated(&mut self.bytes[self.len..], s);
+        self.len += len;
+        self.truncated = len < s.len();
+
+        Ok(())
+    }
+}
+
+/// Draws a panic message that fills the display.
+///
+/// `message` is usually the `PanicInfo` passed to the panic handler. The message is word wrapped,
+/// words that are too long for a line are broken at any character, and lines that don't fit
+/// onto the display are not drawn. Messages longer than [`MESSAGE_BUFFER_SIZE`] bytes are
+/// truncated.
+///
+/// This function doesn't allocate, and it doesn't use any `static` state. The display is not
+/// cleared before drawing.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use core::panic::PanicInfo;
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::panic::draw_panic_message;
+///
+/// // Called from the `#[panic_handler]` function.
+/// fn show_panic(info: &PanicInfo, display: &mut MockDisplay<BinaryColor>) {
+///     let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+///
+///     display.clear(BinaryColor::Off).ok();
+///     draw_panic_message(info, character_style, display).ok();
+/// }
+/// ```
+///
+/// [`MESSAGE_BUFFER_SIZE`]: constant.MESSAGE_BUFFER_SIZE.html
+#[inline]
+pub fn draw_panic_message<M, S, D>(
+    message: &M,
+    character_style: S,
+    display: &mut D,
+) -> Result<(), D::Error>
+where
+    M: fmt::Display + ?Sized,
+    S: TextRenderer,
+    D: DrawTarget<Color = S::Color>,
+{
+    let mut buffer = MessageBuffer::new();
+    write!(buffer, "{}", message).ok();
+
+    MinimalTextBox::new(buffer.as_str(), display.bounding_box(), character_style).draw(display)
+}
+
+#[cfg(test)]
+mod test {
+    use core::fmt::Write;
+
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+    };
+
+    use crate::{
+        panic::{draw_panic_message, MessageBuffer, MESSAGE_BUFFER_SIZE},
+        MinimalTextBox,
+    };
+
+    #[test]
+    fn message_is_drawn_on_the_display() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        draw_panic_message(
+            &format_args!("panicked at {}:{}", "main.rs", 12),
+            character_style,
+            &mut display,
+        )
+        .unwrap();
+
+        let mut expected = MockDisplay::new();
+        MinimalTextBox::new(
+            "panicked at main.rs:12",
+            expected.bounding_box(),
+            character_style,
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn long_messages_are_truncated() {
+        let mut buffer = MessageBuffer::new();
+        for _ in 0..MESSAGE_BUFFER_SIZE - 1 {
+            buffer.write_str("a").unwrap();
+        }
+        buffer.write_str("é").unwrap();
+        buffer.write_str("b").unwrap();
+
+        assert_eq!(buffer.as_str().len(), MESSAGE_BUFFER_SIZE - 1);
+        assert!(buffer.as_str().chars().all(|c| c == 'a'));
+    }
+}
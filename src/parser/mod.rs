@@ -21,13 +21,15 @@ use ansi_parser::AnsiSequence;
 use core::{marker::PhantomData, str::Chars};
 use embedded_graphics::{prelude::PixelColor, text::DecorationColor};
 
+use crate::style::{CarriageReturn, ControlCharacters, EmptyLines, TextBoxStyle};
+
 /// Change text style.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum ChangeTextStyle<C>
 where
     C: PixelColor,
 {
-    /// Reset text style. Disables decoration, removes background color and sets a default text color.
+    /// Reset text style to the character style of the text box.
     Reset,
 
     /// Change text color. `None` means transparent.
@@ -37,10 +39,31 @@ where
     BackgroundColor(Option<C>),
 
     /// Change color of underlining.
+    ///
+    /// `DecorationColor::TextColor` turns on underlining using the current underline color.
     Underline(DecorationColor<C>),
 
+    /// Change the underline color without turning underlining on or off. `None` restores the
+    /// default underline color.
+    UnderlineColor(Option<C>),
+
     /// Change color of strikethrough decoration.
     Strikethrough(DecorationColor<C>),
+
+    /// Switch to one of the fonts of the text box, selected by its index.
+    ///
+    /// The text style is reset to the selected character style. Indices that don't select a
+    /// font are ignored. See [`TextBox::set_fonts`].
+    ///
+    /// [`TextBox::set_fonts`]: struct.TextBox.html#method.set_fonts
+    Font(usize),
+
+    /// Shift the following text up (negative) or down by the given number of pixels, e.g. to
+    /// display superscript or subscript text.
+    ///
+    /// The offset is reset by `Reset` and by selecting a font. Text is clipped to its line, so
+    /// the offset should be smaller than the line height.
+    VerticalOffset(i32),
 }
 
 /// A text token
@@ -70,9 +93,27 @@ where
     /// Change of text style.
     ChangeTextStyle(ChangeTextStyle<C>),
 
+    /// An element drawn by a plugin, e.g. an inline icon.
+    ///
+    /// The value identifies the element for the plugin. Custom tokens are never produced by the
+    /// parser, only by plugins.
+    Custom(u32),
+
     /// An ANSI escape sequence
     #[cfg(feature = "ansi")]
     EscapeSequence(AnsiSequence),
+
+    /// The start of a hyperlink, with the URI of the link target.
+    ///
+    /// Hyperlinks are started and ended by OSC 8 escape sequences, for example
+    /// `"\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"`. The text is drawn normally,
+    /// plugins can use the link tokens to style links or to record where they are displayed.
+    #[cfg(feature = "ansi")]
+    LinkStart(&'a str),
+
+    /// The end of a hyperlink.
+    #[cfg(feature = "ansi")]
+    LinkEnd,
 }
 
 /// Text parser. Turns a string into a stream of [`Token`] objects.
@@ -84,6 +125,9 @@ where
     C: PixelColor,
 {
     inner: Chars<'a>,
+    carriage_return: CarriageReturn,
+    control_characters: ControlCharacters,
+    empty_lines: EmptyLines,
     _marker: PhantomData<C>,
 }
 
@@ -92,6 +136,10 @@ pub(crate) const SPEC_CHAR_ZWSP: char = '\u{200b}';
 pub(crate) const SPEC_CHAR_SHY: char = '\u{ad}';
 pub(crate) const SPEC_CHAR_ESCAPE: char = '\x1b';
 
+/// The start of an OSC 8 hyperlink escape sequence.
+#[cfg(feature = "ansi")]
+pub(crate) const OSC_8: &str = "\x1b]8;";
+
 fn is_word_char(c: char) -> bool {
     // Word tokens are terminated when a whitespace, zwsp or shy character is found. An exception
     // to this rule is the nbsp, which is whitespace but is included in the word.
@@ -99,6 +147,25 @@ fn is_word_char(c: char) -> bool {
         && ![SPEC_CHAR_ZWSP, SPEC_CHAR_SHY, SPEC_CHAR_ESCAPE].contains(&c)
 }
 
+/// The symbols of the Unicode Control Pictures block for the ASCII control characters.
+const CONTROL_PICTURES: [&str; 32] = [
+    "\u{2400}", "\u{2401}", "\u{2402}", "\u{2403}", "\u{2404}", "\u{2405}", "\u{2406}", "\u{2407}",
+    "\u{2408}", "\u{2409}", "\u{240a}", "\u{240b}", "\u{240c}", "\u{240d}", "\u{240e}", "\u{240f}",
+    "\u{2410}", "\u{2411}", "\u{2412}", "\u{2413}", "\u{2414}", "\u{2415}", "\u{2416}", "\u{2417}",
+    "\u{2418}", "\u{2419}", "\u{241a}", "\u{241b}", "\u{241c}", "\u{241d}", "\u{241e}", "\u{241f}",
+];
+
+/// Returns the replacement glyph of `c`, if `c` is a control character that is visualized.
+fn control_picture(mode: ControlCharacters, c: char) -> Option<&'static str> {
+    match (mode, c) {
+        (ControlCharacters::Interpret, _) | (_, '\n') => None,
+        (_, '\x7f') => Some("\u{2421}"),
+        _ if (c as u32) < 0x20 => Some(CONTROL_PICTURES[c as usize]),
+        _ if c.is_control() => Some("\u{fffd}"),
+        _ => None,
+    }
+}
+
 fn is_space_char(c: char) -> bool {
     // zero-width space breaks whitespace sequences - this works as long as
     // space handling is symmetrical (i.e. starting == ending behaviour)
@@ -112,14 +179,42 @@ where
     /// Create a new parser object to process the given piece of text.
     #[inline]
     #[must_use]
-
     pub fn parse(text: &'a str) -> Self {
         Self {
             inner: text.chars(),
+            carriage_return: CarriageReturn::Overprint,
+            control_characters: ControlCharacters::Interpret,
+            empty_lines: EmptyLines::Display,
             _marker: PhantomData,
         }
     }
 
+    /// Applies the parts of `style` that change how the text is split into tokens.
+    pub fn with_style(mut self, style: &TextBoxStyle) -> Self {
+        self.carriage_return = style.carriage_return;
+        self.control_characters = style.control_characters;
+        self.empty_lines = style.empty_lines;
+        self
+    }
+
+    /// Returns a new line token, skipping the line breaks that follow it if empty lines are
+    /// collapsed.
+    fn new_line(&mut self) -> Token<'a, C> {
+        if self.empty_lines == EmptyLines::Collapse {
+            let carriage_return = self.carriage_return;
+            self.inner = self
+                .inner
+                .as_str()
+                .trim_start_matches(|c| {
+                    c == '\n' || (c == '\r' && carriage_return != CarriageReturn::Overprint)
+                })
+                .chars();
+        }
+
+        Token::NewLine
+    }
+
+    #[allow(unsafe_code)]
     pub unsafe fn consume(&mut self, bytes: usize) {
         // SAFETY: caller needs to make sure we end up on character boundary
         self.inner = self.inner.as_str().get_unchecked(bytes..).chars();
@@ -128,6 +223,36 @@ where
     pub fn as_str(&self) -> &str {
         self.inner.as_str()
     }
+
+    /// Parses the OSC 8 hyperlink escape sequence at the start of `string`.
+    ///
+    /// The sequence is `ESC ] 8 ; params ; URI ST`, where the string terminator `ST` is either
+    /// `ESC \` or `BEL`. An empty URI ends the link. Returns `None` if the sequence is not
+    /// terminated.
+    #[cfg(feature = "ansi")]
+    fn hyperlink(&mut self, string: &'a str) -> Option<Token<'a, C>> {
+        let body = &string[OSC_8.len()..];
+        let (end, terminator) = match (body.find('\x07'), body.find("\x1b\\")) {
+            (Some(bel), Some(st)) if st < bel => (st, 2),
+            (Some(bel), _) => (bel, 1),
+            (None, Some(st)) => (st, 2),
+            (None, None) => return None,
+        };
+
+        self.inner = body[end + terminator..].chars();
+
+        let params_and_uri = &body[..end];
+        let uri = match params_and_uri.find(';') {
+            Some(separator) => &params_and_uri[separator + 1..],
+            None => "",
+        };
+
+        if uri.is_empty() {
+            Some(Token::LinkEnd)
+        } else {
+            Some(Token::LinkStart(uri))
+        }
+    }
 }
 
 impl<'a, C> Iterator for Parser<'a, C>
@@ -137,14 +262,24 @@ where
     type Item = Token<'a, C>;
 
     #[inline]
+    #[allow(unsafe_code)]
     fn next(&mut self) -> Option<Self::Item> {
+        let visualize = self.control_characters;
+        if self.carriage_return == CarriageReturn::Ignore
+            && visualize == ControlCharacters::Interpret
+        {
+            self.inner = self.inner.as_str().trim_start_matches('\r').chars();
+        }
+
         let string = self.inner.as_str();
 
         if let Some(c) = self.inner.next() {
-            if is_word_char(c) {
+            if let Some(picture) = control_picture(visualize, c) {
+                Some(Token::Word(picture))
+            } else if is_word_char(c) {
                 // find the longest consecutive slice of text for a Word token
                 for c in &mut self.inner {
-                    if !is_word_char(c) {
+                    if !is_word_char(c) || control_picture(visualize, c).is_some() {
                         // pointer arithmetic to get the offset of `c` relative to `string`
                         let offset = {
                             let ptr_start = string.as_ptr() as usize;
@@ -169,7 +304,13 @@ where
             } else {
                 match c {
                     // special characters
-                    '\n' => Some(Token::NewLine),
+                    '\n' => Some(self.new_line()),
+                    '\r' if self.carriage_return == CarriageReturn::NewLine => {
+                        if self.inner.as_str().starts_with('\n') {
+                            self.inner.next();
+                        }
+                        Some(self.new_line())
+                    }
                     '\r' => Some(Token::CarriageReturn),
                     '\t' => Some(Token::Tab),
                     SPEC_CHAR_ZWSP => Some(Token::Whitespace(0, unsafe {
@@ -186,6 +327,10 @@ where
                         },
                     )),
                     #[cfg(feature = "ansi")]
+                    SPEC_CHAR_ESCAPE if string.starts_with(OSC_8) => self
+                        .hyperlink(string)
+                        .or(Some(Token::EscapeSequence(AnsiSequence::Escape))),
+                    #[cfg(feature = "ansi")]
                     SPEC_CHAR_ESCAPE => ansi_parser::parse_escape(string).map_or(
                         Some(Token::EscapeSequence(AnsiSequence::Escape)),
                         |(string, output)| {
@@ -198,7 +343,7 @@ where
                     _ => {
                         let mut len = 1;
                         for c in &mut self.inner {
-                            if is_space_char(c) {
+                            if is_space_char(c) && control_picture(visualize, c).is_none() {
                                 if c != SPEC_CHAR_ZWSP {
                                     len += 1;
                                 }
@@ -239,6 +384,7 @@ mod test {
     use embedded_graphics::pixelcolor::BinaryColor;
 
     use super::{Parser, Token};
+    use crate::style::{CarriageReturn, ControlCharacters, EmptyLines, TextBoxStyleBuilder};
 
     #[track_caller]
     pub fn assert_tokens(text: &str, tokens: std::vec::Vec<Token<BinaryColor>>) {
@@ -319,6 +465,82 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn carriage_return_modes() {
+        let parse = |mode| {
+            Parser::parse("a\r\nb\rc\r")
+                .with_style(&TextBoxStyleBuilder::new().carriage_return(mode).build())
+                .collect::<std::vec::Vec<Token<BinaryColor>>>()
+        };
+
+        assert_eq!(
+            parse(CarriageReturn::NewLine),
+            vec![
+                Token::Word("a"),
+                Token::NewLine,
+                Token::Word("b"),
+                Token::NewLine,
+                Token::Word("c"),
+                Token::NewLine,
+            ]
+        );
+        assert_eq!(
+            parse(CarriageReturn::Ignore),
+            vec![
+                Token::Word("a"),
+                Token::NewLine,
+                Token::Word("b"),
+                Token::Word("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_lines_are_collapsed() {
+        let style = TextBoxStyleBuilder::new()
+            .empty_lines(EmptyLines::Collapse)
+            .build();
+
+        assert_eq!(
+            Parser::parse("a\n\n\nb\n c")
+                .with_style(&style)
+                .collect::<std::vec::Vec<Token<BinaryColor>>>(),
+            vec![
+                Token::Word("a"),
+                Token::NewLine,
+                Token::Word("b"),
+                Token::NewLine,
+                Token::Whitespace(1, " "),
+                Token::Word("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn control_characters_are_visualized() {
+        let style = TextBoxStyleBuilder::new()
+            .control_characters(ControlCharacters::Visualize)
+            .build();
+
+        assert_eq!(
+            Parser::parse("a\0b\tc \r\x7f\n\u{85}")
+                .with_style(&style)
+                .collect::<std::vec::Vec<Token<BinaryColor>>>(),
+            vec![
+                Token::Word("a"),
+                Token::Word("\u{2400}"),
+                Token::Word("b"),
+                Token::Word("\u{2409}"),
+                Token::Word("c"),
+                Token::Whitespace(1, " "),
+                Token::Word("\u{240d}"),
+                Token::Word("\u{2421}"),
+                Token::NewLine,
+                Token::Word("\u{fffd}"),
+            ]
+        );
+    }
 }
 
 #[cfg(all(feature = "ansi", test))]
@@ -328,6 +550,31 @@ mod ansi_parser_tests {
     use ansi_parser::AnsiSequence;
     use heapless::Vec;
 
+    #[test]
+    fn hyperlinks_are_parsed() {
+        assert_tokens(
+            "a \x1b]8;id=1;https://example.com\x1b\\link\x1b]8;;\x07 b",
+            vec![
+                Token::Word("a"),
+                Token::Whitespace(1, " "),
+                Token::LinkStart("https://example.com"),
+                Token::Word("link"),
+                Token::LinkEnd,
+                Token::Whitespace(1, " "),
+                Token::Word("b"),
+            ],
+        );
+
+        // Unterminated sequences are not links.
+        assert_tokens(
+            "\x1b]8;;x",
+            vec![
+                Token::EscapeSequence(AnsiSequence::Escape),
+                Token::Word("]8;;x"),
+            ],
+        );
+    }
+
     #[test]
     fn escape_char_ignored_if_not_ansi_sequence() {
         assert_tokens(
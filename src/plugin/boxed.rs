@@ -0,0 +1,774 @@
+//! Heap allocated plugins.
+
+use alloc::boxed::Box;
+use core::{fmt, marker::PhantomData};
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    prelude::PixelColor,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline,
+    },
+    Pixel,
+};
+
+use crate::{
+    parser::Token,
+    plugin::{Plugin, PluginMarker, WrapReason},
+    rendering::cursor::Cursor,
+    style::TextBoxStyle,
+    LineMetrics, TextBoxProperties,
+};
+
+/// Error of a type erased draw target. The error of the display is kept by the `ErrorSlot`.
+#[derive(Debug)]
+struct DrawError;
+
+/// Object safe draw target.
+trait DynTarget<C>
+where
+    C: PixelColor,
+{
+    fn bounding_box(&self) -> Rectangle;
+
+    fn draw_iter(&mut self, pixels: &mut dyn Iterator<Item = Pixel<C>>) -> Result<(), DrawError>;
+
+    fn fill_contiguous(
+        &mut self,
+        area: &Rectangle,
+        colors: &mut dyn Iterator<Item = C>,
+    ) -> Result<(), DrawError>;
+
+    fn fill_solid(&mut self, area: &Rectangle, color: C) -> Result<(), DrawError>;
+}
+
+/// Wraps a display, and keeps the first error it returns.
+struct ErrorSlot<'d, D>
+where
+    D: DrawTarget,
+{
+    display: &'d mut D,
+    error: Option<D::Error>,
+}
+
+impl<D> ErrorSlot<'_, D>
+where
+    D: DrawTarget,
+{
+    fn store(&mut self, result: Result<(), D::Error>) -> Result<(), DrawError> {
+        result.map_err(|error| {
+            self.error.get_or_insert(error);
+            DrawError
+        })
+    }
+}
+
+impl<C, D> DynTarget<C> for ErrorSlot<'_, D>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+
+    fn draw_iter(&mut self, pixels: &mut dyn Iterator<Item = Pixel<C>>) -> Result<(), DrawError> {
+        let result = self.display.draw_iter(pixels);
+        self.store(result)
+    }
+
+    fn fill_contiguous(
+        &mut self,
+        area: &Rectangle,
+        colors: &mut dyn Iterator<Item = C>,
+    ) -> Result<(), DrawError> {
+        let result = self.display.fill_contiguous(area, colors);
+        self.store(result)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: C) -> Result<(), DrawError> {
+        let result = self.display.fill_solid(area, color);
+        self.store(result)
+    }
+}
+
+/// A draw target that forwards to a type erased display.
+struct DynDisplay<'d, C>
+where
+    C: PixelColor,
+{
+    target: &'d mut dyn DynTarget<C>,
+}
+
+impl<C> Dimensions for DynDisplay<'_, C>
+where
+    C: PixelColor,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<C> DrawTarget for DynDisplay<'_, C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+    type Error = DrawError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        self.target.draw_iter(&mut pixels.into_iter())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = C>,
+    {
+        self.target.fill_contiguous(area, &mut colors.into_iter())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: C) -> Result<(), Self::Error> {
+        self.target.fill_solid(area, color)
+    }
+}
+
+/// Calls `f` with a type erased version of `display`.
+///
+/// Returns the first error of the display, even if `f` ignored it. `fallback` is returned if `f`
+/// fails without a display error.
+fn with_dyn_display<C, D, R>(
+    display: &mut D,
+    fallback: R,
+    f: impl FnOnce(&mut DynDisplay<'_, C>) -> Result<R, DrawError>,
+) -> Result<R, D::Error>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    let mut slot = ErrorSlot {
+        display,
+        error: None,
+    };
+    let result = f(&mut DynDisplay { target: &mut slot });
+
+    match slot.error {
+        Some(error) => Err(error),
+        None => Ok(result.unwrap_or(fallback)),
+    }
+}
+
+/// Object safe text renderer.
+trait DynRenderer<C>
+where
+    C: PixelColor,
+{
+    fn draw_string(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut DynDisplay<'_, C>,
+    ) -> Result<Point, DrawError>;
+
+    fn draw_whitespace(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut DynDisplay<'_, C>,
+    ) -> Result<Point, DrawError>;
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics;
+
+    fn line_height(&self) -> u32;
+}
+
+impl<C, T> DynRenderer<C> for T
+where
+    C: PixelColor,
+    T: TextRenderer<Color = C>,
+{
+    fn draw_string(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut DynDisplay<'_, C>,
+    ) -> Result<Point, DrawError> {
+        TextRenderer::draw_string(self, text, position, baseline, target)
+    }
+
+    fn draw_whitespace(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut DynDisplay<'_, C>,
+    ) -> Result<Point, DrawError> {
+        TextRenderer::draw_whitespace(self, width, position, baseline, target)
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        TextRenderer::measure_string(self, text, position, baseline)
+    }
+
+    fn line_height(&self) -> u32 {
+        TextRenderer::line_height(self)
+    }
+}
+
+/// A text renderer that forwards to a type erased character style.
+struct DynStyle<'t, C> {
+    style: &'t dyn DynRenderer<C>,
+}
+
+impl<C> TextRenderer for DynStyle<'_, C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        with_dyn_display(target, position, |target| {
+            self.style.draw_string(text, position, baseline, target)
+        })
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        with_dyn_display(target, position, |target| {
+            self.style
+                .draw_whitespace(width, position, baseline, target)
+        })
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        self.style.measure_string(text, position, baseline)
+    }
+
+    fn line_height(&self) -> u32 {
+        self.style.line_height()
+    }
+}
+
+/// Stands in for the character style of the text box in the properties passed to a boxed plugin.
+///
+/// `on_start_render` only knows that the character style implements `CharacterStyle`, which has
+/// no methods to read the style, so the plugin can't observe the difference.
+#[derive(Clone, Copy)]
+struct ErasedCharacterStyle<C>(PhantomData<C>);
+
+impl<C> CharacterStyle for ErasedCharacterStyle<C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+}
+
+/// Object safe version of the plugin hooks.
+trait DynPlugin<'a, C>
+where
+    C: PixelColor,
+{
+    fn new_line(&mut self);
+
+    fn next_token(
+        &mut self,
+        next_token: &mut dyn FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>>;
+
+    fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>>;
+
+    fn word_color(&mut self, word: &str) -> Option<C>;
+
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize>;
+
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>];
+
+    fn indent_wrapped_lines(&mut self) -> bool;
+
+    fn custom_width(&mut self, id: u32) -> u32;
+
+    fn draw_custom(
+        &mut self,
+        id: u32,
+        draw_target: &mut DynDisplay<'_, C>,
+        character_style: &DynStyle<'_, C>,
+        bounds: Rectangle,
+    ) -> Result<(), DrawError>;
+
+    fn post_render(
+        &mut self,
+        draw_target: &mut DynDisplay<'_, C>,
+        character_style: &DynStyle<'_, C>,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), DrawError>;
+
+    fn on_start_render(
+        &mut self,
+        cursor: &mut Cursor,
+        box_style: &TextBoxStyle,
+        text_height: i32,
+        box_height: i32,
+    );
+
+    fn post_line(&mut self, line: LineMetrics);
+
+    fn on_wrap(&mut self, reason: WrapReason);
+
+    fn clone_box(&self) -> Box<dyn DynPlugin<'a, C> + 'a>;
+}
+
+impl<'a, C, P> DynPlugin<'a, C> for P
+where
+    C: PixelColor,
+    P: PluginMarker<'a, C> + 'a,
+{
+    fn new_line(&mut self) {
+        Plugin::new_line(self)
+    }
+
+    fn next_token(
+        &mut self,
+        next_token: &mut dyn FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        Plugin::next_token(self, next_token)
+    }
+
+    fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>> {
+        Plugin::render_token(self, token)
+    }
+
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        Plugin::word_color(self, word)
+    }
+
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        Plugin::hyphenate(self, word, max)
+    }
+
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        Plugin::line_prefix(self, line)
+    }
+
+    fn indent_wrapped_lines(&mut self) -> bool {
+        Plugin::indent_wrapped_lines(self)
+    }
+
+    fn custom_width(&mut self, id: u32) -> u32 {
+        Plugin::custom_width(self, id)
+    }
+
+    fn draw_custom(
+        &mut self,
+        id: u32,
+        draw_target: &mut DynDisplay<'_, C>,
+        character_style: &DynStyle<'_, C>,
+        bounds: Rectangle,
+    ) -> Result<(), DrawError> {
+        Plugin::draw_custom(self, id, draw_target, character_style, bounds)
+    }
+
+    fn post_render(
+        &mut self,
+        draw_target: &mut DynDisplay<'_, C>,
+        character_style: &DynStyle<'_, C>,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), DrawError> {
+        Plugin::post_render(self, draw_target, character_style, text, bounds)
+    }
+
+    fn on_start_render(
+        &mut self,
+        cursor: &mut Cursor,
+        box_style: &TextBoxStyle,
+        text_height: i32,
+        box_height: i32,
+    ) {
+        let props = TextBoxProperties {
+            box_style,
+            char_style: &ErasedCharacterStyle::<C>(PhantomData),
+            text_height,
+            box_height,
+        };
+        Plugin::on_start_render(self, cursor, props)
+    }
+
+    fn post_line(&mut self, line: LineMetrics) {
+        Plugin::post_line(self, line)
+    }
+
+    fn on_wrap(&mut self, reason: WrapReason) {
+        Plugin::on_wrap(self, reason)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynPlugin<'a, C> + 'a> {
+        Box::new(self.clone())
+    }
+}
+
+/// A heap allocated, type erased plugin.
+///
+/// `BoxedPlugin` allows selecting plugins at runtime, and storing text boxes with different
+/// plugins in the same collection.
+///
+/// Every hook of the wrapped plugin is called. The displays and character styles passed to the
+/// drawing hooks are type erased, which makes drawing through them slower. Plugins that only need
+/// to be enabled or disabled at runtime can be wrapped in an `Option` instead, which doesn't use
+/// the heap.
+///
+/// This type is only available with the `alloc` crate feature.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{
+///     plugin::{boxed::BoxedPlugin, markup::Markup},
+///     TextBox,
+/// };
+///
+/// let highlight = true;
+/// let plugin = if highlight {
+///     BoxedPlugin::new(Markup::new())
+/// } else {
+///     BoxedPlugin::none()
+/// };
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let text_box = TextBox::new("{red}ERROR", bounds, character_style).add_plugin(plugin);
+/// ```
+pub struct BoxedPlugin<'a, C>
+where
+    C: PixelColor,
+{
+    plugin: Box<dyn DynPlugin<'a, C> + 'a>,
+}
+
+impl<'a, C> BoxedPlugin<'a, C>
+where
+    C: PixelColor + 'a,
+{
+    /// Moves the given plugin to the heap.
+    #[inline]
+    pub fn new<P>(plugin: P) -> Self
+    where
+        P: PluginMarker<'a, C> + 'a,
+    {
+        Self {
+            plugin: Box::new(plugin),
+        }
+    }
+
+    /// Creates a boxed plugin that doesn't modify the behaviour of the text box.
+    #[inline]
+    pub fn none() -> Self {
+        Self::new(super::NoPlugin::new())
+    }
+}
+
+impl<'a, C> Clone for BoxedPlugin<'a, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            plugin: self.plugin.clone_box(),
+        }
+    }
+}
+
+impl<'a, C> fmt::Debug for BoxedPlugin<'a, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedPlugin").finish()
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for BoxedPlugin<'a, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn new_line(&mut self) {
+        self.plugin.new_line();
+    }
+
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        self.plugin.next_token(&mut next_token)
+    }
+
+    #[inline]
+    fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>> {
+        self.plugin.render_token(token)
+    }
+
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        self.plugin.word_color(word)
+    }
+
+    #[inline]
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        self.plugin.hyphenate(word, max)
+    }
+
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        self.plugin.line_prefix(line)
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        self.plugin.indent_wrapped_lines()
+    }
+
+    #[inline]
+    fn custom_width(&mut self, id: u32) -> u32 {
+        self.plugin.custom_width(id)
+    }
+
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        let style = DynStyle {
+            style: character_style,
+        };
+        with_dyn_display(draw_target, (), |draw_target| {
+            self.plugin.draw_custom(id, draw_target, &style, bounds)
+        })
+    }
+
+    #[inline]
+    fn post_render<T, D>(
+        &mut self,
+        draw_target: &mut D,
+        character_style: &T,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        let style = DynStyle {
+            style: character_style,
+        };
+        with_dyn_display(draw_target, (), |draw_target| {
+            self.plugin.post_render(draw_target, &style, text, bounds)
+        })
+    }
+
+    #[inline]
+    fn on_start_render<S: CharacterStyle>(
+        &mut self,
+        cursor: &mut Cursor,
+        props: TextBoxProperties<'_, S>,
+    ) {
+        self.plugin
+            .on_start_render(cursor, props.box_style, props.text_height, props.box_height)
+    }
+
+    #[inline]
+    fn post_line(&mut self, line: LineMetrics) {
+        self.plugin.post_line(line)
+    }
+
+    #[inline]
+    fn on_wrap(&mut self, reason: WrapReason) {
+        self.plugin.on_wrap(reason)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+        text::{renderer::TextRenderer, Baseline},
+    };
+
+    use crate::{
+        parser::Token,
+        plugin::{boxed::BoxedPlugin, tail::Tail, Plugin},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[test]
+    #[cfg(feature = "plugin-keywords")]
+    fn boxed_plugin_is_called() {
+        use crate::plugin::keywords::{Keyword, Keywords};
+
+        const KEYWORDS: &[Keyword<'static, BinaryColor>] = &[Keyword::exact("a", BinaryColor::Off)];
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a ab", bounds, character_style)
+            .add_plugin(BoxedPlugin::new(Keywords::new(KEYWORDS)))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "                       ",
+            "                   #   ",
+            "                   #   ",
+            "  ...         ###  ### ",
+            " .  .        #  #  #  #",
+            " .  .        #  #  #  #",
+            "  ...         ###  ### ",
+        ]);
+    }
+
+    /// Draws `x` words as custom tokens, and underlines `a` words after they are rendered.
+    #[derive(Clone)]
+    struct Stamp;
+
+    impl<'a> Plugin<'a, BinaryColor> for Stamp {
+        fn next_token(
+            &mut self,
+            mut next_token: impl FnMut() -> Option<Token<'a, BinaryColor>>,
+        ) -> Option<Token<'a, BinaryColor>> {
+            match next_token()? {
+                Token::Word("x") => Some(Token::Custom(0)),
+                token => Some(token),
+            }
+        }
+
+        fn custom_width(&mut self, _id: u32) -> u32 {
+            6
+        }
+
+        fn draw_custom<T, D>(
+            &mut self,
+            _id: u32,
+            draw_target: &mut D,
+            character_style: &T,
+            bounds: Rectangle,
+        ) -> Result<(), D::Error>
+        where
+            T: TextRenderer<Color = BinaryColor>,
+            D: DrawTarget<Color = BinaryColor>,
+        {
+            character_style
+                .draw_string("o", bounds.top_left, Baseline::Top, draw_target)
+                .map(drop)
+        }
+
+        fn post_render<T, D>(
+            &mut self,
+            draw_target: &mut D,
+            _character_style: &T,
+            text: &str,
+            bounds: Rectangle,
+        ) -> Result<(), D::Error>
+        where
+            T: TextRenderer<Color = BinaryColor>,
+            D: DrawTarget<Color = BinaryColor>,
+        {
+            if text != "a" {
+                return Ok(());
+            }
+
+            let underline = Rectangle::new(
+                bounds.top_left + Point::new(0, 8),
+                Size::new(bounds.size.width, 1),
+            );
+            draw_target.fill_solid(&underline, BinaryColor::On)
+        }
+    }
+
+    #[test]
+    fn boxed_plugin_draws() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a x", bounds, character_style)
+            .add_plugin(BoxedPlugin::new(Stamp))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "                  ",
+            "                  ",
+            "                  ",
+            "  ###         ##  ",
+            " #  #        #  # ",
+            " #  #        #  # ",
+            "  ###         ##  ",
+            "                  ",
+            "######            ",
+        ]);
+    }
+
+    #[test]
+    fn boxed_plugin_is_started() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a\nb", bounds, character_style)
+            .add_plugin(BoxedPlugin::new(Tail::new()))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "      ", " #    ", " #    ", " ###  ", " #  # ", " #  # ", " ###  ",
+        ]);
+    }
+}
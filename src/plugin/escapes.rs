@@ -0,0 +1,234 @@
+//! Change the text style using custom escape sequences.
+
+use core::fmt;
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::PixelColor};
+
+use crate::{
+    parser::{ChangeTextStyle, Token},
+    plugin::Plugin,
+};
+
+/// Parses the parameters of an escape sequence.
+///
+/// The function receives the text that follows the introducer, up to the end of the word. It
+/// returns the number of bytes that belong to the escape sequence, and the text style change it
+/// represents, or `None` if the text doesn't start with a valid sequence.
+pub type EscapeHandler<C> = fn(&str) -> Option<(usize, ChangeTextStyle<C>)>;
+
+/// Custom escape sequence plugin.
+///
+/// Changes the text style using escape sequences that start with a user defined introducer
+/// character. The parameters of a sequence are parsed by a user supplied function, which makes
+/// it possible to use compact, application specific markup instead of ANSI escape sequences, for
+/// example in bandwidth constrained protocols.
+///
+/// The introducer must not be a whitespace character, and escape sequences can't contain
+/// whitespace. When the `ansi` feature is enabled, the `\x1b` character is handled by the ANSI
+/// escape sequence parser, so it can't be used as an introducer. Introducers that don't start a
+/// valid sequence are displayed as text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::escapes::EscapeDialect, ChangeTextStyle, TextBox};
+///
+/// // A single digit after `\x10` selects the text color, `\x10.` resets the style.
+/// fn parse(params: &str) -> Option<(usize, ChangeTextStyle<Rgb888>)> {
+///     let change = match params.as_bytes().first()? {
+///         b'0' => ChangeTextStyle::TextColor(Some(Rgb888::WHITE)),
+///         b'1' => ChangeTextStyle::TextColor(Some(Rgb888::RED)),
+///         b'.' => ChangeTextStyle::Reset,
+///         _ => return None,
+///     };
+///
+///     Some((1, change))
+/// }
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let text_box = TextBox::new("\x101ERROR\x10.: disk full", bounds, character_style)
+///     .add_plugin(EscapeDialect::new('\x10', parse));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct EscapeDialect<'a, C>
+where
+    C: PixelColor,
+{
+    introducer: char,
+    handler: EscapeHandler<C>,
+
+    /// The part of the current word that has not been processed.
+    pending: Option<&'a str>,
+}
+
+impl<'a, C> EscapeDialect<'a, C>
+where
+    C: PixelColor,
+{
+    /// Creates a new plugin that parses escape sequences starting with `introducer` using
+    /// `handler`.
+    #[inline]
+    pub fn new(introducer: char, handler: EscapeHandler<C>) -> Self {
+        Self {
+            introducer,
+            handler,
+            pending: None,
+        }
+    }
+
+    /// Finds the first valid escape sequence in `word`.
+    ///
+    /// Returns the byte range of the sequence and the text style change it represents.
+    fn find_sequence(&self, word: &str) -> Option<(usize, usize, ChangeTextStyle<C>)> {
+        let mut from = 0;
+        while let Some(start) = word[from..].find(self.introducer).map(|idx| from + idx) {
+            let params = start + self.introducer.len_utf8();
+            if let Some((len, change)) = (self.handler)(&word[params..]) {
+                let end = params.saturating_add(len);
+                if word.is_char_boundary(end) {
+                    return Some((start, end, change));
+                }
+            }
+            from = params;
+        }
+
+        None
+    }
+}
+
+impl<C> fmt::Debug for EscapeDialect<'_, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EscapeDialect")
+            .field("introducer", &self.introducer)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for EscapeDialect<'a, C>
+where
+    C: PixelColor + From<Rgb888>,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        let word = match self.pending.take() {
+            Some(word) => word,
+            None => match next_token()? {
+                Token::Word(word) => word,
+                token => return Some(token),
+            },
+        };
+
+        match self.find_sequence(word) {
+            Some((0, end, change)) => {
+                if end < word.len() {
+                    self.pending = Some(&word[end..]);
+                }
+                Some(Token::ChangeTextStyle(change))
+            }
+            Some((start, _, _)) => {
+                self.pending = Some(&word[start..]);
+                Some(Token::Word(&word[..start]))
+            }
+            None => Some(Token::Word(word)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        parser::ChangeTextStyle, plugin::escapes::EscapeDialect, utils::test::size_for, TextBox,
+    };
+
+    fn parse(params: &str) -> Option<(usize, ChangeTextStyle<BinaryColor>)> {
+        let change = match params.as_bytes().first()? {
+            b'0' => ChangeTextStyle::TextColor(Some(BinaryColor::Off)),
+            b'/' => ChangeTextStyle::Reset,
+            _ => return None,
+        };
+
+        Some((1, change))
+    }
+
+    #[track_caller]
+    fn assert_rendered(text: &str, size: Size, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(EscapeDialect::new('$', parse))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn sequences_change_style() {
+        assert_rendered(
+            "a$0b$/c",
+            size_for(&FONT_6X9, 3, 1),
+            &[
+                "                 ",
+                "       .         ",
+                "       .         ",
+                "  ###  ...    ###",
+                " #  #  .  .  #   ",
+                " #  #  .  .  #   ",
+                "  ###  ...    ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn invalid_sequences_are_displayed() {
+        assert_rendered(
+            "$x$",
+            size_for(&FONT_6X9, 3, 1),
+            &[
+                "  #           #  ",
+                " ###         ### ",
+                "# # #       # # #",
+                "# #    #  # # #  ",
+                " ###    ##   ### ",
+                "  # #   ##    # #",
+                "# # #  #  # # # #",
+                " ###         ### ",
+                "  #           #  ",
+            ],
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Display line numbers or labels in a gutter.
+
+use core::marker::PhantomData;
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    prelude::{PixelColor, Point},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{parser::Token, plugin::Plugin};
+
+/// The ID of the custom token that draws the gutter. Other plugins must not use this ID.
+pub const GUTTER_TOKEN_ID: u32 = u32::MAX;
+
+/// The prefix that reserves the gutter at the start of every source line.
+struct GutterPrefix<'a, C>(PhantomData<&'a C>);
+
+impl<'a, C> GutterPrefix<'a, C>
+where
+    C: PixelColor,
+{
+    const TOKENS: &'a [Token<'a, C>] = &[Token::Custom(GUTTER_TOKEN_ID)];
+}
+
+/// The contents of the gutter.
+#[derive(Copy, Clone, Debug)]
+enum Label<'a> {
+    Numbers { first: usize },
+    Custom(fn(usize) -> &'a str),
+}
+
+/// Formats `n` into `buffer`.
+fn format_number(n: usize, buffer: &mut [u8; 20]) -> &str {
+    let mut start = buffer.len();
+    let mut n = n;
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+
+    core::str::from_utf8(&buffer[start..]).unwrap_or_default()
+}
+
+/// Gutter plugin.
+///
+/// Reserves a `width` pixels wide gutter at the start of every source line, and displays the
+/// number or a custom label of the line in it. Source lines start at the beginning of the text
+/// and after every newline character. Lines that are started by wrapping the text are indented
+/// by the width of the gutter, so the text is laid out in the width that remains next to the
+/// gutter.
+///
+/// Labels are right aligned in the gutter, and are separated from the text by the width of a
+/// space. The gutter is part of the line, so this plugin is meant to be used with left aligned or
+/// justified text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::gutter::Gutter, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 27));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("fn main() {\n}", bounds, character_style)
+///     .add_plugin(Gutter::line_numbers(18))
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Gutter<'a> {
+    width: u32,
+    label: Label<'a>,
+
+    /// The index of the current source line.
+    line: usize,
+}
+
+impl<'a> Gutter<'a> {
+    /// Creates a new plugin that displays line numbers, starting with 1.
+    #[inline]
+    pub const fn line_numbers(width: u32) -> Self {
+        Self {
+            width,
+            label: Label::Numbers { first: 1 },
+            line: 0,
+        }
+    }
+
+    /// Creates a new plugin that displays the labels returned by a function.
+    ///
+    /// The function receives the index of the source line.
+    #[inline]
+    pub const fn with_labels(width: u32, label: fn(usize) -> &'a str) -> Self {
+        Self {
+            width,
+            label: Label::Custom(label),
+            line: 0,
+        }
+    }
+
+    /// Sets the number of the first line. This has no effect if the plugin displays custom
+    /// labels.
+    #[inline]
+    pub fn first_number(mut self, first: usize) -> Self {
+        if let Label::Numbers { .. } = self.label {
+            self.label = Label::Numbers { first };
+        }
+
+        self
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Gutter<'a>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        self.line = line;
+        GutterPrefix::<'a, C>::TOKENS
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn custom_width(&mut self, id: u32) -> u32 {
+        if id == GUTTER_TOKEN_ID {
+            self.width
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        if id != GUTTER_TOKEN_ID {
+            return Ok(());
+        }
+
+        let mut buffer = [0; 20];
+        let label = match self.label {
+            Label::Numbers { first } => format_number(first.saturating_add(self.line), &mut buffer),
+            Label::Custom(label) => label(self.line),
+        };
+
+        let width = |s| {
+            character_style
+                .measure_string(s, Point::zero(), Baseline::Top)
+                .next_position
+                .x
+        };
+        let right = bounds.top_left.x + bounds.size.width.saturating_as::<i32>() - width(" ");
+
+        character_style.draw_string(
+            label,
+            Point::new(right - width(label), bounds.top_left.y),
+            Baseline::Top,
+            draw_target,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        plugin::gutter::{format_number, Gutter},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_gutter(text: &str, gutter: Gutter<'static>, expected: &[(i32, &str)]) {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let size = size_for(&FONT_6X9, 6, 3);
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(gutter)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected_display = MockDisplay::new();
+        for (row, line) in expected {
+            TextBox::new(
+                line,
+                Rectangle::new(Point::new(0, row * 9), Size::new(size.width, 9)),
+                character_style,
+            )
+            .draw(&mut expected_display)
+            .unwrap();
+        }
+
+        display.assert_eq(&expected_display);
+    }
+
+    #[test]
+    fn numbers_source_lines() {
+        assert_gutter(
+            "ab\ncd",
+            Gutter::line_numbers(18),
+            &[(0, " 1 ab"), (1, " 2 cd")],
+        );
+    }
+
+    #[test]
+    fn wrapped_lines_are_indented() {
+        assert_gutter(
+            "ab cd\nef",
+            Gutter::line_numbers(12).first_number(8),
+            &[(0, "8 ab"), (1, "  cd"), (2, "9 ef")],
+        );
+    }
+
+    #[test]
+    fn custom_labels() {
+        assert_gutter(
+            "ab\ncd",
+            Gutter::with_labels(12, |line| if line == 0 { ">" } else { "" }),
+            &[(0, "> ab"), (1, "  cd")],
+        );
+    }
+
+    #[test]
+    fn numbers_are_formatted() {
+        let mut buffer = [0; 20];
+        assert_eq!(format_number(0, &mut buffer), "0");
+        assert_eq!(format_number(1907, &mut buffer), "1907");
+        assert_eq!(
+            format_number(usize::MAX, &mut buffer),
+            usize::MAX.to_string()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Language specific hyphenation.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::plugin::Plugin;
+
+/// Hyphenation dictionary.
+///
+/// Implement this trait to provide hyphenation points for words, for example using TeX
+/// hyphenation patterns stored in flash.
+pub trait Hyphenator {
+    /// Returns the last position where `word` may be hyphenated, that is not greater than `max`.
+    ///
+    /// The returned value is a byte offset into `word`, and must be on a character boundary.
+    /// Return `None` if the word can't be hyphenated before `max`. Invalid positions are ignored.
+    fn hyphenate(&self, word: &str, max: usize) -> Option<usize>;
+}
+
+impl<H> Hyphenator for &H
+where
+    H: Hyphenator + ?Sized,
+{
+    #[inline]
+    fn hyphenate(&self, word: &str, max: usize) -> Option<usize> {
+        (**self).hyphenate(word, max)
+    }
+}
+
+/// Hyphenation plugin.
+///
+/// When a word doesn't fit into the current line, the text box asks the [`Hyphenator`] for a
+/// position where the word can be broken. The first part of the word is displayed at the end of
+/// the line, followed by a hyphen, and the rest of the word continues in the next line.
+///
+/// If the hyphenator doesn't return a suitable position, the word is wrapped as usual.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{
+///     plugin::hyphenation::{Hyphenation, Hyphenator},
+///     TextBox,
+/// };
+///
+/// /// Allows breaking words after any vowel.
+/// struct AfterVowels;
+///
+/// impl Hyphenator for AfterVowels {
+///     fn hyphenate(&self, word: &str, max: usize) -> Option<usize> {
+///         word[..max]
+///             .char_indices()
+///             .filter(|(_, c)| "aeiou".contains(*c))
+///             .map(|(idx, c)| idx + c.len_utf8())
+///             .filter(|pos| *pos <= max)
+///             .last()
+///     }
+/// }
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let text_box = TextBox::new("A hyphenated text", bounds, character_style)
+///     .add_plugin(Hyphenation::new(&AfterVowels));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+///
+/// [`Hyphenator`]: trait.Hyphenator.html
+#[derive(Copy, Clone, Debug)]
+pub struct Hyphenation<H> {
+    hyphenator: H,
+}
+
+impl<H> Hyphenation<H>
+where
+    H: Hyphenator + Clone,
+{
+    /// Creates a new hyphenation plugin using the given hyphenator.
+    #[inline]
+    pub const fn new(hyphenator: H) -> Self {
+        Self { hyphenator }
+    }
+}
+
+impl<'a, C, H> Plugin<'a, C> for Hyphenation<H>
+where
+    C: PixelColor,
+    H: Hyphenator + Clone,
+{
+    #[inline]
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        self.hyphenator.hyphenate(word, max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        plugin::hyphenation::{Hyphenation, Hyphenator},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    /// Allows hyphenating words after every second character.
+    #[derive(Clone)]
+    struct EverySecond;
+
+    impl Hyphenator for EverySecond {
+        fn hyphenate(&self, _word: &str, max: usize) -> Option<usize> {
+            Some(max - max % 2)
+        }
+    }
+
+    #[track_caller]
+    pub fn assert_rendered(text: &str, size: Size, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(Hyphenation::new(EverySecond))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn hyphenates_at_legal_position() {
+        assert_rendered(
+            "a bcdef",
+            size_for(&FONT_6X9, 5, 2),
+            &[
+                "..............................",
+                ".............#................",
+                ".............#................",
+                "..###........###....###.......",
+                ".#..#........#..#..#....#####.",
+                ".#..#........#..#..#..........",
+                "..###........###....###.......",
+                "..............................",
+                "..............................",
+                "..................            ",
+                "....#..........#..            ",
+                "....#.........#.#.            ",
+                "..###...##....#...            ",
+                ".#..#..#.##..###..            ",
+                ".#..#..##.....#...            ",
+                "..###...###...#...            ",
+                "..................            ",
+                "..................            ",
+            ],
+        );
+    }
+
+    #[test]
+    fn no_legal_position_wraps_word() {
+        assert_rendered(
+            "ab cdef",
+            size_for(&FONT_6X9, 4, 2),
+            &[
+                "............            ",
+                ".......#....            ",
+                ".......#....            ",
+                "..###..###..            ",
+                ".#..#..#..#.            ",
+                ".#..#..#..#.            ",
+                "..###..###..            ",
+                "............            ",
+                "............            ",
+                "........................",
+                "..........#..........#..",
+                "..........#.........#.#.",
+                "..###...###...##....#...",
+                ".#.....#..#..#.##..###..",
+                ".#.....#..#..##.....#...",
+                "..###...###...###...#...",
+                "........................",
+                "........................",
+            ],
+        );
+    }
+
+    #[test]
+    fn long_word_is_hyphenated() {
+        assert_rendered(
+            "abcdefg",
+            size_for(&FONT_6X9, 4, 3),
+            &[
+                "..................",
+                ".......#..........",
+                ".......#..........",
+                "..###..###........",
+                ".#..#..#..#.#####.",
+                ".#..#..#..#.......",
+                "..###..###........",
+                "..................",
+                "..................",
+                "..................",
+                "..........#.......",
+                "..........#.......",
+                "..###...###.......",
+                ".#.....#..#.#####.",
+                ".#.....#..#.......",
+                "..###...###.......",
+                "..................",
+                "..................",
+                "..................",
+                ".........#........",
+                "........#.#.......",
+                "..##....#.....##..",
+                ".#.##..###...#..#.",
+                ".##.....#....#..#.",
+                "..###...#.....###.",
+                "................#.",
+                "..............##..",
+            ],
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Color words based on a keyword table.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::plugin::Plugin;
+
+/// Keyword matching modes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum KeywordMatch {
+    /// The word must be equal to the keyword.
+    Exact,
+
+    /// The word must start with the keyword.
+    Prefix,
+}
+
+/// A single keyword and the color of matching words.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Keyword<'k, C> {
+    /// The keyword.
+    pub word: &'k str,
+
+    /// The text color of matching words.
+    pub color: C,
+
+    /// How words are matched against the keyword.
+    pub matching: KeywordMatch,
+}
+
+impl<'k, C> Keyword<'k, C> {
+    /// Creates a keyword that colors words equal to `word`.
+    #[inline]
+    pub const fn exact(word: &'k str, color: C) -> Self {
+        Self {
+            word,
+            color,
+            matching: KeywordMatch::Exact,
+        }
+    }
+
+    /// Creates a keyword that colors words starting with `word`.
+    #[inline]
+    pub const fn prefix(word: &'k str, color: C) -> Self {
+        Self {
+            word,
+            color,
+            matching: KeywordMatch::Prefix,
+        }
+    }
+
+    /// Returns whether `word` matches this keyword.
+    #[inline]
+    pub fn matches(&self, word: &str) -> bool {
+        match self.matching {
+            KeywordMatch::Exact => word == self.word,
+            KeywordMatch::Prefix => word.starts_with(self.word),
+        }
+    }
+}
+
+/// Keyword coloring plugin.
+///
+/// Draws words that match an entry of the keyword table using the entry's text color. The table
+/// is searched in order, and the first matching entry is used. Other words, and the text around
+/// the matching words are drawn using the current character style.
+///
+/// Words are separated by whitespace. A word that is broken into multiple lines, or that contains
+/// soft hyphens is matched using its first part, and all of its parts are drawn using the same
+/// color.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{
+///     plugin::keywords::{Keyword, Keywords},
+///     TextBox,
+/// };
+///
+/// const LOG_LEVELS: &[Keyword<'static, Rgb888>] = &[
+///     Keyword::exact("ERROR", Rgb888::RED),
+///     Keyword::exact("WARN", Rgb888::YELLOW),
+///     Keyword::prefix("0x", Rgb888::CYAN),
+/// ];
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let text_box = TextBox::new("ERROR at 0x12", bounds, character_style)
+///     .add_plugin(Keywords::new(LOG_LEVELS));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Keywords<'k, C> {
+    keywords: &'k [Keyword<'k, C>],
+}
+
+impl<'k, C> Keywords<'k, C> {
+    /// Creates a new keyword coloring plugin using the given keyword table.
+    #[inline]
+    pub const fn new(keywords: &'k [Keyword<'k, C>]) -> Self {
+        Self { keywords }
+    }
+}
+
+impl<'a, 'k, C: PixelColor> Plugin<'a, C> for Keywords<'k, C> {
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        self.keywords
+            .iter()
+            .find(|keyword| keyword.matches(word))
+            .map(|keyword| keyword.color)
+    }
+}
+
+/// Word highlighting plugin.
+///
+/// Calls a function for every word, and draws the word using the returned text color. Words for
+/// which the function returns `None` are drawn using the current character style. Words are
+/// passed to the function the same way as they are matched by [`Keywords`].
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::keywords::Highlight, TextBox};
+///
+/// fn highlight(word: &str) -> Option<Rgb888> {
+///     match word {
+///         "ERROR" => Some(Rgb888::RED),
+///         "WARN" => Some(Rgb888::YELLOW),
+///         _ if word.starts_with(|c: char| c.is_ascii_digit()) => Some(Rgb888::CYAN),
+///         _ => None,
+///     }
+/// }
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let text_box = TextBox::new("WARN 3 retries", bounds, character_style)
+///     .add_plugin(Highlight::new(highlight));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+///
+/// [`Keywords`]: struct.Keywords.html
+#[derive(Copy, Clone, Debug)]
+pub struct Highlight<C> {
+    color: fn(&str) -> Option<C>,
+}
+
+impl<C> Highlight<C> {
+    /// Creates a new plugin that colors words using the given function.
+    #[inline]
+    pub const fn new(color: fn(&str) -> Option<C>) -> Self {
+        Self { color }
+    }
+}
+
+impl<'a, C: PixelColor> Plugin<'a, C> for Highlight<C> {
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        (self.color)(word)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        plugin::keywords::{Highlight, Keyword, Keywords},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    const KEYWORDS: &[Keyword<'static, BinaryColor>] = &[
+        Keyword::exact("a", BinaryColor::Off),
+        Keyword::prefix("bc", BinaryColor::Off),
+    ];
+
+    #[track_caller]
+    pub fn assert_rendered(text: &str, size: Size, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(Keywords::new(KEYWORDS))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn exact_match() {
+        assert_rendered(
+            "a ab",
+            size_for(&FONT_6X9, 4, 1),
+            &[
+                "                       ",
+                "                   #   ",
+                "                   #   ",
+                "  ...         ###  ### ",
+                " .  .        #  #  #  #",
+                " .  .        #  #  #  #",
+                "  ...         ###  ### ",
+            ],
+        );
+    }
+
+    #[test]
+    fn prefix_match() {
+        assert_rendered(
+            "bcd cb",
+            size_for(&FONT_6X9, 6, 1),
+            &[
+                "                                   ",
+                " .              .              #   ",
+                " .              .              #   ",
+                " ...    ...   ...         ###  ### ",
+                " .  .  .     .  .        #     #  #",
+                " .  .  .     .  .        #     #  #",
+                " ...    ...   ...         ###  ### ",
+            ],
+        );
+    }
+
+    #[test]
+    fn broken_word_keeps_color() {
+        assert_rendered(
+            "b bcdef",
+            size_for(&FONT_6X9, 3, 3),
+            &[
+                "                 ",
+                " #               ",
+                " #               ",
+                " ###             ",
+                " #  #            ",
+                " #  #            ",
+                " ###             ",
+                "                 ",
+                "                 ",
+                "                 ",
+                " .              .",
+                " .              .",
+                " ...    ...   ...",
+                " .  .  .     .  .",
+                " .  .  .     .  .",
+                " ...    ...   ...",
+                "                 ",
+                "                 ",
+                "                 ",
+                "         .       ",
+                "        . .      ",
+                "  ..    .        ",
+                " . ..  ...       ",
+                " ..     .        ",
+                "  ...   .        ",
+            ],
+        );
+    }
+
+    #[test]
+    fn wrapped_word_is_matched() {
+        assert_rendered(
+            "bb bc",
+            size_for(&FONT_6X9, 3, 2),
+            &[
+                "           ",
+                " #     #   ",
+                " #     #   ",
+                " ###   ### ",
+                " #  #  #  #",
+                " #  #  #  #",
+                " ###   ### ",
+                "           ",
+                "           ",
+                "           ",
+                " .         ",
+                " .         ",
+                " ...    ...",
+                " .  .  .   ",
+                " .  .  .   ",
+                " ...    ...",
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_matches_words() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a ab", bounds, character_style)
+            .add_plugin(Highlight::new(|word| match word {
+                "a" => Some(BinaryColor::Off),
+                _ => None,
+            }))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "                       ",
+            "                   #   ",
+            "                   #   ",
+            "  ...         ###  ### ",
+            " .  .        #  #  #  #",
+            " .  .        #  #  #  #",
+            "  ...         ###  ### ",
+        ]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Change the text color using markup tags.
+
+use core::marker::PhantomData;
+
+use embedded_graphics::{
+    pixelcolor::{Rgb888, RgbColor},
+    prelude::PixelColor,
+};
+
+use crate::{
+    parser::{ChangeTextStyle, Token},
+    plugin::Plugin,
+};
+
+/// Color names that can be used in markup tags.
+const COLOR_NAMES: &[(&str, Rgb888)] = &[
+    ("black", Rgb888::BLACK),
+    ("red", Rgb888::RED),
+    ("green", Rgb888::GREEN),
+    ("yellow", Rgb888::YELLOW),
+    ("blue", Rgb888::BLUE),
+    ("magenta", Rgb888::MAGENTA),
+    ("cyan", Rgb888::CYAN),
+    ("white", Rgb888::WHITE),
+];
+
+/// Parses the contents of a markup tag.
+fn parse_tag<C>(tag: &str) -> Option<ChangeTextStyle<C>>
+where
+    C: PixelColor + From<Rgb888>,
+{
+    if tag == "/" {
+        return Some(ChangeTextStyle::Reset);
+    }
+
+    let color = match tag.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => {
+            let channel = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+            Rgb888::new(channel(0)?, channel(2)?, channel(4)?)
+        }
+        Some(_) => return None,
+        None => COLOR_NAMES.iter().find(|(name, _)| *name == tag)?.1,
+    };
+
+    Some(ChangeTextStyle::TextColor(Some(color.into())))
+}
+
+/// Finds the first valid markup tag in `word`.
+///
+/// Returns the byte range of the tag and the text style change it represents.
+fn find_tag<C>(word: &str) -> Option<(usize, usize, ChangeTextStyle<C>)>
+where
+    C: PixelColor + From<Rgb888>,
+{
+    let mut from = 0;
+    while let Some(start) = word[from..].find('{').map(|idx| from + idx) {
+        let end = start + word[start..].find('}')?;
+        if let Some(change) = parse_tag(&word[start + 1..end]) {
+            return Some((start, end + 1, change));
+        }
+        from = start + 1;
+    }
+
+    None
+}
+
+/// Markup plugin.
+///
+/// Changes the text color using markup tags in the text, without depending on the ANSI escape
+/// sequence parser. The following tags are supported:
+///
+///  * `{black}`, `{red}`, `{green}`, `{yellow}`, `{blue}`, `{magenta}`, `{cyan}` and `{white}`
+///    change the text color.
+///  * `{#RRGGBB}` changes the text color to the given hexadecimal RGB color.
+///  * `{/}` resets the text style to the character style of the text box.
+///
+/// Tags don't nest, `{/}` always restores the original text style. Braces that don't form a
+/// supported tag are displayed as text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::markup::Markup, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let text_box = TextBox::new("{red}warning{/}: {#00FF00}ok{/}", bounds, character_style)
+///     .add_plugin(Markup::new());
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Markup<'a, C> {
+    /// The part of the current word that has not been processed.
+    pending: Option<&'a str>,
+    _marker: PhantomData<C>,
+}
+
+impl<'a, C> Markup<'a, C> {
+    /// Creates a new markup plugin.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            pending: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C> Default for Markup<'a, C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Markup<'a, C>
+where
+    C: PixelColor + From<Rgb888>,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        let word = match self.pending.take() {
+            Some(word) => word,
+            None => match next_token()? {
+                Token::Word(word) => word,
+                token => return Some(token),
+            },
+        };
+
+        match find_tag(word) {
+            Some((0, end, change)) => {
+                if end < word.len() {
+                    self.pending = Some(&word[end..]);
+                }
+                Some(Token::ChangeTextStyle(change))
+            }
+            Some((start, _, _)) => {
+                self.pending = Some(&word[start..]);
+                Some(Token::Word(&word[..start]))
+            }
+            None => Some(Token::Word(word)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{plugin::markup::Markup, utils::test::size_for, TextBox};
+
+    #[track_caller]
+    fn assert_rendered(text: &str, size: Size, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(Markup::new())
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn tags_change_color() {
+        assert_rendered(
+            "a{black}b{/}c {#000000}d",
+            size_for(&FONT_6X9, 5, 1),
+            &[
+                "                             ",
+                "       .                    .",
+                "       .                    .",
+                "  ###  ...    ###         ...",
+                " #  #  .  .  #           .  .",
+                " #  #  .  .  #           .  .",
+                "  ###  ...    ###         ...",
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_displayed() {
+        assert_rendered(
+            "{x}",
+            size_for(&FONT_6X9, 3, 1),
+            &[
+                "   #         #  ",
+                "  #           # ",
+                "  #           # ",
+                " #     #  #    #",
+                "  #     ##    # ",
+                "  #     ##    # ",
+                "   #   #  #  #  ",
+            ],
+        );
+    }
+
+    #[test]
+    fn broken_word_after_tag() {
+        assert_rendered(
+            "{black}abcd",
+            size_for(&FONT_6X9, 3, 2),
+            &[
+                "                 ",
+                "       .         ",
+                "       .         ",
+                "  ...  ...    ...",
+                " .  .  .  .  .   ",
+                " .  .  .  .  .   ",
+                "  ...  ...    ...",
+                "                 ",
+                "                 ",
+                "                 ",
+                "    .            ",
+                "    .            ",
+                "  ...            ",
+                " .  .            ",
+                " .  .            ",
+                "  ...            ",
+            ],
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Scroll lines of text horizontally.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::{
+    grapheme::{grapheme_count, grapheme_offset},
+    parser::Token,
+    plugin::Plugin,
+};
+
+/// Returns `s` without its first `n` characters.
+fn skip_n_chars(s: &str, n: u32) -> &str {
+    &s[grapheme_offset(s, n)..]
+}
+
+/// Marquee plugin.
+///
+/// Hides the first `offset` characters of every paragraph, which scrolls the text to the left by
+/// `offset` characters. Animate the effect by drawing the text box with an increasing offset in
+/// every frame, for example `frame % text_length`.
+///
+/// The plugin is meant to be used with [`Wrap::None`] or [`Wrap::Ellipsis`], which display every
+/// paragraph in a single line. Printed characters, spaces and tabs are hidden, text style changes
+/// and custom tokens aren't.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{
+///     plugin::marquee::Marquee,
+///     style::{TextBoxStyleBuilder, Wrap},
+///     TextBox,
+/// };
+///
+/// let text = "Breaking news: embedded-text scrolls";
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let style = TextBoxStyleBuilder::new().wrap(Wrap::None).build();
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// for frame in 0..36 {
+///     let mut display = MockDisplay::new();
+///     TextBox::with_textbox_style(text, bounds, character_style, style)
+///         .add_plugin(Marquee::new(frame % 36))
+///         .draw(&mut display)
+///         .unwrap();
+/// }
+/// ```
+///
+/// [`Wrap::None`]: ../../style/enum.Wrap.html#variant.None
+/// [`Wrap::Ellipsis`]: ../../style/enum.Wrap.html#variant.Ellipsis
+#[derive(Copy, Clone, Debug)]
+pub struct Marquee {
+    offset: u32,
+
+    /// The number of characters that are still hidden in the current paragraph.
+    skip: u32,
+}
+
+impl Marquee {
+    /// Creates a new plugin that hides the first `offset` characters of every paragraph.
+    #[inline]
+    pub const fn new(offset: u32) -> Self {
+        Self {
+            offset,
+            skip: offset,
+        }
+    }
+
+    /// Hides up to `n` characters. Returns the number of characters hidden.
+    fn hide(&mut self, n: u32) -> u32 {
+        let hidden = n.min(self.skip);
+        self.skip -= hidden;
+        hidden
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Marquee
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        loop {
+            let token = next_token()?;
+            if self.skip == 0 {
+                if let Token::NewLine = token {
+                    self.skip = self.offset;
+                }
+                return Some(token);
+            }
+
+            match token {
+                Token::Word(word) => {
+                    let hidden = self.hide(grapheme_count(word));
+                    let word = skip_n_chars(word, hidden);
+                    if !word.is_empty() {
+                        return Some(Token::Word(word));
+                    }
+                }
+                Token::Whitespace(count, seq) => {
+                    let hidden = self.hide(count);
+                    if hidden < count {
+                        return Some(Token::Whitespace(count - hidden, skip_n_chars(seq, hidden)));
+                    }
+                }
+                Token::Tab => {
+                    self.hide(1);
+                }
+                Token::NewLine => {
+                    self.skip = self.offset;
+                    return Some(Token::NewLine);
+                }
+                token => return Some(token),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::Point,
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        plugin::marquee::Marquee,
+        style::{TextBoxStyleBuilder, Wrap},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_scrolled(text: &str, offset: u32, expected: &str) {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new().wrap(Wrap::None).build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 2));
+
+        let mut display = MockDisplay::new();
+        TextBox::with_textbox_style(text, bounds, character_style, style)
+            .add_plugin(Marquee::new(offset))
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected_display = MockDisplay::new();
+        TextBox::with_textbox_style(expected, bounds, character_style, style)
+            .draw(&mut expected_display)
+            .unwrap();
+
+        display.assert_eq(&expected_display);
+    }
+
+    #[test]
+    fn hides_the_start_of_paragraphs() {
+        assert_scrolled("ab cdef", 0, "ab cdef");
+        assert_scrolled("ab cdef", 1, "b cdef");
+        assert_scrolled("ab cdef", 3, "cdef");
+        assert_scrolled("ab cdef", 4, "def");
+        assert_scrolled("ab cdef\nghijkl", 2, " cdef\nijkl");
+    }
+
+    #[test]
+    fn hides_whole_paragraphs() {
+        assert_scrolled("ab\ncdef", 3, "\nf");
+    }
+}
@@ -0,0 +1,180 @@
+//! Hide the text behind mask characters.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::{grapheme::grapheme_count, parser::Token, plugin::Plugin};
+
+/// Masking plugin.
+///
+/// Displays every character of the text as the mask string, like password input fields do.
+/// Spaces and tabs are masked as well, so the lengths of the words are not revealed. Line breaks
+/// are kept.
+///
+/// The mask string should be a single character, for example `"*"`. The masked text is wrapped
+/// at any character if it doesn't fit into a line.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::masking::Masking, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("hunter2", bounds, character_style)
+///     .add_plugin(Masking::new("*"))
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Masking<'a> {
+    mask: &'a str,
+
+    /// The number of mask strings that are still to be returned for the current token.
+    pending: u32,
+}
+
+impl<'a> Masking<'a> {
+    /// Creates a new plugin that displays every character as `mask`.
+    #[inline]
+    pub const fn new(mask: &'a str) -> Self {
+        Self { mask, pending: 0 }
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Masking<'a>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        while self.pending == 0 {
+            self.pending = match next_token()? {
+                Token::Word(word) => grapheme_count(word),
+                Token::Whitespace(count, _) => count,
+                Token::Tab => 1,
+                // Soft hyphens and zero-width spaces would reveal the structure of the text.
+                Token::Break(_, _) => 0,
+                token => return Some(token),
+            };
+        }
+
+        // Masked spaces are words, so the masked text is wrapped like a single long word.
+        self.pending -= 1;
+        Some(Token::Word(self.mask))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::Point,
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{plugin::masking::Masking, utils::test::size_for, TextBox};
+
+    fn draw_masked(text: &str) -> MockDisplay<BinaryColor> {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(Masking::new("*"))
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn characters_are_masked() {
+        draw_masked("ab c").assert_pattern(&[
+            "                       ",
+            "                       ",
+            "#   # #   # #   # #   #",
+            " # #   # #   # #   # # ",
+            "##### ##### ##### #####",
+            " # #   # #   # #   # # ",
+            "#   # #   # #   # #   #",
+        ]);
+    }
+
+    #[test]
+    fn tabs_and_soft_hyphens_are_masked() {
+        let expected = [
+            "                 ",
+            "                 ",
+            "#   # #   # #   #",
+            " # #   # #   # # ",
+            "##### ##### #####",
+            " # #   # #   # # ",
+            "#   # #   # #   #",
+        ];
+
+        draw_masked("a\tb").assert_pattern(&expected);
+        draw_masked("ab\u{AD}c").assert_pattern(&expected);
+    }
+
+    #[test]
+    fn line_breaks_are_kept() {
+        draw_masked("ab\ncd").assert_pattern(&[
+            "           ",
+            "           ",
+            "#   # #   #",
+            " # #   # # ",
+            "##### #####",
+            " # #   # # ",
+            "#   # #   #",
+            "           ",
+            "           ",
+            "           ",
+            "           ",
+            "#   # #   #",
+            " # #   # # ",
+            "##### #####",
+            " # #   # # ",
+            "#   # #   #",
+        ]);
+    }
+
+    #[test]
+    fn masked_text_is_wrapped_anywhere() {
+        draw_masked("ab cdef").assert_pattern(&[
+            "                       ",
+            "                       ",
+            "#   # #   # #   # #   #",
+            " # #   # #   # #   # # ",
+            "##### ##### ##### #####",
+            " # #   # #   # #   # # ",
+            "#   # #   # #   # #   #",
+            "                       ",
+            "                       ",
+            "                       ",
+            "                       ",
+            "#   # #   # #   #      ",
+            " # #   # #   # #       ",
+            "##### ##### #####      ",
+            " # #   # #   # #       ",
+            "#   # #   # #   #      ",
+        ]);
+    }
+}
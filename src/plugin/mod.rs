@@ -15,7 +15,7 @@ use embedded_graphics::{
 use crate::{
     parser::{Parser, Token},
     rendering::cursor::Cursor,
-    TextBoxProperties,
+    LineMetrics, TextBoxProperties,
 };
 
 #[cfg(feature = "plugin")]
@@ -26,9 +26,47 @@ pub use private::Plugin;
 #[cfg(not(feature = "plugin"))]
 mod private;
 #[cfg(not(feature = "plugin"))]
-use private::Plugin;
+pub(crate) use private::Plugin;
 
+#[cfg(feature = "alloc")]
+pub mod boxed;
+pub mod escapes;
+#[cfg(feature = "plugin-gutter")]
+pub mod gutter;
+pub mod hyphenation;
+#[cfg(feature = "plugin-keywords")]
+pub mod keywords;
+pub mod markup;
+#[cfg(feature = "plugin-marquee")]
+pub mod marquee;
+#[cfg(feature = "plugin-masking")]
+pub mod masking;
+pub mod placeholders;
+pub mod prefix;
+pub mod regions;
+pub mod replay;
 pub mod tail;
+#[cfg(feature = "plugin-typewriter")]
+pub mod typewriter;
+#[cfg(feature = "plugin-whitespace")]
+pub mod whitespace;
+
+/// The reason the text box started a new line.
+///
+/// See `Plugin::on_wrap`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum WrapReason {
+    /// A word or a custom token didn't fit into the line, and was moved to the next line.
+    Word,
+
+    /// A word that doesn't fit into an empty line was broken, and its rest was moved to the next
+    /// line.
+    BrokenWord,
+
+    /// A word was broken at a soft hyphen, or at a position chosen by a hyphenation plugin.
+    Hyphenation,
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub(crate) enum ProcessingState {
@@ -74,6 +112,55 @@ where
     pub plugin: M,
     state: ProcessingState,
     peeked_token: (usize, Option<Token<'a, C>>),
+
+    /// Whether the text of the peeked token doesn't start at the current position of the source.
+    peeked_detached: bool,
+
+    /// Whether the peeked token is `remainder`.
+    peeked_remainder: bool,
+
+    /// The unprocessed part of a detached token.
+    remainder: Option<Token<'a, C>>,
+
+    /// Whether the peeked token is the first token of `prefix`.
+    peeked_prefix: bool,
+
+    /// Whether the next token starts a source line.
+    line_start: bool,
+
+    /// Whether the previous line ended with a line break inserted by the layout.
+    wrapped: bool,
+
+    /// The index of the next source line.
+    source_line: usize,
+
+    /// The prefix tokens of the current source line.
+    line_prefix: &'a [Token<'a, C>],
+
+    /// The prefix tokens that have not been processed yet.
+    prefix: &'a [Token<'a, C>],
+}
+
+/// Returns whether the text of `token` starts at the beginning of `text`.
+fn starts_at<C: PixelColor>(token: &Token<'_, C>, text: &str) -> bool {
+    match token {
+        Token::Word(s) | Token::Whitespace(_, s) => s.as_ptr() == text.as_ptr(),
+        _ => true,
+    }
+}
+
+/// Returns the part of `token` that follows the `consumed` part.
+fn token_remainder<'a, C: PixelColor>(
+    token: &Token<'a, C>,
+    consumed: &Token<'a, C>,
+) -> Option<Token<'a, C>> {
+    match (token, consumed) {
+        (Token::Word(w), Token::Word(c)) if c.len() < w.len() => Some(Token::Word(&w[c.len()..])),
+        (Token::Whitespace(n, w), Token::Whitespace(k, c)) if k < n => {
+            Some(Token::Whitespace(n - k, &w[c.len()..]))
+        }
+        _ => None,
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -82,6 +169,10 @@ where
     C: PixelColor,
 {
     pub inner: RefCell<PluginInner<'a, M, C>>,
+
+    /// Whether the plugin is notified about the rendered lines. Text boxes without plugins don't
+    /// measure lines that they don't need to measure.
+    line_metrics: bool,
 }
 
 impl<'a, M, C> Hash for PluginWrapper<'a, M, C>
@@ -93,6 +184,19 @@ where
     }
 }
 
+impl<'a, C> PluginWrapper<'a, NoPlugin<C>, C>
+where
+    C: PixelColor,
+{
+    /// Creates the wrapper of a text box without plugins.
+    pub fn without_plugin() -> Self {
+        Self {
+            line_metrics: false,
+            ..Self::new(NoPlugin::new())
+        }
+    }
+}
+
 impl<'a, M, C> PluginWrapper<'a, M, C>
 where
     C: PixelColor,
@@ -105,7 +209,17 @@ where
                 plugin,
                 state: ProcessingState::Measure,
                 peeked_token: (0, None),
+                peeked_detached: false,
+                peeked_remainder: false,
+                remainder: None,
+                peeked_prefix: false,
+                line_start: true,
+                wrapped: false,
+                source_line: 0,
+                line_prefix: &[],
+                prefix: &[],
             }),
+            line_metrics: true,
         }
     }
 
@@ -118,6 +232,11 @@ where
         this.lookahead = this.plugin.clone();
     }
 
+    /// Restores the state of the plugin from a copy made before.
+    pub fn restore(&self, copy: Self) {
+        *self.inner.borrow_mut() = copy.inner.into_inner();
+    }
+
     pub fn set_state(&self, state: ProcessingState) {
         self.inner.borrow_mut().state = state;
     }
@@ -135,21 +254,60 @@ where
         let mut this = self.inner.borrow_mut();
 
         if this.peeked_token.1.is_none() {
-            let mut cloned = source.clone();
-            this.peeked_token.1 = this.lookahead.next_token(|| cloned.next());
-            this.peeked_token.0 = source.as_str().len() - cloned.as_str().len();
+            if this.line_start {
+                let line = this.source_line;
+                let prefix = this.lookahead.line_prefix(line);
+
+                this.line_start = false;
+                this.source_line += 1;
+                this.line_prefix = prefix;
+                this.prefix = prefix;
+            }
+
+            this.peeked_prefix = false;
+            if let Some(remainder) = this.remainder.clone() {
+                this.peeked_token = (0, Some(remainder));
+                this.peeked_detached = true;
+                this.peeked_remainder = true;
+            } else if let Some(token) = this.prefix.first() {
+                this.peeked_token = (0, Some(token.clone()));
+                this.peeked_detached = true;
+                this.peeked_remainder = false;
+                this.peeked_prefix = true;
+            } else {
+                let mut cloned = source.clone();
+                let token = this.lookahead.next_token(|| cloned.next());
+
+                this.peeked_detached = match &token {
+                    Some(token) => !starts_at(token, source.as_str()),
+                    None => false,
+                };
+                this.peeked_remainder = false;
+                this.peeked_token = (source.as_str().len() - cloned.as_str().len(), token);
+            }
         }
         this.peeked_token.1.clone()
     }
 
+    #[allow(unsafe_code)]
     pub fn consume_peeked_token(&self, source: &mut Parser<'a, C>) {
         let mut this = self.inner.borrow_mut();
 
         unsafe {
             source.consume(this.peeked_token.0);
         }
+        if this.peeked_prefix {
+            this.prefix = &this.prefix[1..];
+            this.peeked_prefix = false;
+        } else if let Some(Token::NewLine) = this.peeked_token.1 {
+            this.line_start = true;
+        }
         this.peeked_token.0 = 0;
         this.peeked_token.1 = None;
+        if this.peeked_remainder {
+            this.remainder = None;
+            this.peeked_remainder = false;
+        }
 
         this.plugin = this.lookahead.clone();
     }
@@ -157,10 +315,23 @@ where
     pub fn replace_peeked_token(&self, len: usize, token: Token<'a, C>) {
         let mut this = self.inner.borrow_mut();
 
-        this.peeked_token.0 = len;
-        this.peeked_token.1.replace(token);
+        if this.peeked_detached {
+            // Plugins may generate tokens whose text is not at the current position of the
+            // source, so the rest of the token can't be parsed again. Keep the rest instead, and
+            // consume what the plugin used to generate the token.
+            this.remainder = this
+                .peeked_token
+                .1
+                .as_ref()
+                .and_then(|peeked| token_remainder(peeked, &token));
+            this.peeked_remainder = false;
+            this.peeked_token.1.replace(token);
+        } else {
+            this.peeked_token.0 = len;
+            this.peeked_token.1.replace(token);
 
-        this.lookahead = this.plugin.clone();
+            this.lookahead = this.plugin.clone();
+        }
     }
 
     pub fn on_start_render<S: CharacterStyle>(
@@ -174,6 +345,94 @@ where
         this.plugin.on_start_render(cursor, props);
     }
 
+    /// Returns whether the next token starts a source line.
+    pub fn at_source_line_start(&self) -> bool {
+        self.inner.borrow().line_start
+    }
+
+    /// Sets whether the next token starts a source line.
+    pub fn set_source_line_start(&self, line_start: bool) {
+        self.inner.borrow_mut().line_start = line_start;
+    }
+
+    /// Returns the index of the next source line.
+    pub fn source_line(&self) -> usize {
+        self.inner.borrow().source_line
+    }
+
+    /// Returns whether the previous line was wrapped, and clears the flag.
+    pub fn take_wrapped(&self) -> bool {
+        core::mem::replace(&mut self.inner.borrow_mut().wrapped, false)
+    }
+
+    /// Returns the prefix of the current source line, if the current line is a wrapped line that
+    /// should be indented to align with the text after the prefix.
+    pub fn wrapped_line_indent(&self) -> Option<&'a [Token<'a, C>]> {
+        let mut this = self.inner.borrow_mut();
+
+        if this.line_start || !this.prefix.is_empty() || this.line_prefix.is_empty() {
+            return None;
+        }
+
+        if this.lookahead.indent_wrapped_lines() {
+            Some(this.line_prefix)
+        } else {
+            None
+        }
+    }
+
+    pub fn word_color(&self, word: &str) -> Option<C> {
+        self.inner.borrow_mut().lookahead.word_color(word)
+    }
+
+    pub fn hyphenate(&self, word: &str, max: usize) -> Option<usize> {
+        self.inner.borrow_mut().lookahead.hyphenate(word, max)
+    }
+
+    pub fn custom_width(&self, id: u32) -> u32 {
+        self.inner.borrow_mut().lookahead.custom_width(id)
+    }
+
+    pub fn draw_custom<T, D>(
+        &self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        self.inner
+            .borrow_mut()
+            .lookahead
+            .draw_custom(id, draw_target, character_style, bounds)
+    }
+
+    /// Returns whether the plugin needs the metrics of the rendered lines.
+    pub fn wants_line_metrics(&self) -> bool {
+        self.line_metrics
+    }
+
+    /// Notifies the plugin about a line break inserted by the layout, while rendering.
+    pub fn on_wrap(&self, reason: WrapReason) {
+        let mut this = self.inner.borrow_mut();
+        this.wrapped = true;
+        if this.state == ProcessingState::Render {
+            this.plugin.on_wrap(reason);
+
+            this.lookahead = this.plugin.clone();
+        }
+    }
+
+    pub fn post_line(&self, line: LineMetrics) {
+        let mut this = self.inner.borrow_mut();
+        this.plugin.post_line(line);
+
+        this.lookahead = this.plugin.clone();
+    }
+
     pub fn post_render<T, D>(
         &self,
         draw_target: &mut D,
@@ -191,3 +450,416 @@ where
             .post_render(draw_target, character_style, text, bounds)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        draw_target::DrawTarget,
+        mock_display::MockDisplay,
+        mono_font::{
+            ascii::{FONT_4X6, FONT_6X9},
+            MonoTextStyle,
+        },
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::{Primitive, PrimitiveStyle, Rectangle},
+        text::renderer::TextRenderer,
+        Drawable,
+    };
+
+    use core::cell::Cell;
+
+    use crate::{
+        alignment::{HorizontalAlignment, VerticalAlignment},
+        parser::{ChangeTextStyle, Token},
+        plugin::{escapes::EscapeDialect, private::Plugin, WrapReason},
+        style::TextBoxStyleBuilder,
+        utils::test::size_for,
+        LineMetrics, TextBox,
+    };
+
+    /// Replaces `@` words with a 4 pixels wide filled box.
+    #[derive(Clone)]
+    struct Icon;
+
+    impl<'a> Plugin<'a, BinaryColor> for Icon {
+        fn next_token(
+            &mut self,
+            mut next_token: impl FnMut() -> Option<Token<'a, BinaryColor>>,
+        ) -> Option<Token<'a, BinaryColor>> {
+            match next_token()? {
+                Token::Word("@") => Some(Token::Custom(0)),
+                token => Some(token),
+            }
+        }
+
+        fn custom_width(&mut self, _id: u32) -> u32 {
+            4
+        }
+
+        fn draw_custom<T, D>(
+            &mut self,
+            _id: u32,
+            draw_target: &mut D,
+            _character_style: &T,
+            bounds: Rectangle,
+        ) -> Result<(), D::Error>
+        where
+            T: TextRenderer<Color = BinaryColor>,
+            D: DrawTarget<Color = BinaryColor>,
+        {
+            Rectangle::new(bounds.top_left + Point::new(0, 3), Size::new(4, 4))
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(draw_target)
+        }
+    }
+
+    #[test]
+    fn custom_tokens_are_drawn_inline() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), Size::new(16, 18));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a @ @", bounds, character_style)
+            .add_plugin(Icon)
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "                ",
+            "                ",
+            "                ",
+            "  ###       ####",
+            " #  #       ####",
+            " #  #       ####",
+            "  ###       ####",
+            "                ",
+            "                ",
+            "                ",
+            "                ",
+            "                ",
+            "####            ",
+            "####            ",
+            "####            ",
+            "####            ",
+        ]);
+    }
+
+    #[test]
+    fn custom_tokens_wrap() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 2));
+
+        let text_box = TextBox::new("a @", bounds, character_style).add_plugin(Icon);
+
+        assert_eq!(text_box.line_metrics().count(), 2);
+    }
+
+    /// Draws the first source line using the first font of the text box.
+    #[derive(Clone, Default)]
+    struct Heading {
+        started: bool,
+        reset: bool,
+    }
+
+    impl<'a> Plugin<'a, BinaryColor> for Heading {
+        fn next_token(
+            &mut self,
+            mut next_token: impl FnMut() -> Option<Token<'a, BinaryColor>>,
+        ) -> Option<Token<'a, BinaryColor>> {
+            if !self.started {
+                self.started = true;
+                return Some(Token::ChangeTextStyle(ChangeTextStyle::Font(0)));
+            }
+
+            if self.reset {
+                self.reset = false;
+                return Some(Token::ChangeTextStyle(ChangeTextStyle::Reset));
+            }
+
+            match next_token()? {
+                Token::NewLine => {
+                    self.reset = true;
+                    Some(Token::NewLine)
+                }
+                token => Some(token),
+            }
+        }
+    }
+
+    #[test]
+    fn lines_use_the_height_of_their_font() {
+        let body = MonoTextStyle::new(&FONT_4X6, BinaryColor::On);
+        let fonts = [MonoTextStyle::new(&FONT_6X9, BinaryColor::On)];
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Bottom)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), Size::new(12, 20));
+
+        let mut display = MockDisplay::new();
+        let mut text_box = TextBox::with_textbox_style("ab\nab", bounds, body, style)
+            .add_plugin(Heading::default());
+        text_box.set_fonts(&fonts);
+        text_box.draw(&mut display).unwrap();
+
+        // The heading is 9 pixels tall, the body line is 6 pixels tall.
+        let mut expected = MockDisplay::new();
+        TextBox::new(
+            "ab",
+            Rectangle::new(Point::new(0, 5), Size::new(12, 9)),
+            fonts[0],
+        )
+        .draw(&mut expected)
+        .unwrap();
+        TextBox::new(
+            "ab",
+            Rectangle::new(Point::new(0, 14), Size::new(12, 6)),
+            body,
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    /// Replaces a word with an other word.
+    #[derive(Clone)]
+    struct Rename(&'static str, &'static str);
+
+    impl<'a> Plugin<'a, BinaryColor> for Rename {
+        fn next_token(
+            &mut self,
+            mut next_token: impl FnMut() -> Option<Token<'a, BinaryColor>>,
+        ) -> Option<Token<'a, BinaryColor>> {
+            match next_token()? {
+                Token::Word(word) if word == self.0 => Some(Token::Word(self.1)),
+                token => Some(token),
+            }
+        }
+    }
+
+    #[test]
+    fn plugins_read_tokens_of_the_previous_plugin() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1));
+
+        let mut forward = MockDisplay::new();
+        TextBox::new("a", bounds, character_style)
+            .add_plugin(Rename("a", "b"))
+            .add_plugin(Rename("b", "c"))
+            .add_plugin(Rename("c", "d"))
+            .draw(&mut forward)
+            .unwrap();
+
+        let mut backward = MockDisplay::new();
+        TextBox::new("a", bounds, character_style)
+            .add_plugin(Rename("c", "d"))
+            .add_plugin(Rename("b", "c"))
+            .add_plugin(Rename("a", "b"))
+            .draw(&mut backward)
+            .unwrap();
+
+        let expected = |text| {
+            let mut display = MockDisplay::new();
+            TextBox::new(text, bounds, character_style)
+                .draw(&mut display)
+                .unwrap();
+            display
+        };
+
+        forward.assert_eq(&expected("d"));
+        backward.assert_eq(&expected("b"));
+    }
+
+    #[test]
+    fn optional_plugins_can_be_disabled() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1));
+
+        let draw = |first: bool, second: bool| {
+            let mut display = MockDisplay::new();
+            TextBox::new("a", bounds, character_style)
+                .add_plugin(Some(Rename("a", "b")).filter(|_| first))
+                .add_plugin(Some(Rename("a", "c")).filter(|_| second))
+                .draw(&mut display)
+                .unwrap();
+            display
+        };
+
+        let expected = |text| {
+            let mut display = MockDisplay::new();
+            TextBox::new(text, bounds, character_style)
+                .draw(&mut display)
+                .unwrap();
+            display
+        };
+
+        draw(false, false).assert_eq(&expected("a"));
+        draw(true, true).assert_eq(&expected("b"));
+        draw(false, true).assert_eq(&expected("c"));
+    }
+
+    /// Records the metrics of the first rendered lines.
+    #[derive(Clone)]
+    struct LineRecorder<'r> {
+        lines: &'r Cell<[Option<LineMetrics>; 4]>,
+    }
+
+    impl<'a> Plugin<'a, BinaryColor> for LineRecorder<'_> {
+        fn post_line(&mut self, line: LineMetrics) {
+            let mut lines = self.lines.get();
+            if let Some(slot) = lines.iter_mut().find(|slot| slot.is_none()) {
+                *slot = Some(line);
+            }
+            self.lines.set(lines);
+        }
+    }
+
+    #[test]
+    fn rendered_lines_are_reported() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let style = TextBoxStyleBuilder::new()
+            .alignment(HorizontalAlignment::Center)
+            .build();
+        let bounds = Rectangle::new(Point::new(1, 2), size_for(&FONT_6X9, 5, 2));
+
+        let lines = Cell::new([None; 4]);
+        let text_box = TextBox::with_textbox_style("ab\nc d ef", bounds, character_style, style)
+            .add_plugin(LineRecorder { lines: &lines });
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        let mut expected = text_box.line_metrics();
+        let first = expected.next();
+        let second = expected.next();
+        assert_eq!(lines.get(), [first, second, None, None]);
+
+        assert_eq!(
+            first.unwrap().bounds,
+            Rectangle::new(Point::new(10, 2), Size::new(12, 9))
+        );
+        assert_eq!((first.unwrap().start, first.unwrap().end), (0, 3));
+        assert_eq!((second.unwrap().start, second.unwrap().end), (3, 7));
+    }
+
+    /// Records the reasons of the first line breaks.
+    #[derive(Clone)]
+    struct WrapRecorder<'r> {
+        reasons: &'r Cell<[Option<WrapReason>; 4]>,
+    }
+
+    impl<'a> Plugin<'a, BinaryColor> for WrapRecorder<'_> {
+        fn on_wrap(&mut self, reason: WrapReason) {
+            let mut reasons = self.reasons.get();
+            if let Some(slot) = reasons.iter_mut().find(|slot| slot.is_none()) {
+                *slot = Some(reason);
+            }
+            self.reasons.set(reasons);
+        }
+    }
+
+    #[test]
+    fn line_breaks_are_reported() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 6));
+
+        let reasons = Cell::new([None; 4]);
+        let text_box = TextBox::new("ab cd\nefghijkl xyz\u{ad}uvw", bounds, character_style)
+            .add_plugin(WrapRecorder { reasons: &reasons });
+
+        // Measuring the text doesn't report line breaks.
+        assert_eq!(text_box.line_metrics().count(), 6);
+        assert_eq!(reasons.get(), [None; 4]);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        assert_eq!(
+            reasons.get(),
+            [
+                Some(WrapReason::Word),
+                Some(WrapReason::BrokenWord),
+                Some(WrapReason::Word),
+                Some(WrapReason::Hyphenation),
+            ]
+        );
+    }
+
+    /// Counts the tokens that are read from the text.
+    #[derive(Clone)]
+    struct TokenCounter<'r> {
+        count: &'r Cell<u32>,
+    }
+
+    impl<'a> Plugin<'a, BinaryColor> for TokenCounter<'_> {
+        fn next_token(
+            &mut self,
+            mut next_token: impl FnMut() -> Option<Token<'a, BinaryColor>>,
+        ) -> Option<Token<'a, BinaryColor>> {
+            self.count.set(self.count.get() + 1);
+            next_token()
+        }
+    }
+
+    #[test]
+    fn justified_lines_are_processed_once() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let style = TextBoxStyleBuilder::new()
+            .alignment(HorizontalAlignment::Justified)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 11, 3));
+
+        let count = Cell::new(0);
+        let text_box = TextBox::with_textbox_style(
+            "lorem ipsum dolor sit amet",
+            bounds,
+            character_style,
+            style,
+        )
+        .add_plugin(TokenCounter { count: &count });
+
+        // Measuring the text processes every line once.
+        assert_eq!(text_box.line_metrics().count(), 3);
+        let measured = count.replace(0);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        assert_eq!(count.get(), measured);
+    }
+
+    #[test]
+    fn vertical_offset_shifts_text() {
+        fn parse(params: &str) -> Option<(usize, ChangeTextStyle<BinaryColor>)> {
+            let change = match params.as_bytes().first()? {
+                b'v' => ChangeTextStyle::VerticalOffset(2),
+                b'/' => ChangeTextStyle::Reset,
+                _ => return None,
+            };
+
+            Some((1, change))
+        }
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a$vb$/c", bounds, character_style)
+            .add_plugin(EscapeDialect::new('$', parse))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "                 ",
+            "                 ",
+            "                 ",
+            "  ###  #      ###",
+            " #  #  #     #   ",
+            " #  #  ###   #   ",
+            "  ###  #  #   ###",
+            "       #  #      ",
+            "       ###       ",
+        ]);
+    }
+}
@@ -0,0 +1,280 @@
+//! Substitute formatted values for placeholders in the text.
+
+use core::fmt::{self, Write};
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::{parser::Token, plugin::Plugin};
+
+/// A value that can be substituted for a placeholder.
+#[derive(Copy, Clone)]
+pub struct Value<'a> {
+    /// The name of the placeholder, without the braces.
+    pub name: &'a str,
+
+    /// The value, formatted using its `Display` implementation.
+    pub value: &'a dyn fmt::Display,
+}
+
+impl<'a> Value<'a> {
+    /// Creates a new value for the placeholder `{name}`.
+    #[inline]
+    pub fn new(name: &'a str, value: &'a dyn fmt::Display) -> Self {
+        Self { name, value }
+    }
+}
+
+impl fmt::Debug for Value<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Value")
+            .field("name", &self.name)
+            .field("value", &format_args!("{}", self.value))
+            .finish()
+    }
+}
+
+/// Writes formatted text into a byte buffer.
+struct BufferWriter<'b> {
+    buffer: &'b mut [u8],
+    len: usize,
+}
+
+impl Write for BufferWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buffer
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+/// Counts the bytes of formatted text.
+struct LengthCounter(usize);
+
+impl Write for LengthCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Returns the number of bytes `value` is formatted to.
+fn formatted_len(value: &dyn fmt::Display) -> usize {
+    let mut counter = LengthCounter(0);
+    // Writing to the counter never fails.
+    let _ = write!(counter, "{}", value);
+    counter.0
+}
+
+/// Placeholder substitution plugin.
+///
+/// Replaces placeholders in the text with formatted values, so the layout of a dashboard can be
+/// kept in a static string, and only the values need to be updated for every frame. A
+/// placeholder is the name of a value in braces, like `{temp}`, or the index of a value in the
+/// table, like `{0}`. Placeholders can be part of a word, like `{temp}°C`. Placeholders that
+/// don't match a value are displayed as text.
+///
+/// The values are formatted into a buffer when the plugin is created, using their `Display`
+/// implementation. Use a wrapper type to control the formatting, like the number of decimals.
+/// A formatted value is drawn as a single word, so it isn't wrapped.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{
+///     plugin::placeholders::{Placeholders, Value},
+///     TextBox,
+/// };
+///
+/// const LAYOUT: &str = "Temp: {temp}C\nFan: {1}%";
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let values = [Value::new("temp", &21), Value::new("fan", &80)];
+/// let mut buffer = [0; 16];
+/// let placeholders = Placeholders::new(&values, &mut buffer).unwrap();
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new(LAYOUT, bounds, character_style)
+///     .add_plugin(placeholders)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Placeholders<'a> {
+    values: &'a [Value<'a>],
+
+    /// The formatted values, one after the other.
+    formatted: &'a str,
+
+    /// The part of the current word that has not been processed.
+    pending: Option<&'a str>,
+}
+
+impl<'a> Placeholders<'a> {
+    /// Creates a new plugin that formats `values` into `buffer`.
+    ///
+    /// Returns an error if the formatted values don't fit into `buffer`, or if formatting a value
+    /// fails.
+    #[inline]
+    pub fn new(values: &'a [Value<'a>], buffer: &'a mut [u8]) -> Result<Self, fmt::Error> {
+        let mut writer = BufferWriter { buffer, len: 0 };
+        for value in values {
+            write!(writer, "{}", value.value)?;
+        }
+
+        let BufferWriter { buffer, len } = writer;
+        let buffer: &'a [u8] = buffer;
+        let formatted = core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?;
+
+        Ok(Self {
+            values,
+            formatted,
+            pending: None,
+        })
+    }
+
+    /// Returns the formatted value that belongs to the placeholder `name`.
+    fn value(&self, name: &str) -> Option<&'a str> {
+        let index = match name.parse::<usize>() {
+            Ok(index) if index < self.values.len() => index,
+            _ => self.values.iter().position(|value| value.name == name)?,
+        };
+
+        let start = self.values[..index]
+            .iter()
+            .map(|value| formatted_len(value.value))
+            .sum::<usize>();
+        let end = start + formatted_len(self.values[index].value);
+
+        self.formatted.get(start..end)
+    }
+
+    /// Finds the first placeholder in `word` that matches a value.
+    ///
+    /// Returns the byte range of the placeholder and the formatted value.
+    fn find_placeholder(&self, word: &str) -> Option<(usize, usize, &'a str)> {
+        let mut from = 0;
+        while let Some(start) = word[from..].find('{').map(|idx| from + idx) {
+            let end = match word[start..].find('}') {
+                Some(len) => start + len + 1,
+                None => return None,
+            };
+
+            if let Some(value) = self.value(&word[start + 1..end - 1]) {
+                return Some((start, end, value));
+            }
+            from = start + 1;
+        }
+
+        None
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Placeholders<'a>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        let word = match self.pending.take() {
+            Some(word) => word,
+            None => match next_token()? {
+                Token::Word(word) => word,
+                token => return Some(token),
+            },
+        };
+
+        match self.find_placeholder(word) {
+            Some((0, end, value)) => {
+                if end < word.len() {
+                    self.pending = Some(&word[end..]);
+                }
+                Some(Token::Word(value))
+            }
+            Some((start, _, _)) => {
+                self.pending = Some(&word[start..]);
+                Some(Token::Word(&word[..start]))
+            }
+            None => Some(Token::Word(word)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        plugin::placeholders::{Placeholders, Value},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_substituted(text: &str, values: &[Value<'_>], expected: &str) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 6, 2));
+
+        let mut buffer = [0; 16];
+        let placeholders = Placeholders::new(values, &mut buffer).unwrap();
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(placeholders)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected_display = MockDisplay::new();
+        TextBox::new(expected, bounds, character_style)
+            .draw(&mut expected_display)
+            .unwrap();
+
+        display.assert_eq(&expected_display);
+    }
+
+    #[test]
+    fn placeholders_are_substituted() {
+        let values = [Value::new("t", &-4), Value::new("hum", &"55")];
+
+        assert_substituted("t={t}C {1}%", &values, "t=-4C 55%");
+        assert_substituted("{hum}{t}", &values, "55-4");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_displayed() {
+        let values = [Value::new("t", &1)];
+
+        assert_substituted("{x} {2} {t", &values, "{x} {2} {t");
+    }
+
+    #[test]
+    fn values_must_fit_into_the_buffer() {
+        let values = [Value::new("a", &12345), Value::new("b", &6789)];
+
+        assert!(Placeholders::new(&values, &mut [0; 9]).is_ok());
+        assert!(Placeholders::new(&values, &mut [0; 8]).is_err());
+    }
+}
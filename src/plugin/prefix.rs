@@ -0,0 +1,195 @@
+//! Insert a prefix at the start of every source line.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::{parser::Token, plugin::Plugin};
+
+/// Line prefix plugin.
+///
+/// Inserts the tokens returned by a function at the start of every source line, for example to
+/// display timestamps or log levels in front of log messages. Source lines start at the
+/// beginning of the text and after every newline character. Lines that are started by wrapping
+/// the text don't get a prefix, but they can be indented to align with the text that follows the
+/// prefix.
+///
+/// The function receives the index of the source line. The returned tokens must not contain
+/// newlines.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::prefix::LinePrefix, TextBox, Token};
+///
+/// fn level<'a>(line: usize) -> &'a [Token<'a, BinaryColor>] {
+///     match line {
+///         0 => &[Token::Word("I:"), Token::Whitespace(1, " ")],
+///         _ => &[Token::Word("E:"), Token::Whitespace(1, " ")],
+///     }
+/// }
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 36));
+///
+/// let text_box = TextBox::new("started\nsomething went wrong", bounds, character_style)
+///     .add_plugin(LinePrefix::new(level).indent_wrapped_lines(true));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct LinePrefix<'a, C>
+where
+    C: PixelColor,
+{
+    prefix: fn(usize) -> &'a [Token<'a, C>],
+    indent: bool,
+}
+
+impl<'a, C> LinePrefix<'a, C>
+where
+    C: PixelColor,
+{
+    /// Creates a new line prefix plugin.
+    #[inline]
+    pub fn new(prefix: fn(usize) -> &'a [Token<'a, C>]) -> Self {
+        Self {
+            prefix,
+            indent: false,
+        }
+    }
+
+    /// Sets whether wrapped lines are indented by the width of the prefix of their source line.
+    #[inline]
+    pub fn indent_wrapped_lines(mut self, indent: bool) -> Self {
+        self.indent = indent;
+        self
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for LinePrefix<'a, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        (self.prefix)(line)
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        self.indent
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{parser::Token, plugin::prefix::LinePrefix, utils::test::size_for, TextBox};
+
+    fn prefix<'a>(line: usize) -> &'a [Token<'a, BinaryColor>] {
+        match line {
+            0 => &[Token::Word("a"), Token::Whitespace(1, " ")],
+            _ => &[Token::Word("b"), Token::Whitespace(1, " ")],
+        }
+    }
+
+    #[track_caller]
+    fn assert_rendered(text: &str, plugin: LinePrefix<'static, BinaryColor>, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let size: Size = size_for(&FONT_6X9, 4, 3);
+
+        TextBox::new(text, Rectangle::new(Point::zero(), size), character_style)
+            .add_plugin(plugin)
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn prefix_source_lines() {
+        assert_rendered(
+            "c c\nc",
+            LinePrefix::new(prefix),
+            &[
+                "                 ",
+                "                 ",
+                "                 ",
+                "  ###         ###",
+                " #  #        #   ",
+                " #  #        #   ",
+                "  ###         ###",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "  ###            ",
+                " #               ",
+                " #               ",
+                "  ###            ",
+                "                 ",
+                "                 ",
+                "                 ",
+                " #               ",
+                " #               ",
+                " ###          ###",
+                " #  #        #   ",
+                " #  #        #   ",
+                " ###          ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn wrapped_lines_are_indented() {
+        assert_rendered(
+            "c c\nc",
+            LinePrefix::new(prefix).indent_wrapped_lines(true),
+            &[
+                "                 ",
+                "                 ",
+                "                 ",
+                "  ###         ###",
+                " #  #        #   ",
+                " #  #        #   ",
+                "  ###         ###",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "              ###",
+                "             #   ",
+                "             #   ",
+                "              ###",
+                "                 ",
+                "                 ",
+                "                 ",
+                " #               ",
+                " #               ",
+                " ###          ###",
+                " #  #        #   ",
+                " #  #        #   ",
+                " ###          ###",
+            ],
+        );
+    }
+}
@@ -8,7 +8,9 @@ use embedded_graphics::{
 };
 use object_chain::{Chain, ChainElement, Link};
 
-use crate::{parser::Token, rendering::cursor::Cursor, TextBoxProperties};
+use crate::{
+    parser::Token, plugin::WrapReason, rendering::cursor::Cursor, LineMetrics, TextBoxProperties,
+};
 
 /// Plugin trait.
 ///
@@ -18,6 +20,22 @@ use crate::{parser::Token, rendering::cursor::Cursor, TextBoxProperties};
 /// This is an experimental, unstable feature. It can be, and probably will be modified without
 /// any prior notice.
 /// Using plugins require enabling the `plugin` crate feature.
+///
+/// # Combining plugins
+///
+/// Any number of plugins can be added to a text box by calling `TextBox::add_plugin` repeatedly.
+/// The hooks of combined plugins are called in a fixed order:
+///
+///  * `next_token`: every plugin reads its tokens from the plugin that was added before it, and
+///    the first plugin reads the tokens of the parser. The text box reads the tokens of the
+///    plugin that was added last.
+///  * `new_line`, `render_token`, `draw_custom`, `post_render`, `post_line`, `on_wrap` and
+///    `on_start_render` are called in the order the plugins were added. `render_token` passes the token returned by
+///    a plugin to the next one.
+///  * `word_color`, `hyphenate` and `line_prefix` use the result of the plugin that was added
+///    last, and ask the plugins added before it if it has no result.
+///  * `custom_width` returns the largest width, and `indent_wrapped_lines` is enabled if any
+///    plugin enables it.
 pub trait Plugin<'a, C>: Clone
 where
     C: PixelColor,
@@ -44,6 +62,67 @@ where
         Some(token)
     }
 
+    /// Select the text color of a word.
+    ///
+    /// Called before the first part of every word is rendered. Returning `Some(color)` draws the
+    /// word using the given text color, without changing the text style of the rest of the text.
+    #[inline]
+    fn word_color(&mut self, _word: &str) -> Option<C> {
+        None
+    }
+
+    /// Find a hyphenation point in a word that doesn't fit into the current line.
+    ///
+    /// Returns the byte offset of the last position where `word` may be broken, that is not
+    /// greater than `max`. The text box inserts a hyphen at the returned position.
+    #[inline]
+    fn hyphenate(&mut self, _word: &str, _max: usize) -> Option<usize> {
+        None
+    }
+
+    /// Returns the tokens that are inserted at the start of a source line.
+    ///
+    /// Source lines start at the beginning of the text, and after every newline character. Lines
+    /// that are started by wrapping the text are not source lines. `line` is the index of the
+    /// source line. The returned tokens must not contain newlines.
+    #[inline]
+    fn line_prefix(&mut self, _line: usize) -> &'a [Token<'a, C>] {
+        &[]
+    }
+
+    /// Returns whether wrapped lines are indented by the width of the prefix of their source line.
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        false
+    }
+
+    /// Returns the width of a custom token, in pixels.
+    ///
+    /// The line is wrapped before a custom token that doesn't fit into the line. Custom tokens
+    /// that aren't known by the plugin should have a width of 0.
+    #[inline]
+    fn custom_width(&mut self, _id: u32) -> u32 {
+        0
+    }
+
+    /// Draws a custom token.
+    ///
+    /// `bounds` is as wide as the width returned by `custom_width`, and as tall as the line.
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        _id: u32,
+        _draw_target: &mut D,
+        _character_style: &T,
+        _bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        Ok(())
+    }
+
     /// Called after a piece of text is rendered.
     #[inline]
     fn post_render<T, D>(
@@ -68,20 +147,168 @@ where
         _props: TextBoxProperties<'_, S>,
     ) {
     }
+
+    /// Called after a line is rendered.
+    ///
+    /// `line` contains the area and the byte range of the line, and the layout information
+    /// that was used to place it. Lines are reported in the order they are rendered, including
+    /// lines above the visible area. Rendering stops after the last visible line, so lines below
+    /// it are not reported.
+    #[inline]
+    fn post_line(&mut self, _line: LineMetrics) {}
+
+    /// Called when the text is wrapped to a new line.
+    ///
+    /// Only line breaks that are inserted by the layout are reported, newline characters in the
+    /// text are not. The hook is called while the text is rendered, including the lines that
+    /// are above the visible area.
+    #[inline]
+    fn on_wrap(&mut self, _reason: WrapReason) {}
 }
 
 impl<'a, C> Plugin<'a, C> for super::NoPlugin<C> where C: PixelColor {}
 
+/// An optional plugin.
+///
+/// `None` doesn't modify the behaviour of the text box. Plugins that are enabled at runtime can be
+/// wrapped in an `Option`, so the type of the text box doesn't depend on which plugins are
+/// enabled, and the text box is only compiled once for every combination of enabled plugins.
+impl<'a, C, P> Plugin<'a, C> for Option<P>
+where
+    P: Plugin<'a, C>,
+    C: PixelColor,
+{
+    #[inline]
+    fn new_line(&mut self) {
+        if let Some(plugin) = self {
+            plugin.new_line();
+        }
+    }
+
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        match self {
+            Some(plugin) => plugin.next_token(next_token),
+            None => next_token(),
+        }
+    }
+
+    #[inline]
+    fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>> {
+        match self {
+            Some(plugin) => plugin.render_token(token),
+            None => Some(token),
+        }
+    }
+
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        self.as_mut().and_then(|plugin| plugin.word_color(word))
+    }
+
+    #[inline]
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        self.as_mut().and_then(|plugin| plugin.hyphenate(word, max))
+    }
+
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        match self {
+            Some(plugin) => plugin.line_prefix(line),
+            None => &[],
+        }
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        match self {
+            Some(plugin) => plugin.indent_wrapped_lines(),
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn custom_width(&mut self, id: u32) -> u32 {
+        self.as_mut().map_or(0, |plugin| plugin.custom_width(id))
+    }
+
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        match self {
+            Some(plugin) => plugin.draw_custom(id, draw_target, character_style, bounds),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn post_render<T, D>(
+        &mut self,
+        draw_target: &mut D,
+        character_style: &T,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        match self {
+            Some(plugin) => plugin.post_render(draw_target, character_style, text, bounds),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn on_start_render<S: CharacterStyle>(
+        &mut self,
+        cursor: &mut Cursor,
+        props: TextBoxProperties<'_, S>,
+    ) {
+        if let Some(plugin) = self {
+            plugin.on_start_render(cursor, props);
+        }
+    }
+
+    #[inline]
+    fn post_line(&mut self, line: LineMetrics) {
+        if let Some(plugin) = self {
+            plugin.post_line(line);
+        }
+    }
+
+    #[inline]
+    fn on_wrap(&mut self, reason: WrapReason) {
+        if let Some(plugin) = self {
+            plugin.on_wrap(reason);
+        }
+    }
+}
+
 impl<'a, C, P> Plugin<'a, C> for Chain<P>
 where
     P: Plugin<'a, C>,
     C: PixelColor,
     Chain<P>: Clone,
 {
+    #[inline]
     fn new_line(&mut self) {
         self.object.new_line();
     }
 
+    #[inline]
     fn next_token(
         &mut self,
         next_token: impl FnMut() -> Option<Token<'a, C>>,
@@ -89,10 +316,53 @@ where
         self.object.next_token(next_token)
     }
 
+    #[inline]
     fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>> {
         self.object.render_token(token)
     }
 
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        self.object.word_color(word)
+    }
+
+    #[inline]
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        self.object.hyphenate(word, max)
+    }
+
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        self.object.line_prefix(line)
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        self.object.indent_wrapped_lines()
+    }
+
+    #[inline]
+    fn custom_width(&mut self, id: u32) -> u32 {
+        self.object.custom_width(id)
+    }
+
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        self.object
+            .draw_custom(id, draw_target, character_style, bounds)
+    }
+
+    #[inline]
     fn post_render<T, D>(
         &mut self,
         draw_target: &mut D,
@@ -108,6 +378,7 @@ where
             .post_render(draw_target, character_style, text, bounds)
     }
 
+    #[inline]
     fn on_start_render<S: CharacterStyle>(
         &mut self,
         cursor: &mut Cursor,
@@ -115,6 +386,16 @@ where
     ) {
         self.object.on_start_render(cursor, props)
     }
+
+    #[inline]
+    fn post_line(&mut self, line: LineMetrics) {
+        self.object.post_line(line)
+    }
+
+    #[inline]
+    fn on_wrap(&mut self, reason: WrapReason) {
+        self.object.on_wrap(reason)
+    }
 }
 
 impl<'a, C, P, CE> Plugin<'a, C> for Link<P, CE>
@@ -124,11 +405,13 @@ where
     C: PixelColor,
     Link<P, CE>: Clone,
 {
+    #[inline]
     fn new_line(&mut self) {
         self.parent.new_line();
         self.object.new_line();
     }
 
+    #[inline]
     fn next_token(
         &mut self,
         mut next_token: impl FnMut() -> Option<Token<'a, C>>,
@@ -138,12 +421,70 @@ where
         self.object.next_token(next_token)
     }
 
+    #[inline]
     fn render_token(&mut self, token: Token<'a, C>) -> Option<Token<'a, C>> {
         self.parent
             .render_token(token)
             .and_then(|t| self.object.render_token(t))
     }
 
+    #[inline]
+    fn word_color(&mut self, word: &str) -> Option<C> {
+        self.object
+            .word_color(word)
+            .or_else(|| self.parent.word_color(word))
+    }
+
+    #[inline]
+    fn hyphenate(&mut self, word: &str, max: usize) -> Option<usize> {
+        self.object
+            .hyphenate(word, max)
+            .or_else(|| self.parent.hyphenate(word, max))
+    }
+
+    #[inline]
+    fn line_prefix(&mut self, line: usize) -> &'a [Token<'a, C>] {
+        let parent = self.parent.line_prefix(line);
+        let object = self.object.line_prefix(line);
+
+        if object.is_empty() {
+            parent
+        } else {
+            object
+        }
+    }
+
+    #[inline]
+    fn indent_wrapped_lines(&mut self) -> bool {
+        let parent = self.parent.indent_wrapped_lines();
+        self.object.indent_wrapped_lines() || parent
+    }
+
+    #[inline]
+    fn custom_width(&mut self, id: u32) -> u32 {
+        let parent = self.parent.custom_width(id);
+        self.object.custom_width(id).max(parent)
+    }
+
+    #[inline]
+    fn draw_custom<T, D>(
+        &mut self,
+        id: u32,
+        draw_target: &mut D,
+        character_style: &T,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        self.parent
+            .draw_custom(id, draw_target, character_style, bounds)?;
+        self.object
+            .draw_custom(id, draw_target, character_style, bounds)
+    }
+
+    #[inline]
     fn post_render<T, D>(
         &mut self,
         draw_target: &mut D,
@@ -161,6 +502,7 @@ where
             .post_render(draw_target, character_style, text, bounds)
     }
 
+    #[inline]
     fn on_start_render<S: CharacterStyle>(
         &mut self,
         cursor: &mut Cursor,
@@ -169,4 +511,16 @@ where
         self.parent.on_start_render(cursor, props.clone());
         self.object.on_start_render(cursor, props);
     }
+
+    #[inline]
+    fn post_line(&mut self, line: LineMetrics) {
+        self.parent.post_line(line);
+        self.object.post_line(line);
+    }
+
+    #[inline]
+    fn on_wrap(&mut self, reason: WrapReason) {
+        self.parent.on_wrap(reason);
+        self.object.on_wrap(reason);
+    }
 }
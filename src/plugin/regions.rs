@@ -0,0 +1,403 @@
+//! Collect the displayed areas of marked spans while the text is rendered.
+
+use core::{
+    cell::{Cell, RefCell},
+    ops::Range,
+};
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    prelude::PixelColor,
+    primitives::Rectangle,
+    text::renderer::TextRenderer,
+};
+
+#[cfg(feature = "ansi")]
+use crate::parser::{Parser, Token, OSC_8};
+use crate::{
+    plugin::Plugin,
+    utils::{offset_in, str_width},
+};
+
+/// Storage for the areas collected by [`RegionCollector`].
+///
+/// The buffer wraps a slice that is provided by the application. Every collected area is stored
+/// as a pair of the byte range of its span, and the area the span covers in a single line.
+/// Areas that don't fit into the slice are dropped, which is reported by [`overflowed`].
+///
+/// [`RegionCollector`]: struct.RegionCollector.html
+/// [`overflowed`]: #method.overflowed
+#[derive(Debug)]
+pub struct RegionBuffer<'b> {
+    regions: RefCell<&'b mut [(Range<usize>, Rectangle)]>,
+    len: Cell<usize>,
+    overflowed: Cell<bool>,
+}
+
+impl<'b> RegionBuffer<'b> {
+    /// Creates a new, empty buffer that stores the areas in `regions`.
+    #[inline]
+    pub fn new(regions: &'b mut [(Range<usize>, Rectangle)]) -> Self {
+        Self {
+            regions: RefCell::new(regions),
+            len: Cell::new(0),
+            overflowed: Cell::new(false),
+        }
+    }
+
+    /// Returns the number of collected areas.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns whether no areas were collected.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether some areas were dropped because the buffer was full.
+    #[inline]
+    pub fn overflowed(&self) -> bool {
+        self.overflowed.get()
+    }
+
+    /// Removes the collected areas, so the buffer can be used to render the text again.
+    #[inline]
+    pub fn clear(&self) {
+        self.len.set(0);
+        self.overflowed.set(false);
+    }
+
+    /// Returns the range of the span that is displayed at the given point.
+    #[inline]
+    pub fn span_at(&self, point: Point) -> Option<Range<usize>> {
+        self.regions.borrow()[..self.len()]
+            .iter()
+            .find(|(_, area)| area.contains(point))
+            .map(|(range, _)| range.clone())
+    }
+
+    /// Consumes the buffer, and returns the collected areas.
+    #[inline]
+    pub fn into_regions(self) -> &'b mut [(Range<usize>, Rectangle)] {
+        let len = self.len();
+        &mut self.regions.into_inner()[..len]
+    }
+
+    /// Adds `area` to the area of `range` in the same line, or stores it as a new area.
+    fn add(&self, range: Range<usize>, area: Rectangle) {
+        let mut regions = self.regions.borrow_mut();
+        let len = self.len();
+
+        let same_line = regions[..len]
+            .iter_mut()
+            .rev()
+            .take_while(|(_, other)| other.top_left.y == area.top_left.y);
+        for (other_range, other) in same_line {
+            if *other_range == range {
+                let top_left = other.top_left.x.min(area.top_left.x);
+                let right = (other.top_left.x + other.size.width.saturating_as::<i32>())
+                    .max(area.top_left.x + area.size.width.saturating_as::<i32>());
+                other.top_left.x = top_left;
+                other.size.width = (right - top_left).saturating_as();
+                other.size.height = other.size.height.max(area.size.height);
+                return;
+            }
+        }
+
+        match regions.get_mut(len) {
+            Some(slot) => {
+                *slot = (range, area);
+                self.len.set(len + 1);
+            }
+            None => self.overflowed.set(true),
+        }
+    }
+}
+
+/// The spans whose areas are collected.
+#[derive(Clone, Copy, Debug)]
+enum Marked<'r> {
+    Spans(&'r [Range<usize>]),
+    #[cfg(feature = "ansi")]
+    Links,
+}
+
+/// Collects the displayed areas of marked spans of the text while it is rendered.
+///
+/// [`TextBox::regions`] lays out the text again to find the areas of spans. When the text is
+/// drawn anyway, this plugin records the areas of the marked spans from the pieces of text that
+/// are rendered, so touch handlers can map taps to actions without measuring the text a second
+/// time. The marked spans are either byte ranges chosen by the application, or the text of the
+/// OSC 8 hyperlinks of the text.
+///
+/// A span that is wrapped into multiple lines is stored as one area for every line. Only the
+/// visible lines are rendered, so spans that are scrolled out of the bounding box don't have an
+/// area. Text that is generated by other plugins is not part of any span.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use core::ops::Range;
+/// use embedded_text::{
+///     plugin::regions::{RegionBuffer, RegionCollector},
+///     TextBox,
+/// };
+///
+/// let text = "[OK] [Cancel]";
+/// let spans = [0..4, 5..13];
+///
+/// let mut storage: [(Range<usize>, Rectangle); 4] = Default::default();
+/// let regions = RegionBuffer::new(&mut storage);
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new(text, bounds, character_style)
+///     .add_plugin(RegionCollector::new(text, &spans, &regions))
+///     .draw(&mut display)
+///     .unwrap();
+///
+/// // "[Cancel]" is wrapped into the second line.
+/// assert_eq!(regions.span_at(Point::new(10, 4)), Some(0..4));
+/// assert_eq!(regions.span_at(Point::new(10, 13)), Some(5..13));
+/// assert_eq!(regions.span_at(Point::new(40, 4)), None);
+/// ```
+///
+/// [`TextBox::regions`]: ../../struct.TextBox.html#method.regions
+#[derive(Clone, Copy, Debug)]
+pub struct RegionCollector<'r, 'b> {
+    text: &'r str,
+    marked: Marked<'r>,
+    buffer: &'r RegionBuffer<'b>,
+}
+
+impl<'r, 'b> RegionCollector<'r, 'b> {
+    /// Creates a plugin that collects the areas of `spans` into `buffer`.
+    ///
+    /// `text` must be the text of the text box the plugin is added to, and `spans` are byte
+    /// ranges of `text`.
+    #[inline]
+    pub fn new(text: &'r str, spans: &'r [Range<usize>], buffer: &'r RegionBuffer<'b>) -> Self {
+        Self {
+            text,
+            marked: Marked::Spans(spans),
+            buffer,
+        }
+    }
+
+    /// Creates a plugin that collects the areas of the OSC 8 hyperlinks of `text` into `buffer`.
+    ///
+    /// `text` must be the text of the text box the plugin is added to. The collected range of a
+    /// link covers the text between its opening and closing escape sequences.
+    #[cfg(feature = "ansi")]
+    #[inline]
+    pub fn links(text: &'r str, buffer: &'r RegionBuffer<'b>) -> Self {
+        Self {
+            text,
+            marked: Marked::Links,
+            buffer,
+        }
+    }
+
+    /// Stores the area of the part of `st` that is covered by `range`.
+    fn collect<T: TextRenderer>(
+        &self,
+        style: &T,
+        st: &str,
+        offset: usize,
+        bounds: Rectangle,
+        range: Range<usize>,
+    ) {
+        let from = range.start.saturating_sub(offset);
+        let to = range.end.saturating_sub(offset).min(st.len());
+        if from >= to {
+            return;
+        }
+
+        let area = if from == 0 && to == st.len() {
+            bounds
+        } else {
+            match (st.get(..from), st.get(from..to)) {
+                (Some(before), Some(covered)) => Rectangle::new(
+                    bounds.top_left + Point::new(str_width(style, before).saturating_as(), 0),
+                    Size::new(str_width(style, covered), bounds.size.height),
+                ),
+                _ => return,
+            }
+        };
+
+        self.buffer.add(range, area);
+    }
+}
+
+/// Returns the text range of the OSC 8 hyperlink that contains the byte at `offset`.
+#[cfg(feature = "ansi")]
+fn link_at(text: &str, offset: usize) -> Option<Range<usize>> {
+    let escape = text[..offset].rfind(OSC_8)?;
+
+    let mut parser = Parser::<embedded_graphics::pixelcolor::BinaryColor>::parse(&text[escape..]);
+    match parser.next() {
+        Some(Token::LinkStart(_)) => {
+            let start = text.len() - parser.as_str().len();
+            let end = match text[start..].find(OSC_8) {
+                Some(end) => start + end,
+                None => text.len(),
+            };
+
+            if offset >= start && offset < end {
+                Some(start..end)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl<'a, 'r, 'b, C: PixelColor> Plugin<'a, C> for RegionCollector<'r, 'b> {
+    #[inline]
+    fn post_render<T, D>(
+        &mut self,
+        _draw_target: &mut D,
+        character_style: &T,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        let offset = match offset_in(self.text, text) {
+            Some(offset) if !text.is_empty() => offset,
+            _ => return Ok(()),
+        };
+
+        match self.marked {
+            Marked::Spans(spans) => {
+                for span in spans {
+                    self.collect(character_style, text, offset, bounds, span.clone());
+                }
+            }
+
+            #[cfg(feature = "ansi")]
+            Marked::Links => {
+                if let Some(link) = link_at(self.text, offset) {
+                    self.collect(character_style, text, offset, bounds, link);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::ops::Range;
+
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        plugin::regions::{RegionBuffer, RegionCollector},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn area(x: i32, y: i32, width: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, 9))
+    }
+
+    #[test]
+    fn wrapped_spans_are_collected_per_line() {
+        let text = "ab cd ef";
+        let spans = [1..2, 3..8];
+
+        let mut storage: [(Range<usize>, Rectangle); 4] = Default::default();
+        let regions = RegionBuffer::new(&mut storage);
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(RegionCollector::new(text, &spans, &regions))
+            .draw(&mut display)
+            .unwrap();
+
+        assert!(!regions.overflowed());
+        assert_eq!(
+            regions.into_regions(),
+            [
+                (1..2, area(6, 0, 6)),
+                (3..8, area(18, 0, 12)),
+                (3..8, area(0, 9, 12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn areas_that_dont_fit_are_dropped() {
+        let text = "ab cd";
+        let spans = [0..2, 3..5];
+
+        let mut storage: [(Range<usize>, Rectangle); 1] = Default::default();
+        let regions = RegionBuffer::new(&mut storage);
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(RegionCollector::new(text, &spans, &regions))
+            .draw(&mut display)
+            .unwrap();
+
+        assert!(regions.overflowed());
+        assert_eq!(regions.span_at(Point::new(3, 3)), Some(0..2));
+        assert_eq!(regions.span_at(Point::new(21, 3)), None);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn links_are_collected() {
+        let text = "a \x1b]8;;u\x1b\\bc d\x1b]8;;\x1b\\ e";
+        let link: Range<usize> = 10..14;
+        assert_eq!(&text[link.clone()], "bc d");
+
+        let mut storage: [(Range<usize>, Rectangle); 4] = Default::default();
+        let regions = RegionBuffer::new(&mut storage);
+
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(RegionCollector::links(text, &regions))
+            .draw(&mut display)
+            .unwrap();
+
+        assert_eq!(
+            regions.into_regions(),
+            [(link.clone(), area(12, 0, 18)), (link, area(0, 9, 6))]
+        );
+    }
+}
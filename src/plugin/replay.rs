@@ -0,0 +1,179 @@
+//! Record the tokens of a text, and draw them again without parsing the text.
+
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::PixelColor,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    parser::{Parser, Token},
+    plugin::{Plugin, PluginMarker},
+    TextBox,
+};
+
+/// Replays recorded tokens.
+///
+/// [`TextBox::record_tokens`] parses the text of a text box, passes it through the `next_token`
+/// hook of its plugins, and stores the resulting tokens in a buffer. A text box that uses the
+/// `Replay` plugin draws the recorded tokens instead of its own text, so drawing the same text
+/// multiple times, for example on double buffered displays, doesn't parse the text and run the
+/// plugins again. The text of the replaying text box is not used, and should be empty.
+///
+/// Only the `next_token` hook of the recorded plugins is replayed. Plugins that use other hooks,
+/// like [`Gutter`] or [`Hyphenation`], must be added to the replaying text box after `Replay`.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::replay::Replay, TextBox, Token};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// const EMPTY: Token<'static, BinaryColor> = Token::NewLine;
+///
+/// let mut buffer = [EMPTY; 16];
+/// let tokens = TextBox::new("Hello, World!", bounds, character_style)
+///     .record_tokens(&mut buffer)
+///     .unwrap();
+///
+/// let text_box = TextBox::new("", bounds, character_style).add_plugin(Replay::new(tokens));
+///
+/// for _ in 0..2 {
+///     let mut display = MockDisplay::new();
+///     text_box.draw(&mut display).unwrap();
+/// }
+/// ```
+///
+/// [`TextBox::record_tokens`]: ../../struct.TextBox.html#method.record_tokens
+/// [`Gutter`]: ../gutter/struct.Gutter.html
+/// [`Hyphenation`]: ../hyphenation/struct.Hyphenation.html
+#[derive(Clone, Debug)]
+pub struct Replay<'t, 'a, C>
+where
+    C: PixelColor,
+{
+    tokens: &'t [Token<'a, C>],
+}
+
+impl<'t, 'a, C> Replay<'t, 'a, C>
+where
+    C: PixelColor,
+{
+    /// Creates a new plugin that replays `tokens`.
+    #[inline]
+    pub fn new(tokens: &'t [Token<'a, C>]) -> Self {
+        Self { tokens }
+    }
+}
+
+impl<'t, 'a, C> Plugin<'a, C> for Replay<'t, 'a, C>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        _next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        let (token, rest) = self.tokens.split_first()?;
+        self.tokens = rest;
+
+        Some(token.clone())
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: PluginMarker<'a, <S as TextRenderer>::Color>,
+{
+    /// Parses the text, and stores the tokens returned by the plugins in `buffer`.
+    ///
+    /// Returns the recorded tokens, or `None` if they don't fit into `buffer`. The recorded tokens
+    /// can be drawn using the [`Replay`] plugin.
+    ///
+    /// `Token` is not `Copy`, so the buffer can be created by repeating a constant, like
+    /// `[EMPTY; 16]` where `EMPTY` is a `const` that holds `Token::NewLine`.
+    ///
+    /// [`Replay`]: plugin/replay/struct.Replay.html
+    #[inline]
+    pub fn record_tokens<'t>(
+        &self,
+        buffer: &'t mut [Token<'a, <S as TextRenderer>::Color>],
+    ) -> Option<&'t [Token<'a, <S as TextRenderer>::Color>]> {
+        let mut plugin = self.plugin.inner.borrow().plugin.clone();
+        let mut parser = Parser::parse(self.text).with_style(&self.style);
+
+        let mut len = 0;
+        while let Some(token) = plugin.next_token(|| parser.next()) {
+            *buffer.get_mut(len)? = token;
+            len += 1;
+        }
+
+        Some(&buffer[..len])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        parser::{ChangeTextStyle, Token},
+        plugin::{escapes::EscapeDialect, replay::Replay},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    const EMPTY: Token<'static, BinaryColor> = Token::NewLine;
+
+    fn parse(params: &str) -> Option<(usize, ChangeTextStyle<BinaryColor>)> {
+        let change = match params.as_bytes().first()? {
+            b'o' => ChangeTextStyle::TextColor(Some(BinaryColor::Off)),
+            b'/' => ChangeTextStyle::Reset,
+            _ => return None,
+        };
+
+        Some((1, change))
+    }
+
+    #[test]
+    fn replayed_tokens_are_drawn_like_the_text() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        let text_box = TextBox::new("a $obcd$/e\nfghij", bounds, character_style)
+            .add_plugin(EscapeDialect::new('$', parse));
+
+        let mut buffer = [EMPTY; 16];
+        let tokens = text_box.record_tokens(&mut buffer).unwrap();
+        assert!(text_box.record_tokens(&mut [EMPTY; 4]).is_none());
+
+        let mut expected = MockDisplay::new();
+        text_box.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        TextBox::new("", bounds, character_style)
+            .add_plugin(Replay::new(tokens))
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
@@ -1,5 +1,6 @@
 //! Display the last lines of the text.
 
+use az::SaturatingAs;
 use embedded_graphics::{prelude::PixelColor, text::renderer::CharacterStyle};
 
 use crate::{plugin::Plugin, rendering::cursor::Cursor, TextBoxProperties};
@@ -8,17 +9,69 @@ use crate::{plugin::Plugin, rendering::cursor::Cursor, TextBoxProperties};
 ///
 /// Aligns the last line of the text to be always visible. If the text fits inside the text box,
 /// it will be top aligned. If the text is longer, it will be bottom aligned.
-#[derive(Clone)]
-pub struct Tail;
+///
+/// The last line can be anchored above the bottom of the text box using
+/// [`Tail::with_bottom_margin`], for example to leave room for an input line below a log. Use
+/// [`TextBox::scrolled_off`] to find out how much of the text was scrolled off the top.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::tail::Tail, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 22));
+///
+/// let text_box = TextBox::new("one\ntwo\nthree\nfour", bounds, character_style)
+///     .add_plugin(Tail::with_bottom_margin(4));
+///
+/// // "three" and "four" are displayed above the 4 pixel margin.
+/// let scrolled_off = text_box.scrolled_off();
+/// assert_eq!(scrolled_off.height, 18);
+/// assert_eq!(scrolled_off.lines, 2);
+/// ```
+///
+/// [`Tail::with_bottom_margin`]: #method.with_bottom_margin
+/// [`TextBox::scrolled_off`]: ../../struct.TextBox.html#method.scrolled_off
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tail {
+    bottom_margin: u32,
+}
+
+impl Tail {
+    /// Creates a new plugin that aligns the last line to the bottom of the text box.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_bottom_margin(0)
+    }
+
+    /// Creates a new plugin that aligns the last line `margin` pixels above the bottom of the text
+    /// box.
+    #[inline]
+    pub const fn with_bottom_margin(margin: u32) -> Self {
+        Self {
+            bottom_margin: margin,
+        }
+    }
+}
 
 impl<'a, C: PixelColor> Plugin<'a, C> for Tail {
+    #[inline]
     fn on_start_render<S: CharacterStyle>(
         &mut self,
         cursor: &mut Cursor,
         props: TextBoxProperties<'_, S>,
     ) {
-        if props.text_height > props.box_height {
-            let offset = props.box_height - props.text_height;
+        let visible_height = props.box_height - self.bottom_margin.saturating_as::<i32>();
+
+        if props.text_height > visible_height {
+            let offset = visible_height - props.text_height;
 
             cursor.y += offset
         }
@@ -40,6 +93,11 @@ mod test {
 
     #[track_caller]
     pub fn assert_rendered(text: &str, size: Size, pattern: &[&str]) {
+        assert_rendered_with(Tail::new(), text, size, pattern);
+    }
+
+    #[track_caller]
+    fn assert_rendered_with(tail: Tail, text: &str, size: Size, pattern: &[&str]) {
         let mut display = MockDisplay::new();
 
         let character_style = MonoTextStyleBuilder::new()
@@ -56,7 +114,7 @@ mod test {
             character_style,
             style,
         )
-        .add_plugin(Tail)
+        .add_plugin(tail)
         .draw(&mut display)
         .unwrap();
 
@@ -118,4 +176,24 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn bottom_margin_moves_the_last_line_up() {
+        assert_rendered_with(
+            Tail::with_bottom_margin(3),
+            "word word2",
+            Size::new(30, 12),
+            &[
+                "..............................",
+                "......................#...##..",
+                "......................#..#..#.",
+                "#...#...##...#.#....###.....#.",
+                "#.#.#..#..#..##.#..#..#....#..",
+                "#.#.#..#..#..#.....#..#...#...",
+                ".#.#....##...#......###..####.",
+                "..............................",
+                "..............................",
+            ],
+        );
+    }
 }
@@ -0,0 +1,198 @@
+//! Reveal the text character by character.
+
+use embedded_graphics::prelude::PixelColor;
+
+use crate::{
+    grapheme::{grapheme_count, grapheme_offset},
+    parser::Token,
+    plugin::Plugin,
+};
+
+/// Returns the first `n` characters of `s`.
+fn first_n_chars(s: &str, n: u32) -> &str {
+    &s[..grapheme_offset(s, n)]
+}
+
+/// Typewriter effect plugin.
+///
+/// Only displays the first `characters` characters of the text. Animate the effect by drawing
+/// the text box with an increasing number of characters in every frame.
+///
+/// Every printed character, space, tab and line break counts as a character. Text style changes
+/// and custom tokens don't. The text box lays out the displayed characters only, so a partially
+/// displayed word that doesn't fit into the line moves to the next line while it is typed.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::typewriter::Typewriter, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// for frame in 0..12 {
+///     let mut display = MockDisplay::new();
+///     TextBox::new("Hello, World", bounds, character_style)
+///         .add_plugin(Typewriter::new(frame))
+///         .draw(&mut display)
+///         .unwrap();
+/// }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Typewriter {
+    /// The number of characters that may still be displayed.
+    remaining: u32,
+}
+
+impl Typewriter {
+    /// Creates a new plugin that displays the first `characters` characters of the text.
+    #[inline]
+    pub const fn new(characters: u32) -> Self {
+        Self {
+            remaining: characters,
+        }
+    }
+
+    /// Takes `n` characters from the remaining ones. Returns the number of characters taken.
+    fn take(&mut self, n: u32) -> u32 {
+        let taken = n.min(self.remaining);
+        self.remaining -= taken;
+        taken
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for Typewriter
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn next_token(
+        &mut self,
+        mut next_token: impl FnMut() -> Option<Token<'a, C>>,
+    ) -> Option<Token<'a, C>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match next_token()? {
+            Token::Word(word) => {
+                let n = self.take(grapheme_count(word));
+                Some(Token::Word(first_n_chars(word, n)))
+            }
+            Token::Whitespace(count, seq) => {
+                let n = self.take(count);
+                Some(Token::Whitespace(n, first_n_chars(seq, n)))
+            }
+            token @ Token::NewLine | token @ Token::CarriageReturn | token @ Token::Tab => {
+                self.take(1);
+                Some(token)
+            }
+            token => Some(token),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::Point,
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{plugin::typewriter::Typewriter, utils::test::size_for, TextBox};
+
+    fn draw_typed(text: &str, characters: u32) -> MockDisplay<BinaryColor> {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .add_plugin(Typewriter::new(characters))
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn displays_the_first_characters() {
+        draw_typed("ab\ncd", 0).assert_pattern(&[]);
+        draw_typed("ab\ncd", 1).assert_pattern(&[
+            "     ", "     ", "     ", "  ###", " #  #", " #  #", "  ###",
+        ]);
+    }
+
+    #[test]
+    fn line_breaks_are_counted() {
+        draw_typed("ab\ncd", 4).assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+            "           ",
+            "           ",
+            "           ",
+            "           ",
+            "           ",
+            "  ###      ",
+            " #         ",
+            " #         ",
+            "  ###      ",
+        ]);
+    }
+
+    #[test]
+    fn displays_short_text_completely() {
+        draw_typed("ab\ncd", 100).assert_pattern(&[
+            "           ",
+            "       #   ",
+            "       #   ",
+            "  ###  ### ",
+            " #  #  #  #",
+            " #  #  #  #",
+            "  ###  ### ",
+            "           ",
+            "           ",
+            "           ",
+            "          #",
+            "          #",
+            "  ###   ###",
+            " #     #  #",
+            " #     #  #",
+            "  ###   ###",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn style_changes_are_not_counted() {
+        draw_typed("a\x1b[4mb\x1b[24mc", 2).assert_pattern(&[
+            "            ",
+            "       #    ",
+            "       #    ",
+            "  ###  ###  ",
+            " #  #  #  # ",
+            " #  #  #  # ",
+            "  ###  ###  ",
+            "            ",
+            "      ######",
+        ]);
+    }
+}
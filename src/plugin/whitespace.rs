@@ -0,0 +1,155 @@
+//! Make spaces and tabs visible.
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    prelude::{PixelColor, Point},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::plugin::Plugin;
+
+/// Whitespace visualization plugin.
+///
+/// Draws a marker string over every displayed space and tab, using the current character style.
+/// The markers don't change the layout of the text: space markers are drawn at the start of
+/// every space, and tab markers at the start of every tab. Spaces and tabs that the text box
+/// skips, for example because of the [`WhiteSpace`] style, don't get a marker.
+///
+/// [`WhiteSpace`]: ../../style/enum.WhiteSpace.html
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{plugin::whitespace::VisibleWhitespace, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("fn main() {}", bounds, character_style)
+///     .add_plugin(VisibleWhitespace::new(".", ">"))
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct VisibleWhitespace<'a> {
+    space: &'a str,
+    tab: &'a str,
+}
+
+impl<'a> VisibleWhitespace<'a> {
+    /// Creates a new plugin that marks spaces with `space` and tabs with `tab`.
+    #[inline]
+    pub const fn new(space: &'a str, tab: &'a str) -> Self {
+        Self { space, tab }
+    }
+}
+
+impl<'a, C> Plugin<'a, C> for VisibleWhitespace<'a>
+where
+    C: PixelColor,
+{
+    #[inline]
+    fn post_render<T, D>(
+        &mut self,
+        draw_target: &mut D,
+        character_style: &T,
+        text: &str,
+        bounds: Rectangle,
+    ) -> Result<(), D::Error>
+    where
+        T: TextRenderer<Color = C>,
+        D: DrawTarget<Color = C>,
+    {
+        let count = text.chars().count() as u32;
+        if count == 0 || bounds.size.width == 0 {
+            return Ok(());
+        }
+
+        let (marker, count) = if text == "\t" {
+            (self.tab, 1)
+        } else if text.chars().all(|c| c == ' ') {
+            (self.space, count)
+        } else {
+            return Ok(());
+        };
+
+        // Stretched spaces of justified text are wider than the space character.
+        let step = bounds.size.width / count;
+        for i in 0..count {
+            let x = (i * step) as i32;
+            character_style.draw_string(
+                marker,
+                bounds.top_left + Point::new(x, 0),
+                Baseline::Top,
+                draw_target,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::Point,
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        plugin::whitespace::VisibleWhitespace,
+        style::{TabSize, TextBoxStyleBuilder},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_visible(text: &str, expected: &str) {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .tab_size(TabSize::Spaces(2))
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 6, 2));
+
+        let mut display = MockDisplay::new();
+        TextBox::with_textbox_style(text, bounds, character_style, style)
+            .add_plugin(VisibleWhitespace::new(".", ">"))
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected_display = MockDisplay::new();
+        TextBox::with_textbox_style(expected, bounds, character_style, style)
+            .draw(&mut expected_display)
+            .unwrap();
+
+        display.assert_eq(&expected_display);
+    }
+
+    #[test]
+    fn spaces_and_tabs_are_marked() {
+        assert_visible("a  b", "a..b");
+        assert_visible("a\tb", "a>b");
+    }
+
+    #[test]
+    fn leading_spaces_are_marked() {
+        assert_visible("  a", "..a");
+        assert_visible("a\n  b", "a\n..b");
+    }
+}
@@ -1,6 +1,10 @@
 //! ANSI escape sequence related types and functions.
 
-use embedded_graphics::{pixelcolor::Rgb888, prelude::PixelColor, text::DecorationColor};
+use embedded_graphics::{
+    pixelcolor::{Rgb888, RgbColor},
+    prelude::PixelColor,
+    text::DecorationColor,
+};
 
 use crate::parser::ChangeTextStyle;
 
@@ -10,15 +14,36 @@ pub(crate) enum Sgr {
     /// Reset all styling options
     Reset,
 
+    /// Bold or increased intensity
+    Bold,
+
+    /// Faint or decreased intensity
+    Faint,
+
+    /// Neither bold nor faint
+    NormalIntensity,
+
     /// Draw a line under the text
     Underline,
 
+    /// Swap the text and background colors
+    Reverse,
+
+    /// Disable swapping the text and background colors
+    NotReverse,
+
     /// Cross out the text
     CrossedOut,
 
     /// Disable drawing underline
     UnderlineOff,
 
+    /// Change the underline color
+    ChangeUnderlineColor(Rgb888),
+
+    /// Reset the underline color to the default
+    DefaultUnderlineColor,
+
     /// Disable crossing out
     NotCrossedOut,
 
@@ -35,25 +60,169 @@ pub(crate) enum Sgr {
     DefaultBackgroundColor,
 }
 
-impl<C: PixelColor + From<Rgb888>> From<Sgr> for ChangeTextStyle<C> {
-    #[inline]
-    fn from(sgr: Sgr) -> Self {
-        match sgr {
+impl Sgr {
+    /// Returns the text style change that corresponds to the sequence.
+    ///
+    /// Intensity and reverse video changes depend on the current colors, so they are handled by
+    /// [`SgrState`].
+    ///
+    /// [`SgrState`]: struct.SgrState.html
+    pub(crate) fn text_style_change<C>(self) -> Option<ChangeTextStyle<C>>
+    where
+        C: PixelColor + From<Rgb888>,
+    {
+        let change = match self {
             Sgr::Reset => ChangeTextStyle::Reset,
+            Sgr::Bold | Sgr::Faint | Sgr::NormalIntensity => return None,
+            Sgr::Reverse | Sgr::NotReverse => return None,
             Sgr::Underline => ChangeTextStyle::Underline(DecorationColor::TextColor),
             Sgr::CrossedOut => ChangeTextStyle::Strikethrough(DecorationColor::TextColor),
             Sgr::UnderlineOff => ChangeTextStyle::Underline(DecorationColor::None),
+            Sgr::ChangeUnderlineColor(c) => ChangeTextStyle::UnderlineColor(Some(c.into())),
+            Sgr::DefaultUnderlineColor => ChangeTextStyle::UnderlineColor(None),
             Sgr::NotCrossedOut => ChangeTextStyle::Strikethrough(DecorationColor::None),
             Sgr::ChangeTextColor(c) => ChangeTextStyle::TextColor(Some(c.into())),
             Sgr::DefaultTextColor => ChangeTextStyle::TextColor(None),
             Sgr::ChangeBackgroundColor(c) => ChangeTextStyle::BackgroundColor(Some(c.into())),
             Sgr::DefaultBackgroundColor => ChangeTextStyle::BackgroundColor(None),
+        };
+
+        Some(change)
+    }
+}
+
+/// Text intensity selected by SGR sequences.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Intensity {
+    Normal,
+    Bold,
+    Faint,
+}
+
+impl Intensity {
+    fn apply(self, color: Rgb888) -> Rgb888 {
+        match self {
+            Intensity::Normal => color,
+
+            // Standard colors are switched to their high intensity variants, other colors are
+            // brightened.
+            Intensity::Bold => match (0..8).find(|&idx| standard_to_rgb(idx) == color) {
+                Some(idx) => standard_to_rgb(idx + 8),
+                None => {
+                    let brighten = |c: u8| c + (255 - c) / 2;
+                    Rgb888::new(
+                        brighten(color.r()),
+                        brighten(color.g()),
+                        brighten(color.b()),
+                    )
+                }
+            },
+
+            Intensity::Faint => {
+                let dim = |c: u8| (u16::from(c) * 2 / 3) as u8;
+                Rgb888::new(dim(color.r()), dim(color.g()), dim(color.b()))
+            }
+        }
+    }
+}
+
+/// Keeps track of the colors, intensity and reverse video selected by SGR sequences.
+///
+/// Bold and faint text is emulated by changing the text color, which is only possible if the text
+/// color was also set by an SGR sequence. Similarly, reverse video swaps the colors that were set
+/// by SGR sequences.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct SgrState {
+    text_color: Option<Rgb888>,
+    background_color: Option<Rgb888>,
+    intensity: Intensity,
+    reverse: bool,
+}
+
+impl SgrState {
+    pub const fn new() -> Self {
+        Self {
+            text_color: None,
+            background_color: None,
+            intensity: Intensity::Normal,
+            reverse: false,
+        }
+    }
+
+    /// Updates the state and returns the resulting text style changes.
+    pub fn apply<C>(&mut self, sgr: Sgr) -> [Option<ChangeTextStyle<C>>; 2]
+    where
+        C: PixelColor + From<Rgb888>,
+    {
+        let reverse = self.reverse;
+        match sgr {
+            Sgr::Reset => *self = Self::new(),
+            Sgr::ChangeTextColor(color) => self.text_color = Some(color),
+            Sgr::DefaultTextColor => self.text_color = None,
+            Sgr::ChangeBackgroundColor(color) => self.background_color = Some(color),
+            Sgr::DefaultBackgroundColor => self.background_color = None,
+            Sgr::Bold => self.intensity = Intensity::Bold,
+            Sgr::Faint => self.intensity = Intensity::Faint,
+            Sgr::NormalIntensity => self.intensity = Intensity::Normal,
+            Sgr::Reverse => self.reverse = true,
+            Sgr::NotReverse => self.reverse = false,
+            _ => {}
+        }
+
+        match sgr {
+            Sgr::Bold | Sgr::Faint | Sgr::NormalIntensity if self.text_color.is_none() => {
+                [None, None]
+            }
+            Sgr::ChangeTextColor(_)
+            | Sgr::DefaultTextColor
+            | Sgr::Bold
+            | Sgr::Faint
+            | Sgr::NormalIntensity => [Some(self.text_color_change()), None],
+            Sgr::ChangeBackgroundColor(_) | Sgr::DefaultBackgroundColor => {
+                [Some(self.background_color_change()), None]
+            }
+            Sgr::Reverse | Sgr::NotReverse if self.reverse != reverse => [
+                Some(self.text_color_change()),
+                Some(self.background_color_change()),
+            ],
+            _ => [sgr.text_style_change(), None],
+        }
+    }
+
+    /// Returns the change that displays the text color, which is drawn as the background color
+    /// when reverse video is enabled.
+    fn text_color_change<C>(&self) -> ChangeTextStyle<C>
+    where
+        C: PixelColor + From<Rgb888>,
+    {
+        let intensity = self.intensity;
+        let color = self.text_color.map(|c| intensity.apply(c).into());
+
+        if self.reverse {
+            ChangeTextStyle::BackgroundColor(color)
+        } else {
+            ChangeTextStyle::TextColor(color)
+        }
+    }
+
+    /// Returns the change that displays the background color, which is drawn as the text color
+    /// when reverse video is enabled.
+    fn background_color_change<C>(&self) -> ChangeTextStyle<C>
+    where
+        C: PixelColor + From<Rgb888>,
+    {
+        let color = self.background_color.map(Into::into);
+
+        if self.reverse {
+            ChangeTextStyle::TextColor(color)
+        } else {
+            ChangeTextStyle::BackgroundColor(color)
         }
     }
 }
 
 fn try_parse_8b_color(v: &[u8]) -> Option<Rgb888> {
-    let color = *v.get(0)?;
+    let color = *v.first()?;
     match color {
         //   0-  7:  standard colors (as in ESC [ 30–37 m)
         //   8- 15:  high intensity colors (as in ESC [ 90–97 m)
@@ -86,7 +255,7 @@ fn try_parse_8b_color(v: &[u8]) -> Option<Rgb888> {
 }
 
 fn try_parse_rgb(v: &[u8]) -> Option<Rgb888> {
-    let r = *v.get(0)?;
+    let r = *v.first()?;
     let g = *v.get(1)?;
     let b = *v.get(2)?;
 
@@ -117,7 +286,7 @@ fn standard_to_rgb(idx: u8) -> Rgb888 {
 }
 
 fn try_parse_color(v: &[u8]) -> Option<Rgb888> {
-    let color_type = *v.get(0)?;
+    let color_type = *v.first()?;
 
     match color_type {
         2 => try_parse_rgb(&v[1..]),
@@ -127,13 +296,72 @@ fn try_parse_color(v: &[u8]) -> Option<Rgb888> {
     }
 }
 
-/// Parse a set of SGR parameter numbers into a more convenient type
+/// Returns the number of parameters used by the color that starts at `v[0]`.
+fn color_param_count(v: &[u8]) -> usize {
+    match v.first() {
+        Some(2) => 4,
+        Some(5) => 2,
+        _ => v.len(),
+    }
+}
+
+/// Returns the number of parameters used by the attribute that starts at `v[0]`.
+fn sgr_param_count(v: &[u8]) -> usize {
+    match v.first() {
+        Some(38) | Some(48) | Some(58) => 1 + color_param_count(&v[1..]),
+        _ => 1,
+    }
+}
+
+/// Iterates over the attributes set by an SGR sequence.
+///
+/// A single sequence may set multiple attributes, e.g. `\x1b[1;4;32m`. Unsupported attributes are
+/// skipped.
+#[derive(Clone, Debug)]
+pub(crate) struct SgrParams<'a> {
+    params: &'a [u8],
+}
+
+impl<'a> Iterator for SgrParams<'a> {
+    type Item = Sgr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Sgr> {
+        while !self.params.is_empty() {
+            let len = sgr_param_count(self.params).min(self.params.len());
+            let (current, rest) = self.params.split_at(len);
+            self.params = rest;
+
+            if let Some(sgr) = try_parse_sgr(current) {
+                return Some(sgr);
+            }
+        }
+
+        None
+    }
+}
+
+/// Parse the parameters of an SGR sequence.
+///
+/// A sequence without parameters (`\x1b[m`) resets every attribute.
 #[inline]
-pub(crate) fn try_parse_sgr(v: &[u8]) -> Option<Sgr> {
-    let code = *v.get(0)?;
+pub(crate) fn parse_sgr(v: &[u8]) -> SgrParams<'_> {
+    SgrParams {
+        params: if v.is_empty() { &[0] } else { v },
+    }
+}
+
+/// Parse a set of SGR parameter numbers into a more convenient type
+fn try_parse_sgr(v: &[u8]) -> Option<Sgr> {
+    let code = *v.first()?;
     match code {
         0 => Some(Sgr::Reset),
+        1 => Some(Sgr::Bold),
+        2 => Some(Sgr::Faint),
+        22 => Some(Sgr::NormalIntensity),
         4 => Some(Sgr::Underline),
+        7 => Some(Sgr::Reverse),
+        27 => Some(Sgr::NotReverse),
         9 => Some(Sgr::CrossedOut),
         24 => Some(Sgr::UnderlineOff),
         29 => Some(Sgr::NotCrossedOut),
@@ -151,6 +379,134 @@ pub(crate) fn try_parse_sgr(v: &[u8]) -> Option<Sgr> {
             Some(Sgr::ChangeBackgroundColor(color))
         }
         100..=107 => Some(Sgr::ChangeBackgroundColor(standard_to_rgb(code - 92))),
+        58 => {
+            let color = try_parse_color(&v[1..])?;
+            Some(Sgr::ChangeUnderlineColor(color))
+        }
+        59 => Some(Sgr::DefaultUnderlineColor),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::pixelcolor::Rgb888;
+
+    use crate::{
+        parser::ChangeTextStyle,
+        rendering::ansi::{parse_sgr, Sgr, SgrState},
+    };
+
+    fn apply_all(state: &mut SgrState, code: &[u8]) -> [Option<ChangeTextStyle<Rgb888>>; 2] {
+        state.apply(parse_sgr(code).next().unwrap())
+    }
+
+    fn apply(state: &mut SgrState, code: &[u8]) -> Option<ChangeTextStyle<Rgb888>> {
+        let [change, other] = apply_all(state, code);
+        assert_eq!(other, None);
+        change
+    }
+
+    #[test]
+    fn multiple_attributes_in_one_sequence() {
+        let mut params = parse_sgr(&[1, 4, 32]);
+        assert_eq!(params.next(), Some(Sgr::Bold));
+        assert_eq!(params.next(), Some(Sgr::Underline));
+        assert_eq!(
+            params.next(),
+            Some(Sgr::ChangeTextColor(Rgb888::new(19, 161, 14)))
+        );
+        assert_eq!(params.next(), None);
+    }
+
+    #[test]
+    fn multiple_attributes_with_extended_colors() {
+        let mut params = parse_sgr(&[38, 5, 196, 4]);
+        assert_eq!(
+            params.next(),
+            Some(Sgr::ChangeTextColor(Rgb888::new(255, 0, 0)))
+        );
+        assert_eq!(params.next(), Some(Sgr::Underline));
+        assert_eq!(params.next(), None);
+
+        // Unsupported attributes are skipped.
+        let mut params = parse_sgr(&[5, 9]);
+        assert_eq!(params.next(), Some(Sgr::CrossedOut));
+        assert_eq!(params.next(), None);
+    }
+
+    #[test]
+    fn empty_sequence_resets() {
+        assert_eq!(parse_sgr(&[]).collect::<Vec<_>>(), [Sgr::Reset]);
+    }
+
+    #[test]
+    fn bold_uses_high_intensity_colors() {
+        let mut state = SgrState::new();
+
+        // Intensity can't be emulated before the text color is known.
+        assert_eq!(apply(&mut state, &[1]), None);
+
+        assert_eq!(
+            apply(&mut state, &[31]),
+            Some(ChangeTextStyle::TextColor(Some(Rgb888::new(231, 72, 86))))
+        );
+        assert_eq!(
+            apply(&mut state, &[38, 2, 100, 0, 200]),
+            Some(ChangeTextStyle::TextColor(Some(Rgb888::new(177, 127, 227))))
+        );
+        assert_eq!(
+            apply(&mut state, &[22]),
+            Some(ChangeTextStyle::TextColor(Some(Rgb888::new(100, 0, 200))))
+        );
+    }
+
+    #[test]
+    fn faint_dims_text_color() {
+        let mut state = SgrState::new();
+
+        apply(&mut state, &[38, 2, 30, 60, 90]);
+        assert_eq!(
+            apply(&mut state, &[2]),
+            Some(ChangeTextStyle::TextColor(Some(Rgb888::new(20, 40, 60))))
+        );
+
+        assert_eq!(apply(&mut state, &[0]), Some(ChangeTextStyle::Reset));
+        assert_eq!(apply(&mut state, &[2]), None);
+    }
+
+    #[test]
+    fn reverse_swaps_colors() {
+        let mut state = SgrState::new();
+
+        apply(&mut state, &[31]);
+        assert_eq!(
+            apply_all(&mut state, &[7]),
+            [
+                Some(ChangeTextStyle::BackgroundColor(Some(Rgb888::new(
+                    197, 15, 31
+                )))),
+                Some(ChangeTextStyle::TextColor(None)),
+            ]
+        );
+
+        // Reverse video is already on.
+        assert_eq!(apply_all(&mut state, &[7]), [None, None]);
+
+        // Color changes are swapped, too.
+        assert_eq!(
+            apply(&mut state, &[44]),
+            Some(ChangeTextStyle::TextColor(Some(Rgb888::new(0, 55, 218))))
+        );
+
+        assert_eq!(
+            apply_all(&mut state, &[27]),
+            [
+                Some(ChangeTextStyle::TextColor(Some(Rgb888::new(197, 15, 31)))),
+                Some(ChangeTextStyle::BackgroundColor(Some(Rgb888::new(
+                    0, 55, 218
+                )))),
+            ]
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! Cursor to track rendering position.
-use embedded_graphics::{geometry::Point, primitives::Rectangle, text::LineHeight};
+use core::ops::Range;
+
+use embedded_graphics::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+    text::LineHeight,
+};
 
 use az::{SaturatingAs, SaturatingCast};
 
@@ -10,6 +16,9 @@ pub struct LineCursor {
     width: u32,
     position: u32,
     tab_width: u32,
+
+    /// The number of characters that still fit into the line.
+    chars_left: u32,
 }
 
 impl LineCursor {
@@ -20,6 +29,37 @@ impl LineCursor {
             width,
             tab_width,
             position: 0,
+            chars_left: u32::MAX,
+        }
+    }
+
+    /// Limits the number of characters in the line. `None` removes the limit.
+    pub fn limit_chars(&mut self, max: Option<u32>) {
+        self.chars_left = max.unwrap_or(u32::MAX);
+    }
+
+    /// Returns the number of characters that still fit into the line.
+    pub fn chars_left(&self) -> u32 {
+        self.chars_left
+    }
+
+    /// Returns whether the given number of characters fit into the line.
+    pub fn fits_chars(&self, chars: u32) -> bool {
+        chars <= self.chars_left
+    }
+
+    /// Uses up room for the given number of characters.
+    pub fn consume_chars(&mut self, chars: u32) {
+        self.chars_left = self.chars_left.saturating_sub(chars);
+    }
+
+    /// Moves the cursor past an object, if both its width and its characters fit into the line.
+    pub fn advance(&mut self, width: u32, chars: u32) -> bool {
+        if self.fits_chars(chars) && self.move_cursor(width.saturating_as()).is_ok() {
+            self.consume_chars(chars);
+            true
+        } else {
+            false
         }
     }
 
@@ -27,6 +67,12 @@ impl LineCursor {
         self.start + Point::new(self.position.saturating_as(), 0)
     }
 
+    /// Returns the horizontal position relative to the start of the line.
+    #[cfg(feature = "ansi")]
+    pub fn offset(&self) -> u32 {
+        self.position
+    }
+
     /// Returns the distance to the next tab position.
     pub fn next_tab_width(&self) -> u32 {
         let next_tab_pos = (self.position / self.tab_width + 1) * self.tab_width;
@@ -38,6 +84,44 @@ impl LineCursor {
         self.width
     }
 
+    /// Shortens the line so that it only occupies the horizontal positions in `span`.
+    pub fn limit(&mut self, span: Range<i32>) {
+        let start = self.start.x.max(span.start);
+        let end = span
+            .end
+            .min(self.start.x + self.width.saturating_as::<i32>());
+
+        self.width = (end - start).saturating_as();
+        self.start.x = start;
+        self.position = self.position.min(self.width);
+    }
+
+    /// Shortens the line so that it doesn't overlap `area`, if the line is `height` pixels high.
+    ///
+    /// The line keeps the longer of the parts to the left and to the right of the area.
+    pub fn exclude(&mut self, area: Rectangle, height: u32) {
+        let line = Rectangle::new(self.start, Size::new(self.width, height));
+        let overlap = line.intersection(&area);
+        if overlap.size.width == 0 || overlap.size.height == 0 {
+            return;
+        }
+
+        let before = (overlap.top_left.x - self.start.x).saturating_as::<u32>();
+        let after = self.width - before - overlap.size.width;
+        if after >= before {
+            self.start.x += (before + overlap.size.width).saturating_as::<i32>();
+            self.width = after;
+        } else {
+            self.width = before;
+        }
+        self.position = self.position.min(self.width);
+    }
+
+    /// Makes the line narrower by the given amount.
+    pub fn reserve(&mut self, width: u32) {
+        self.width = self.width.saturating_sub(width).max(self.position);
+    }
+
     /// Returns whether the current line has enough space to also include an object of given width.
     pub fn fits_in_line(&self, width: u32) -> bool {
         width <= self.space()
@@ -51,7 +135,7 @@ impl LineCursor {
     /// Moves the cursor by a given amount.
     pub fn move_cursor(&mut self, by: i32) -> Result<i32, i32> {
         if by < 0 {
-            let abs = by.abs() as u32;
+            let abs = by.saturating_abs() as u32;
             if abs <= self.position {
                 self.position -= abs;
                 Ok(by)
@@ -71,6 +155,19 @@ impl LineCursor {
     }
 }
 
+/// Cursor movement to a different line, requested by an escape sequence.
+#[cfg(feature = "ansi")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMove {
+    /// Move up (negative) or down by a number of lines, keeping the horizontal position.
+    ///
+    /// `x` is the horizontal position in the current line.
+    Relative { lines: i32, x: u32 },
+
+    /// Move to a line, counted from the first line of the text, and a horizontal position.
+    Absolute { line: u32, x: u32 },
+}
+
 /// Internal structure that keeps track of position information while rendering a [`TextBox`].
 ///
 /// [`TextBox`]: ../../struct.TextBox.html
@@ -85,6 +182,22 @@ pub struct Cursor {
     line_height: i32,
     line_spacing: i32,
     tab_width: u32,
+
+    /// Line height setting of the text box.
+    line_height_setting: LineHeight,
+
+    /// Height of the tallest line in the current row.
+    row_height: u32,
+
+    /// Horizontal position where the next line starts.
+    column: u32,
+
+    /// Spacing of the grid the lines are snapped to. 0 means that lines are not snapped.
+    grid: u32,
+
+    /// Vertical position of the first line of the text.
+    #[cfg(feature = "ansi")]
+    first_line: i32,
 }
 
 impl Cursor {
@@ -101,18 +214,28 @@ impl Cursor {
             y: bounds.top_left.y,
             line_height: base_line_height.saturating_as(),
             line_spacing: line_height.to_absolute(base_line_height).saturating_as(),
+            line_height_setting: line_height,
+            row_height: 0,
             bounds,
             tab_width,
+            column: 0,
+            grid: 0,
+            #[cfg(feature = "ansi")]
+            first_line: bounds.top_left.y,
         }
     }
 
     #[must_use]
     pub fn line(&self) -> LineCursor {
         LineCursor {
-            start: Point::new(self.bounds.top_left.x, self.y),
-            width: self.bounds.size.width,
+            start: Point::new(
+                self.bounds.top_left.x + self.column.saturating_as::<i32>(),
+                self.y,
+            ),
+            width: self.bounds.size.width - self.column,
             position: 0,
             tab_width: self.tab_width,
+            chars_left: u32::MAX,
         }
     }
 
@@ -140,10 +263,101 @@ impl Cursor {
         self.line_height
     }
 
+    /// Sets the height of the font used in the current line.
+    ///
+    /// Lines that share a row, e.g. because of carriage returns, use the tallest of their heights.
+    #[inline]
+    pub fn set_line_height(&mut self, base_line_height: u32) {
+        self.row_height = self.row_height.max(base_line_height);
+        self.line_height = self.row_height.saturating_as();
+        self.line_spacing = self
+            .line_height_setting
+            .to_absolute(self.row_height)
+            .saturating_as();
+    }
+
+    /// Snaps the current and every following line to a grid of horizontal lines that are `grid`
+    /// pixels apart. `None` turns snapping off.
+    #[inline]
+    pub fn snap_to_grid(&mut self, grid: Option<u32>) {
+        self.grid = grid.unwrap_or(0);
+        self.snap();
+    }
+
+    /// Moves the cursor down to the next grid line.
+    #[inline]
+    pub fn snap(&mut self) {
+        if self.grid > 1 {
+            let grid = self.grid.saturating_as::<i32>();
+            let offset = self.y.rem_euclid(grid);
+            if offset != 0 {
+                self.y += grid - offset;
+            }
+        }
+    }
+
     /// Starts a new line.
     #[inline]
     pub fn new_line(&mut self) {
         self.y += self.line_spacing;
+        self.column = 0;
+        self.row_height = 0;
+        self.snap();
+    }
+
+    /// Moves the cursor to the start of the current line.
+    #[inline]
+    pub fn carriage_return(&mut self) {
+        self.column = 0;
+    }
+
+    /// Returns whether the next line starts at the left edge of the bounding box.
+    #[inline]
+    pub fn at_line_start(&self) -> bool {
+        self.column == 0
+    }
+
+    /// Moves the cursor to the horizontal position `x` in the current line, so the next line
+    /// continues it.
+    #[inline]
+    pub fn continue_line(&mut self, x: i32) {
+        self.column = (x - self.bounds.top_left.x)
+            .saturating_as::<u32>()
+            .min(self.bounds.size.width);
+    }
+
+    /// Marks the current line as the first line of the text.
+    ///
+    /// Absolute cursor movements are measured from this line.
+    #[cfg(feature = "ansi")]
+    #[inline]
+    pub fn mark_first_line(&mut self) {
+        self.first_line = self.y;
+    }
+
+    /// Moves the cursor to a different line.
+    ///
+    /// The cursor can't be moved above the first line of the text. The horizontal position is
+    /// limited so that the line has room for at least one pixel.
+    #[cfg(feature = "ansi")]
+    #[inline]
+    pub fn move_to(&mut self, movement: CursorMove) {
+        let column = match movement {
+            CursorMove::Relative { lines, x } => {
+                self.y += lines.saturating_mul(self.line_spacing);
+                self.column.saturating_add(x)
+            }
+            CursorMove::Absolute { line, x } => {
+                self.y = self.first_line
+                    + line
+                        .saturating_as::<i32>()
+                        .saturating_mul(self.line_spacing);
+                x
+            }
+        };
+
+        self.y = self.y.max(self.first_line);
+        self.column = column.min(self.bounds.size.width.saturating_sub(1));
     }
 
     /// Returns whether the cursor is completely in the bounding box.
@@ -0,0 +1,357 @@
+//! Map points to positions in the text.
+use core::convert::Infallible;
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    geometry::{Dimensions, Point},
+    pixelcolor::Rgb888,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    parser::Parser,
+    plugin::{NoPlugin, PluginMarker as Plugin, PluginWrapper},
+    rendering::{
+        cursor::LineCursor,
+        layout::{LaidOutLine, LineLayout},
+        line_iter::{ElementHandler, LineElementParser},
+    },
+    utils::{offset_in, str_width},
+    TextBox,
+};
+
+/// Controls how points between lines are mapped to lines by [`TextBox::hit_test`].
+///
+/// Lines are separated by gaps when the line height is larger than the height of the font, or if
+/// paragraph spacing is used. The area above the first line and below the last line is also
+/// considered a gap.
+///
+/// [`TextBox::hit_test`]: ../struct.TextBox.html#method.hit_test
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LineGap {
+    /// Points in a gap are mapped to the nearest line.
+    ///
+    /// Points that are at equal distance from two lines are mapped to the upper line.
+    Nearest,
+
+    /// Points in a gap don't hit any line.
+    Ignore,
+}
+
+/// A position in the text of a text box.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TextPosition {
+    /// The index of the line.
+    pub line: usize,
+
+    /// Byte offset of the character boundary closest to the point, in the text of the text box.
+    pub offset: usize,
+}
+
+/// Finds the character boundary closest to a horizontal position.
+struct HitTestHandler<'a, 'b, S> {
+    style: &'b S,
+    text: &'a str,
+    target: i32,
+    pos: i32,
+    end: Option<usize>,
+    hit: Option<usize>,
+}
+
+impl<'a, 'b, S> HitTestHandler<'a, 'b, S> {
+    /// Advances the position over `st`, splitting `width` evenly between its characters.
+    fn advance(&mut self, st: &str, width: u32, mut char_width: impl FnMut(char) -> u32) {
+        let offset = match offset_in(self.text, st) {
+            Some(offset) if self.hit.is_none() => offset,
+            _ => {
+                self.pos += width.saturating_as::<i32>();
+                return;
+            }
+        };
+
+        for (idx, c) in st.char_indices() {
+            let w = char_width(c).saturating_as::<i32>();
+            if self.target < self.pos + w / 2 {
+                self.hit = Some(offset + idx);
+                return;
+            }
+            self.pos += w;
+        }
+
+        self.end = Some(offset + st.len());
+    }
+}
+
+impl<'a, 'b, S> ElementHandler<'_> for HitTestHandler<'a, 'b, S>
+where
+    S: TextRenderer,
+    S::Color: From<Rgb888>,
+{
+    type Error = Infallible;
+    type Color = S::Color;
+
+    fn measure(&self, st: &str) -> u32 {
+        str_width(self.style, st)
+    }
+
+    fn whitespace(&mut self, st: &str, _space_count: u32, width: u32) -> Result<(), Self::Error> {
+        let count = st.chars().count().saturating_as::<u32>().max(1);
+        let single = width / count;
+        let last = width - single * (count - 1);
+        let mut remaining = count;
+
+        self.advance(st, width, |_| {
+            remaining -= 1;
+            if remaining == 0 {
+                last
+            } else {
+                single
+            }
+        });
+
+        Ok(())
+    }
+
+    fn printed_characters(&mut self, st: &str, width: u32) -> Result<(), Self::Error> {
+        let style = self.style;
+        let mut buffer = [0; 4];
+        self.advance(st, width, |c| str_width(style, c.encode_utf8(&mut buffer)));
+
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.pos += by;
+        Ok(())
+    }
+
+    fn custom(&mut self, _id: u32, width: u32) -> Result<(), Self::Error> {
+        self.pos += width.saturating_as::<i32>();
+        Ok(())
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Returns the position in the text that is closest to the given point.
+    ///
+    /// Points outside of the bounding box of the text box don't hit any line. Points inside the
+    /// bounding box, but between two lines are handled according to `gap`. Horizontally, the
+    /// character boundary closest to the point is returned, so two hit tests can be used to
+    /// select a range of text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    ///     text::LineHeight,
+    /// };
+    /// use embedded_text::{style::TextBoxStyleBuilder, LineGap, TextBox};
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .line_height(LineHeight::Pixels(20))
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(30, 40));
+    ///
+    /// let text_box = TextBox::with_textbox_style("Hello World", bounds, character_style, style);
+    ///
+    /// // The point is on the "W" character in the second line.
+    /// let hit = text_box.hit_test(Point::new(2, 25), LineGap::Ignore).unwrap();
+    /// assert_eq!((hit.line, hit.offset), (1, 6));
+    ///
+    /// // The point is in the gap below the first line.
+    /// assert_eq!(text_box.hit_test(Point::new(2, 12), LineGap::Ignore), None);
+    ///
+    /// let hit = text_box.hit_test(Point::new(2, 12), LineGap::Nearest).unwrap();
+    /// assert_eq!(hit.line, 0);
+    /// ```
+    #[inline]
+    pub fn hit_test(&self, point: Point, gap: LineGap) -> Option<TextPosition> {
+        if !self.bounding_box().contains(point) {
+            return None;
+        }
+
+        let line_height = self.character_style.line_height().saturating_as::<i32>();
+
+        let mut previous: Option<(usize, LaidOutLine<'a>, i32)> = None;
+        for (index, line) in LineLayout::new(self, self.text).enumerate() {
+            let top = line.position.y;
+            let bottom = top + line_height;
+
+            if point.y < top {
+                if gap == LineGap::Ignore {
+                    return None;
+                }
+
+                let (index, line) = match previous {
+                    Some((index, line, bottom)) if point.y - bottom <= top - point.y => {
+                        (index, line)
+                    }
+                    _ => (index, line),
+                };
+
+                return Some(self.hit_test_line(index, &line, point.x));
+            }
+
+            if point.y < bottom {
+                return Some(self.hit_test_line(index, &line, point.x));
+            }
+
+            previous = Some((index, line, bottom));
+        }
+
+        match gap {
+            LineGap::Nearest => {
+                previous.map(|(index, line, _)| self.hit_test_line(index, &line, point.x))
+            }
+            LineGap::Ignore => None,
+        }
+    }
+
+    fn hit_test_line(&self, index: usize, line: &LaidOutLine<'a>, x: i32) -> TextPosition {
+        let plugin = PluginWrapper::new(NoPlugin::new());
+        plugin.set_source_line_start(line.paragraph_start);
+        let width = self.text_bounds().size.width;
+        let text = &self.text[line.offset..];
+
+        let lm = self.style.measure_line(
+            &plugin.clone(),
+            &self.character_style,
+            &mut Parser::parse(text).with_style(&self.style),
+            width,
+        );
+        let (_, space_config) =
+            self.style
+                .alignment
+                .place_line(&self.character_style, self.style.word_spacing, lm);
+
+        let mut handler = HitTestHandler {
+            style: &self.character_style,
+            text: self.text,
+            target: x - line.position.x,
+            pos: 0,
+            end: None,
+            hit: None,
+        };
+
+        let cursor = LineCursor::new(
+            width,
+            self.style.tab_size.into_pixels(&self.character_style),
+        );
+        LineElementParser::new(
+            &mut Parser::parse(text).with_style(&self.style),
+            &plugin,
+            cursor,
+            space_config,
+            &self.style,
+        )
+        .process(&mut handler)
+        .unwrap();
+
+        let offset = handler
+            .hit
+            .or(handler.end)
+            .unwrap_or(line.offset)
+            .max(line.offset);
+
+        TextPosition {
+            line: index,
+            offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::HorizontalAlignment,
+        rendering::hit_test::{LineGap, TextPosition},
+        style::TextBoxStyleBuilder,
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[test]
+    fn hits_character_boundaries() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let text_box = TextBox::new("ab cd", bounds, character_style);
+
+        let offset = |x| {
+            text_box
+                .hit_test(Point::new(x, 3), LineGap::Ignore)
+                .unwrap()
+                .offset
+        };
+        assert_eq!(offset(0), 0);
+        assert_eq!(offset(2), 0);
+        assert_eq!(offset(3), 1);
+        assert_eq!(offset(14), 2);
+        assert_eq!(offset(20), 3);
+        assert_eq!(offset(22), 4);
+        assert_eq!(offset(29), 5);
+    }
+
+    #[test]
+    fn aligned_line() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1));
+
+        let text_box = TextBox::with_textbox_style(
+            "ab",
+            bounds,
+            character_style,
+            TextBoxStyleBuilder::new()
+                .alignment(HorizontalAlignment::Right)
+                .build(),
+        );
+
+        let hit = |x| text_box.hit_test(Point::new(x, 3), LineGap::Ignore);
+        assert_eq!(hit(0), Some(TextPosition { line: 0, offset: 0 }));
+        assert_eq!(hit(25), Some(TextPosition { line: 0, offset: 1 }));
+        assert_eq!(hit(29), Some(TextPosition { line: 0, offset: 2 }));
+        assert_eq!(hit(30), None);
+    }
+
+    #[test]
+    fn gaps_between_paragraphs() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), Size::new(30, 40));
+
+        let text_box = TextBox::with_textbox_style(
+            "ab\ncd",
+            bounds,
+            character_style,
+            TextBoxStyleBuilder::new().paragraph_spacing(10).build(),
+        );
+
+        // Lines are at rows 0..9 and 19..28.
+        let line = |y, gap| text_box.hit_test(Point::new(0, y), gap).map(|hit| hit.line);
+        assert_eq!(line(8, LineGap::Ignore), Some(0));
+        assert_eq!(line(9, LineGap::Ignore), None);
+        assert_eq!(line(19, LineGap::Ignore), Some(1));
+        assert_eq!(line(30, LineGap::Ignore), None);
+
+        assert_eq!(line(14, LineGap::Nearest), Some(0));
+        assert_eq!(line(15, LineGap::Nearest), Some(1));
+        assert_eq!(line(39, LineGap::Nearest), Some(1));
+    }
+}
@@ -0,0 +1,634 @@
+//! Line layout.
+//!
+//! Lays out a piece of text line by line the same way the text box renderer does, without drawing
+//! anything.
+use core::ops::Range;
+
+use crate::{
+    parser::Parser,
+    plugin::{PluginMarker as Plugin, PluginWrapper, ProcessingState},
+    rendering::{cursor::Cursor, line_iter::LineEndType, SharedMeasurements},
+    style::{StyleContinuity, TextBoxStyle},
+    TextBox,
+};
+use az::SaturatingAs;
+use embedded_graphics::{
+    geometry::{Point, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+#[cfg(feature = "ansi")]
+use crate::rendering::cursor::CursorMove;
+
+/// A single laid out line.
+#[derive(Clone, Debug)]
+pub(crate) struct LaidOutLine<'a> {
+    /// The source text of the line.
+    pub text: &'a str,
+
+    /// Byte offset of the line in the laid out text.
+    pub offset: usize,
+
+    /// Position of the first rendered character, after horizontal alignment.
+    pub position: Point,
+
+    /// Width of the line in pixels, excluding trailing whitespace.
+    pub width: u32,
+
+    /// Height of the line in pixels.
+    pub height: u32,
+
+    /// Whether the whitespace in this line is stretched by the alignment.
+    pub stretched: bool,
+
+    /// The range of the line's rows that are displayed.
+    pub displayed_rows: Range<i32>,
+
+    /// Whether the line is completely inside the bounding box, vertically.
+    pub in_bounds: bool,
+
+    /// Number of words in the line.
+    pub word_count: u32,
+
+    /// Number of rendered spaces in the line.
+    pub space_count: u32,
+
+    /// Whether the line is the first line of a paragraph.
+    pub paragraph_start: bool,
+
+    /// Whether the line is ended by a newline character.
+    pub ends_with_newline: bool,
+}
+
+impl LaidOutLine<'_> {
+    /// Returns the displayed area of the line between the given horizontal offsets.
+    pub fn area(&self, from: u32, to: u32) -> Rectangle {
+        Rectangle::new(
+            self.position + Point::new(from.saturating_as(), self.displayed_rows.start),
+            Size::new(
+                to.saturating_sub(from),
+                self.displayed_rows.len().saturating_as(),
+            ),
+        )
+    }
+}
+
+/// Iterates over the lines of a text.
+pub(crate) struct LineLayout<'a, 'b, S, M>
+where
+    S: TextRenderer,
+{
+    text: &'a str,
+    text_box: &'b TextBox<'a, S, M>,
+    style: TextBoxStyle,
+    character_style: &'b S,
+    fonts: &'b [S],
+    font: Option<usize>,
+    parser: Parser<'a, S::Color>,
+    plugin: PluginWrapper<'a, M, S::Color>,
+    cursor: Cursor,
+    line_index: u32,
+    finished: bool,
+}
+
+impl<'a, 'b, S, M> LineLayout<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Lays out `text` using the properties of the given text box.
+    pub fn new(text_box: &'b TextBox<'a, S, M>, text: &'a str) -> Self {
+        let plugin = text_box.plugin.clone();
+        let shared = SharedMeasurements::new(&text_box.character_style, &text_box.style, true);
+        let cursor = text_box.start_cursor(text, &plugin, &shared);
+
+        plugin.set_state(ProcessingState::Measure);
+
+        Self {
+            text,
+            text_box,
+            style: text_box.style,
+            character_style: &text_box.character_style,
+            fonts: text_box.fonts,
+            font: None,
+            parser: Parser::parse(text).with_style(&text_box.style),
+            plugin,
+            cursor,
+            line_index: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<'a, 'b, S, M> Iterator for LineLayout<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Item = LaidOutLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        self.plugin.new_line();
+
+        let line_cursor = self.text_box.line_cursor(&self.cursor, self.line_index);
+        self.line_index += 1;
+        let start = self.text.len() - self.parser.as_str().len();
+        let paragraph_start = self.plugin.at_source_line_start();
+        let lm = self.style.measure_line_with_fonts(
+            &self.plugin,
+            self.character_style,
+            self.fonts,
+            &mut self.font,
+            &mut self.parser,
+            line_cursor.line_width(),
+        );
+        if !self.fonts.is_empty() {
+            self.cursor.set_line_height(lm.line_height);
+        }
+        let end = self.text.len() - self.parser.as_str().len();
+
+        let width = lm.width;
+        let end_type = lm.line_end_type;
+        let word_count = lm.word_count;
+        let space_count = lm.space_count;
+        let (left, space_config) =
+            self.style
+                .alignment
+                .place_line(self.character_style, self.style.word_spacing, lm);
+
+        let line = LaidOutLine {
+            text: &self.text[start..end],
+            offset: start,
+            position: line_cursor.pos() + Point::new(left.saturating_as(), 0),
+            width,
+            height: self.cursor.line_height().saturating_as(),
+            stretched: space_config.is_stretched(),
+            displayed_rows: self
+                .style
+                .height_mode
+                .calculate_displayed_row_range(&self.cursor),
+            in_bounds: self.cursor.in_display_area(),
+            word_count,
+            space_count,
+            paragraph_start,
+            ends_with_newline: end_type == LineEndType::NewLine,
+        };
+
+        match end_type {
+            LineEndType::EndOfText => self.finished = true,
+            LineEndType::CarriageReturn => self.cursor.carriage_return(),
+            #[cfg(feature = "ansi")]
+            LineEndType::MoveCursor(movement) => self.cursor.move_to(movement),
+            #[cfg(feature = "ansi")]
+            LineEndType::EraseDisplay(x) => {
+                self.cursor.move_to(CursorMove::Relative { lines: 0, x })
+            }
+            LineEndType::NewLine | LineEndType::LineBreak => {
+                self.cursor.new_line();
+
+                if end_type == LineEndType::NewLine {
+                    self.cursor.y += self.style.paragraph_spacing.saturating_as::<i32>();
+
+                    if self.style.style_continuity == StyleContinuity::ResetParagraphs {
+                        self.font = None;
+                    }
+                }
+            }
+        }
+
+        Some(line)
+    }
+}
+
+/// Measurements of a single line of text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LineMetrics {
+    /// Width of the line in pixels, excluding trailing whitespace.
+    pub width: u32,
+
+    /// Number of words in the line.
+    ///
+    /// Words are separated by whitespace. A word that is broken into multiple lines is counted in
+    /// every line it appears in.
+    pub word_count: u32,
+
+    /// Number of spaces in the line, excluding leading whitespace that is not rendered and
+    /// trailing whitespace.
+    pub space_count: u32,
+
+    /// The area of the line.
+    ///
+    /// The area starts at the first rendered character, after horizontal alignment. It is as wide
+    /// as the line, and as tall as the line height.
+    pub bounds: Rectangle,
+
+    /// Byte offset of the start of the line in the text.
+    pub start: usize,
+
+    /// Byte offset of the end of the line in the text, including the whitespace and the newline
+    /// character that end the line.
+    pub end: usize,
+
+    /// Whether the whitespace in the line is stretched by the justified alignment.
+    pub stretched: bool,
+}
+
+/// The amount of text that doesn't fit into the bounding box of a text box.
+///
+/// See [`TextBox::overflow`].
+///
+/// [`TextBox::overflow`]: ../struct.TextBox.html#method.overflow
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Overflow {
+    /// The number of pixels the text is taller than the text area of the text box.
+    pub height: u32,
+
+    /// The number of lines that are not completely inside the text area.
+    ///
+    /// Lines are counted as they are laid out, so lines that are drawn over each other, for
+    /// example by a carriage return, are counted separately.
+    pub lines: u32,
+}
+
+impl Overflow {
+    /// Returns whether any part of the text is outside of the text area.
+    #[inline]
+    pub const fn is_overflowing(&self) -> bool {
+        self.height > 0 || self.lines > 0
+    }
+}
+
+/// Iterator over the measurements of the lines of a text box.
+///
+/// This struct is created by [`TextBox::line_metrics`].
+///
+/// [`TextBox::line_metrics`]: ../struct.TextBox.html#method.line_metrics
+pub struct LineMetricsIter<'a, 'b, S, M>
+where
+    S: TextRenderer,
+{
+    lines: LineLayout<'a, 'b, S, M>,
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Returns an iterator over the measurements of every line of the text.
+    ///
+    /// The lines are measured the same way they are laid out when the text box is drawn,
+    /// including lines that fall outside of the bounding box.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 0));
+    ///
+    /// let text_box = TextBox::new("Lorem ipsum dolor sit amet", bounds, character_style);
+    ///
+    /// let words: u32 = text_box.line_metrics().map(|line| line.word_count).sum();
+    /// assert_eq!(words, 5);
+    /// ```
+    #[inline]
+    pub fn line_metrics(&self) -> LineMetricsIter<'a, '_, S, M> {
+        LineMetricsIter {
+            lines: LineLayout::new(self, self.text),
+        }
+    }
+
+    /// Returns an iterator over the text and width of every laid out line of the text.
+    ///
+    /// Every item is a tuple of the source text of the line, its width in pixels, and whether the
+    /// line is ended by a newline character. The newline character itself is not part of the
+    /// returned text. The lines are wrapped the same way they are when the text box is drawn, so
+    /// the iterator can be used to implement custom renderers or to export the layout.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(72, 0));
+    ///
+    /// let text_box = TextBox::new("Lorem ipsum dolor\nsit amet", bounds, character_style);
+    ///
+    /// let mut lines = text_box.line_measurements();
+    /// assert_eq!(lines.next(), Some(("Lorem ipsum ", 66, false)));
+    /// assert_eq!(lines.next(), Some(("dolor", 30, true)));
+    /// assert_eq!(lines.next(), Some(("sit amet", 48, false)));
+    /// assert_eq!(lines.next(), None);
+    /// ```
+    #[inline]
+    pub fn line_measurements(&self) -> LineMeasurementIter<'a, '_, S, M> {
+        LineMeasurementIter {
+            lines: LineLayout::new(self, self.text),
+        }
+    }
+
+    /// Returns how much of the text doesn't fit into the text box.
+    ///
+    /// The returned value contains both the number of pixels the text is taller than the text
+    /// area, and the number of lines that are not completely displayed, so scroll bars or
+    /// "more" indicators can be driven by a single call. Lines that are moved out of the text
+    /// area by the vertical offset or the vertical alignment are also counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// let text_box = TextBox::new("one\ntwo\nthree\nfour", bounds, character_style);
+    ///
+    /// let overflow = text_box.overflow();
+    /// assert!(overflow.is_overflowing());
+    /// assert_eq!(overflow.height, 18);
+    /// assert_eq!(overflow.lines, 2);
+    /// ```
+    #[inline]
+    pub fn overflow(&self) -> Overflow {
+        let metrics = self.measure_lines(
+            self.plugin.clone(),
+            self.fonts,
+            self.text,
+            &self.top_cursor(),
+        );
+
+        Overflow {
+            height: metrics
+                .height
+                .saturating_sub(self.text_bounds().size.height),
+            lines: LineLayout::new(self, self.text)
+                .filter(|line| !line.in_bounds)
+                .count()
+                .saturating_as(),
+        }
+    }
+
+    /// Returns how much of the text is scrolled off the top of the text box.
+    ///
+    /// Text is scrolled off the top when the vertical alignment, the vertical offset or a plugin
+    /// like [`Tail`] moves the first lines above the text area. The returned value can be used to
+    /// display "N more lines" indicators, for example in log views.
+    ///
+    /// See the [`Tail`] plugin for an example.
+    ///
+    /// [`Tail`]: plugin/tail/struct.Tail.html
+    #[inline]
+    pub fn scrolled_off(&self) -> ScrolledOff {
+        let top = self.text_bounds().top_left.y;
+
+        let mut scrolled_off = ScrolledOff::default();
+        for line in LineLayout::new(self, self.text) {
+            if line.position.y >= top {
+                break;
+            }
+            if scrolled_off.lines == 0 {
+                scrolled_off.height = (top - line.position.y).saturating_as();
+            }
+            scrolled_off.lines += 1;
+        }
+
+        scrolled_off
+    }
+}
+
+/// The amount of text that is scrolled off the top of a text box.
+///
+/// See [`TextBox::scrolled_off`].
+///
+/// [`TextBox::scrolled_off`]: ../struct.TextBox.html#method.scrolled_off
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ScrolledOff {
+    /// The number of pixels between the top of the first line and the top of the text area.
+    pub height: u32,
+
+    /// The number of lines that start above the text area, including partially displayed lines.
+    pub lines: u32,
+}
+
+/// Iterator over the text and width of the lines of a text box.
+///
+/// This struct is created by [`TextBox::line_measurements`].
+///
+/// [`TextBox::line_measurements`]: ../struct.TextBox.html#method.line_measurements
+pub struct LineMeasurementIter<'a, 'b, S, M>
+where
+    S: TextRenderer,
+{
+    lines: LineLayout<'a, 'b, S, M>,
+}
+
+impl<'a, 'b, S, M> Iterator for LineMetricsIter<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Item = LineMetrics;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(|line| LineMetrics {
+            width: line.width,
+            word_count: line.word_count,
+            space_count: line.space_count,
+            bounds: Rectangle::new(line.position, Size::new(line.width, line.height)),
+            start: line.offset,
+            end: line.offset + line.text.len(),
+            stretched: line.stretched,
+        })
+    }
+}
+
+impl<'a, 'b, S, M> Iterator for LineMeasurementIter<'a, 'b, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Item = (&'a str, u32, bool);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(|line| {
+            let text = if line.ends_with_newline {
+                line.text.strip_suffix('\n').unwrap_or(line.text)
+            } else {
+                line.text
+            };
+
+            (text, line.width, line.ends_with_newline)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{style::TextBoxStyleBuilder, utils::test::size_for, TextBox};
+
+    #[track_caller]
+    fn assert_metrics(text: &str, expected: &[(u32, u32, u32)]) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let text_box = TextBox::new(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 6, 3)),
+            character_style,
+        );
+
+        let metrics = text_box
+            .line_metrics()
+            .map(|line| (line.width, line.word_count, line.space_count))
+            .collect::<Vec<_>>();
+
+        assert_eq!(metrics, expected);
+    }
+
+    #[test]
+    fn counts_words_and_spaces() {
+        assert_metrics("a b  c", &[(36, 3, 3)]);
+        assert_metrics("a b\nc ", &[(18, 2, 1), (6, 1, 0)]);
+    }
+
+    #[test]
+    fn wrapped_lines() {
+        assert_metrics("foo bar baz", &[(18, 1, 0), (18, 1, 0), (18, 1, 0)]);
+        assert_metrics("sam\u{ad}ple", &[(36, 1, 0)]);
+        assert_metrics("abcdefgh", &[(36, 1, 0), (12, 1, 0)]);
+    }
+
+    #[test]
+    fn max_chars_per_line() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let widths = |text, max| {
+            TextBox::with_textbox_style(
+                text,
+                Rectangle::new(Point::zero(), size_for(&FONT_6X9, 20, 3)),
+                character_style,
+                TextBoxStyleBuilder::new().max_chars_per_line(max).build(),
+            )
+            .line_metrics()
+            .map(|line| line.width / 6)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(widths("foo bar baz", 7), [7, 3]);
+        assert_eq!(widths("foo bar baz", 6), [3, 3, 3]);
+        assert_eq!(widths("abcdefgh", 3), [3, 3, 2]);
+        assert_eq!(widths("sam\u{ad}ple", 4), [4, 3]);
+        assert_eq!(widths("a\tb", 2), [1, 1]);
+    }
+
+    #[test]
+    fn overflow() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let overflow = |text, height, offset| {
+            let mut text_box = TextBox::new(
+                text,
+                Rectangle::new(Point::zero(), Size::new(24, height)),
+                character_style,
+            );
+            text_box.set_vertical_offset(offset);
+
+            let overflow = text_box.overflow();
+            (overflow.height, overflow.lines)
+        };
+
+        assert_eq!(overflow("ab\ncd", 18, 0), (0, 0));
+        assert_eq!(overflow("ab\ncd\nef", 20, 0), (7, 1));
+        assert_eq!(overflow("ab cd ef", 9, 0), (18, 2));
+        assert_eq!(overflow("ab\ncd", 18, -9), (0, 1));
+    }
+
+    #[test]
+    fn scrolled_off() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let scrolled_off = |text, offset| {
+            let mut text_box = TextBox::new(
+                text,
+                Rectangle::new(Point::new(0, 5), Size::new(24, 18)),
+                character_style,
+            );
+            text_box.set_vertical_offset(offset);
+
+            let scrolled_off = text_box.scrolled_off();
+            (scrolled_off.height, scrolled_off.lines)
+        };
+
+        assert_eq!(scrolled_off("ab\ncd\nef", 0), (0, 0));
+        assert_eq!(scrolled_off("ab\ncd\nef", 4), (0, 0));
+        assert_eq!(scrolled_off("ab\ncd\nef", -4), (4, 1));
+        assert_eq!(scrolled_off("ab\ncd\nef", -12), (12, 2));
+    }
+
+    #[test]
+    fn line_measurements() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let text_box = TextBox::new(
+            "foo bar\n\nsam\u{ad}ple\r",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3)),
+            character_style,
+        );
+
+        assert_eq!(
+            text_box.line_measurements().collect::<Vec<_>>(),
+            [
+                ("foo ", 18, false),
+                ("bar", 18, true),
+                ("", 0, true),
+                ("sam\u{ad}", 24, false),
+                ("ple\r", 18, false),
+                ("", 0, false),
+            ]
+        );
+    }
+}
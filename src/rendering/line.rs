@@ -1,21 +1,25 @@
 //! Line rendering.
-use core::convert::Infallible;
+use core::{
+    convert::Infallible,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
 use crate::{
     parser::{ChangeTextStyle, Parser},
-    plugin::{PluginMarker as Plugin, PluginWrapper, ProcessingState},
+    plugin::{PluginMarker as Plugin, PluginWrapper},
     rendering::{
         cursor::LineCursor,
-        line_iter::{LineElementParser, LineEndType},
+        line_iter::{LineElementParser, LineElements, LineEndType},
     },
-    style::TextBoxStyle,
-    utils::str_width,
+    style::{Advance, LineMeasurement, StyleContinuity, StyledRanges, TextBoxStyle},
+    utils::{offset_in, str_width},
 };
 use az::SaturatingAs;
 use embedded_graphics::{
     draw_target::DrawTarget,
     geometry::Point,
-    pixelcolor::{BinaryColor, Rgb888},
+    pixelcolor::Rgb888,
     prelude::{PixelColor, Size},
     primitives::Rectangle,
     text::{
@@ -27,26 +31,267 @@ use embedded_graphics::{
 
 use super::{line_iter::ElementHandler, space_config::SpaceConfig};
 
+#[cfg(feature = "ansi")]
+use super::ansi::{Sgr, SgrState};
+
+impl<C> ChangeTextStyle<C>
+where
+    C: PixelColor,
+{
+    /// Returns the font selected by the change, if it changes the font.
+    ///
+    /// `None` selects the character style of the text box, `Some(index)` selects one of the
+    /// `font_count` fonts of the text box.
+    pub(crate) fn selected_font(self, font_count: usize) -> Option<Option<usize>> {
+        match self {
+            ChangeTextStyle::Reset => Some(None),
+            ChangeTextStyle::Font(font) if font < font_count => Some(Some(font)),
+            _ => None,
+        }
+    }
+}
+
 impl<C> ChangeTextStyle<C>
 where
     C: PixelColor + From<Rgb888>,
 {
-    pub(crate) fn apply<S: CharacterStyle<Color = C>>(self, style: &mut S) {
+    /// Applies the change to `style`. `Reset` restores the `default` style, `Font` switches to
+    /// one of the `fonts`.
+    pub(crate) fn apply<S: CharacterStyle<Color = C> + Clone>(
+        self,
+        style: &mut S,
+        default: &S,
+        fonts: &[S],
+        font: &mut Option<usize>,
+        underline: &mut UnderlineState<C>,
+    ) {
+        if let Some(selected) = self.selected_font(fonts.len()) {
+            *font = selected;
+            *style = font_style(default, fonts, selected).clone();
+            underline.reset();
+            return;
+        }
+
         match self {
-            ChangeTextStyle::Reset => {
-                style.set_text_color(Some(Into::<Rgb888>::into(BinaryColor::On).into()));
-                style.set_background_color(None);
-                style.set_underline_color(DecorationColor::None);
-                style.set_strikethrough_color(DecorationColor::None);
-            }
+            ChangeTextStyle::Reset
+            | ChangeTextStyle::Font(_)
+            | ChangeTextStyle::VerticalOffset(_) => {}
             ChangeTextStyle::TextColor(color) => style.set_text_color(color),
             ChangeTextStyle::BackgroundColor(color) => style.set_background_color(color),
-            ChangeTextStyle::Underline(color) => style.set_underline_color(color),
+            ChangeTextStyle::Underline(DecorationColor::TextColor) => {
+                underline.enabled = true;
+                style.set_underline_color(underline.decoration_color());
+            }
+            ChangeTextStyle::Underline(color) => {
+                underline.enabled = color != DecorationColor::None;
+                style.set_underline_color(color);
+            }
+            ChangeTextStyle::UnderlineColor(color) => {
+                underline.color = color.or(underline.default_color);
+                if underline.enabled {
+                    style.set_underline_color(underline.decoration_color());
+                }
+            }
             ChangeTextStyle::Strikethrough(color) => style.set_strikethrough_color(color),
         }
     }
 }
 
+impl<C> ChangeTextStyle<C>
+where
+    C: PixelColor,
+{
+    /// Applies the change to the vertical offset of the text.
+    pub(crate) fn apply_vertical_offset(self, offset: &mut i32, font_count: usize) {
+        if let ChangeTextStyle::VerticalOffset(new_offset) = self {
+            *offset = new_offset;
+        } else if self.selected_font(font_count).is_some() {
+            *offset = 0;
+        }
+    }
+}
+
+/// Returns the character style of the selected font, or `default` if no font is selected.
+pub(crate) fn font_style<'s, S>(default: &'s S, fonts: &'s [S], font: Option<usize>) -> &'s S {
+    match font.and_then(|font| fonts.get(font)) {
+        Some(style) => style,
+        None => default,
+    }
+}
+
+/// Keeps track of the underline color selected by text style changes.
+///
+/// Character styles can't be queried for their decorations, so the underline color is stored
+/// separately, to be applied when underlining is turned on.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UnderlineState<C> {
+    default_color: Option<C>,
+    color: Option<C>,
+    enabled: bool,
+}
+
+impl<C> UnderlineState<C>
+where
+    C: PixelColor,
+{
+    pub fn new(default_color: Option<C>) -> Self {
+        Self {
+            default_color,
+            color: default_color,
+            enabled: false,
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.default_color);
+    }
+
+    fn decoration_color(&self) -> DecorationColor<C> {
+        match self.color {
+            Some(color) => DecorationColor::Custom(color),
+            None => DecorationColor::TextColor,
+        }
+    }
+}
+
+/// Callback that selects the text color of a word.
+pub(crate) struct WordColorFn<C>(pub fn(usize, &str) -> Option<C>);
+
+impl<C> Clone for WordColorFn<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for WordColorFn<C> {}
+
+impl<C> fmt::Debug for WordColorFn<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WordColorFn")
+            .field(&(self.0 as usize as *const ()))
+            .finish()
+    }
+}
+
+impl<C> Hash for WordColorFn<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.0 as usize).hash(state)
+    }
+}
+
+/// Keeps track of word boundaries across lines.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WordState<C> {
+    pub callback: Option<WordColorFn<C>>,
+    pub index: usize,
+    pub in_word: bool,
+    color: Option<C>,
+}
+
+impl<C> WordState<C> {
+    pub fn new(callback: Option<WordColorFn<C>>) -> Self {
+        Self {
+            callback,
+            index: 0,
+            in_word: false,
+            color: None,
+        }
+    }
+
+    /// Marks the end of the current word, if any.
+    fn separator(&mut self) {
+        if self.in_word {
+            self.in_word = false;
+            self.index += 1;
+        }
+    }
+
+    /// Registers a (part of a) word and returns the color it should be drawn with.
+    ///
+    /// The color is selected when the first part of the word is encountered, either by the
+    /// callback or by the plugins.
+    fn word(&mut self, st: &str, plugin_color: impl FnOnce(&str) -> Option<C>) -> Option<C>
+    where
+        C: Copy,
+    {
+        if !self.in_word {
+            self.in_word = true;
+            self.color = match self.callback {
+                Some(callback) => (callback.0)(self.index, st),
+                None => None,
+            }
+            .or_else(|| plugin_color(st));
+        }
+
+        self.color
+    }
+}
+
+/// Styled ranges, and the text they refer to.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RangeStyles<'a, C> {
+    pub text: &'a str,
+    pub ranges: StyledRanges<'a, C>,
+}
+
+impl<'a, C> RangeStyles<'a, C>
+where
+    C: PixelColor,
+{
+    /// Returns the style `st` starts with.
+    fn style_of<S>(&self, style: &S, st: &str) -> S
+    where
+        S: CharacterStyle<Color = C> + Clone,
+    {
+        let mut style = style.clone();
+        if let Some(offset) = offset_in(self.text, st) {
+            self.ranges.delta_at(offset).apply(&mut style);
+        }
+
+        style
+    }
+
+    /// Draws `st`, split into parts that are drawn using the style of their range.
+    fn draw_string<S, D>(
+        &self,
+        style: &S,
+        st: &str,
+        mut pos: Point,
+        display: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        S: TextRenderer<Color = C> + CharacterStyle<Color = C> + Clone,
+        D: DrawTarget<Color = C>,
+    {
+        let mut offset = match offset_in(self.text, st) {
+            Some(offset) => offset,
+            None => return style.draw_string(st, pos, Baseline::Top, display),
+        };
+
+        let mut rest = st;
+        while !rest.is_empty() {
+            let len = self
+                .ranges
+                .next_boundary(offset)
+                .map_or(rest.len(), |boundary| boundary - offset)
+                .min(rest.len());
+            let len = (len..rest.len())
+                .find(|len| rest.is_char_boundary(*len))
+                .unwrap_or(rest.len());
+            let (part, next) = rest.split_at(len);
+
+            let mut part_style = style.clone();
+            self.ranges.delta_at(offset).apply(&mut part_style);
+            pos = part_style.draw_string(part, pos, Baseline::Top, display)?;
+
+            offset += len;
+            rest = next;
+        }
+
+        Ok(pos)
+    }
+}
+
 /// Render a single line of styled text.
 pub(crate) struct StyledLineRenderer<'a, 'b, S, M>
 where
@@ -55,6 +300,28 @@ where
 {
     cursor: LineCursor,
     state: LineRenderState<'a, 'b, S, M>,
+
+    /// The measured line, if it's already known.
+    measured: Option<MeasuredLine<'a, M, S::Color>>,
+}
+
+/// A line that was measured before it's drawn.
+///
+/// The elements of the line are recorded while it's measured, so the line can be drawn without
+/// processing its text again.
+pub(crate) struct MeasuredLine<'a, M, C>
+where
+    C: PixelColor,
+{
+    pub measurement: LineMeasurement,
+    elements: LineElements<'a, C>,
+
+    /// The state of the parser and the plugin after the line.
+    parser: Parser<'a, C>,
+    plugin: PluginWrapper<'a, M, C>,
+
+    /// The position of the cursor after the last element of the line.
+    end: Point,
 }
 
 #[derive(Clone)]
@@ -65,9 +332,56 @@ where
 {
     pub parser: Parser<'a, S::Color>,
     pub character_style: S,
+    pub default_style: S,
+    pub fonts: &'b [S],
+    pub font: Option<usize>,
+    pub vertical_offset: i32,
     pub style: TextBoxStyle,
     pub end_type: LineEndType,
     pub plugin: &'b PluginWrapper<'a, M, S::Color>,
+    pub words: WordState<S::Color>,
+    pub ranges: Option<RangeStyles<'a, S::Color>>,
+    pub underline: UnderlineState<S::Color>,
+    #[cfg(feature = "ansi")]
+    pub sgr: SgrState,
+
+    /// The position after the last element of the previously rendered line.
+    pub line_end: Point,
+}
+
+impl<'a, 'b, F, M> LineRenderState<'a, 'b, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color>,
+{
+    /// Measures the next line, starting at `cursor`, without changing the state.
+    pub fn measure_line(
+        &self,
+        cursor: LineCursor,
+    ) -> MeasuredLine<'a, M, <F as TextRenderer>::Color> {
+        let mut parser = self.parser.clone();
+        let plugin = self.plugin.clone();
+        let mut elements = LineElements::new();
+
+        let (measurement, end) = self.style.measure_line_elements(
+            &plugin,
+            &self.default_style,
+            self.fonts,
+            &mut self.font.clone(),
+            &mut parser,
+            cursor,
+            Some(&mut elements),
+        );
+
+        MeasuredLine {
+            measurement,
+            elements,
+            parser,
+            plugin,
+            end: end.pos(),
+        }
+    }
 }
 
 impl<'a, 'b, F, M> StyledLineRenderer<'a, 'b, F, M>
@@ -78,7 +392,68 @@ where
 {
     /// Creates a new line renderer.
     pub fn new(cursor: LineCursor, state: LineRenderState<'a, 'b, F, M>) -> Self {
-        Self { cursor, state }
+        Self {
+            cursor,
+            state,
+            measured: None,
+        }
+    }
+
+    /// Draws a line that was measured by `LineRenderState::measure_line`, instead of processing
+    /// the line again.
+    pub fn with_measurement(
+        mut self,
+        measured: Option<MeasuredLine<'a, M, <F as TextRenderer>::Color>>,
+    ) -> Self {
+        self.measured = measured;
+        self
+    }
+}
+
+/// Draws `st` in the outline color, shifted by 1 pixel in every direction.
+fn draw_outline<S, D>(
+    style: &S,
+    color: <S as CharacterStyle>::Color,
+    st: &str,
+    pos: Point,
+    display: &mut D,
+) -> Result<(), D::Error>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle + Clone,
+    D: DrawTarget<Color = <S as TextRenderer>::Color>,
+{
+    let mut outline_style = style.clone();
+    outline_style.set_text_color(Some(color));
+    outline_style.set_background_color(None);
+    outline_style.set_underline_color(DecorationColor::None);
+    outline_style.set_strikethrough_color(DecorationColor::None);
+
+    for y in -1..=1 {
+        for x in -1..=1 {
+            if x != 0 || y != 0 {
+                outline_style.draw_string(st, pos + Point::new(x, y), Baseline::Top, display)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves `pos` past an element that was measured to be `width` wide, and the drawing of which
+/// ended at `end`.
+///
+/// Returns the width the element is considered to take up.
+fn advance(advance: Advance, pos: &mut Point, width: u32, end: Point) -> u32 {
+    match advance {
+        Advance::Measured => {
+            *pos += Point::new(width.saturating_as(), 0);
+            width
+        }
+        Advance::Rendered => {
+            let width = (end.x - pos.x).saturating_as();
+            pos.x = end.x;
+            width
+        }
     }
 }
 
@@ -88,14 +463,36 @@ where
     D: DrawTarget<Color = F::Color>,
 {
     style: &'b mut F,
+    default_style: &'b F,
+    fonts: &'b [F],
+    font: &'b mut Option<usize>,
+    vertical_offset: &'b mut i32,
+    underline: &'b mut UnderlineState<F::Color>,
+    #[cfg(feature = "ansi")]
+    sgr: &'b mut SgrState,
     display: &'b mut D,
     pos: Point,
     plugin: &'b PluginWrapper<'a, M, F::Color>,
+    words: &'b mut WordState<F::Color>,
+    ranges: Option<RangeStyles<'a, F::Color>>,
+    outline: Option<F::Color>,
+    advance: Advance,
 }
 
-impl<'a, 'b, 'c, F, D, M> ElementHandler for RenderElementHandler<'a, 'c, F, D, M>
+impl<'a, 'c, F, D, M> RenderElementHandler<'a, 'c, F, D, M>
 where
-    F: CharacterStyle + TextRenderer,
+    F: TextRenderer,
+    D: DrawTarget<Color = F::Color>,
+{
+    /// Returns the position where the next piece of text is drawn.
+    fn text_position(&self) -> Point {
+        self.pos + Point::new(0, *self.vertical_offset)
+    }
+}
+
+impl<'a, 'c, F, D, M> ElementHandler<'_> for RenderElementHandler<'a, 'c, F, D, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
     <F as CharacterStyle>::Color: From<Rgb888>,
     D: DrawTarget<Color = <F as TextRenderer>::Color>,
     M: Plugin<'a, <F as TextRenderer>::Color>,
@@ -108,14 +505,26 @@ where
     }
 
     fn whitespace(&mut self, st: &str, space_count: u32, width: u32) -> Result<(), Self::Error> {
+        self.words.separator();
+
         let top_left = self.pos;
-        if space_count > 0 {
-            self.pos = self
-                .style
-                .draw_whitespace(width, self.pos, Baseline::Top, self.display)?;
+        let end = if space_count > 0 {
+            let pos = self.text_position();
+            match self.ranges {
+                Some(ranges) => ranges.style_of(self.style, st).draw_whitespace(
+                    width,
+                    pos,
+                    Baseline::Top,
+                    self.display,
+                )?,
+                None => self
+                    .style
+                    .draw_whitespace(width, pos, Baseline::Top, self.display)?,
+            }
         } else {
-            self.pos += Point::new(width.saturating_as(), 0);
-        }
+            self.pos + Point::new(width.saturating_as(), 0)
+        };
+        let width = advance(self.advance, &mut self.pos, width, end);
 
         let size = Size::new(width, self.style.line_height().saturating_as());
         let bounds = Rectangle::new(top_left, size);
@@ -127,45 +536,191 @@ where
     }
 
     fn printed_characters(&mut self, st: &str, width: u32) -> Result<(), Self::Error> {
-        let top_left = self.pos;
-        self.style
-            .draw_string(st, self.pos, Baseline::Top, self.display)?;
+        let mut word_style;
+        let plugin = self.plugin;
+        let style = match self.words.word(st, |word| plugin.word_color(word)) {
+            Some(color) => {
+                word_style = self.style.clone();
+                word_style.set_text_color(Some(color));
+                &word_style
+            }
+            None => &*self.style,
+        };
 
-        self.pos += Point::new(width.saturating_as(), 0);
+        let pos = self.text_position();
+        if let Some(color) = self.outline {
+            draw_outline(style, color, st, pos, self.display)?;
+        }
+        let end = match self.ranges {
+            Some(ranges) => ranges.draw_string(style, st, pos, self.display)?,
+            None => style.draw_string(st, pos, Baseline::Top, self.display)?,
+        };
 
-        let size = Size::new(width, self.style.line_height().saturating_as());
+        let top_left = self.pos;
+        let width = advance(self.advance, &mut self.pos, width, end);
+
+        let size = Size::new(width, style.line_height().saturating_as());
         let bounds = Rectangle::new(top_left, size);
 
-        self.plugin
-            .post_render(self.display, self.style, st, bounds)?;
+        self.plugin.post_render(self.display, style, st, bounds)?;
 
         Ok(())
     }
 
     fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.words.separator();
+
         // LineElementIterator ensures this new pos is valid.
         self.pos = Point::new(self.pos.x + by, self.pos.y);
         Ok(())
     }
 
+    fn custom(&mut self, id: u32, width: u32) -> Result<(), Self::Error> {
+        self.words.separator();
+
+        let size = Size::new(width, self.style.line_height().saturating_as());
+        let bounds = Rectangle::new(self.pos, size);
+
+        self.plugin
+            .draw_custom(id, self.display, self.style, bounds)?;
+
+        self.pos += Point::new(width.saturating_as(), 0);
+        Ok(())
+    }
+
+    fn change_text_style(
+        &mut self,
+        change: ChangeTextStyle<<F as CharacterStyle>::Color>,
+    ) -> Result<(), Self::Error> {
+        change.apply_vertical_offset(self.vertical_offset, self.fonts.len());
+        change.apply(
+            self.style,
+            self.default_style,
+            self.fonts,
+            self.font,
+            self.underline,
+        );
+        Ok(())
+    }
+
     #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        for change in self.sgr.apply(sgr).iter().flatten() {
+            self.change_text_style(*change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the shadow of a line.
+struct ShadowElementHandler<'b, F, D>
+where
+    F: TextRenderer,
+    D: DrawTarget<Color = F::Color>,
+{
+    style: F,
+    default_style: &'b F,
+    fonts: &'b [F],
+    font: Option<usize>,
+    vertical_offset: i32,
+    underline: UnderlineState<F::Color>,
+    #[cfg(feature = "ansi")]
+    sgr: SgrState,
+    display: &'b mut D,
+    pos: Point,
+    color: F::Color,
+    advance: Advance,
+}
+
+impl<'b, F, D> ElementHandler<'_> for ShadowElementHandler<'b, F, D>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    D: DrawTarget<Color = <F as TextRenderer>::Color>,
+{
+    type Error = D::Error;
+    type Color = <F as CharacterStyle>::Color;
+
+    fn measure(&self, st: &str) -> u32 {
+        str_width(&self.style, st)
+    }
+
+    fn whitespace(&mut self, _st: &str, _space_count: u32, width: u32) -> Result<(), Self::Error> {
+        let end = self.pos + Point::new(width.saturating_as(), 0);
+        advance(self.advance, &mut self.pos, width, end);
+        Ok(())
+    }
+
+    fn printed_characters(&mut self, st: &str, width: u32) -> Result<(), Self::Error> {
+        let mut shadow_style = self.style.clone();
+        shadow_style.set_text_color(Some(self.color));
+        shadow_style.set_background_color(None);
+        shadow_style.set_underline_color(DecorationColor::None);
+        shadow_style.set_strikethrough_color(DecorationColor::None);
+
+        let pos = self.pos + Point::new(0, self.vertical_offset);
+        let end = shadow_style.draw_string(st, pos, Baseline::Top, self.display)?;
+        advance(self.advance, &mut self.pos, width, end);
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.pos = Point::new(self.pos.x + by, self.pos.y);
+        Ok(())
+    }
+
+    fn custom(&mut self, _id: u32, width: u32) -> Result<(), Self::Error> {
+        self.pos += Point::new(width.saturating_as(), 0);
+        Ok(())
+    }
+
     fn change_text_style(
         &mut self,
         change: ChangeTextStyle<<F as CharacterStyle>::Color>,
     ) -> Result<(), Self::Error> {
-        change.apply(self.style);
+        change.apply_vertical_offset(&mut self.vertical_offset, self.fonts.len());
+        change.apply(
+            &mut self.style,
+            self.default_style,
+            self.fonts,
+            &mut self.font,
+            &mut self.underline,
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        for change in self.sgr.apply(sgr).iter().flatten() {
+            self.change_text_style(*change)?;
+        }
+
         Ok(())
     }
 }
 
-struct StyleOnlyRenderElementHandler<'a, F> {
-    style: &'a mut F,
+struct StyleOnlyRenderElementHandler<'a, 'b, F, M>
+where
+    F: TextRenderer,
+{
+    style: &'b mut F,
+    default_style: &'b F,
+    fonts: &'b [F],
+    font: &'b mut Option<usize>,
+    vertical_offset: &'b mut i32,
+    underline: &'b mut UnderlineState<F::Color>,
+    #[cfg(feature = "ansi")]
+    sgr: &'b mut SgrState,
+    words: &'b mut WordState<F::Color>,
+    plugin: &'b PluginWrapper<'a, M, F::Color>,
 }
 
-impl<'a, F> ElementHandler for StyleOnlyRenderElementHandler<'a, F>
+impl<'a, 'b, F, M> ElementHandler<'_> for StyleOnlyRenderElementHandler<'a, 'b, F, M>
 where
-    F: CharacterStyle + TextRenderer,
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
     <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color>,
 {
     type Error = Infallible;
     type Color = <F as CharacterStyle>::Color;
@@ -174,12 +729,49 @@ where
         str_width(self.style, st)
     }
 
-    #[cfg(feature = "ansi")]
+    fn whitespace(&mut self, _st: &str, _space_count: u32, _width: u32) -> Result<(), Self::Error> {
+        self.words.separator();
+        Ok(())
+    }
+
+    fn printed_characters(&mut self, st: &str, _width: u32) -> Result<(), Self::Error> {
+        // Keep word indices and colors consistent with the visible lines.
+        let plugin = self.plugin;
+        self.words.word(st, |word| plugin.word_color(word));
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, _by: i32) -> Result<(), Self::Error> {
+        self.words.separator();
+        Ok(())
+    }
+
+    fn custom(&mut self, _id: u32, _width: u32) -> Result<(), Self::Error> {
+        self.words.separator();
+        Ok(())
+    }
+
     fn change_text_style(
         &mut self,
         change: ChangeTextStyle<<F as CharacterStyle>::Color>,
     ) -> Result<(), Self::Error> {
-        change.apply(self.style);
+        change.apply_vertical_offset(self.vertical_offset, self.fonts.len());
+        change.apply(
+            self.style,
+            self.default_style,
+            self.fonts,
+            self.font,
+            self.underline,
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        for change in self.sgr.apply(sgr).iter().flatten() {
+            self.change_text_style(*change)?;
+        }
+
         Ok(())
     }
 }
@@ -201,64 +793,204 @@ where
         let LineRenderState {
             mut parser,
             mut character_style,
+            default_style,
+            fonts,
+            mut font,
+            mut vertical_offset,
             style,
             plugin,
+            mut words,
+            ranges,
+            mut underline,
+            #[cfg(feature = "ansi")]
+            mut sgr,
             ..
         } = self.state.clone();
 
-        let mut cloned_parser = parser.clone();
-        let measure_plugin = plugin.clone();
-        measure_plugin.set_state(ProcessingState::Measure);
-        let lm = style.measure_line(
-            &measure_plugin,
-            &character_style,
-            &mut cloned_parser,
-            self.cursor.line_width(),
-        );
+        let on_display = display.bounding_box().size.height != 0;
+
+        // Lines are only measured if their placement depends on it, or if they were measured
+        // before they are drawn.
+        let measured_line;
+        let measured = match self.measured.as_ref() {
+            Some(measured) => Some(measured),
+            None if on_display && style.alignment.needs_measurement() => {
+                measured_line = self.state.measure_line(self.cursor.clone());
+                Some(&measured_line)
+            }
+            None => None,
+        };
 
-        let (end_type, end_pos) = if display.bounding_box().size.height == 0 {
+        // Measured lines are drawn from their recorded elements, unless they have more elements
+        // than what can be recorded.
+        let recorded = measured.filter(|measured| measured.elements.is_complete());
+        if let Some(measured) = recorded {
+            parser = measured.parser.clone();
+            plugin.restore(measured.plugin.clone());
+        }
+
+        let (end_type, end_pos) = if !on_display {
             // We're outside of the view. Use simpler render element handler and space config.
-            let mut elements = LineElementParser::new(
-                &mut parser,
+            let space_config = SpaceConfig::new_from_renderer(&character_style)
+                .with_word_spacing(style.word_spacing);
+            let mut handler = StyleOnlyRenderElementHandler {
+                style: &mut character_style,
+                default_style: &default_style,
+                fonts,
+                font: &mut font,
+                vertical_offset: &mut vertical_offset,
+                underline: &mut underline,
+                #[cfg(feature = "ansi")]
+                sgr: &mut sgr,
+                words: &mut words,
                 plugin,
-                self.cursor.clone(),
-                SpaceConfig::new_from_renderer(&character_style),
-                style.alignment,
-            );
-
-            let end_type = elements
-                .process(&mut StyleOnlyRenderElementHandler {
-                    style: &mut character_style,
-                })
-                .unwrap();
-
-            (end_type, elements.cursor.pos())
+            };
+
+            match recorded {
+                Some(measured) => {
+                    measured
+                        .elements
+                        .replay(&mut handler, space_config)
+                        .unwrap();
+
+                    (measured.measurement.line_end_type, measured.end)
+                }
+                None => {
+                    let mut elements = LineElementParser::new(
+                        &mut parser,
+                        plugin,
+                        self.cursor.clone(),
+                        space_config,
+                        &style,
+                    );
+                    let end_type = elements.process(&mut handler).unwrap();
+
+                    (end_type, elements.cursor.pos())
+                }
+            }
         } else {
-            let (left, space_config) = style.alignment.place_line(&character_style, lm);
+            let (left, space_config) = match measured {
+                Some(measured) if style.alignment.needs_measurement() => style
+                    .alignment
+                    .place_line(&character_style, style.word_spacing, measured.measurement),
+                _ => (
+                    0,
+                    SpaceConfig::new_from_renderer(&character_style)
+                        .with_word_spacing(style.word_spacing),
+                ),
+            };
 
             let mut cursor = self.cursor.clone();
             cursor.move_cursor(left.saturating_as()).ok();
 
             let pos = cursor.pos();
-            let mut elements =
-                LineElementParser::new(&mut parser, plugin, cursor, space_config, style.alignment);
 
-            let end_type = elements.process(&mut RenderElementHandler {
+            if let Some(shadow) = style.shadow {
+                // The shadow is drawn first, from copies of the state, so that it stays beneath
+                // the text.
+                let mut handler = ShadowElementHandler {
+                    style: character_style.clone(),
+                    default_style: &default_style,
+                    fonts,
+                    font,
+                    vertical_offset,
+                    underline,
+                    #[cfg(feature = "ansi")]
+                    sgr,
+                    display,
+                    pos: pos + shadow.offset,
+                    color: shadow.color.into(),
+                    advance: style.advance,
+                };
+
+                match recorded {
+                    Some(measured) => {
+                        measured.elements.replay(&mut handler, space_config)?;
+                    }
+                    None => {
+                        let shadow_plugin = plugin.clone();
+                        LineElementParser::new(
+                            &mut parser.clone(),
+                            &shadow_plugin,
+                            cursor.clone(),
+                            space_config,
+                            &style,
+                        )
+                        .process(&mut handler)?;
+                    }
+                }
+            }
+
+            let mut handler = RenderElementHandler {
                 style: &mut character_style,
+                default_style: &default_style,
+                fonts,
+                font: &mut font,
+                vertical_offset: &mut vertical_offset,
+                underline: &mut underline,
+                #[cfg(feature = "ansi")]
+                sgr: &mut sgr,
                 display,
                 pos,
                 plugin,
-            })?;
-
-            (end_type, elements.cursor.pos())
+                words: &mut words,
+                ranges,
+                outline: style.outline_color.map(Into::into),
+                advance: style.advance,
+            };
+
+            match recorded {
+                Some(measured) => {
+                    let stretched = measured.elements.replay(&mut handler, space_config)?;
+
+                    (
+                        measured.measurement.line_end_type,
+                        measured.end + (pos - self.cursor.pos()) + Point::new(stretched, 0),
+                    )
+                }
+                None => {
+                    let mut elements =
+                        LineElementParser::new(&mut parser, plugin, cursor, space_config, &style);
+                    let end_type = elements.process(&mut handler)?;
+
+                    (end_type, elements.cursor.pos())
+                }
+            }
         };
 
+        if end_type != LineEndType::LineBreak {
+            words.separator();
+        }
+
+        if end_type == LineEndType::NewLine
+            && style.style_continuity == StyleContinuity::ResetParagraphs
+        {
+            character_style = default_style.clone();
+            font = None;
+            vertical_offset = 0;
+            underline.reset();
+            #[cfg(feature = "ansi")]
+            {
+                sgr = SgrState::new();
+            }
+        }
+
         let next_state = LineRenderState {
             parser,
             character_style,
+            default_style,
+            fonts,
+            font,
+            vertical_offset,
             style,
             end_type,
             plugin,
+            words,
+            ranges,
+            underline,
+            #[cfg(feature = "ansi")]
+            sgr,
+            line_end: end_pos,
         };
 
         if next_state.end_type == LineEndType::EndOfText {
@@ -284,24 +1016,31 @@ mod test {
         plugin::{NoPlugin, PluginWrapper},
         rendering::{
             cursor::LineCursor,
-            line::{LineRenderState, StyledLineRenderer},
+            line::{LineRenderState, StyledLineRenderer, UnderlineState, WordState},
             line_iter::LineEndType,
         },
-        style::{TabSize, TextBoxStyle, TextBoxStyleBuilder},
+        style::{Advance, TabSize, TextBoxStyle, TextBoxStyleBuilder},
         utils::test::size_for,
     };
+
+    #[cfg(feature = "ansi")]
+    use crate::rendering::ansi::SgrState;
     use embedded_graphics::{
+        draw_target::DrawTarget,
         geometry::Point,
         mock_display::MockDisplay,
-        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
-        pixelcolor::{BinaryColor, Rgb888},
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Rgb888, RgbColor},
         primitives::Rectangle,
-        text::renderer::{CharacterStyle, TextRenderer},
+        text::{
+            renderer::{CharacterStyle, TextMetrics, TextRenderer},
+            Baseline,
+        },
         Drawable,
     };
 
-    fn test_rendered_text<'a, S>(
-        text: &'a str,
+    fn test_rendered_text<S>(
+        text: &str,
         bounds: Rectangle,
         character_style: S,
         style: TextBoxStyle,
@@ -320,10 +1059,20 @@ mod test {
 
         let state = LineRenderState {
             parser,
-            character_style,
+            character_style: character_style.clone(),
+            default_style: character_style.clone(),
+            fonts: &[],
+            font: None,
+            vertical_offset: 0,
             style,
             end_type: LineEndType::EndOfText,
             plugin: &plugin,
+            words: WordState::new(None),
+            ranges: None,
+            underline: UnderlineState::new(None),
+            #[cfg(feature = "ansi")]
+            sgr: SgrState::new(),
+            line_end: Point::zero(),
         };
 
         let renderer = StyledLineRenderer::new(cursor, state);
@@ -422,6 +1171,151 @@ mod test {
         );
     }
 
+    #[test]
+    fn outline_is_drawn_around_glyphs() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .outline_color(Rgb888::BLACK)
+            .build();
+
+        test_rendered_text(
+            "o",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1)),
+            character_style,
+            style,
+            &[
+                "      ", "      ", " .... ", "..##..", ".#..#.", ".#..#.", "..##..", " .... ",
+            ],
+        );
+    }
+
+    #[test]
+    fn shadow_is_drawn_beneath_text() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .shadow(Point::new(1, 1), Rgb888::BLACK)
+            .build();
+
+        test_rendered_text(
+            "ab",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            character_style,
+            style,
+            &[
+                "            ",
+                "       #    ",
+                "       #.   ",
+                "  ###  ###  ",
+                " # .#. #..# ",
+                " #. #. #. #.",
+                "  ###. ### .",
+                "   ...  ... ",
+            ],
+        );
+    }
+
+    /// Character style that measures text 1 pixel wider per character than it draws it.
+    #[derive(Clone)]
+    struct LooseMeasure(MonoTextStyle<'static, BinaryColor>);
+
+    impl CharacterStyle for LooseMeasure {
+        type Color = BinaryColor;
+    }
+
+    impl TextRenderer for LooseMeasure {
+        type Color = BinaryColor;
+
+        fn draw_string<D>(
+            &self,
+            text: &str,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.0.draw_string(text, position, baseline, target)
+        }
+
+        fn draw_whitespace<D>(
+            &self,
+            width: u32,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.0.draw_whitespace(width, position, baseline, target)
+        }
+
+        fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+            let mut metrics = self.0.measure_string(text, position, baseline);
+            metrics.next_position.x += text.chars().count() as i32;
+            metrics
+        }
+
+        fn line_height(&self) -> u32 {
+            self.0.line_height()
+        }
+    }
+
+    #[test]
+    fn rendered_advance_follows_the_renderer() {
+        let character_style = LooseMeasure(MonoTextStyle::new(&FONT_6X9, BinaryColor::On));
+
+        let style = TextBoxStyleBuilder::new()
+            .advance(Advance::Rendered)
+            .build();
+
+        test_rendered_text(
+            "ab c",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1)),
+            character_style,
+            style,
+            &[
+                "                        ",
+                "       #                ",
+                "       #                ",
+                "  ###  ###           ###",
+                " #  #  #  #         #   ",
+                " #  #  #  #         #   ",
+                "  ###  ###           ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn measured_advance_ignores_the_renderer() {
+        let character_style = LooseMeasure(MonoTextStyle::new(&FONT_6X9, BinaryColor::On));
+
+        test_rendered_text(
+            "ab c",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1)),
+            character_style,
+            TextBoxStyle::default(),
+            &[
+                "                          ",
+                "       #                  ",
+                "       #                  ",
+                "  ###  ###             ###",
+                " #  #  #  #           #   ",
+                " #  #  #  #           #   ",
+                "  ###  ###             ###",
+            ],
+        );
+    }
+
     #[test]
     fn newline_stops_render() {
         let character_style = MonoTextStyleBuilder::new()
@@ -458,14 +1352,16 @@ mod ansi_parser_tests {
         parser::Parser,
         plugin::{NoPlugin, PluginWrapper},
         rendering::{
+            ansi::SgrState,
             cursor::LineCursor,
-            line::{LineRenderState, StyledLineRenderer},
+            line::{LineRenderState, StyledLineRenderer, UnderlineState, WordState},
             line_iter::LineEndType,
         },
         style::{TabSize, TextBoxStyleBuilder},
         utils::test::size_for,
     };
     use embedded_graphics::{
+        geometry::Point,
         mock_display::MockDisplay,
         mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
         pixelcolor::BinaryColor,
@@ -496,9 +1392,19 @@ mod ansi_parser_tests {
         let state = LineRenderState {
             parser,
             character_style,
+            default_style: character_style,
+            fonts: &[],
+            font: None,
+            vertical_offset: 0,
             style,
             end_type: LineEndType::EndOfText,
             plugin: &plugin,
+            words: WordState::new(None),
+            ranges: None,
+            underline: UnderlineState::new(None),
+            #[cfg(feature = "ansi")]
+            sgr: SgrState::new(),
+            line_end: Point::zero(),
         };
         StyledLineRenderer::new(cursor, state)
             .draw(&mut display)
@@ -0,0 +1,96 @@
+//! Rendering of lines into a pixel buffer.
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// Collects the pixels of a line, and draws them with a single call when the line is done.
+///
+/// If no buffer is used, pixels are passed to the target unchanged.
+pub(crate) struct BufferedLine<'d, 'b, D>
+where
+    D: DrawTarget,
+{
+    target: &'d mut D,
+    buffer: Option<(&'b mut [D::Color], Rectangle)>,
+}
+
+impl<'d, 'b, D> BufferedLine<'d, 'b, D>
+where
+    D: DrawTarget,
+{
+    /// Collects the pixels of `area` in `buffer`, which is filled with `background` first.
+    ///
+    /// Pixels are drawn directly into `target` if `background` is `None`, or if `buffer` is too
+    /// small to hold the area.
+    pub fn new(
+        target: &'d mut D,
+        buffer: &'b mut [D::Color],
+        area: Rectangle,
+        background: Option<D::Color>,
+    ) -> Self {
+        let len = (area.size.width as usize).saturating_mul(area.size.height as usize);
+
+        let buffer = match background {
+            Some(background) if len > 0 && buffer.len() >= len => {
+                let pixels = &mut buffer[..len];
+                for pixel in pixels.iter_mut() {
+                    *pixel = background;
+                }
+
+                Some((pixels, area))
+            }
+            _ => None,
+        };
+
+        Self { target, buffer }
+    }
+
+    /// Draws the collected pixels into the target.
+    pub fn flush(self) -> Result<(), D::Error> {
+        match self.buffer {
+            Some((pixels, area)) => self.target.fill_contiguous(&area, pixels.iter().copied()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'d, 'b, D> Dimensions for BufferedLine<'d, 'b, D>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<'d, 'b, D> DrawTarget for BufferedLine<'d, 'b, D>
+where
+    D: DrawTarget,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (buffer, area) = match self.buffer.as_mut() {
+            Some((buffer, area)) => (buffer, *area),
+            None => return self.target.draw_iter(pixels),
+        };
+
+        let width = area.size.width as usize;
+        for Pixel(point, color) in pixels {
+            if area.contains(point) {
+                let Point { x, y } = point - area.top_left;
+                buffer[y.saturating_as::<usize>() * width + x.saturating_as::<usize>()] = color;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -5,19 +5,27 @@
 //! handling tab characters, soft wrapping characters, non-breaking spaces, etc.
 use crate::{
     alignment::HorizontalAlignment,
+    grapheme::{grapheme_count, graphemes},
     parser::{ChangeTextStyle, Parser, Token, SPEC_CHAR_NBSP},
-    plugin::{PluginMarker as Plugin, PluginWrapper},
+    plugin::{PluginMarker as Plugin, PluginWrapper, WrapReason},
     rendering::{cursor::LineCursor, space_config::SpaceConfig},
+    style::{LayoutVersion, ParagraphSpaces, TextBoxStyle, Wrap, WrapMarker},
 };
 use az::{SaturatingAs, SaturatingCast};
 use embedded_graphics::{pixelcolor::Rgb888, prelude::PixelColor};
 
 #[cfg(feature = "ansi")]
-use super::ansi::try_parse_sgr;
+use super::{
+    ansi::{parse_sgr, Sgr},
+    cursor::CursorMove,
+};
 #[cfg(feature = "ansi")]
 use ansi_parser::AnsiSequence;
-#[cfg(feature = "ansi")]
-use as_slice::AsSlice;
+
+/// Returns the number of characters in `s`, counting grapheme clusters as single characters.
+fn char_count(s: &str) -> u32 {
+    grapheme_count(s)
+}
 
 /// Parser to break down a line into primitive elements used by measurement and rendering.
 #[derive(Debug)]
@@ -33,8 +41,12 @@ where
     parser: &'b mut Parser<'a, C>,
 
     spaces: SpaceConfig,
-    alignment: HorizontalAlignment,
+    style: TextBoxStyle,
     empty: bool,
+
+    /// Whether the line is the first line of a paragraph.
+    paragraph_start: bool,
+    after_space: bool,
     plugin: &'b PluginWrapper<'a, M, C>,
 }
 
@@ -44,22 +56,52 @@ pub enum LineEndType {
     CarriageReturn,
     EndOfText,
     LineBreak,
+
+    /// An escape sequence moved the cursor to a different line.
+    #[cfg(feature = "ansi")]
+    MoveCursor(CursorMove),
+
+    /// An escape sequence erased the display. The next line continues at the given horizontal
+    /// position.
+    #[cfg(feature = "ansi")]
+    EraseDisplay(u32),
 }
 
-pub trait ElementHandler {
+/// Receives the elements of a line. `'a` is the lifetime of the text.
+pub trait ElementHandler<'a> {
     type Error;
-    type Color: PixelColor;
+    type Color: PixelColor + From<Rgb888>;
 
     /// Returns the width of the given string in pixels.
     fn measure(&self, st: &str) -> u32;
 
     /// A whitespace block with the given width.
-    fn whitespace(&mut self, _st: &str, _space_count: u32, _width: u32) -> Result<(), Self::Error> {
+    fn whitespace(
+        &mut self,
+        _st: &'a str,
+        _space_count: u32,
+        _width: u32,
+    ) -> Result<(), Self::Error> {
         Ok(())
     }
 
+    /// A whitespace block whose width was taken from the space configuration of the line.
+    ///
+    /// `consumed` spaces were taken from the configuration. `full` is set if `width` is the width
+    /// of every consumed space, and it's not shortened or skipped at the start or end of the line.
+    fn configured_whitespace(
+        &mut self,
+        st: &'a str,
+        space_count: u32,
+        width: u32,
+        _consumed: u32,
+        _full: bool,
+    ) -> Result<(), Self::Error> {
+        self.whitespace(st, space_count, width)
+    }
+
     /// A string of printable characters.
-    fn printed_characters(&mut self, _st: &str, _width: u32) -> Result<(), Self::Error> {
+    fn printed_characters(&mut self, _st: &'a str, _width: u32) -> Result<(), Self::Error> {
         Ok(())
     }
 
@@ -68,6 +110,16 @@ pub trait ElementHandler {
         Ok(())
     }
 
+    /// A custom token with the given width.
+    fn custom(&mut self, _id: u32, _width: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// A word that doesn't fit into an empty line is broken into multiple lines.
+    fn word_broken(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Text style change
     fn change_text_style(
         &mut self,
@@ -75,6 +127,212 @@ pub trait ElementHandler {
     ) -> Result<(), Self::Error> {
         Ok(())
     }
+
+    /// An SGR escape sequence.
+    #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        match sgr.text_style_change() {
+            Some(change) => self.change_text_style(change),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The number of elements of a line that can be recorded.
+const RECORDED_ELEMENTS: usize = 32;
+
+/// An element of a line, as it was passed to an [`ElementHandler`].
+#[derive(Clone, Copy, Debug)]
+enum LineElement<'a, C>
+where
+    C: PixelColor,
+{
+    Whitespace {
+        st: &'a str,
+        space_count: u32,
+        width: u32,
+        consumed: u32,
+        full: bool,
+    },
+    PrintedCharacters(&'a str, u32),
+    MoveCursor(i32),
+    Custom(u32, u32),
+    WordBroken,
+    ChangeTextStyle(ChangeTextStyle<C>),
+    #[cfg(feature = "ansi")]
+    Sgr(Sgr),
+}
+
+/// The elements of a line, recorded while the line is measured.
+///
+/// Measured lines are drawn by replaying the recorded elements, instead of processing the text of
+/// the line a second time.
+#[derive(Debug)]
+pub(crate) struct LineElements<'a, C>
+where
+    C: PixelColor,
+{
+    elements: [Option<LineElement<'a, C>>; RECORDED_ELEMENTS],
+    len: usize,
+
+    /// Set if the line has more elements than what can be recorded.
+    overflowed: bool,
+}
+
+impl<'a, C> LineElements<'a, C>
+where
+    C: PixelColor + From<Rgb888>,
+{
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Self {
+            elements: [None; RECORDED_ELEMENTS],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Returns whether every element of the line was recorded.
+    pub fn is_complete(&self) -> bool {
+        !self.overflowed
+    }
+
+    fn push(&mut self, element: LineElement<'a, C>) {
+        match self.elements.get_mut(self.len) {
+            Some(slot) => {
+                *slot = Some(element);
+                self.len += 1;
+            }
+            None => self.overflowed = true,
+        }
+    }
+
+    /// Passes the recorded elements to `handler`.
+    ///
+    /// If `spaces` is stretched, the whitespace that was taken from the space configuration of
+    /// the line is resized to the stretched width. Returns how much wider the line became.
+    pub fn replay<E: ElementHandler<'a, Color = C>>(
+        &self,
+        handler: &mut E,
+        mut spaces: SpaceConfig,
+    ) -> Result<i32, E::Error> {
+        let mut stretched = 0;
+        for element in self.elements[..self.len].iter().flatten() {
+            match *element {
+                LineElement::Whitespace {
+                    st,
+                    space_count,
+                    width,
+                    consumed,
+                    full,
+                } => {
+                    let width = if spaces.is_stretched() && consumed > 0 {
+                        let stretched_width = spaces.consume(consumed);
+                        if full {
+                            stretched += stretched_width.saturating_as::<i32>()
+                                - width.saturating_as::<i32>();
+                            stretched_width
+                        } else {
+                            width
+                        }
+                    } else {
+                        width
+                    };
+                    handler.whitespace(st, space_count, width)?;
+                }
+                LineElement::PrintedCharacters(st, width) => {
+                    handler.printed_characters(st, width)?
+                }
+                LineElement::MoveCursor(by) => handler.move_cursor(by)?,
+                LineElement::Custom(id, width) => handler.custom(id, width)?,
+                LineElement::WordBroken => handler.word_broken()?,
+                LineElement::ChangeTextStyle(change) => handler.change_text_style(change)?,
+                #[cfg(feature = "ansi")]
+                LineElement::Sgr(sgr) => handler.sgr(sgr)?,
+            }
+        }
+
+        Ok(stretched)
+    }
+}
+
+/// Passes the elements of a line to `handler`, and records them.
+pub(crate) struct ElementRecorder<'r, 'a, H>
+where
+    H: ElementHandler<'a>,
+{
+    pub handler: &'r mut H,
+    pub elements: &'r mut LineElements<'a, H::Color>,
+}
+
+impl<'r, 'a, H> ElementHandler<'a> for ElementRecorder<'r, 'a, H>
+where
+    H: ElementHandler<'a>,
+{
+    type Error = H::Error;
+    type Color = H::Color;
+
+    fn measure(&self, st: &str) -> u32 {
+        self.handler.measure(st)
+    }
+
+    fn whitespace(&mut self, st: &'a str, space_count: u32, width: u32) -> Result<(), Self::Error> {
+        self.configured_whitespace(st, space_count, width, 0, false)
+    }
+
+    fn configured_whitespace(
+        &mut self,
+        st: &'a str,
+        space_count: u32,
+        width: u32,
+        consumed: u32,
+        full: bool,
+    ) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::Whitespace {
+            st,
+            space_count,
+            width,
+            consumed,
+            full,
+        });
+        self.handler
+            .configured_whitespace(st, space_count, width, consumed, full)
+    }
+
+    fn printed_characters(&mut self, st: &'a str, width: u32) -> Result<(), Self::Error> {
+        self.elements
+            .push(LineElement::PrintedCharacters(st, width));
+        self.handler.printed_characters(st, width)
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::MoveCursor(by));
+        self.handler.move_cursor(by)
+    }
+
+    fn custom(&mut self, id: u32, width: u32) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::Custom(id, width));
+        self.handler.custom(id, width)
+    }
+
+    fn word_broken(&mut self) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::WordBroken);
+        self.handler.word_broken()
+    }
+
+    fn change_text_style(
+        &mut self,
+        change: ChangeTextStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::ChangeTextStyle(change));
+        self.handler.change_text_style(change)
+    }
+
+    #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        self.elements.push(LineElement::Sgr(sgr));
+        self.handler.sgr(sgr)
+    }
 }
 
 impl<'a, 'b, M, C> LineElementParser<'a, 'b, M, C>
@@ -89,21 +347,29 @@ where
         plugin: &'b PluginWrapper<'a, M, C>,
         cursor: LineCursor,
         spaces: SpaceConfig,
-        alignment: HorizontalAlignment,
+        style: &TextBoxStyle,
     ) -> Self {
+        let mut cursor = cursor;
+        cursor.limit_chars(style.max_chars_per_line);
+
         Self {
             parser,
             spaces,
             cursor,
-            alignment,
+            style: *style,
             empty: true,
+            paragraph_start: plugin.at_source_line_start(),
+            after_space: false,
             plugin,
         }
     }
 
-    fn next_word_width<E: ElementHandler>(&mut self, handler: &E) -> Option<u32> {
-        let mut width = None;
-
+    /// Returns whether the line can be continued with the word that follows a soft hyphen.
+    ///
+    /// Every break opportunity in the word is evaluated, so the word fits if any of its hyphenated
+    /// parts fits into the line, or if the whole word fits. Returns `None` if the next token is
+    /// not a word.
+    fn next_fragment_fits<E: ElementHandler<'a>>(&self, handler: &E) -> Option<bool> {
         // This looks extremely inefficient.
         let lookahead = self.plugin.clone();
         let mut lookahead_parser = self.parser.clone();
@@ -111,45 +377,265 @@ where
         // We don't want to count the current token.
         lookahead.consume_peeked_token(&mut lookahead_parser);
 
-        'lookahead: loop {
-            match lookahead.peek_token(&mut lookahead_parser) {
+        self.fragment_fits(handler, &self.cursor, &lookahead, &mut lookahead_parser)
+    }
+
+    /// Returns whether the word that starts at the next token of `lookahead_parser` can be placed
+    /// at `cursor`. See [`next_fragment_fits`](Self::next_fragment_fits).
+    fn fragment_fits<E: ElementHandler<'a>>(
+        &self,
+        handler: &E,
+        cursor: &LineCursor,
+        lookahead: &PluginWrapper<'a, M, C>,
+        lookahead_parser: &mut Parser<'a, C>,
+    ) -> Option<bool> {
+        let mut measured = None;
+
+        let fits = |width: u32, chars: u32| cursor.fits_in_line(width) && cursor.fits_chars(chars);
+
+        loop {
+            match lookahead.peek_token(lookahead_parser) {
                 Some(Token::Word(w)) => {
-                    *width.get_or_insert(0) += handler.measure(w);
+                    let (width, chars) = measured.get_or_insert((0, 0));
+                    *width += handler.measure(w);
+                    *chars += char_count(w);
+
+                    if !fits(*width, *chars) {
+                        // Later parts of the word only make the line longer.
+                        return Some(false);
+                    }
                 }
 
-                Some(Token::Break(w, _original)) => {
-                    *width.get_or_insert(0) += handler.measure(w);
+                Some(Token::Break(_, _original)) => {
+                    if let Some((width, chars)) = measured {
+                        let hyphen = self.style.hyphen_string;
+                        if fits(width + handler.measure(hyphen), chars + char_count(hyphen)) {
+                            return Some(true);
+                        }
+                    }
+                }
+
+                #[cfg(feature = "ansi")]
+                Some(Token::EscapeSequence(_))
+                | Some(Token::LinkStart(_))
+                | Some(Token::LinkEnd) => {}
+
+                _ => return measured.map(|(width, chars)| fits(width, chars)),
+            }
+            lookahead.consume_peeked_token(lookahead_parser);
+        }
+    }
+
+    /// Returns whether the word, which is the current token, fits into the line.
+    ///
+    /// If the word is followed by a soft hyphen, and the rest of the word wraps into the next
+    /// line, the word only fits if the hyphen also fits after it.
+    fn word_fits<E: ElementHandler<'a>>(&self, handler: &E, w: &str, width: u32) -> bool {
+        let mut cursor = self.cursor.clone();
+        if !cursor.advance(width, char_count(w)) {
+            return false;
+        }
+
+        // The word can't be moved to the next line, the hyphen is left out if it doesn't fit.
+        if self.empty || self.style.layout_version != LayoutVersion::Latest {
+            return true;
+        }
+
+        let lookahead = self.plugin.clone();
+        let mut lookahead_parser = self.parser.clone();
+
+        // We don't want to count the current token.
+        lookahead.consume_peeked_token(&mut lookahead_parser);
+        if !matches!(
+            lookahead.peek_token(&mut lookahead_parser),
+            Some(Token::Break(_, _))
+        ) {
+            return true;
+        }
+        lookahead.consume_peeked_token(&mut lookahead_parser);
+
+        match self.fragment_fits(handler, &cursor, &lookahead, &mut lookahead_parser) {
+            Some(false) => {
+                let hyphen = self.style.hyphen_string;
+                cursor.advance(handler.measure(hyphen), char_count(hyphen))
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns whether only whitespace follows the current token in the paragraph.
+    fn ends_paragraph(&self) -> bool {
+        let lookahead = self.plugin.clone();
+        let mut lookahead_parser = self.parser.clone();
 
-                    break 'lookahead;
+        // We don't want to check the current token.
+        lookahead.consume_peeked_token(&mut lookahead_parser);
+
+        loop {
+            match lookahead.peek_token(&mut lookahead_parser) {
+                Some(Token::NewLine) | None => return true,
+                Some(Token::Whitespace(_, _)) | Some(Token::Tab) => {}
+                _ => return false,
+            }
+            lookahead.consume_peeked_token(&mut lookahead_parser);
+        }
+    }
+
+    /// Returns whether the rest of the line fits without wrapping.
+    fn rest_of_line_fits<E: ElementHandler<'a>>(&self, handler: &E) -> bool {
+        let mut cursor = self.cursor.clone();
+        let mut spaces = self.spaces;
+
+        // Whitespace that doesn't fit is only a problem if it's followed by a word.
+        let mut overflowed = false;
+
+        let lookahead = self.plugin.clone();
+        let mut lookahead_parser = self.parser.clone();
+
+        loop {
+            let (width, chars) = match lookahead.peek_token(&mut lookahead_parser) {
+                Some(Token::Word(w)) => {
+                    if overflowed || !cursor.advance(handler.measure(w), char_count(w)) {
+                        return false;
+                    }
+                    (0, 0)
+                }
+                Some(Token::Custom(id)) => {
+                    if overflowed || !cursor.advance(lookahead.custom_width(id), 1) {
+                        return false;
+                    }
+                    (0, 0)
                 }
+                Some(Token::Whitespace(n, _))
+                    if n > 0 && self.style.white_space.collapses_spaces() =>
+                {
+                    (spaces.consume(1), 1)
+                }
+                Some(Token::Whitespace(n, _)) => (spaces.consume(n), n),
+                Some(Token::Tab) => (cursor.next_tab_width(), 1),
+                Some(Token::NewLine) if !self.style.white_space.collapses_breaks() => return true,
+                Some(Token::CarriageReturn) | None => return true,
 
                 #[cfg(feature = "ansi")]
-                Some(Token::EscapeSequence(_)) => {}
+                Some(Token::EscapeSequence(seq))
+                    if self.escape_line_end(&seq, handler).is_some() =>
+                {
+                    return true
+                }
 
-                _ => break 'lookahead,
+                _ => (0, 0),
+            };
+
+            if !cursor.advance(width, chars) {
+                overflowed = true;
             }
+
             lookahead.consume_peeked_token(&mut lookahead_parser);
         }
+    }
+
+    /// Draws the part of the word that fits into the line, and skips the rest of the line.
+    fn overflow<E: ElementHandler<'a, Color = C>>(
+        &mut self,
+        handler: &mut E,
+        w: &'a str,
+    ) -> Result<LineEndType, E::Error> {
+        let (word, _) = self.longest_fitting_substr(handler, w);
+        if !word.is_empty() {
+            self.empty = false;
+            if let Some(Token::Word(word)) = self.plugin.render_token(Token::Word(word)) {
+                self.process_word(handler, word)?;
+            }
+        }
 
-        width
+        if let Wrap::Ellipsis(ellipsis) = self.style.wrap {
+            handler.printed_characters(ellipsis, handler.measure(ellipsis))?;
+        }
+        self.consume_token();
+
+        while let Some(token) = self.peek_next_token() {
+            self.consume_token();
+            match token {
+                Token::NewLine if !self.style.white_space.collapses_breaks() => {
+                    handler.whitespace("\n", 0, 0)?;
+                    return Ok(LineEndType::NewLine);
+                }
+
+                Token::CarriageReturn => {
+                    handler.whitespace("\r", 0, 0)?;
+                    return Ok(LineEndType::CarriageReturn);
+                }
+
+                // Text style changes apply to the following lines.
+                Token::ChangeTextStyle(change) => handler.change_text_style(change)?,
+
+                #[cfg(feature = "ansi")]
+                Token::EscapeSequence(AnsiSequence::SetGraphicsMode(vec)) => {
+                    for sgr in parse_sgr(&vec) {
+                        handler.sgr(sgr)?;
+                    }
+                }
+
+                #[cfg(feature = "ansi")]
+                Token::EscapeSequence(seq) => {
+                    if let Some(end_type) = self.escape_line_end(&seq, handler) {
+                        return Ok(end_type);
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        Ok(LineEndType::EndOfText)
     }
 
     fn move_cursor(&mut self, by: i32) -> Result<i32, i32> {
         self.cursor.move_cursor(by)
     }
 
-    fn longest_fitting_substr<E: ElementHandler>(
+    /// Returns how the current line ends, if the escape sequence ends it.
+    #[cfg(feature = "ansi")]
+    fn escape_line_end<E: ElementHandler<'a>>(
+        &self,
+        seq: &AnsiSequence,
+        handler: &E,
+    ) -> Option<LineEndType> {
+        let x = self.cursor.offset();
+        let movement = match *seq {
+            AnsiSequence::CursorUp(n) => CursorMove::Relative {
+                lines: -n.saturating_as::<i32>(),
+                x,
+            },
+            AnsiSequence::CursorDown(n) => CursorMove::Relative {
+                lines: n.saturating_as(),
+                x,
+            },
+            // Rows and columns are numbered from 1.
+            AnsiSequence::CursorPos(row, column) => CursorMove::Absolute {
+                line: row.saturating_sub(1),
+                x: column.saturating_sub(1) * handler.measure(" "),
+            },
+            AnsiSequence::EraseDisplay => return Some(LineEndType::EraseDisplay(x)),
+            _ => return None,
+        };
+
+        Some(LineEndType::MoveCursor(movement))
+    }
+
+    /// Splits the word at the last grapheme cluster boundary that fits into the line.
+    #[allow(unsafe_code)]
+    fn longest_fitting_substr<E: ElementHandler<'a>>(
         &mut self,
         handler: &E,
         w: &'a str,
     ) -> (&'a str, Option<&'a str>) {
         let mut width = 0;
-        for (idx, c) in w.char_indices() {
-            let char_width = handler.measure(unsafe {
-                // SAFETY: we are working on character boundaries
-                w.get_unchecked(idx..idx + c.len_utf8())
-            });
-            if !self.cursor.fits_in_line(width + char_width) {
+        for (chars, (idx, grapheme)) in graphemes(w).enumerate() {
+            let char_width = handler.measure(grapheme);
+            if !self.cursor.fits_in_line(width + char_width)
+                || !self.cursor.fits_chars(chars.saturating_as::<u32>() + 1)
+            {
                 return (
                     unsafe {
                         // SAFETY: we are working on character boundaries
@@ -164,7 +650,56 @@ where
         (w, None)
     }
 
-    fn next_word_fits<E: ElementHandler>(&self, space_width: i32, handler: &mut E) -> bool {
+    /// Returns the part of the word that fits into the line together with a hyphen, if the plugins
+    /// allow hyphenating the word.
+    fn hyphenation_point<E: ElementHandler<'a>>(
+        &self,
+        handler: &E,
+        cursor: &LineCursor,
+        w: &'a str,
+    ) -> Option<&'a str> {
+        let hyphen = self.style.hyphen_string;
+        let available = cursor.space().checked_sub(handler.measure(hyphen))?;
+        let available_chars = cursor.chars_left().checked_sub(char_count(hyphen))?;
+
+        let mut width = 0;
+        let mut max = w.len();
+        for (chars, (idx, grapheme)) in graphemes(w).enumerate() {
+            width += handler.measure(grapheme);
+            if width > available || chars.saturating_as::<u32>() >= available_chars {
+                max = idx;
+                break;
+            }
+        }
+
+        match self.plugin.hyphenate(w, max) {
+            Some(pos) if pos > 0 && pos <= max && w.is_char_boundary(pos) => Some(&w[..pos]),
+            _ => None,
+        }
+    }
+
+    /// Hyphenates the word if possible, and moves the cursor past the hyphen.
+    fn hyphenate<E: ElementHandler<'a>>(&mut self, handler: &E, w: &'a str) -> Option<&'a str> {
+        let word = self.hyphenation_point(handler, &self.cursor, w)?;
+
+        let hyphen = self.style.hyphen_string;
+        let width = handler.measure(word) + handler.measure(hyphen);
+        if !self
+            .cursor
+            .advance(width, char_count(word) + char_count(hyphen))
+        {
+            return None;
+        }
+
+        Some(word)
+    }
+
+    fn next_word_fits<E: ElementHandler<'a>>(
+        &self,
+        space_width: i32,
+        space_chars: u32,
+        handler: &mut E,
+    ) -> bool {
         let mut cursor = self.cursor.clone();
         let mut spaces = self.spaces;
 
@@ -178,47 +713,98 @@ where
         lookahead.consume_peeked_token(&mut lookahead_parser);
 
         let _ = cursor.move_cursor(space_width);
+        cursor.consume_chars(space_chars);
         while !exit {
-            let width = match lookahead.peek_token(&mut lookahead_parser) {
-                Some(Token::Word(w)) | Some(Token::Break(w, _)) => {
+            let (width, chars) = match lookahead.peek_token(&mut lookahead_parser) {
+                Some(Token::Word(w)) => {
                     exit = true;
-                    handler.measure(w).saturating_as()
+                    let width = handler.measure(w);
+                    let chars = char_count(w);
+                    if !(cursor.fits_in_line(width) && cursor.fits_chars(chars))
+                        && self.hyphenation_point(handler, &cursor, w).is_some()
+                    {
+                        return true;
+                    }
+                    (width.saturating_as(), chars)
                 }
 
-                Some(Token::Whitespace(n, _)) => spaces.consume(n).saturating_as(),
-                Some(Token::Tab) => cursor.next_tab_width().saturating_as(),
+                Some(Token::Custom(id)) => {
+                    exit = true;
+                    (lookahead.custom_width(id).saturating_as(), 1)
+                }
+
+                Some(Token::Break(_, _)) => {
+                    exit = true;
+                    let hyphen = self.style.hyphen_string;
+                    (handler.measure(hyphen).saturating_as(), char_count(hyphen))
+                }
+
+                // Collapsed whitespace is already counted in `space_width`.
+                Some(Token::Whitespace(n, _))
+                    if n > 0 && self.style.white_space.collapses_spaces() =>
+                {
+                    (0, 0)
+                }
+                Some(Token::NewLine) if self.style.white_space.collapses_breaks() => (0, 0),
+
+                Some(Token::Whitespace(n, _)) => (spaces.consume(n).saturating_as(), n),
+                Some(Token::Tab) => (cursor.next_tab_width().saturating_as(), 1),
 
                 #[cfg(feature = "ansi")]
-                Some(Token::EscapeSequence(AnsiSequence::CursorForward(by))) => by.saturating_as(),
+                Some(Token::EscapeSequence(AnsiSequence::CursorForward(by))) => {
+                    (by.saturating_as(), 0)
+                }
 
                 #[cfg(feature = "ansi")]
                 Some(Token::EscapeSequence(AnsiSequence::CursorBackward(by))) => {
-                    -by.saturating_as::<i32>()
+                    (-by.saturating_as::<i32>(), 0)
+                }
+
+                #[cfg(feature = "ansi")]
+                Some(Token::EscapeSequence(seq))
+                    if self.escape_line_end(&seq, handler).is_some() =>
+                {
+                    return false
                 }
 
                 #[cfg(feature = "ansi")]
-                Some(Token::EscapeSequence(_)) => 0,
+                Some(Token::EscapeSequence(_))
+                | Some(Token::LinkStart(_))
+                | Some(Token::LinkEnd) => (0, 0),
 
                 _ => return false,
             };
 
             lookahead.consume_peeked_token(&mut lookahead_parser);
-            if cursor.move_cursor(width).is_err() {
+            if !cursor.fits_chars(chars) || cursor.move_cursor(width).is_err() {
                 return false;
             }
+            cursor.consume_chars(chars);
         }
 
         true
     }
 
     fn render_trailing_spaces(&self) -> bool {
-        // TODO: make this configurable
-        false
+        self.style.paragraph_spaces == ParagraphSpaces::Preserve
+            && !self.style.white_space.collapses_spaces()
+            && self.ends_paragraph()
     }
 
     fn render_leading_spaces(&self) -> bool {
-        // TODO: make this configurable
-        match self.alignment {
+        if self.style.white_space.collapses_spaces() {
+            return false;
+        }
+
+        if self.paragraph_start {
+            match self.style.paragraph_spaces {
+                ParagraphSpaces::Auto => {}
+                ParagraphSpaces::Preserve => return true,
+                ParagraphSpaces::Trim => return false,
+            }
+        }
+
+        match self.style.alignment {
             HorizontalAlignment::Left => true,
             HorizontalAlignment::Center => false,
             HorizontalAlignment::Right => false,
@@ -226,7 +812,7 @@ where
         }
     }
 
-    fn draw_whitespace<E: ElementHandler>(
+    fn draw_whitespace<E: ElementHandler<'a>>(
         &mut self,
         handler: &mut E,
         string: &'a str,
@@ -234,26 +820,46 @@ where
         space_width: u32,
     ) -> Result<(), E::Error> {
         if self.empty && !self.render_leading_spaces() {
-            handler.whitespace(string, 0, 0)?;
+            handler.configured_whitespace(string, 0, 0, space_count, false)?;
             return Ok(());
         }
         let draw_whitespace = (self.empty && self.render_leading_spaces())
             || self.render_trailing_spaces()
-            || self.next_word_fits(space_width.saturating_as(), handler);
+            || self.next_word_fits(space_width.saturating_as(), space_count, handler);
 
-        match self.move_cursor(space_width.saturating_cast()) {
+        let moved = if self.cursor.fits_chars(space_count) {
+            self.move_cursor(space_width.saturating_cast())
+        } else {
+            Err(self.cursor.space().saturating_as())
+        };
+
+        match moved {
             Ok(moved) => {
+                self.cursor.consume_chars(space_count);
                 let spaces = if draw_whitespace { space_count } else { 0 };
-                handler.whitespace(string, spaces, moved.saturating_as())?;
+                let width = moved.saturating_as();
+                handler.configured_whitespace(
+                    string,
+                    spaces,
+                    width,
+                    space_count,
+                    width == space_width,
+                )?;
             }
 
             Err(moved) => {
-                let single = space_width / space_count;
-                let consumed = moved as u32 / single;
+                let single = (space_width / space_count).max(1);
+                let consumed = (moved as u32 / single).min(self.cursor.chars_left());
                 if consumed > 0 {
                     let (pos, _) = string.char_indices().nth(consumed as usize).unwrap();
                     let (consumed_str, _) = string.split_at(pos);
-                    handler.whitespace(consumed_str, consumed, consumed * single)?;
+                    handler.configured_whitespace(
+                        consumed_str,
+                        consumed,
+                        consumed * single,
+                        space_count,
+                        false,
+                    )?;
 
                     self.replace_peeked_token(
                         consumed as usize,
@@ -266,7 +872,23 @@ where
         Ok(())
     }
 
-    fn draw_tab<E: ElementHandler>(
+    /// Draws a whitespace block collapsed into a single space.
+    fn draw_collapsed_whitespace<E: ElementHandler<'a>>(
+        &mut self,
+        handler: &mut E,
+        string: &'a str,
+    ) -> Result<(), E::Error> {
+        if self.after_space {
+            return handler.whitespace("", 0, 0);
+        }
+        self.after_space = true;
+
+        let first_len = string.chars().next().map_or(0, char::len_utf8);
+        let space_width = self.spaces.consume(1);
+        self.draw_whitespace(handler, &string[..first_len], 1, space_width)
+    }
+
+    fn draw_tab<E: ElementHandler<'a>>(
         &mut self,
         handler: &mut E,
         space_width: u32,
@@ -277,9 +899,18 @@ where
 
         let draw_whitespace = (self.empty && self.render_leading_spaces())
             || self.render_trailing_spaces()
-            || self.next_word_fits(space_width.saturating_as(), handler);
+            || self.next_word_fits(space_width.saturating_as(), 1, handler);
+
+        let moved = if self.cursor.fits_chars(1) {
+            self.move_cursor(space_width.saturating_cast())
+        } else {
+            Err(0)
+        };
+        if moved.is_ok() {
+            self.cursor.consume_chars(1);
+        }
 
-        match self.move_cursor(space_width.saturating_cast()) {
+        match moved {
             Ok(moved) if draw_whitespace => handler.whitespace("\t", 1, moved.saturating_as())?,
 
             Ok(moved) | Err(moved) => {
@@ -289,12 +920,27 @@ where
         Ok(())
     }
 
+    /// Ends the line with a line break inserted by the layout.
+    fn wrap<E: ElementHandler<'a>>(
+        &self,
+        handler: &mut E,
+        reason: WrapReason,
+    ) -> Result<LineEndType, E::Error> {
+        if let WrapMarker::End(marker) = self.style.wrap_marker {
+            // The space of the marker is reserved when the line is started.
+            handler.printed_characters(marker, handler.measure(marker))?;
+        }
+        self.plugin.on_wrap(reason);
+
+        Ok(LineEndType::LineBreak)
+    }
+
     fn peek_next_token(&mut self) -> Option<Token<'a, C>> {
-        self.plugin.peek_token(&mut self.parser)
+        self.plugin.peek_token(self.parser)
     }
 
     fn consume_token(&mut self) {
-        self.plugin.consume_peeked_token(&mut self.parser);
+        self.plugin.consume_peeked_token(self.parser);
     }
 
     fn replace_peeked_token(&mut self, len: usize, token: Token<'a, C>) {
@@ -302,38 +948,100 @@ where
     }
 
     #[inline]
-    pub fn process<E: ElementHandler<Color = C>>(
+    pub fn process<E: ElementHandler<'a, Color = C>>(
         &mut self,
         handler: &mut E,
     ) -> Result<LineEndType, E::Error> {
+        if self.paragraph_start {
+            let indent = self
+                .style
+                .paragraph_indent
+                .first_line(self.plugin.source_line());
+            if indent > 0 {
+                if let Ok(moved) = self.move_cursor(indent.saturating_as()) {
+                    handler.move_cursor(moved)?;
+                }
+            }
+        }
+
+        if let Some(prefix) = self.plugin.wrapped_line_indent() {
+            let indent: u32 = prefix
+                .iter()
+                .map(|token| match token {
+                    Token::Word(s) | Token::Whitespace(_, s) => handler.measure(s),
+                    Token::Custom(id) => self.plugin.custom_width(*id),
+                    _ => 0,
+                })
+                .sum();
+
+            if let Ok(moved) = self.move_cursor(indent.saturating_as()) {
+                handler.move_cursor(moved)?;
+            }
+        }
+
+        let wrapped = self.plugin.take_wrapped();
+        let reserved = match (self.style.wrap, self.style.wrap_marker) {
+            (Wrap::Ellipsis(ellipsis), _) => Some(ellipsis),
+            (Wrap::Word, WrapMarker::End(marker)) => Some(marker),
+            (Wrap::Word, WrapMarker::Start(marker)) if wrapped => {
+                let width = handler.measure(marker);
+                if self.cursor.advance(width, char_count(marker)) {
+                    handler.printed_characters(marker, width)?;
+                }
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(reserved) = reserved {
+            if !self.rest_of_line_fits(handler) {
+                self.cursor.reserve(handler.measure(reserved));
+                self.cursor.consume_chars(char_count(reserved));
+            }
+        }
+
         while let Some(token) = self.peek_next_token() {
             match token {
+                Token::Whitespace(n, seq) if n > 0 && self.style.white_space.collapses_spaces() => {
+                    self.draw_collapsed_whitespace(handler, seq)?;
+                }
+
                 Token::Whitespace(n, seq) => {
                     let space_width = self.spaces.consume(n);
                     self.draw_whitespace(handler, seq, n, space_width)?;
                 }
 
                 Token::Tab => {
+                    self.after_space = false;
                     let space_width = self.cursor.next_tab_width();
                     self.draw_tab(handler, space_width)?;
                 }
 
-                Token::Break(c, _original) => {
-                    if let Some(word_width) = self.next_word_width(handler) {
-                        if !self.cursor.fits_in_line(word_width) || self.empty {
+                // Soft hyphens are only displayed when a word is wrapped.
+                Token::Break(_, _) if self.style.wrap != Wrap::Word => {}
+
+                Token::Break(_, _original) => {
+                    self.after_space = false;
+                    if let Some(fits) = self.next_fragment_fits(handler) {
+                        if !fits || self.empty {
                             // this line is done, decide how to end
 
                             // If the next Word token does not fit the line, display break character
-                            let width = handler.measure(c);
-                            if self.move_cursor(width.saturating_as()).is_ok() {
-                                if let Some(Token::Break(c, _)) = self.plugin.render_token(token) {
-                                    handler.printed_characters(c, width)?;
+                            let hyphen = self.style.hyphen_string;
+                            let width = handler.measure(hyphen);
+                            if self.cursor.advance(width, char_count(hyphen)) {
+                                if let Some(Token::Break(_, _)) = self.plugin.render_token(token) {
+                                    handler.printed_characters(hyphen, width)?;
                                 }
                                 self.consume_token();
+                            } else if self.style.layout_version == LayoutVersion::Latest {
+                                // The hyphen doesn't fit, but it must not be carried over to
+                                // the start of the next line.
+                                self.consume_token();
                             }
 
                             if !self.empty {
-                                return Ok(LineEndType::LineBreak);
+                                return self.wrap(handler, WrapReason::Hyphenation);
                             }
                         }
                     } else {
@@ -341,27 +1049,78 @@ where
                     }
                 }
 
+                Token::Custom(id) => {
+                    self.after_space = false;
+                    let width = self.plugin.custom_width(id);
+                    if !self.cursor.advance(width, 1) {
+                        if self.style.wrap != Wrap::Word {
+                            return self.overflow(handler, "");
+                        } else if !self.empty {
+                            return self.wrap(handler, WrapReason::Word);
+                        }
+
+                        // This token does not fit into an empty line. Let it overflow the line.
+                        let space = self.cursor.space();
+                        self.move_cursor(space.saturating_as()).ok();
+                        self.cursor.consume_chars(1);
+                    }
+
+                    self.empty = false;
+                    if let Some(Token::Custom(id)) = self.plugin.render_token(token) {
+                        handler.custom(id, width)?;
+                    }
+                }
+
                 Token::Word(w) => {
+                    self.after_space = false;
                     let width = handler.measure(w);
-                    let (word, remainder) = if self.move_cursor(width.saturating_as()).is_ok() {
+                    let (word, remainder) = if self.word_fits(handler, w, width) {
+                        self.cursor.advance(width, char_count(w));
                         // We can move the cursor here since `process_word()`
                         // doesn't depend on it.
                         (w, None)
+                    } else if self.style.wrap != Wrap::Word {
+                        return self.overflow(handler, w);
+                    } else if let Some(word) = self.hyphenate(handler, w) {
+                        self.empty = false;
+
+                        if let Some(Token::Word(word)) = self.plugin.render_token(Token::Word(word))
+                        {
+                            self.process_word(handler, word)?;
+                        }
+                        let hyphen = self.style.hyphen_string;
+                        handler.printed_characters(hyphen, handler.measure(hyphen))?;
+
+                        // Consume what was printed.
+                        self.replace_peeked_token(word.len(), Token::Word(word));
+                        self.consume_token();
+                        return self.wrap(handler, WrapReason::Hyphenation);
                     } else if self.empty {
                         // This word does not fit into an empty line. Find longest part
                         // that fits and push the rest to the next line.
                         match self.longest_fitting_substr(handler, w) {
+                            (part, _) if !self.style.word_break.can_break(part) => {
+                                // The word must not be broken here. Let it overflow the line.
+                                let space = self.cursor.space();
+                                self.move_cursor(space.saturating_as()).ok();
+                                self.cursor.consume_chars(char_count(w));
+                                (w, None)
+                            }
                             ("", _) => {
                                 // Weird case where width doesn't permit drawing anything.
                                 // End here to prevent infinite looping.
+                                handler.word_broken()?;
                                 self.consume_token();
-                                return Ok(LineEndType::LineBreak);
+                                return self.wrap(handler, WrapReason::BrokenWord);
+                            }
+                            other => {
+                                handler.word_broken()?;
+                                other
                             }
-                            other => other,
                         }
                     } else {
                         // word wrapping - push this word to the next line
-                        return Ok(LineEndType::LineBreak);
+                        return self.wrap(handler, WrapReason::Word);
                     };
 
                     self.empty = false;
@@ -374,7 +1133,7 @@ where
                         // Consume what was printed.
                         self.replace_peeked_token(word.len(), Token::Word(word));
                         self.consume_token();
-                        return Ok(LineEndType::LineBreak);
+                        return self.wrap(handler, WrapReason::BrokenWord);
                     }
                 }
 
@@ -382,8 +1141,8 @@ where
                 Token::EscapeSequence(seq) => {
                     match seq {
                         AnsiSequence::SetGraphicsMode(vec) => {
-                            if let Some(sgr) = try_parse_sgr(vec.as_slice()) {
-                                handler.change_text_style(sgr.into())?;
+                            for sgr in parse_sgr(&vec) {
+                                handler.sgr(sgr)?;
                             }
                         }
 
@@ -417,13 +1176,42 @@ where
                             }
                         }
 
+                        AnsiSequence::EraseLine => {
+                            // Fill the rest of the line with the background color, without
+                            // moving the cursor.
+                            let width = self.cursor.space();
+                            handler.whitespace("", 1, width)?;
+                            handler.move_cursor(-width.saturating_as::<i32>())?;
+                        }
+
+                        AnsiSequence::CursorUp(_)
+                        | AnsiSequence::CursorDown(_)
+                        | AnsiSequence::CursorPos(_, _)
+                        | AnsiSequence::EraseDisplay => {
+                            if let Some(end_type) = self.escape_line_end(&seq, handler) {
+                                self.consume_token();
+                                return Ok(end_type);
+                            }
+                        }
+
                         _ => {
                             // ignore for now
                         }
                     }
                 }
 
-                Token::ChangeTextStyle(change) => handler.change_text_style(change)?,
+                // Links are only marked for plugins, the text of the link is drawn normally.
+                #[cfg(feature = "ansi")]
+                Token::LinkStart(_) | Token::LinkEnd => {}
+
+                Token::ChangeTextStyle(change) => {
+                    handler.change_text_style(change)?;
+
+                    // The font may have changed, spaces should follow it unless they are stretched.
+                    if !self.spaces.is_stretched() {
+                        self.spaces.set_width(handler.measure(" "));
+                    }
+                }
 
                 Token::CarriageReturn => {
                     handler.whitespace("\r", 0, 0)?;
@@ -431,6 +1219,10 @@ where
                     return Ok(LineEndType::CarriageReturn);
                 }
 
+                Token::NewLine if self.style.white_space.collapses_breaks() => {
+                    self.draw_collapsed_whitespace(handler, " ")?;
+                }
+
                 Token::NewLine => {
                     handler.whitespace("\n", 0, 0)?;
                     self.consume_token();
@@ -443,10 +1235,11 @@ where
         Ok(LineEndType::EndOfText)
     }
 
-    fn process_word<E: ElementHandler>(
+    #[allow(unsafe_code)]
+    fn process_word<E: ElementHandler<'a>>(
         &mut self,
         handler: &mut E,
-        w: &str,
+        w: &'a str,
     ) -> Result<(), E::Error> {
         match w.char_indices().find(|(_, c)| *c == SPEC_CHAR_NBSP) {
             Some((space_pos, _)) => {
@@ -459,7 +1252,7 @@ where
                     handler.printed_characters(word, handler.measure(word))?;
                 }
 
-                handler.whitespace("\u{a0}", 1, self.spaces.consume(1))?;
+                handler.configured_whitespace("\u{a0}", 1, self.spaces.consume(1), 1, true)?;
 
                 // If we have anything after the space...
                 if let Some(word) = w.get(space_pos + SPEC_CHAR_NBSP.len_utf8()..) {
@@ -501,6 +1294,7 @@ mod test {
         String(String, u32),
         MoveCursor(i32),
         ChangeTextStyle(ChangeTextStyle<C>),
+        Custom(u32, u32),
     }
 
     impl<C: PixelColor> RenderElement<C> {
@@ -529,7 +1323,11 @@ mod test {
         }
     }
 
-    impl<'el, F: TextRenderer> ElementHandler for TestElementHandler<F> {
+    impl<F> ElementHandler<'_> for TestElementHandler<F>
+    where
+        F: TextRenderer,
+        F::Color: From<Rgb888>,
+    {
         type Error = Infallible;
         type Color = F::Color;
 
@@ -538,8 +1336,7 @@ mod test {
         }
 
         fn whitespace(&mut self, _string: &str, count: u32, width: u32) -> Result<(), Self::Error> {
-            self.elements
-                .push(RenderElement::Space(width, (count > 0) as bool));
+            self.elements.push(RenderElement::Space(width, count > 0));
             Ok(())
         }
 
@@ -554,7 +1351,11 @@ mod test {
             Ok(())
         }
 
-        #[cfg(feature = "ansi")]
+        fn custom(&mut self, id: u32, width: u32) -> Result<(), Self::Error> {
+            self.elements.push(RenderElement::Custom(id, width));
+            Ok(())
+        }
+
         fn change_text_style(
             &mut self,
             change: ChangeTextStyle<Self::Color>,
@@ -586,7 +1387,7 @@ mod test {
 
         let mut handler = TestElementHandler::new(style);
         let mut line1 =
-            LineElementParser::new(parser, plugin, cursor, config, HorizontalAlignment::Left);
+            LineElementParser::new(parser, plugin, cursor, config, &TextBoxStyle::default());
 
         line1.process(&mut handler).unwrap();
 
@@ -609,14 +1410,9 @@ mod test {
         .line();
 
         let mut handler = TestElementHandler::new(style);
-        let mut mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
-        let mut line1 = LineElementParser::new(
-            &mut parser,
-            &mut mw,
-            cursor,
-            config,
-            HorizontalAlignment::Left,
-        );
+        let mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
+        let mut line1 =
+            LineElementParser::new(&mut parser, &mw, cursor, config, &TextBoxStyle::default());
 
         line1.process(&mut handler).unwrap();
 
@@ -661,18 +1457,74 @@ mod test {
         let mut parser = Parser::parse("sam\u{00AD}mm");
         let mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
 
+        // The hyphen doesn't fit, and it's not carried over to the next line.
         assert_line_elements(&mut parser, 3, &[RenderElement::string("sam", 18)], &mw);
+        assert_line_elements(&mut parser, 3, &[RenderElement::string("mm", 12)], &mw);
+    }
+
+    #[test]
+    fn soft_hyphen_exact_fit() {
+        // The whole word fits exactly, the hyphen is not needed.
+        let mut parser = Parser::parse("sam\u{00AD}mm");
+        let mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
+
         assert_line_elements(
             &mut parser,
-            3,
+            5,
             &[
-                RenderElement::string("-", 6),
+                RenderElement::string("sam", 18),
                 RenderElement::string("mm", 12),
             ],
             &mw,
         );
     }
 
+    #[test]
+    fn word_only_fits_with_its_hyphen() {
+        // "ab sam" fits exactly, but "ab sam-" doesn't, so "sam" is moved to the next line.
+        let mut parser = Parser::parse("ab sam\u{00AD}mmm");
+        let mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
+
+        assert_line_elements(
+            &mut parser,
+            6,
+            &[
+                RenderElement::string("ab", 12),
+                RenderElement::Space(6, true),
+            ],
+            &mw,
+        );
+        assert_line_elements(
+            &mut parser,
+            6,
+            &[
+                RenderElement::string("sam", 18),
+                RenderElement::string("mmm", 18),
+            ],
+            &mw,
+        );
+    }
+
+    #[test]
+    fn word_fits_with_its_hyphen() {
+        // "ab sam-" fits exactly.
+        let mut parser = Parser::parse("ab sam\u{00AD}mmm");
+        let mw = PluginWrapper::new(NoPlugin::<Rgb888>::new());
+
+        assert_line_elements(
+            &mut parser,
+            7,
+            &[
+                RenderElement::string("ab", 12),
+                RenderElement::Space(6, true),
+                RenderElement::string("sam", 18),
+                RenderElement::string("-", 6),
+            ],
+            &mw,
+        );
+        assert_line_elements(&mut parser, 7, &[RenderElement::string("mmm", 18)], &mw);
+    }
+
     #[test]
     fn nbsp_issue() {
         let mut parser = Parser::parse("a b c\u{a0}d e f");
@@ -715,8 +1567,8 @@ mod test {
             &mut parser,
             5,
             &[
-                RenderElement::string("-", 6),
                 RenderElement::string("cali", 24),
+                RenderElement::string("-", 6),
             ],
             &mw,
         );
@@ -2,31 +2,55 @@
 #[cfg(feature = "ansi")]
 mod ansi;
 pub(crate) mod cursor;
+pub(crate) mod hit_test;
+pub(crate) mod layout;
 mod line;
+#[cfg(feature = "buffered")]
+mod line_buffer;
 pub(crate) mod line_iter;
+pub(crate) mod regions;
+pub(crate) mod session;
+#[cfg(feature = "snapshot")]
+mod snapshot;
 pub(crate) mod space_config;
 
 use crate::{
+    alignment::VerticalAlignment,
     parser::Parser,
-    plugin::{PluginMarker as Plugin, ProcessingState},
+    plugin::{PluginMarker as Plugin, PluginWrapper, ProcessingState},
     rendering::{
-        cursor::Cursor,
-        line::{LineRenderState, StyledLineRenderer},
+        cursor::{Cursor, LineCursor},
+        line::{LineRenderState, RangeStyles, StyledLineRenderer, UnderlineState, WordState},
     },
-    style::TextBoxStyle,
-    TextBox,
+    style::{vertical_overdraw::Masked, BackgroundFill, Border, TextBoxStyle, TextMetrics},
+    LineMetrics, TextBox,
 };
 use az::SaturatingAs;
+use core::{convert::Infallible, marker::PhantomData};
 use embedded_graphics::{
     draw_target::{DrawTarget, DrawTargetExt},
+    geometry::OriginDimensions,
     pixelcolor::Rgb888,
-    prelude::{Dimensions, Point, Size},
-    primitives::Rectangle,
+    prelude::{PixelColor, Point, Size},
+    primitives::{
+        Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, RoundedRectangle,
+        StrokeAlignment,
+    },
     text::renderer::{CharacterStyle, TextRenderer},
-    Drawable,
+    transform::Transform,
+    Drawable, Pixel,
 };
 use line_iter::LineEndType;
 
+#[cfg(feature = "ansi")]
+use ansi::SgrState;
+#[cfg(feature = "ansi")]
+use cursor::CursorMove;
+#[cfg(feature = "buffered")]
+use line_buffer::BufferedLine;
+
+pub(crate) use line::{font_style, WordColorFn};
+
 /// Text box properties.
 ///
 /// This struct holds information about the text box.
@@ -45,38 +69,101 @@ pub struct TextBoxProperties<'a, S> {
     pub box_height: i32,
 }
 
-impl<'a, F, M> Drawable for TextBox<'a, F, M>
+/// A draw target without any pixels, used to process lines without drawing them.
+struct NullDisplay<C>(PhantomData<C>);
+
+impl<C> NullDisplay<C> {
+    fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C> OriginDimensions for NullDisplay<C> {
+    fn size(&self) -> Size {
+        Size::zero()
+    }
+}
+
+impl<C: PixelColor> DrawTarget for NullDisplay<C> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        Ok(())
+    }
+}
+
+/// Progress of drawing the lines of a text, kept when drawing is paused and resumed.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct LineProgress {
+    /// The index of the next line in the current column.
+    pub line_index: u32,
+
+    /// Whether any line has been displayed.
+    pub anything_drawn: bool,
+
+    /// Whether drawing was paused before the end of the text.
+    pub paused: bool,
+}
+
+/// Called after every drawn line with the number of displayed pixels of the line. Returns
+/// whether drawing should be paused before the next line.
+pub(crate) type PauseFn<'p> = &'p mut dyn FnMut(u32) -> bool;
+
+/// Measurements that only depend on the styling, and can be shared between text boxes.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SharedMeasurements {
+    tab_width: u32,
+
+    /// Whether the height of the text needs to be measured before rendering.
+    measure_text_height: bool,
+}
+
+impl SharedMeasurements {
+    /// Measures the styling of a text box.
+    ///
+    /// Set `plugins` to `true` if the text boxes use plugins, which may need the text height.
+    pub fn new<S: TextRenderer>(character_style: &S, style: &TextBoxStyle, plugins: bool) -> Self {
+        Self {
+            tab_width: style.tab_size.into_pixels(character_style),
+            measure_text_height: plugins || style.vertical_alignment != VerticalAlignment::Top,
+        }
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
 where
-    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
-    <F as CharacterStyle>::Color: From<Rgb888>,
-    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
 {
-    type Color = <F as CharacterStyle>::Color;
-    type Output = &'a str;
-
-    #[inline]
-    fn draw<D: DrawTarget<Color = Self::Color>>(
+    /// Creates the cursor used to render `text` and notifies the plugin about the start of
+    /// rendering.
+    pub(crate) fn start_cursor(
         &self,
-        display: &mut D,
-    ) -> Result<&'a str, D::Error> {
+        text: &'a str,
+        plugin: &PluginWrapper<'a, M, <S as TextRenderer>::Color>,
+        shared: &SharedMeasurements,
+    ) -> Cursor {
         let mut cursor = Cursor::new(
-            self.bounds,
+            self.text_bounds(),
             self.character_style.line_height(),
             self.style.line_height,
-            self.style.tab_size.into_pixels(&self.character_style),
+            shared.tab_width,
         );
 
-        let text_height = self
-            .style
-            .measure_text_height_impl(
-                self.plugin.clone(),
-                &self.character_style,
-                self.text,
-                cursor.line_width(),
-            )
-            .saturating_as::<i32>();
+        let text_height = if shared.measure_text_height {
+            self.measure_lines(plugin.clone(), self.fonts, text, &cursor)
+                .height
+                .saturating_as::<i32>()
+        } else {
+            0
+        };
 
-        let box_height = self.bounding_box().size.height.saturating_as::<i32>();
+        let box_height = self.text_bounds().size.height.saturating_as::<i32>();
 
         self.style.vertical_alignment.apply_vertical_alignment(
             &mut cursor,
@@ -85,6 +172,10 @@ where
         );
 
         cursor.y += self.vertical_offset;
+        cursor.snap_to_grid(self.style.baseline_grid);
+
+        #[cfg(feature = "ansi")]
+        cursor.mark_first_line();
 
         let props = TextBoxProperties {
             box_style: &self.style,
@@ -93,47 +184,618 @@ where
             box_height,
         };
 
-        self.plugin.on_start_render(&mut cursor, props);
+        plugin.on_start_render(&mut cursor, props);
+
+        cursor
+    }
+}
+
+impl<'a, F, M> Drawable for TextBox<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = &'a str;
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+        self.draw_with(display, &shared)
+    }
+}
+
+/// The result of [`TextBox::draw_until`].
+///
+/// [`TextBox::draw_until`]: struct.TextBox.html#method.draw_until
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DrawProgress<'a> {
+    /// Every line was drawn. Contains the text that didn't fit into the text box, like the return
+    /// value of `draw`.
+    Finished(&'a str),
+
+    /// Drawing was interrupted. Contains the text that wasn't drawn, starting at the first line
+    /// that was not drawn.
+    Interrupted(&'a str),
+}
+
+impl<'a, F, M> TextBox<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    /// Draws the text box, stopping early if `interrupt` returns `true`.
+    ///
+    /// `interrupt` is called after every drawn line. If it returns `true`, drawing stops before
+    /// the next line, so rendering can be cancelled when the text changes before it was
+    /// completely drawn, for example when a log is scrolled quickly. The lines that were drawn are
+    /// complete.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{DrawProgress, TextBox};
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 27));
+    /// let text_box = TextBox::new("first\nsecond\nthird", bounds, character_style);
+    ///
+    /// let mut display = MockDisplay::new();
+    /// let mut lines = 0;
+    /// let progress = text_box
+    ///     .draw_until(&mut display, || {
+    ///         lines += 1;
+    ///         lines == 2
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(progress, DrawProgress::Interrupted("third"));
+    /// ```
+    #[inline]
+    pub fn draw_until<D, I>(
+        &self,
+        display: &mut D,
+        mut interrupt: I,
+    ) -> Result<DrawProgress<'a>, D::Error>
+    where
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+        I: FnMut() -> bool,
+    {
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+
+        let mut progress = LineProgress::default();
+        let rest = self.draw_with_buffer(
+            display,
+            &shared,
+            &mut progress,
+            Some(&mut |_| interrupt()),
+            &mut [],
+        )?;
+
+        if progress.paused {
+            Ok(DrawProgress::Interrupted(rest))
+        } else {
+            Ok(DrawProgress::Finished(rest))
+        }
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<'a, F, M> TextBox<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    /// Draws the text box, rendering every line into `buffer` first.
+    ///
+    /// Every line is rendered into the buffer, and then drawn into `display` with a single call
+    /// to [`DrawTarget::fill_contiguous`], instead of drawing the glyphs pixel by pixel. This is
+    /// much faster on displays that can receive a block of pixels in one transfer. The buffer
+    /// holds one line: it needs room for the width of the text area multiplied by the line
+    /// height. Lines that don't fit into the buffer are drawn directly.
+    ///
+    /// The buffer is filled with the background fill color before rendering a line, so lines are
+    /// only buffered if the [`BackgroundFill`] of the style is set.
+    ///
+    /// Returns the same value as [`draw`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::{BinaryColor, Rgb888},
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{
+    ///     style::{BackgroundFill, TextBoxStyleBuilder},
+    ///     TextBox,
+    /// };
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .background_fill(BackgroundFill::Bounds(Rgb888::BLACK))
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// // Room for one 60 pixels wide and 9 pixels high line.
+    /// let mut buffer = [BinaryColor::Off; 60 * 9];
+    ///
+    /// let mut display = MockDisplay::new();
+    /// display.set_allow_overdraw(true);
+    /// TextBox::with_textbox_style("Hello, World!", bounds, character_style, style)
+    ///     .draw_buffered(&mut display, &mut buffer)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`DrawTarget::fill_contiguous`]: https://docs.rs/embedded-graphics/0.7/embedded_graphics/draw_target/trait.DrawTarget.html#method.fill_contiguous
+    /// [`BackgroundFill`]: style/enum.BackgroundFill.html
+    /// [`draw`]: #method.draw
+    #[inline]
+    pub fn draw_buffered<D>(
+        &self,
+        display: &mut D,
+        buffer: &mut [<F as CharacterStyle>::Color],
+    ) -> Result<&'a str, D::Error>
+    where
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+        self.draw_with_buffer(display, &shared, &mut LineProgress::default(), None, buffer)
+    }
+}
+
+impl<'a, F> TextBox<'a, F>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Draws the text box without using the heap or any `static` state.
+    ///
+    /// This method draws the same image as [`draw`], but it is only available for text boxes
+    /// without plugins. It can be called from interrupt handlers and other restricted contexts,
+    /// even if the interrupted code is drawing a different text box at the same time.
+    ///
+    /// User code must not be called while drawing, so the text box must not have a word color
+    /// callback, a line span callback or a theme, which is usually shared through a `static`.
+    /// This is checked by a debug assertion.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+    ///
+    /// let mut display = MockDisplay::new();
+    /// TextBox::new("PANIC", bounds, character_style)
+    ///     .render_no_alloc_no_static(&mut display)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`draw`]: #method.draw
+    #[inline]
+    pub fn render_no_alloc_no_static<D>(&self, display: &mut D) -> Result<&'a str, D::Error>
+    where
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        debug_assert!(
+            self.word_color.is_none() && self.line_span.is_none() && self.theme.is_none(),
+            "render_no_alloc_no_static called on a text box with callbacks or a theme"
+        );
+
+        self.draw(display)
+    }
+}
+
+impl<'a, F, M> TextBox<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    /// Fills the area selected by the background fill style. `cursor` must be at the start of the
+    /// first line.
+    pub(crate) fn fill_background<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        cursor: &Cursor,
+    ) -> Result<(), D::Error> {
+        let (area, color) = match self.style.background_fill {
+            BackgroundFill::None => return Ok(()),
+            BackgroundFill::Bounds(color) => match self.style.border {
+                Some(border) if border.corner_radius > 0 => {
+                    return self
+                        .rounded_bounds(border)
+                        .into_styled(PrimitiveStyle::with_fill(color.into()))
+                        .draw(display);
+                }
+                _ => (self.bounds, color),
+            },
+            BackgroundFill::Text(color) => {
+                let height = self
+                    .measure_lines(self.plugin.clone(), self.fonts, self.text, cursor)
+                    .height;
+                let text_area = Rectangle::new(
+                    Point::new(self.bounds.top_left.x, cursor.y),
+                    Size::new(self.bounds.size.width, height),
+                );
+
+                (text_area.intersection(&self.bounds), color)
+            }
+        };
+
+        display.fill_solid(&area, color.into())
+    }
+
+    /// Returns the bounding box with the corners of `border`.
+    fn rounded_bounds(&self, border: Border) -> RoundedRectangle {
+        let radius = Size::new(border.corner_radius, border.corner_radius);
+
+        RoundedRectangle::with_equal_corners(self.bounds, radius)
+    }
+
+    /// Draws the border, if the style has one.
+    pub(crate) fn draw_border<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<(), D::Error> {
+        let border = match self.style.border {
+            Some(border) if border.thickness > 0 => border,
+            _ => return Ok(()),
+        };
+
+        let style = PrimitiveStyleBuilder::new()
+            .stroke_color(border.color.into())
+            .stroke_width(border.thickness)
+            .stroke_alignment(StrokeAlignment::Inside)
+            .build();
+
+        self.rounded_bounds(border).into_styled(style).draw(display)
+    }
+
+    /// Returns the character style of the text box, with the colors of its theme applied.
+    pub(crate) fn themed_character_style(&self) -> F {
+        let mut character_style = self.character_style.clone();
+        if let Some(theme) = self.theme {
+            theme.apply(&mut character_style);
+        }
+
+        character_style
+    }
+
+    /// Returns the render state at the start of the text.
+    fn initial_state<'b>(
+        &'b self,
+        plugin: &'b PluginWrapper<'a, M, <F as TextRenderer>::Color>,
+    ) -> LineRenderState<'a, 'b, F, M> {
+        let default_style = self.themed_character_style();
 
-        let mut state = LineRenderState {
+        LineRenderState {
             style: self.style,
-            character_style: self.character_style.clone(),
-            parser: Parser::parse(self.text),
+            character_style: default_style.clone(),
+            default_style,
+            parser: Parser::parse(self.text).with_style(&self.style),
             end_type: LineEndType::EndOfText,
-            plugin: &self.plugin,
+            plugin,
+            fonts: self.fonts,
+            font: None,
+            vertical_offset: 0,
+            words: WordState::new(self.word_color),
+            ranges: self.styled_ranges.map(|ranges| RangeStyles {
+                text: self.text,
+                ranges,
+            }),
+            underline: UnderlineState::new(self.style.underline_color.map(Into::into)),
+            #[cfg(feature = "ansi")]
+            sgr: SgrState::new(),
+            line_end: Point::zero(),
+        }
+    }
+
+    /// Returns the character style that is in effect at the given byte offset of the text.
+    ///
+    /// The text style changes, like ANSI sequences and markup, are applied to the character style
+    /// of the text box up to the end of the line that contains `offset`, or up to `offset` if it
+    /// is at the start of a line. Applications that
+    /// display a long text page by page can use the returned style as the character style of
+    /// the text box of the next page, so that the styling continues where the previous page
+    /// ended. `draw` returns the text that didn't fit into the text box, so the offset of the
+    /// next page is `text.len() - rest.len()`.
+    ///
+    /// *Note:* the selected font, vertical offset and the emulated SGR intensity are not part of
+    /// the character style, so they are not carried over.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::Rgb888,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{plugin::markup::Markup, TextBox};
+    ///
+    /// let text = "{red}first page\nsecond page";
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(66, 9));
+    ///
+    /// let text_box = TextBox::new(text, bounds, character_style).add_plugin(Markup::new());
+    ///
+    /// let mut display = MockDisplay::new();
+    /// let rest = text_box.draw(&mut display).unwrap();
+    /// assert_eq!(rest, "second page");
+    ///
+    /// // The second page continues in red.
+    /// let next_style = text_box.character_style_at(text.len() - rest.len());
+    /// assert_eq!(next_style.text_color, Some(Rgb888::RED));
+    /// ```
+    #[inline]
+    pub fn character_style_at(&self, offset: usize) -> F {
+        let plugin = self.plugin.clone();
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+        let mut cursor = self.start_cursor(self.text, &plugin, &shared);
+        let mut state = self.initial_state(&plugin);
+
+        plugin.set_state(ProcessingState::Render);
+
+        let mut line_index = 0;
+        while self.text.len() - state.parser.as_str().len() < offset {
+            plugin.new_line();
+
+            // The line is processed without drawing, only to track the text style changes.
+            let line_cursor = self.line_cursor(&cursor, line_index);
+            line_index += 1;
+            state = match StyledLineRenderer::new(line_cursor, state).draw(&mut NullDisplay::new())
+            {
+                Ok(state) => state,
+                Err(never) => match never {},
+            };
+
+            match state.end_type {
+                LineEndType::EndOfText => break,
+                LineEndType::CarriageReturn => cursor.carriage_return(),
+                #[cfg(feature = "ansi")]
+                LineEndType::MoveCursor(movement) => cursor.move_to(movement),
+                #[cfg(feature = "ansi")]
+                LineEndType::EraseDisplay(x) => {
+                    cursor.move_to(CursorMove::Relative { lines: 0, x })
+                }
+                _ => cursor.new_line(),
+            }
+        }
+
+        state.character_style
+    }
+
+    /// Draws the text box using measurements shared with other text boxes.
+    pub(crate) fn draw_with<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        shared: &SharedMeasurements,
+    ) -> Result<&'a str, D::Error> {
+        self.draw_with_buffer(display, shared, &mut LineProgress::default(), None, &mut [])
+    }
+
+    /// Draws the text box using measurements shared with other text boxes, rendering lines into
+    /// `buffer` if it's large enough.
+    ///
+    /// Drawing is paused if `pause` returns `true`, see `draw_lines`.
+    fn draw_with_buffer<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        shared: &SharedMeasurements,
+        progress: &mut LineProgress,
+        pause: Option<PauseFn<'_>>,
+        buffer: &mut [<F as CharacterStyle>::Color],
+    ) -> Result<&'a str, D::Error> {
+        if self.style.columns.count > 1 {
+            return self.draw_columns(display, shared, progress, pause, buffer);
+        }
+
+        let mut cursor = self.start_cursor(self.text, &self.plugin, shared);
+
+        self.fill_background(display, &cursor)?;
+        self.draw_border(display)?;
+
+        let state = self.initial_state(&self.plugin);
+
+        self.draw_lines(display, &mut cursor, state, progress, pause, buffer)
+            .map(|(rest, _)| rest)
+    }
+
+    /// Draws the text into the columns of the style. Every column continues the text where the
+    /// previous column ended.
+    fn draw_columns<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        shared: &SharedMeasurements,
+        progress: &mut LineProgress,
+        mut pause: Option<PauseFn<'_>>,
+        buffer: &mut [<F as CharacterStyle>::Color],
+    ) -> Result<&'a str, D::Error> {
+        // The plugin is notified about the start of rendering by creating the cursor.
+        let cursor = self.start_cursor(self.text, &self.plugin, shared);
+
+        match self.style.background_fill {
+            BackgroundFill::Text(color) => display.fill_solid(&self.bounds, color.into())?,
+            _ => self.fill_background(display, &cursor)?,
+        }
+        self.draw_border(display)?;
+
+        let mut state = self.initial_state(&self.plugin);
+
+        let mut rest = self.text;
+        for area in self.style.columns.areas(self.text_bounds()) {
+            let mut cursor = Cursor::new(
+                area,
+                self.character_style.line_height(),
+                self.style.line_height,
+                shared.tab_width,
+            );
+            cursor.y += self.vertical_offset;
+            cursor.snap_to_grid(self.style.baseline_grid);
+
+            #[cfg(feature = "ansi")]
+            cursor.mark_first_line();
+
+            // Lines are counted per column.
+            *progress = LineProgress::default();
+            let (column_rest, column_state) = self.draw_lines(
+                display,
+                &mut cursor,
+                state,
+                progress,
+                pause.as_mut().map(|pause| &mut **pause as PauseFn<'_>),
+                buffer,
+            )?;
+            rest = column_rest;
+            state = column_state;
+
+            if rest.is_empty() || progress.paused {
+                break;
+            }
+        }
+
+        Ok(rest)
+    }
+
+    /// Returns whether the first line of the text can be placed at `cursor` without breaking a
+    /// word.
+    pub(crate) fn fits_at(&self, cursor: &Cursor, character_style: &F) -> bool {
+        let plugin = self.plugin.clone();
+        plugin.set_state(ProcessingState::Measure);
+
+        let lm = self.style.measure_line(
+            &plugin,
+            character_style,
+            &mut Parser::parse(self.text).with_style(&self.style),
+            cursor.line().line_width(),
+        );
+
+        !lm.word_broken
+    }
+
+    /// Draws the text of the text box after it has been appended to previously drawn text.
+    ///
+    /// Drawing starts at `cursor`, using `character_style` as the initial text style. Both are
+    /// updated to continue after the drawn text. Unlike `draw`, the background and the border are
+    /// not drawn, and drawing stops if the first line is not displayed.
+    pub(crate) fn draw_appended<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        cursor: &mut Cursor,
+        character_style: &mut F,
+    ) -> Result<&'a str, D::Error> {
+        let mut state = self.initial_state(&self.plugin);
+        state.character_style = character_style.clone();
+
+        let mut progress = LineProgress {
+            anything_drawn: true,
+            ..LineProgress::default()
         };
+        let (rest, state) =
+            self.draw_lines(display, cursor, state, &mut progress, None, &mut [])?;
+
+        if state.end_type == LineEndType::EndOfText {
+            cursor.continue_line(state.line_end.x);
+        }
+        *character_style = state.character_style;
 
+        Ok(rest)
+    }
+
+    /// Draws the lines of the text, starting at `cursor`.
+    ///
+    /// Drawing stops at the first line that is not displayed, if any line was drawn before it, or
+    /// `progress.anything_drawn` is set. If `pause` returns `true` after a line, drawing stops
+    /// before the next line, and `progress.paused` is set. Returns the text that wasn't drawn, and
+    /// the render state after the last drawn line.
+    pub(crate) fn draw_lines<'b, D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &'b self,
+        display: &mut D,
+        cursor: &mut Cursor,
+        mut state: LineRenderState<'a, 'b, F, M>,
+        progress: &mut LineProgress,
+        mut pause: Option<PauseFn<'_>>,
+        #[cfg_attr(not(feature = "buffered"), allow(unused_variables))]
+        buffer: &mut [<F as CharacterStyle>::Color],
+    ) -> Result<(&'a str, LineRenderState<'a, 'b, F, M>), D::Error> {
         state.plugin.set_state(ProcessingState::Render);
 
-        let mut anything_drawn = false;
+        progress.paused = false;
         loop {
             state.plugin.new_line();
-            let line_cursor = cursor.line();
 
-            let display_range = self
-                .style
-                .height_mode
-                .calculate_displayed_row_range(&cursor);
+            let line_cursor = self.line_cursor(cursor, progress.line_index);
+            progress.line_index += 1;
+
+            // The line height depends on the fonts used in the line, and plugins are notified
+            // about the metrics of the line. The measurement is reused to place the line.
+            let line_metrics = state.plugin.wants_line_metrics();
+            let measured = if self.fonts.is_empty() && !line_metrics {
+                None
+            } else {
+                let measured = state.measure_line(line_cursor.clone());
+                if !self.fonts.is_empty() {
+                    cursor.set_line_height(measured.measurement.line_height);
+                }
+                Some(measured)
+            };
+            let measurement = measured.as_ref().map(|measured| measured.measurement);
+
+            let overdraw = self.style.height_mode.overdraw();
+            let display_range = overdraw.calculate_displayed_row_range(cursor);
             let display_size = Size::new(
                 cursor.line_width(),
                 display_range.clone().count().saturating_as(),
             );
 
             let line_start = line_cursor.pos();
+            let display_area = Rectangle::new(
+                line_start + Point::new(0, display_range.start),
+                display_size,
+            );
 
             // FIXME: cropping isn't necessary for whole lines, but make sure not to blow up the
             // binary size as well.
-            let mut display = display.clipped(&Rectangle::new(
-                line_start + Point::new(0, display_range.start),
-                display_size,
-            ));
+            let mut clipped_display = display.clipped(&display_area);
+            let mut line_display = Masked::new(
+                &mut clipped_display,
+                overdraw.row_mask(cursor, &display_range, display_area),
+            );
             if display_range.start == display_range.end {
-                if anything_drawn {
+                if progress.anything_drawn {
                     let remaining_bytes = state.parser.as_str().len();
                     let consumed_bytes = self.text.len() - remaining_bytes;
 
                     state.plugin.post_render(
-                        &mut display,
+                        &mut line_display,
                         &self.character_style,
                         "",
                         Rectangle::new(
@@ -141,28 +803,171 @@ where
                             Size::new(0, cursor.line_height().saturating_as()),
                         ),
                     )?;
-                    return Ok(self.text.get(consumed_bytes..).unwrap());
+                    return Ok((self.text.get(consumed_bytes..).unwrap(), state));
                 }
             } else {
-                anything_drawn = true;
+                progress.anything_drawn = true;
+            }
+
+            let line_start_state = if line_metrics {
+                let start = self.text.len() - state.parser.as_str().len();
+                Some((start, state.character_style.clone()))
+            } else {
+                None
+            };
+
+            #[cfg(feature = "buffered")]
+            {
+                let background = self.style.background_fill.color().map(Into::into);
+                let mut buffered_display =
+                    BufferedLine::new(&mut line_display, buffer, display_area, background);
+                state = StyledLineRenderer::new(line_cursor, state)
+                    .with_measurement(measured)
+                    .draw(&mut buffered_display)?;
+                buffered_display.flush()?;
+            }
+            #[cfg(not(feature = "buffered"))]
+            {
+                state = StyledLineRenderer::new(line_cursor, state)
+                    .with_measurement(measured)
+                    .draw(&mut line_display)?;
             }
 
-            state = StyledLineRenderer::new(line_cursor, state).draw(&mut display)?;
+            if let (Some(lm), Some((start, line_style))) = (measurement, line_start_state) {
+                let (left, space_config) =
+                    self.style
+                        .alignment
+                        .place_line(&line_style, self.style.word_spacing, lm);
+
+                state.plugin.post_line(LineMetrics {
+                    width: lm.width,
+                    word_count: lm.word_count,
+                    space_count: lm.space_count,
+                    bounds: Rectangle::new(
+                        line_start + Point::new(left.saturating_as(), 0),
+                        Size::new(lm.width, cursor.line_height().saturating_as()),
+                    ),
+                    start,
+                    end: self.text.len() - state.parser.as_str().len(),
+                    stretched: space_config.is_stretched(),
+                });
+            }
 
             match state.end_type {
                 LineEndType::EndOfText => break,
-                LineEndType::CarriageReturn => {}
+                LineEndType::CarriageReturn => cursor.carriage_return(),
+                #[cfg(feature = "ansi")]
+                LineEndType::MoveCursor(movement) => cursor.move_to(movement),
+                #[cfg(feature = "ansi")]
+                LineEndType::EraseDisplay(x) => {
+                    self.erase_display(display, &state.character_style)?;
+                    cursor.move_to(CursorMove::Relative { lines: 0, x });
+                }
                 _ => {
                     cursor.new_line();
 
                     if state.end_type == LineEndType::NewLine {
                         cursor.y += self.style.paragraph_spacing.saturating_as::<i32>();
+                        cursor.snap();
                     }
                 }
             }
+
+            if let Some(pause) = pause.as_mut() {
+                if pause(display_size.width * display_size.height) {
+                    progress.paused = true;
+
+                    let consumed_bytes = self.text.len() - state.parser.as_str().len();
+                    return Ok((self.text.get(consumed_bytes..).unwrap(), state));
+                }
+            }
+        }
+
+        Ok(("", state))
+    }
+}
+
+impl<'a, F, M> TextBox<'a, F, M>
+where
+    F: TextRenderer,
+{
+    /// Returns the cursor at the top of the text area, before vertical alignment is applied.
+    pub(crate) fn top_cursor(&self) -> Cursor {
+        Cursor::new(
+            self.text_bounds(),
+            self.character_style.line_height(),
+            self.style.line_height,
+            self.style.tab_size.into_pixels(&self.character_style),
+        )
+    }
+
+    /// Measures `text` laid out from `cursor`, wrapping every line at its width after the line
+    /// span and the excluded areas are applied.
+    pub(crate) fn measure_lines<P>(
+        &self,
+        plugin: PluginWrapper<'a, P, F::Color>,
+        fonts: &[F],
+        text: &'a str,
+        cursor: &Cursor,
+    ) -> TextMetrics
+    where
+        P: Plugin<'a, F::Color>,
+        F::Color: From<Rgb888>,
+    {
+        self.style.measure_text_with_widths(
+            plugin,
+            &self.character_style,
+            fonts,
+            text,
+            |line_index, offset| {
+                let mut line = *cursor;
+                line.y += offset.saturating_as::<i32>();
+                self.line_cursor(&line, line_index).line_width()
+            },
+        )
+    }
+
+    /// Returns the cursor of the current line, shortened by the line span and the excluded areas.
+    pub(crate) fn line_cursor(&self, cursor: &Cursor, line_index: u32) -> LineCursor {
+        let mut line_cursor = cursor.line();
+        if let Some(line_span) = self.line_span {
+            let origin = self.bounds.top_left;
+            let span = line_span(line_index, cursor.y - origin.y);
+            line_cursor.limit(origin.x + span.start..origin.x + span.end);
+        }
+
+        let line_height = cursor.line_height().saturating_as();
+        for area in self.exclusions {
+            line_cursor.exclude(area.translate(self.bounds.top_left), line_height);
+        }
+
+        line_cursor
+    }
+
+    /// Fills the text box with the background color of `character_style`.
+    #[cfg(feature = "ansi")]
+    fn erase_display<D>(&self, display: &mut D, character_style: &F) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = F::Color>,
+    {
+        use embedded_graphics::text::Baseline;
+
+        let bounds = self.text_bounds();
+        let mut display = display.clipped(&bounds);
+
+        let line_height = character_style.line_height().max(1).saturating_as::<i32>();
+        let mut top_left = bounds.top_left;
+        while top_left.y < bounds.top_left.y + bounds.size.height.saturating_as::<i32>() {
+            character_style.draw_whitespace(
+                bounds.size.width,
+                top_left,
+                Baseline::Top,
+                &mut display,
+            )?;
+            top_left.y += line_height;
         }
 
-        Ok("")
+        Ok(())
     }
 }
 
@@ -171,14 +976,17 @@ pub mod test {
     use embedded_graphics::{
         mock_display::MockDisplay,
         mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
-        pixelcolor::BinaryColor,
+        pixelcolor::{BinaryColor, Rgb888},
         prelude::*,
         primitives::Rectangle,
     };
 
     use crate::{
-        alignment::HorizontalAlignment,
-        style::{HeightMode, TextBoxStyleBuilder, VerticalOverdraw},
+        alignment::{HorizontalAlignment, VerticalAlignment},
+        style::{
+            BackgroundFill, Border, HeightMode, Insets, ParagraphSpaces, TextBoxStyleBuilder,
+            VerticalOverdraw, WhiteSpace, WordBreak, Wrap,
+        },
         utils::test::size_for,
         TextBox,
     };
@@ -318,4 +1126,1424 @@ pub mod test {
             "..............................",
         ]);
     }
+
+    #[test]
+    fn render_no_alloc_no_static() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "ab",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            character_style,
+        )
+        .render_no_alloc_no_static(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "            ",
+            "       #    ",
+            "       #    ",
+            "  ###  ###  ",
+            " #  #  #  # ",
+            " #  #  #  # ",
+            "  ###  ###  ",
+        ]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "render_no_alloc_no_static called on a text box with callbacks")]
+    fn render_no_alloc_no_static_rejects_callbacks() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "ab",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            character_style,
+        )
+        .set_word_color(|_, _| None)
+        .render_no_alloc_no_static(&mut display)
+        .unwrap();
+    }
+
+    #[test]
+    fn word_color() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "a bcdefg h",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3)),
+            character_style,
+        )
+        .set_word_color(|index, _| {
+            if index == 1 {
+                Some(BinaryColor::Off)
+            } else {
+                None
+            }
+        })
+        .draw(&mut display)
+        .unwrap();
+
+        // The wrapped word keeps its color.
+        display.assert_pattern(&[
+            "                       ",
+            "                       ",
+            "                       ",
+            "  ###                  ",
+            " #  #                  ",
+            " #  #                  ",
+            "  ###                  ",
+            "                       ",
+            "                       ",
+            "                       ",
+            " .              .      ",
+            " .              .      ",
+            " ...    ...   ...   .. ",
+            " .  .  .     .  .  . ..",
+            " .  .  .     .  .  ..  ",
+            " ...    ...   ...   ...",
+            "                       ",
+            "                       ",
+            "                       ",
+            "   .               #   ",
+            "  . .              #   ",
+            "  .     ..         ### ",
+            " ...   .  .        #  #",
+            "  .    .  .        #  #",
+            "  .     ...        #  #",
+            "          .            ",
+            "        ..             ",
+        ]);
+    }
+
+    #[test]
+    fn word_spacing() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::with_textbox_style(
+            "a b c",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().word_spacing(3).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            ".....................",
+            "................#....",
+            "................#....",
+            "..###...........###..",
+            ".#..#...........#..#.",
+            ".#..#...........#..#.",
+            "..###...........###..",
+            ".....................",
+            ".....................",
+            "......               ",
+            "......               ",
+            "......               ",
+            "..###.               ",
+            ".#....               ",
+            ".#....               ",
+            "..###.               ",
+            "......               ",
+            "......               ",
+        ]);
+    }
+
+    #[test]
+    fn negative_word_spacing() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::with_textbox_style(
+            "a b c",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1)),
+            character_style,
+            TextBoxStyleBuilder::new().word_spacing(-3).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "........................",
+            "..........#.............",
+            "..........#.............",
+            "..###.....###.......###.",
+            ".#..#.....#..#.....#....",
+            ".#..#.....#..#.....#....",
+            "..###.....###.......###.",
+            "........................",
+            "........................",
+        ]);
+    }
+
+    #[track_caller]
+    fn assert_word_break(word_break: WordBreak, text: &str, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().word_break(word_break).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn word_break_never_clips_word() {
+        assert_word_break(
+            WordBreak::Never,
+            "abcd ef",
+            &[
+                "..................",
+                ".......#..........",
+                ".......#..........",
+                "..###..###....###.",
+                ".#..#..#..#..#....",
+                ".#..#..#..#..#....",
+                "..###..###....###.",
+                "..................",
+                "..................",
+                "............      ",
+                ".........#..      ",
+                "........#.#.      ",
+                "..##....#...      ",
+                ".#.##..###..      ",
+                ".##.....#...      ",
+                "..###...#...      ",
+                "............      ",
+                "............      ",
+            ],
+        );
+    }
+
+    #[test]
+    fn word_break_min_length() {
+        // Only 3 characters fit before the break.
+        assert_word_break(
+            WordBreak::MinLength(4),
+            "abcd",
+            &[
+                "..................",
+                ".......#..........",
+                ".......#..........",
+                "..###..###....###.",
+                ".#..#..#..#..#....",
+                ".#..#..#..#..#....",
+                "..###..###....###.",
+                "..................",
+                "..................",
+            ],
+        );
+        // The rest of the word may be shorter than the minimum length.
+        assert_word_break(
+            WordBreak::MinLength(3),
+            "abcd",
+            &[
+                "..................",
+                ".......#..........",
+                ".......#..........",
+                "..###..###....###.",
+                ".#..#..#..#..#....",
+                ".#..#..#..#..#....",
+                "..###..###....###.",
+                "..................",
+                "..................",
+                "......            ",
+                "....#.            ",
+                "....#.            ",
+                "..###.            ",
+                ".#..#.            ",
+                ".#..#.            ",
+                "..###.            ",
+                "......            ",
+                "......            ",
+            ],
+        );
+    }
+
+    #[test]
+    fn long_hyphen_string_does_not_wrap_early() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        // "c==" doesn't fit after "ab", but the rest of the word does.
+        let text_box = TextBox::with_textbox_style(
+            "ab\u{AD}c\u{AD}d",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().hyphen_string("==").build(),
+        );
+
+        assert_eq!(text_box.line_metrics().count(), 1);
+    }
+
+    #[test]
+    fn grapheme_clusters_are_not_broken() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 3));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("ae\u{301}b", bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("a\ne\u{301}\nb", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn wrapped_lines_are_marked() {
+        use crate::style::WrapMarker;
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        for &(marker, text, expected) in &[
+            (WrapMarker::Start(">"), "abc def\nghi", "abc\n>def\nghi"),
+            (WrapMarker::End(">"), "ab cdef\nghi", "ab >\ncdef\nghi"),
+            (WrapMarker::End(">"), "abc def", "abc>\ndef"),
+            (WrapMarker::End(">"), "abcdef", "abc>\ndef"),
+        ] {
+            let mut display = MockDisplay::new();
+            TextBox::with_textbox_style(
+                text,
+                bounds,
+                character_style,
+                TextBoxStyleBuilder::new().wrap_marker(marker).build(),
+            )
+            .draw(&mut display)
+            .unwrap();
+
+            let mut expected_display = MockDisplay::new();
+            TextBox::new(expected, bounds, character_style)
+                .draw(&mut expected_display)
+                .unwrap();
+
+            display.assert_eq(&expected_display);
+        }
+    }
+
+    #[test]
+    fn custom_hyphen_string() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::with_textbox_style(
+            "ab\u{AD}cde",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().hyphen_string("=>").build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "........................",
+            ".......#................",
+            ".......#..........##....",
+            "..###..###..#####...##..",
+            ".#..#..#..#...........#.",
+            ".#..#..#..#.#####...##..",
+            "..###..###........##....",
+            "........................",
+            "........................",
+            "..................      ",
+            "..........#.......      ",
+            "..........#.......      ",
+            "..###...###...##..      ",
+            ".#.....#..#..#.##.      ",
+            ".#.....#..#..##...      ",
+            "..###...###...###.      ",
+            "..................      ",
+            "..................      ",
+        ]);
+    }
+
+    #[track_caller]
+    fn assert_paragraph_spaces(
+        paragraph_spaces: ParagraphSpaces,
+        alignment: HorizontalAlignment,
+        text: &str,
+        pattern: &[&str],
+    ) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 2)),
+            character_style,
+            TextBoxStyleBuilder::new()
+                .alignment(alignment)
+                .paragraph_spaces(paragraph_spaces)
+                .build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn paragraph_spaces_preserve() {
+        assert_paragraph_spaces(
+            ParagraphSpaces::Preserve,
+            HorizontalAlignment::Right,
+            " a \n b",
+            &[
+                "      ..................",
+                "      ..................",
+                "      ..................",
+                "      ........###.......",
+                "      .......#..#.......",
+                "      .......#..#.......",
+                "      ........###.......",
+                "      ..................",
+                "      ..................",
+                "            ............",
+                "            .......#....",
+                "            .......#....",
+                "            .......###..",
+                "            .......#..#.",
+                "            .......#..#.",
+                "            .......###..",
+                "            ............",
+                "            ............",
+            ],
+        );
+    }
+
+    #[test]
+    fn paragraph_spaces_trim() {
+        assert_paragraph_spaces(
+            ParagraphSpaces::Trim,
+            HorizontalAlignment::Left,
+            " a \n b",
+            &[
+                "......", "......", "......", "..###.", ".#..#.", ".#..#.", "..###.", "......",
+                "......", "......", ".#....", ".#....", ".###..", ".#..#.", ".#..#.", ".###..",
+                "......", "......",
+            ],
+        );
+    }
+
+    #[track_caller]
+    fn assert_white_space(white_space: WhiteSpace, text: &str, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().white_space(white_space).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn white_space_collapse() {
+        assert_white_space(
+            WhiteSpace::Collapse,
+            "  a   b\n c",
+            &[
+                "                             ",
+                "             #               ",
+                "             #               ",
+                "  ###        ###          ###",
+                " #  #        #  #        #   ",
+                " #  #        #  #        #   ",
+                "  ###        ###          ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn white_space_preserve_breaks() {
+        assert_white_space(
+            WhiteSpace::PreserveBreaks,
+            "  a   b\n c",
+            &[
+                "                 ",
+                "             #   ",
+                "             #   ",
+                "  ###        ### ",
+                " #  #        #  #",
+                " #  #        #  #",
+                "  ###        ### ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "                 ",
+                "  ###            ",
+                " #               ",
+                " #               ",
+                "  ###            ",
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn reset_restores_changed_character_style() {
+        use embedded_graphics::text::renderer::CharacterStyle;
+
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let mut text_box = TextBox::new(
+            "\x1b[97ma\x1b[0mb",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            character_style,
+        );
+        text_box.set_text_color(Some(BinaryColor::Off));
+        text_box.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "           ",
+            "       .   ",
+            "       .   ",
+            "  ###  ... ",
+            " #  #  .  .",
+            " #  #  .  .",
+            "  ###  ... ",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn ansi_cursor_movement() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "a\x1b[Bb\x1b[2Ac\x1b[3;1Hd",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3)),
+            character_style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "                 ",
+            "                 ",
+            "                 ",
+            "  ###         ###",
+            " #  #        #   ",
+            " #  #        #   ",
+            "  ###         ###",
+            "                 ",
+            "                 ",
+            "                 ",
+            "       #         ",
+            "       #         ",
+            "       ###       ",
+            "       #  #      ",
+            "       #  #      ",
+            "       ###       ",
+            "                 ",
+            "                 ",
+            "                 ",
+            "    #            ",
+            "    #            ",
+            "  ###            ",
+            " #  #            ",
+            " #  #            ",
+            "  ###            ",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn ansi_erase_line() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "a\x1b[40m\x1b[Kb",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1)),
+            character_style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "      ..................",
+            "      .#................",
+            "      .#................",
+            "  ### .###..............",
+            " #  # .#..#.............",
+            " #  # .#..#.............",
+            "  ### .###..............",
+            "      ..................",
+            "      ..................",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn ansi_erase_display() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "a\nb\x1b[40m\x1b[2Jc",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 2)),
+            character_style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "........###.......",
+            ".......#..........",
+            ".......#..........",
+            "........###.......",
+            "..................",
+            "..................",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn ansi_underline_color() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::new(
+            "\x1b[58;2;0;0;0ma\x1b[4mb\x1b[59mc",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1)),
+            character_style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "                  ",
+            "       #          ",
+            "       #          ",
+            "  ###  ###    ### ",
+            " #  #  #  #  #    ",
+            " #  #  #  #  #    ",
+            "  ###  ###    ### ",
+            "                  ",
+            "      ......######",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn underline_color_from_style() {
+        use embedded_graphics::pixelcolor::Rgb888;
+
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::with_textbox_style(
+            "a\x1b[4mb\x1b[0mc",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1)),
+            character_style,
+            TextBoxStyleBuilder::new()
+                .underline_color(Rgb888::BLACK)
+                .build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "                 ",
+            "       #         ",
+            "       #         ",
+            "  ###  ###    ###",
+            " #  #  #  #  #   ",
+            " #  #  #  #  #   ",
+            "  ###  ###    ###",
+            "                 ",
+            "      ......     ",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn ansi_multiple_attributes() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1));
+
+        // Strikethrough overlaps the glyphs.
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        TextBox::new("a\x1b[4;9mb\x1b[0mc", bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        TextBox::new("a\x1b[4m\x1b[9mb\x1b[0mc", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn style_continuity_resets_paragraphs() {
+        use crate::style::StyleContinuity;
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .style_continuity(StyleContinuity::ResetParagraphs)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3));
+
+        let mut display = MockDisplay::new();
+        TextBox::with_textbox_style("\x1b[4mab cd\ne", bounds, character_style, style)
+            .draw(&mut display)
+            .unwrap();
+
+        // Wrapped lines keep the style, new paragraphs don't.
+        let mut expected = MockDisplay::new();
+        TextBox::new("\x1b[4mab cd\n\x1b[0me", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn character_style_at() {
+        use crate::style::{StyleContinuity, TextBoxStyle};
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(Rgb888::WHITE)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3));
+        let text = "ab\n\x1b[31mcd\nef";
+
+        let colors_at = |style| {
+            let text_box = TextBox::with_textbox_style(text, bounds, character_style, style);
+            [0, 3, 11, 12]
+                .iter()
+                .map(|&offset| text_box.character_style_at(offset).text_color)
+                .collect::<Vec<_>>()
+        };
+
+        let white = Some(Rgb888::WHITE);
+        let red = Some(Rgb888::new(197, 15, 31));
+        assert_eq!(colors_at(TextBoxStyle::default()), [white, white, red, red]);
+        assert_eq!(
+            colors_at(
+                TextBoxStyleBuilder::new()
+                    .style_continuity(StyleContinuity::ResetParagraphs)
+                    .build()
+            ),
+            [white; 4]
+        );
+    }
+
+    #[track_caller]
+    fn assert_wrap(wrap: Wrap, text: &str, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 2)),
+            character_style,
+            TextBoxStyleBuilder::new().wrap(wrap).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn wrap_none_clips_lines() {
+        assert_wrap(
+            Wrap::None,
+            "abcdef\nab cd",
+            &[
+                "                       ",
+                "       #              #",
+                "       #              #",
+                "  ###  ###    ###   ###",
+                " #  #  #  #  #     #  #",
+                " #  #  #  #  #     #  #",
+                "  ###  ###    ###   ###",
+                "                       ",
+                "                       ",
+                "                       ",
+                "       #               ",
+                "       #               ",
+                "  ###  ###          ###",
+                " #  #  #  #        #   ",
+                " #  #  #  #        #   ",
+                "  ###  ###          ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn wrap_ellipsis() {
+        assert_wrap(
+            Wrap::Ellipsis("."),
+            "abcdef\nab cd",
+            &[
+                "                      ",
+                "       #              ",
+                "       #              ",
+                "  ###  ###    ###     ",
+                " #  #  #  #  #        ",
+                " #  #  #  #  #      ##",
+                "  ###  ###    ###   ##",
+                "                      ",
+                "                      ",
+                "                      ",
+                "       #              ",
+                "       #              ",
+                "  ###  ###            ",
+                " #  #  #  #           ",
+                " #  #  #  #         ##",
+                "  ###  ###          ##",
+            ],
+        );
+        assert_wrap(
+            Wrap::Ellipsis("."),
+            "abcd\nab c",
+            &[
+                "                       ",
+                "       #              #",
+                "       #              #",
+                "  ###  ###    ###   ###",
+                " #  #  #  #  #     #  #",
+                " #  #  #  #  #     #  #",
+                "  ###  ###    ###   ###",
+                "                       ",
+                "                       ",
+                "                       ",
+                "       #               ",
+                "       #               ",
+                "  ###  ###          ###",
+                " #  #  #  #        #   ",
+                " #  #  #  #        #   ",
+                "  ###  ###          ###",
+            ],
+        );
+    }
+
+    #[track_caller]
+    fn assert_background_fill(fill: BackgroundFill, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Middle)
+            .background_fill(fill)
+            .build();
+
+        TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 3)),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn background_fill() {
+        assert_background_fill(
+            BackgroundFill::Bounds(Rgb888::BLACK),
+            &[
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "..###.......",
+                ".#..#.......",
+                ".#..#.......",
+                "..###.......",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+                "............",
+            ],
+        );
+        assert_background_fill(
+            BackgroundFill::Text(Rgb888::BLACK),
+            &[
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "............",
+                "............",
+                "............",
+                "..###.......",
+                ".#..#.......",
+                ".#..#.......",
+                "..###.......",
+                "............",
+                "............",
+            ],
+        );
+    }
+
+    #[test]
+    fn padding_shrinks_the_text_area() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+        let text = "abc def ghi";
+
+        let mut display = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .with_padding(Insets::new(1, 2, 3, 4))
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(
+            text,
+            Rectangle::new(Point::new(4, 1), bounds.size - Size::new(6, 4)),
+            character_style,
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn fit_height_includes_padding() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(HeightMode::FitToText)
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "abc def",
+            Rectangle::new(Point::zero(), Size::new(22, 0)),
+            character_style,
+            style,
+        )
+        .with_padding(Insets::uniform(2));
+
+        assert_eq!(text_box.bounding_box().size, Size::new(22, 22));
+    }
+
+    #[test]
+    fn fit_width() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(HeightMode::FitToText)
+            .build();
+
+        let text_box = |text| {
+            TextBox::with_textbox_style(
+                text,
+                Rectangle::new(Point::zero(), Size::zero()),
+                character_style,
+                style,
+            )
+        };
+
+        assert_eq!(
+            text_box("ab cd").fit_width().bounding_box().size,
+            Size::new(30, 9)
+        );
+        assert_eq!(
+            text_box("ab\ncdef ").fit_width().bounding_box().size,
+            Size::new(24, 18)
+        );
+
+        let mut padded = text_box("ab cd").with_padding(Insets::uniform(1));
+        padded.fit_width_limited(20);
+        assert_eq!(padded.bounding_box().size, Size::new(20, 20));
+    }
+
+    #[test]
+    fn border_follows_fitted_bounds() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(HeightMode::FitToText)
+            .border(Border::new(Rgb888::WHITE, 1))
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), Size::new(8, 0)),
+            character_style,
+            style,
+        )
+        .with_padding(Insets::uniform(1));
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        text_box.draw(&mut display).unwrap();
+
+        assert_eq!(display.affected_area(), text_box.bounding_box());
+        display.assert_pattern(&[
+            "########", "#      #", "#      #", "#      #", "#  ### #", "# #  # #", "# #  # #",
+            "#  ### #", "#      #", "#      #", "########",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "buffered")]
+    fn buffered_drawing_matches_direct_drawing() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .background_fill(BackgroundFill::Bounds(Rgb888::BLACK))
+            .height_mode(HeightMode::Exact(VerticalOverdraw::Hidden))
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "ab cd efg",
+            Rectangle::new(Point::new(1, 2), Size::new(24, 22)),
+            character_style,
+            style,
+        );
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        text_box.draw(&mut expected).unwrap();
+
+        for buffer_size in [0, 24 * 4, 24 * 9].iter() {
+            let mut buffer = vec![BinaryColor::On; *buffer_size];
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            text_box.draw_buffered(&mut display, &mut buffer).unwrap();
+
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn text_flows_into_columns() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let mut display = MockDisplay::new();
+        let rest = TextBox::with_textbox_style(
+            "ab cd ef gh ij",
+            bounds,
+            character_style,
+            TextBoxStyleBuilder::new().columns(2, 6).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+        assert_eq!(rest, "ij");
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("ab ef\ncd gh", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn text_flows_around_exclusions() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::new(6, 0), size_for(&FONT_6X9, 5, 2));
+        let exclusions = [Rectangle::new(Point::zero(), Size::new(12, 9))];
+
+        let mut text_box = TextBox::new("ab cd ef", bounds, character_style);
+        text_box.set_exclusions(&exclusions);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        for &(text, top_left) in &[("ab", Point::new(18, 0)), ("cd ef", Point::new(6, 9))] {
+            TextBox::new(
+                text,
+                Rectangle::new(top_left, size_for(&FONT_6X9, 5, 1)),
+                character_style,
+            )
+            .draw(&mut expected)
+            .unwrap();
+        }
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn exclusions_are_measured() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Middle)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+        let exclusions = [Rectangle::new(Point::zero(), Size::new(12, 27))];
+
+        let mut text_box = TextBox::with_textbox_style("ab cd", bounds, character_style, style);
+        text_box.set_exclusions(&exclusions);
+
+        // Two lines are centered vertically.
+        let mut lines = text_box.line_metrics().map(|line| line.bounds.top_left);
+        assert_eq!(lines.next(), Some(Point::new(12, 4)));
+        assert_eq!(lines.next(), Some(Point::new(12, 13)));
+        assert_eq!(lines.next(), None);
+
+        text_box.fit_height();
+        assert_eq!(text_box.bounding_box().size.height, 18);
+    }
+
+    #[test]
+    fn line_span_is_measured() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(HeightMode::FitToText)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1));
+
+        let mut text_box = TextBox::with_textbox_style("ab cd", bounds, character_style, style);
+        assert_eq!(text_box.bounding_box().size.height, 9);
+        assert_eq!(text_box.line_metrics().count(), 1);
+
+        // The first line is too narrow for both words.
+        text_box.set_line_span(|line, _| if line == 0 { 0..18 } else { 0..30 });
+        assert_eq!(text_box.bounding_box().size.height, 18);
+        assert_eq!(text_box.line_metrics().count(), 2);
+    }
+
+    #[test]
+    fn lines_are_limited_to_their_span() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let mut text_box = TextBox::new("ab cd ef", bounds, character_style);
+        text_box.set_line_span(|line, _| if line == 0 { 6..24 } else { -10..100 });
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        for &(text, top_left) in &[("ab", Point::new(6, 0)), ("cd ef", Point::new(0, 9))] {
+            TextBox::new(
+                text,
+                Rectangle::new(top_left, size_for(&FONT_6X9, 5, 1)),
+                character_style,
+            )
+            .draw(&mut expected)
+            .unwrap();
+        }
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn lines_are_snapped_to_the_baseline_grid() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::new(0, 3), size_for(&FONT_6X9, 2, 3));
+
+        let mut display = MockDisplay::new();
+        TextBox::with_textbox_style(
+            "a\nb",
+            bounds,
+            character_style,
+            TextBoxStyleBuilder::new().baseline_grid(10).build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        let mut expected = MockDisplay::new();
+        for &(text, y) in &[("a", 10), ("b", 20)] {
+            TextBox::new(
+                text,
+                Rectangle::new(Point::new(0, y), size_for(&FONT_6X9, 2, 1)),
+                character_style,
+            )
+            .draw(&mut expected)
+            .unwrap();
+        }
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn relative_line_height_follows_the_font() {
+        use embedded_graphics::{mono_font::ascii::FONT_10X20, text::LineHeight};
+
+        let style = TextBoxStyleBuilder::new()
+            .line_height(LineHeight::Percent(150))
+            .build();
+
+        for &(font, spacing) in &[(&FONT_6X9, 13), (&FONT_10X20, 30)] {
+            let character_style = MonoTextStyleBuilder::new()
+                .font(font)
+                .text_color(BinaryColor::On)
+                .build();
+            let bounds = Rectangle::new(Point::zero(), size_for(font, 1, 3));
+
+            let mut display = MockDisplay::new();
+            TextBox::with_textbox_style("a\nb", bounds, character_style, style)
+                .draw(&mut display)
+                .unwrap();
+
+            let mut expected = MockDisplay::new();
+            for &(text, y) in &[("a", 0), ("b", spacing)] {
+                TextBox::new(
+                    text,
+                    Rectangle::new(Point::new(0, y), size_for(font, 1, 1)),
+                    character_style,
+                )
+                .draw(&mut expected)
+                .unwrap();
+            }
+
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn paragraphs_are_indented() {
+        use crate::style::ParagraphIndent;
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        for &(indent, expected) in &[
+            (ParagraphIndent::Every(6), " ab\n cd\nef"),
+            (ParagraphIndent::AfterFirst(6), "ab\n cd\nef"),
+        ] {
+            let mut display = MockDisplay::new();
+            TextBox::with_textbox_style(
+                "ab\ncd ef",
+                bounds,
+                character_style,
+                TextBoxStyleBuilder::new().paragraph_indent(indent).build(),
+            )
+            .draw(&mut display)
+            .unwrap();
+
+            let mut expected_display = MockDisplay::new();
+            TextBox::new(expected, bounds, character_style)
+                .draw(&mut expected_display)
+                .unwrap();
+
+            display.assert_eq(&expected_display);
+        }
+    }
+
+    #[test]
+    fn interrupted_drawing_returns_the_rest() {
+        use crate::DrawProgress;
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 7, 2));
+        let style = TextBoxStyleBuilder::new().columns(2, 6).build();
+        let text = "ab cd ef gh";
+
+        for &(lines, expected, progress) in &[
+            (1, "ab", DrawProgress::Interrupted("cd ef gh")),
+            (3, "ab  ef\ncd", DrawProgress::Interrupted("gh")),
+            (5, "ab  ef\ncd  gh", DrawProgress::Finished("")),
+        ] {
+            let mut display = MockDisplay::new();
+            let mut drawn = 0;
+            let result = TextBox::with_textbox_style(text, bounds, character_style, style)
+                .draw_until(&mut display, || {
+                    drawn += 1;
+                    drawn == lines
+                })
+                .unwrap();
+            assert_eq!(result, progress);
+
+            let mut expected_display = MockDisplay::new();
+            TextBox::new(expected, bounds, character_style)
+                .draw(&mut expected_display)
+                .unwrap();
+
+            display.assert_eq(&expected_display);
+        }
+    }
 }
@@ -0,0 +1,387 @@
+//! Find the displayed areas of ranges of the text.
+use core::{convert::Infallible, ops::Range};
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    geometry::Point,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    parser::Parser,
+    plugin::{NoPlugin, PluginMarker as Plugin, PluginWrapper},
+    rendering::{
+        cursor::LineCursor,
+        layout::{LaidOutLine, LineLayout},
+        line_iter::{ElementHandler, LineElementParser},
+    },
+    utils::{offset_in, str_width},
+    TextBox,
+};
+
+/// The displayed area of an interactive span of the text, in a single line.
+///
+/// See [`TextBox::regions`].
+///
+/// [`TextBox::regions`]: struct.TextBox.html#method.regions
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Region {
+    /// The ID of the span.
+    pub id: u32,
+
+    /// The area covered by the span in a single line.
+    pub area: Rectangle,
+}
+
+/// Iterator over the displayed areas of interactive spans.
+///
+/// This struct is created by [`TextBox::regions`].
+///
+/// [`TextBox::regions`]: struct.TextBox.html#method.regions
+pub struct Regions<'a, 'b, 'r, S, M>
+where
+    S: TextRenderer,
+{
+    text_box: &'b TextBox<'a, S, M>,
+    spans: &'r [(Range<usize>, u32)],
+    lines: LineLayout<'a, 'b, S, M>,
+    line: Option<LaidOutLine<'a>>,
+    next_span: usize,
+}
+
+/// Finds the horizontal extent of a range of the text in a line.
+struct SpanHandler<'a, 'b, S> {
+    style: &'b S,
+    text: &'a str,
+    range: Range<usize>,
+    pos: i32,
+    from: Option<i32>,
+    to: i32,
+}
+
+impl<'a, 'b, S> SpanHandler<'a, 'b, S> {
+    /// Advances the position over `st`, splitting `width` evenly between its characters.
+    fn advance(&mut self, st: &str, width: u32, mut char_width: impl FnMut(char) -> u32) {
+        let offset = match offset_in(self.text, st) {
+            Some(offset) => offset,
+            None => {
+                self.pos += width.saturating_as::<i32>();
+                return;
+            }
+        };
+
+        for (idx, c) in st.char_indices() {
+            let start = self.pos;
+            self.pos += char_width(c).saturating_as::<i32>();
+
+            if self.range.contains(&(offset + idx)) {
+                self.from.get_or_insert(start);
+                self.to = self.pos;
+            }
+        }
+    }
+}
+
+impl<'a, 'b, S> ElementHandler<'_> for SpanHandler<'a, 'b, S>
+where
+    S: TextRenderer,
+    S::Color: From<Rgb888>,
+{
+    type Error = Infallible;
+    type Color = S::Color;
+
+    fn measure(&self, st: &str) -> u32 {
+        str_width(self.style, st)
+    }
+
+    fn whitespace(&mut self, st: &str, _space_count: u32, width: u32) -> Result<(), Self::Error> {
+        let count = st.chars().count().saturating_as::<u32>().max(1);
+        let single = width / count;
+        let last = width - single * (count - 1);
+        let mut remaining = count;
+
+        self.advance(st, width, |_| {
+            remaining -= 1;
+            if remaining == 0 {
+                last
+            } else {
+                single
+            }
+        });
+
+        Ok(())
+    }
+
+    fn printed_characters(&mut self, st: &str, width: u32) -> Result<(), Self::Error> {
+        let style = self.style;
+        let mut buffer = [0; 4];
+        self.advance(st, width, |c| str_width(style, c.encode_utf8(&mut buffer)));
+
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.pos += by;
+        Ok(())
+    }
+
+    fn custom(&mut self, _id: u32, width: u32) -> Result<(), Self::Error> {
+        self.pos += width.saturating_as::<i32>();
+        Ok(())
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Returns the displayed areas of interactive spans of the text.
+    ///
+    /// Every span is a pair of a byte range in the text of the text box, and an ID chosen by the
+    /// application. The returned areas can be used as touch targets, which makes it possible to
+    /// render buttons or menu entries as text. A span that is wrapped into multiple lines returns
+    /// one area for every line. Areas are returned line by line, and in the order of `spans`
+    /// within a line. Spans that are not displayed, for example because they are scrolled out of
+    /// the bounding box, don't return an area.
+    ///
+    /// The areas are laid out the same way [`draw`] renders the text, so they follow the
+    /// alignment, wrapping and vertical offset of the text box.
+    ///
+    /// *Note:* lines are measured without plugins.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// const OK: u32 = 0;
+    /// const CANCEL: u32 = 1;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(90, 9));
+    ///
+    /// let text_box = TextBox::new("[OK] [Cancel]", bounds, character_style);
+    /// let spans = [(0..4, OK), (5..13, CANCEL)];
+    ///
+    /// let mut regions = text_box.regions(&spans);
+    /// assert_eq!(
+    ///     regions.next().map(|region| region.area),
+    ///     Some(Rectangle::new(Point::new(0, 0), Size::new(24, 9)))
+    /// );
+    /// assert_eq!(
+    ///     regions.next().map(|region| region.area),
+    ///     Some(Rectangle::new(Point::new(30, 0), Size::new(48, 9)))
+    /// );
+    ///
+    /// assert_eq!(text_box.region_at(&spans, Point::new(40, 4)), Some(CANCEL));
+    /// ```
+    ///
+    /// [`draw`]: #method.draw
+    #[inline]
+    pub fn regions<'b, 'r>(
+        &'b self,
+        spans: &'r [(Range<usize>, u32)],
+    ) -> Regions<'a, 'b, 'r, S, M> {
+        Regions {
+            text_box: self,
+            spans,
+            lines: LineLayout::new(self, self.text),
+            line: None,
+            next_span: 0,
+        }
+    }
+
+    /// Returns the ID of the interactive span that is displayed at the given point.
+    ///
+    /// If multiple spans cover the point, the ID of the first one is returned. See
+    /// [`regions`] for details.
+    ///
+    /// [`regions`]: #method.regions
+    #[inline]
+    pub fn region_at(&self, spans: &[(Range<usize>, u32)], point: Point) -> Option<u32> {
+        self.regions(spans)
+            .find(|region| region.area.contains(point))
+            .map(|region| region.id)
+    }
+
+    /// Returns the horizontal extent of `range` in the given line, relative to the line position.
+    fn span_extent(&self, line: &LaidOutLine<'a>, range: Range<usize>) -> Option<(i32, i32)> {
+        let plugin = PluginWrapper::new(NoPlugin::new());
+        plugin.set_source_line_start(line.paragraph_start);
+        let width = self.text_bounds().size.width;
+        let text = &self.text[line.offset..];
+
+        let lm = self.style.measure_line(
+            &plugin.clone(),
+            &self.character_style,
+            &mut Parser::parse(text).with_style(&self.style),
+            width,
+        );
+        let (_, space_config) =
+            self.style
+                .alignment
+                .place_line(&self.character_style, self.style.word_spacing, lm);
+
+        let mut handler = SpanHandler {
+            style: &self.character_style,
+            text: self.text,
+            range,
+            pos: 0,
+            from: None,
+            to: 0,
+        };
+
+        let cursor = LineCursor::new(
+            width,
+            self.style.tab_size.into_pixels(&self.character_style),
+        );
+        LineElementParser::new(
+            &mut Parser::parse(text).with_style(&self.style),
+            &plugin,
+            cursor,
+            space_config,
+            &self.style,
+        )
+        .process(&mut handler)
+        .unwrap();
+
+        handler.from.map(|from| (from, handler.to))
+    }
+}
+
+impl<'a, 'b, 'r, S, M> Iterator for Regions<'a, 'b, 'r, S, M>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as TextRenderer>::Color: From<Rgb888>,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    type Item = Region;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.line.as_ref() {
+                Some(line) if self.next_span < self.spans.len() => line,
+                _ => {
+                    self.line = Some(self.lines.next()?);
+                    self.next_span = 0;
+                    continue;
+                }
+            };
+
+            let (range, id) = self.spans[self.next_span].clone();
+            self.next_span += 1;
+
+            let line_range = line.offset..line.offset + line.text.len();
+            if range.start >= line_range.end || range.end <= line_range.start {
+                continue;
+            }
+
+            let (from, to) = match self.text_box.span_extent(line, range) {
+                Some(extent) => extent,
+                None => continue,
+            };
+
+            let area = line.area(from.saturating_as(), to.saturating_as());
+            if area.size.width > 0 && area.size.height > 0 {
+                return Some(Region { id, area });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::HorizontalAlignment, rendering::regions::Region, style::TextBoxStyle,
+        utils::test::size_for, TextBox,
+    };
+
+    fn region(id: u32, x: i32, y: i32, width: u32) -> Region {
+        Region {
+            id,
+            area: Rectangle::new(Point::new(x, y), Size::new(width, 9)),
+        }
+    }
+
+    #[track_caller]
+    fn assert_regions(
+        alignment: HorizontalAlignment,
+        text: &str,
+        spans: &[(core::ops::Range<usize>, u32)],
+        expected: &[Region],
+    ) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let text_box = TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3)),
+            character_style,
+            TextBoxStyle::with_alignment(alignment),
+        );
+
+        let regions = text_box.regions(spans).collect::<Vec<_>>();
+        assert_eq!(regions, expected);
+    }
+
+    #[test]
+    fn spans_in_a_line() {
+        assert_regions(
+            HorizontalAlignment::Left,
+            "ab cd",
+            &[(3..5, 1), (0..2, 0)],
+            &[region(1, 18, 0, 12), region(0, 0, 0, 12)],
+        );
+    }
+
+    #[test]
+    fn wrapped_span_returns_an_area_per_line() {
+        assert_regions(
+            HorizontalAlignment::Left,
+            "ab cd ef",
+            &[(3..8, 7)],
+            &[region(7, 18, 0, 12), region(7, 0, 9, 12)],
+        );
+    }
+
+    #[test]
+    fn areas_follow_the_alignment() {
+        assert_regions(
+            HorizontalAlignment::Right,
+            "ab",
+            &[(1..2, 0)],
+            &[region(0, 24, 0, 6)],
+        );
+    }
+
+    #[test]
+    fn region_at() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+        let text_box = TextBox::new("ab cd", bounds, character_style);
+
+        let spans = [(0..2, 0), (3..5, 1)];
+        assert_eq!(text_box.region_at(&spans, Point::new(5, 4)), Some(0));
+        assert_eq!(text_box.region_at(&spans, Point::new(14, 4)), None);
+        assert_eq!(text_box.region_at(&spans, Point::new(20, 4)), Some(1));
+        assert_eq!(text_box.region_at(&spans, Point::new(20, 12)), None);
+    }
+}
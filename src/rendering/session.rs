@@ -0,0 +1,239 @@
+//! Drawing a text box in multiple steps.
+
+use core::task::Poll;
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    plugin::PluginMarker as Plugin,
+    rendering::{cursor::Cursor, line::LineRenderState, LineProgress, SharedMeasurements},
+    TextBox,
+};
+
+/// A resumable rendering of a text box.
+///
+/// Drawing a large amount of text can take longer than a main loop iteration or a frame is
+/// allowed to take. A `RenderSession` draws the text box over multiple calls to [`step`], each
+/// drawing whole lines until a pixel budget is used up. The background and the border are drawn
+/// by the first step.
+///
+/// The session keeps the position and the text style between steps, so the display must not be
+/// cleared until the session is finished. Text boxes with multiple columns are drawn completely
+/// by the first step.
+///
+/// This struct is created by [`TextBox::render_session`].
+///
+/// # Example
+///
+/// ```rust
+/// use core::task::Poll;
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::TextBox;
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 36));
+/// let text_box = TextBox::new("First line\nSecond", bounds, character_style);
+///
+/// let mut display = MockDisplay::new();
+/// let mut session = text_box.render_session();
+///
+/// // Draw a single line in every step.
+/// let mut steps = 1;
+/// while session.step(&mut display, 1).unwrap().is_pending() {
+///     steps += 1;
+/// }
+/// assert_eq!(steps, 2);
+/// ```
+///
+/// [`step`]: #method.step
+/// [`TextBox::render_session`]: ../struct.TextBox.html#method.render_session
+pub struct RenderSession<'a, 'b, F, M>
+where
+    F: TextRenderer + CharacterStyle,
+    M: Plugin<'a, <F as TextRenderer>::Color>,
+{
+    text_box: &'b TextBox<'a, F, M>,
+    shared: SharedMeasurements,
+    cursor: Cursor,
+    progress: LineProgress,
+    started: bool,
+
+    /// The render state of the next line, or `None` if the session is finished.
+    state: Option<LineRenderState<'a, 'b, F, M>>,
+
+    /// The text that wasn't drawn.
+    rest: &'a str,
+}
+
+impl<'a, F, M> TextBox<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    /// Starts drawing the text box in multiple steps.
+    ///
+    /// See [`RenderSession`] for details.
+    ///
+    /// [`RenderSession`]: struct.RenderSession.html
+    #[inline]
+    pub fn render_session<'b>(&'b self) -> RenderSession<'a, 'b, F, M> {
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+        let cursor = self.start_cursor(self.text, &self.plugin, &shared);
+
+        RenderSession {
+            text_box: self,
+            shared,
+            cursor,
+            progress: LineProgress::default(),
+            started: false,
+            state: Some(self.initial_state(&self.plugin)),
+            rest: self.text,
+        }
+    }
+}
+
+impl<'a, 'b, F, M> RenderSession<'a, 'b, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    /// Draws the next lines of the text box.
+    ///
+    /// Lines are drawn until the number of displayed pixels of the drawn lines reaches
+    /// `budget_pixels`. At least one line is drawn by every step, so the session always makes
+    /// progress. Returns `Poll::Ready` with the text that didn't fit into the text box when the
+    /// session is finished, and `Poll::Pending` if there are lines left to draw.
+    #[inline]
+    pub fn step<D>(
+        &mut self,
+        display: &mut D,
+        budget_pixels: u32,
+    ) -> Result<Poll<&'a str>, D::Error>
+    where
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        let text_box = self.text_box;
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => return Ok(Poll::Ready(self.rest)),
+        };
+
+        if !self.started {
+            self.started = true;
+
+            if text_box.style.columns.count > 1 {
+                self.rest = text_box.draw_with_buffer(
+                    display,
+                    &self.shared,
+                    &mut self.progress,
+                    None,
+                    &mut [],
+                )?;
+                return Ok(Poll::Ready(self.rest));
+            }
+
+            text_box.fill_background(display, &self.cursor)?;
+            text_box.draw_border(display)?;
+        }
+
+        let mut used = 0_u32;
+        let mut pause = |pixels: u32| {
+            used = used.saturating_add(pixels);
+            used >= budget_pixels
+        };
+
+        let (rest, state) = text_box.draw_lines(
+            display,
+            &mut self.cursor,
+            state,
+            &mut self.progress,
+            Some(&mut pause),
+            &mut [],
+        )?;
+        self.rest = rest;
+
+        if self.progress.paused {
+            self.state = Some(state);
+            Ok(Poll::Pending)
+        } else {
+            Ok(Poll::Ready(rest))
+        }
+    }
+
+    /// Returns whether every line has been drawn.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.state.is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::task::Poll;
+
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::VerticalAlignment, style::TextBoxStyleBuilder, utils::test::size_for, TextBox,
+    };
+
+    #[test]
+    fn steps_draw_the_same_pixels_as_draw() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Middle)
+            .build();
+        let bounds = Rectangle::new(Point::new(1, 2), size_for(&FONT_6X9, 5, 6));
+
+        let text_box =
+            TextBox::with_textbox_style("The quick brown fox", bounds, character_style, style);
+
+        let mut expected = MockDisplay::new();
+        let expected_rest = text_box.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        let mut session = text_box.render_session();
+
+        // Two lines fit into the budget of every step.
+        let budget = bounds.size.width * 9 * 2;
+        let mut steps = 0;
+        let rest = loop {
+            steps += 1;
+            if let Poll::Ready(rest) = session.step(&mut display, budget).unwrap() {
+                break rest;
+            }
+        };
+
+        assert_eq!(steps, 2);
+        assert_eq!(rest, expected_rest);
+        assert!(session.is_finished());
+        assert_eq!(
+            session.step(&mut display, budget).unwrap(),
+            Poll::Ready(rest)
+        );
+
+        display.assert_eq(&expected);
+    }
+}
@@ -0,0 +1,244 @@
+//! Textual snapshots of the rendered elements.
+use alloc::string::String;
+use core::{
+    convert::Infallible,
+    fmt::{Debug, Write},
+};
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    text::renderer::{CharacterStyle, TextRenderer},
+};
+
+use crate::{
+    parser::{ChangeTextStyle, Parser},
+    plugin::{PluginMarker as Plugin, ProcessingState},
+    rendering::{
+        line::UnderlineState,
+        line_iter::{ElementHandler, LineElementParser, LineEndType},
+        SharedMeasurements,
+    },
+    utils::str_width,
+    TextBox,
+};
+
+#[cfg(feature = "ansi")]
+use crate::rendering::cursor::CursorMove;
+
+/// Writes the elements of a line in a textual form.
+struct SnapshotHandler<'o, 'b, S>
+where
+    S: TextRenderer + CharacterStyle,
+{
+    output: &'o mut String,
+    style: S,
+    default_style: &'b S,
+    fonts: &'b [S],
+    font: Option<usize>,
+    underline: UnderlineState<<S as CharacterStyle>::Color>,
+}
+
+impl<'o, 'b, S> ElementHandler<'_> for SnapshotHandler<'o, 'b, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle + Clone,
+    <S as CharacterStyle>::Color: From<Rgb888> + Debug,
+{
+    type Error = Infallible;
+    type Color = <S as CharacterStyle>::Color;
+
+    fn measure(&self, st: &str) -> u32 {
+        str_width(&self.style, st)
+    }
+
+    fn whitespace(&mut self, st: &str, space_count: u32, _width: u32) -> Result<(), Self::Error> {
+        writeln!(self.output, "  space {:?} {}", st, space_count).ok();
+        Ok(())
+    }
+
+    fn printed_characters(&mut self, st: &str, _width: u32) -> Result<(), Self::Error> {
+        writeln!(self.output, "  text {:?}", st).ok();
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        writeln!(self.output, "  move {}", by).ok();
+        Ok(())
+    }
+
+    fn custom(&mut self, id: u32, _width: u32) -> Result<(), Self::Error> {
+        writeln!(self.output, "  custom {}", id).ok();
+        Ok(())
+    }
+
+    fn change_text_style(
+        &mut self,
+        change: ChangeTextStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        writeln!(self.output, "  style {:?}", change).ok();
+        change.apply(
+            &mut self.style,
+            self.default_style,
+            self.fonts,
+            &mut self.font,
+            &mut self.underline,
+        );
+        Ok(())
+    }
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888> + Debug,
+    M: Plugin<'a, <S as TextRenderer>::Color>,
+{
+    /// Returns the elements the text box is rendered from, in a textual form.
+    ///
+    /// The snapshot lists every line and the text, whitespace, custom tokens and text style
+    /// changes it consists of, after wrapping, alignment and plugins have been applied. Widths are
+    /// not included, so snapshots can be compared in tests of plugins and styles without
+    /// depending on the exact glyphs of a font, unlike pixel patterns. Cursor movements are
+    /// listed in pixels.
+    ///
+    /// The format is meant to be read by humans, and compared to previously recorded snapshots.
+    ///
+    /// This method is only available with the `snapshot` crate feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(30, 18));
+    ///
+    /// let text_box = TextBox::new("Hello World", bounds, character_style);
+    ///
+    /// assert_eq!(
+    ///     text_box.render_snapshot(),
+    ///     concat!(
+    ///         "line 0\n",
+    ///         "  text \"Hello\"\n",
+    ///         "  end LineBreak\n",
+    ///         "line 1\n",
+    ///         "  text \"World\"\n",
+    ///         "  end EndOfText\n",
+    ///     )
+    /// );
+    /// ```
+    #[inline]
+    pub fn render_snapshot(&self) -> String {
+        let mut output = String::new();
+
+        let shared = SharedMeasurements::new(&self.character_style, &self.style, true);
+        let plugin = self.plugin.clone();
+        let mut cursor = self.start_cursor(self.text, &plugin, &shared);
+        let mut parser = Parser::parse(self.text).with_style(&self.style);
+
+        let mut handler = SnapshotHandler {
+            output: &mut output,
+            style: self.character_style.clone(),
+            default_style: &self.character_style,
+            fonts: self.fonts,
+            font: None,
+            underline: UnderlineState::new(self.style.underline_color.map(Into::into)),
+        };
+
+        plugin.set_state(ProcessingState::Render);
+
+        for index in 0_u32.. {
+            plugin.new_line();
+            writeln!(handler.output, "line {}", index).ok();
+
+            let mut line_cursor = self.line_cursor(&cursor, index);
+
+            let measure_plugin = plugin.clone();
+            measure_plugin.set_state(ProcessingState::Measure);
+            let lm = self.style.measure_line_with_fonts(
+                &measure_plugin,
+                &self.character_style,
+                self.fonts,
+                &mut handler.font.clone(),
+                &mut parser.clone(),
+                line_cursor.line_width(),
+            );
+            let (left, space_config) =
+                self.style
+                    .alignment
+                    .place_line(&handler.style, self.style.word_spacing, lm);
+            line_cursor.move_cursor(left.saturating_as()).ok();
+
+            let end_type = LineElementParser::new(
+                &mut parser,
+                &plugin,
+                line_cursor,
+                space_config,
+                &self.style,
+            )
+            .process(&mut handler)
+            .unwrap();
+
+            writeln!(handler.output, "  end {:?}", end_type).ok();
+
+            match end_type {
+                LineEndType::EndOfText => break,
+                LineEndType::CarriageReturn => cursor.carriage_return(),
+                #[cfg(feature = "ansi")]
+                LineEndType::MoveCursor(movement) => cursor.move_to(movement),
+                #[cfg(feature = "ansi")]
+                LineEndType::EraseDisplay(x) => {
+                    cursor.move_to(CursorMove::Relative { lines: 0, x })
+                }
+                LineEndType::NewLine | LineEndType::LineBreak => cursor.new_line(),
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{plugin::markup::Markup, utils::test::size_for, TextBox};
+
+    #[test]
+    fn snapshot_lists_elements() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let text_box =
+            TextBox::new("a{black}b{/} c\nx", bounds, character_style).add_plugin(Markup::new());
+
+        assert_eq!(
+            text_box.render_snapshot(),
+            concat!(
+                "line 0\n",
+                "  text \"a\"\n",
+                "  style TextColor(Some(Off))\n",
+                "  text \"b\"\n",
+                "  style Reset\n",
+                "  space \" \" 1\n",
+                "  text \"c\"\n",
+                "  space \"\\n\" 0\n",
+                "  end NewLine\n",
+                "line 1\n",
+                "  text \"x\"\n",
+                "  end EndOfText\n",
+            )
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Space rendering config
 
+use az::SaturatingAs;
 use embedded_graphics::text::renderer::TextRenderer;
 
 use crate::utils::str_width;
@@ -12,13 +13,20 @@ pub struct SpaceConfig {
     /// Stores how many characters are rendered using the `width` width. This field changes
     /// during rendering.
     count: Option<u32>,
+
+    /// Extra pixels added to every whitespace block.
+    word_spacing: i32,
 }
 
 /// Retrieves size of space characters.
 impl SpaceConfig {
     /// Creates a new SpaceConfig object.
     pub fn new(width: u32, count: Option<u32>) -> Self {
-        Self { width, count }
+        Self {
+            width,
+            count,
+            word_spacing: 0,
+        }
     }
 
     pub fn new_from_renderer(renderer: &impl TextRenderer) -> Self {
@@ -26,11 +34,35 @@ impl SpaceConfig {
         Self::new(width, None)
     }
 
+    /// Sets the number of pixels added to every whitespace block.
+    pub fn with_word_spacing(self, word_spacing: i32) -> Self {
+        Self {
+            word_spacing,
+            ..self
+        }
+    }
+
+    /// Changes the width of the whitespace characters.
+    pub fn set_width(&mut self, width: u32) {
+        self.width = width;
+    }
+
+    /// Returns whether the spaces are stretched to fill the line.
+    pub fn is_stretched(&self) -> bool {
+        self.count.is_some()
+    }
+
     /// Look at the size of next n spaces, without advancing.
     pub fn peek_next_width(&self, n: u32) -> u32 {
-        match self.count {
+        let width = match self.count {
             None => n * self.width,
             Some(count) => n * self.width + count.min(n),
+        };
+
+        if n == 0 || self.word_spacing == 0 {
+            width
+        } else {
+            (width.saturating_as::<i32>() + self.word_spacing).max(0) as u32
         }
     }
 
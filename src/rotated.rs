@@ -0,0 +1,476 @@
+//! Rendering of rotated and mirrored text boxes.
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable, Pixel,
+};
+
+use crate::{plugin::PluginMarker as Plugin, TextBox};
+
+/// The clockwise rotation of a [`Rotated`] text box.
+///
+/// [`Rotated`]: struct.Rotated.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Rotation {
+    /// The text is rotated by 90 degrees clockwise, and reads from top to bottom.
+    Rotate90,
+
+    /// The text is rotated by 180 degrees, and is upside down.
+    Rotate180,
+
+    /// The text is rotated by 270 degrees clockwise, and reads from bottom to top.
+    Rotate270,
+}
+
+/// The mirroring of a [`Mirrored`] text box.
+///
+/// [`Mirrored`]: struct.Mirrored.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Mirror {
+    /// The text is mirrored horizontally, so it reads from right to left.
+    Horizontal,
+
+    /// The text is mirrored vertically, so it is upside down.
+    Vertical,
+
+    /// The text is mirrored horizontally and vertically.
+    Both,
+}
+
+impl Rotation {
+    /// Returns the size of the unrotated area that is rotated into an area of `size`.
+    fn layout_size(self, size: Size) -> Size {
+        match self {
+            Rotation::Rotate90 | Rotation::Rotate270 => Size::new(size.height, size.width),
+            Rotation::Rotate180 => size,
+        }
+    }
+}
+
+/// A text box that is drawn rotated.
+///
+/// The text is laid out normally, in an area that is as large as the bounding box of the text
+/// box rotated back, and the pixels are rotated into the bounding box while they are drawn. This
+/// makes it possible to draw text on displays that are mounted in portrait orientation, or to
+/// draw side labels, without a rotating draw target.
+///
+/// This struct is created by [`TextBox::rotated`]. The bounding box of the text box is the area
+/// the rotated text is drawn into. Text that is drawn outside of the bounding box, for example
+/// lines that are partially visible, is not drawn.
+///
+/// *Note:* the height mode of the text box style is applied to the unrotated layout, but the
+/// rotated text is always positioned in the original bounding box.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{Rotation, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+///
+/// // A narrow label, that is tall enough for 6 characters.
+/// let bounds = Rectangle::new(Point::zero(), Size::new(9, 36));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("Volume", bounds, character_style)
+///     .rotated(Rotation::Rotate270)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`TextBox::rotated`]: struct.TextBox.html#method.rotated
+#[derive(Clone)]
+#[must_use]
+pub struct Rotated<'a, S, M>
+where
+    S: TextRenderer,
+{
+    text_box: TextBox<'a, S, M>,
+    bounds: Rectangle,
+    rotation: Rotation,
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer,
+    M: Plugin<'a, S::Color>,
+{
+    /// Draws the text box rotated into its bounding box.
+    ///
+    /// See [`Rotated`] for more information.
+    ///
+    /// [`Rotated`]: struct.Rotated.html
+    #[inline]
+    pub fn rotated(mut self, rotation: Rotation) -> Rotated<'a, S, M> {
+        let bounds = self.bounds;
+        self.bounds = Rectangle::new(bounds.top_left, rotation.layout_size(bounds.size));
+
+        Rotated {
+            text_box: self,
+            bounds,
+            rotation,
+        }
+    }
+}
+
+impl<'a, S, M> Rotated<'a, S, M>
+where
+    S: TextRenderer,
+{
+    /// Returns the rotation.
+    #[inline]
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Returns the text box, with its bounding box rotated back.
+    #[inline]
+    pub fn into_inner(mut self) -> TextBox<'a, S, M> {
+        self.text_box.bounds = self.bounds;
+        self.text_box
+    }
+}
+
+impl<'a, S, M> Dimensions for Rotated<'a, S, M>
+where
+    S: TextRenderer,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl<'a, F, M> Drawable for Rotated<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = &'a str;
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let mut target = TransformedTarget {
+            target: display,
+            transformer: Transformer {
+                area: self.text_box.bounds,
+                transform: Transform::Rotate(self.rotation),
+            },
+        };
+
+        self.text_box.draw(&mut target)
+    }
+}
+
+/// A text box that is drawn mirrored.
+///
+/// The text is laid out normally, and the pixels are mirrored inside the bounding box while they
+/// are drawn. Mirrored text is meant for displays that are viewed through a reflection, like
+/// head-up displays and teleprompters.
+///
+/// This struct is created by [`TextBox::mirrored`]. Text that is drawn outside of the bounding
+/// box, for example lines that are partially visible, is not drawn.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{Mirror, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut display = MockDisplay::new();
+/// TextBox::new("80 km/h", bounds, character_style)
+///     .mirrored(Mirror::Horizontal)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`TextBox::mirrored`]: struct.TextBox.html#method.mirrored
+#[derive(Clone)]
+#[must_use]
+pub struct Mirrored<'a, S, M>
+where
+    S: TextRenderer,
+{
+    text_box: TextBox<'a, S, M>,
+    mirror: Mirror,
+}
+
+impl<'a, S, M> TextBox<'a, S, M>
+where
+    S: TextRenderer,
+    M: Plugin<'a, S::Color>,
+{
+    /// Draws the text box mirrored inside its bounding box.
+    ///
+    /// See [`Mirrored`] for more information.
+    ///
+    /// [`Mirrored`]: struct.Mirrored.html
+    #[inline]
+    pub fn mirrored(self, mirror: Mirror) -> Mirrored<'a, S, M> {
+        Mirrored {
+            text_box: self,
+            mirror,
+        }
+    }
+}
+
+impl<'a, S, M> Mirrored<'a, S, M>
+where
+    S: TextRenderer,
+{
+    /// Returns the mirroring.
+    #[inline]
+    pub fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Returns the text box.
+    #[inline]
+    pub fn into_inner(self) -> TextBox<'a, S, M> {
+        self.text_box
+    }
+}
+
+impl<'a, S, M> Dimensions for Mirrored<'a, S, M>
+where
+    S: TextRenderer,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.text_box.bounds
+    }
+}
+
+impl<'a, F, M> Drawable for Mirrored<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = &'a str;
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let mut target = TransformedTarget {
+            target: display,
+            transformer: Transformer {
+                area: self.text_box.bounds,
+                transform: Transform::Mirror(self.mirror),
+            },
+        };
+
+        self.text_box.draw(&mut target)
+    }
+}
+
+/// A rotation or a mirroring.
+#[derive(Copy, Clone)]
+enum Transform {
+    Rotate(Rotation),
+    Mirror(Mirror),
+}
+
+/// Moves the points of `area` to where they are drawn by a `Rotated` or `Mirrored` text box.
+#[derive(Copy, Clone)]
+struct Transformer {
+    area: Rectangle,
+    transform: Transform,
+}
+
+impl Transformer {
+    fn transform(self, point: Point) -> Point {
+        let Point { x, y } = point - self.area.top_left;
+        let width = self.area.size.width.saturating_as::<i32>();
+        let height = self.area.size.height.saturating_as::<i32>();
+
+        let transformed = match self.transform {
+            Transform::Rotate(Rotation::Rotate90) => Point::new(height - 1 - y, x),
+            Transform::Rotate(Rotation::Rotate180) | Transform::Mirror(Mirror::Both) => {
+                Point::new(width - 1 - x, height - 1 - y)
+            }
+            Transform::Rotate(Rotation::Rotate270) => Point::new(y, width - 1 - x),
+            Transform::Mirror(Mirror::Horizontal) => Point::new(width - 1 - x, y),
+            Transform::Mirror(Mirror::Vertical) => Point::new(x, height - 1 - y),
+        };
+
+        self.area.top_left + transformed
+    }
+}
+
+/// Draw target that transforms the pixels drawn into the area of the transformer.
+struct TransformedTarget<'d, D> {
+    target: &'d mut D,
+    transformer: Transformer,
+}
+
+impl<D> Dimensions for TransformedTarget<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.transformer.area
+    }
+}
+
+impl<D> DrawTarget for TransformedTarget<'_, D>
+where
+    D: DrawTarget,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let transformer = self.transformer;
+
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(move |Pixel(point, _)| transformer.area.contains(*point))
+                .map(move |Pixel(point, color)| Pixel(transformer.transform(point), color)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.transformer.area);
+        match area.bottom_right() {
+            Some(bottom_right) => {
+                let area = Rectangle::with_corners(
+                    self.transformer.transform(area.top_left),
+                    self.transformer.transform(bottom_right),
+                );
+                self.target.fill_solid(&area, color)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, Mirror, Rotation, TextBox};
+
+    #[test]
+    fn pixels_are_rotated() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let size = size_for(&FONT_6X9, 2, 1);
+        let (width, height) = (size.width as i32, size.height as i32);
+
+        let mut unrotated = MockDisplay::new();
+        TextBox::new("ab", Rectangle::new(Point::zero(), size), character_style)
+            .draw(&mut unrotated)
+            .unwrap();
+
+        for &(rotation, rotated_size) in &[
+            (Rotation::Rotate90, Size::new(size.height, size.width)),
+            (Rotation::Rotate180, size),
+            (Rotation::Rotate270, Size::new(size.height, size.width)),
+        ] {
+            let mut display = MockDisplay::new();
+            TextBox::new(
+                "ab",
+                Rectangle::new(Point::zero(), rotated_size),
+                character_style,
+            )
+            .rotated(rotation)
+            .draw(&mut display)
+            .unwrap();
+
+            let mut expected = MockDisplay::new();
+            for y in 0..height {
+                for x in 0..width {
+                    let point = match rotation {
+                        Rotation::Rotate90 => Point::new(height - 1 - y, x),
+                        Rotation::Rotate180 => Point::new(width - 1 - x, height - 1 - y),
+                        Rotation::Rotate270 => Point::new(y, width - 1 - x),
+                    };
+                    if let Some(color) = unrotated.get_pixel(Point::new(x, y)) {
+                        expected.set_pixel(point, Some(color));
+                    }
+                }
+            }
+
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn pixels_are_mirrored() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1));
+        let (width, height) = (bounds.size.width as i32, bounds.size.height as i32);
+
+        let mut unmirrored = MockDisplay::new();
+        TextBox::new("ab", bounds, character_style)
+            .draw(&mut unmirrored)
+            .unwrap();
+
+        for &mirror in &[Mirror::Horizontal, Mirror::Vertical, Mirror::Both] {
+            let mut display = MockDisplay::new();
+            TextBox::new("ab", bounds, character_style)
+                .mirrored(mirror)
+                .draw(&mut display)
+                .unwrap();
+
+            let mut expected = MockDisplay::new();
+            for y in 0..height {
+                for x in 0..width {
+                    let point = match mirror {
+                        Mirror::Horizontal => Point::new(width - 1 - x, y),
+                        Mirror::Vertical => Point::new(x, height - 1 - y),
+                        Mirror::Both => Point::new(width - 1 - x, height - 1 - y),
+                    };
+                    if let Some(color) = unmirrored.get_pixel(Point::new(x, y)) {
+                        expected.set_pixel(point, Some(color));
+                    }
+                }
+            }
+
+            display.assert_eq(&expected);
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Text that is read in chunks.
+
+use core::slice::Iter;
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+use crate::{style::BackgroundFill, TextBox, TextBoxAppender};
+
+/// A source of text that is read one chunk at a time.
+///
+/// Text that is stored outside of RAM, like in an external flash chip, or text that is generated
+/// while it is drawn, can be drawn by [`TextBox::draw_source`] without first collecting the whole
+/// document in a single `&str`. A chunk only needs to be valid until the next chunk is requested,
+/// so sources can reuse a single buffer for every chunk.
+///
+/// Chunks contain UTF-8 encoded text. A chunk may end in the middle of a word, or in the middle of
+/// a multi-byte character, the rest of which is read from the next chunk.
+///
+/// `TextSource` is implemented for iterators over slices of string slices and byte slices, which
+/// return the slices as chunks.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_text::TextSource;
+///
+/// /// Reads text from a storage in 16 byte blocks.
+/// struct Blocks<'a> {
+///     storage: &'a [u8],
+///     buffer: [u8; 16],
+/// }
+///
+/// impl TextSource for Blocks<'_> {
+///     fn next_chunk(&mut self) -> Option<&[u8]> {
+///         let len = self.storage.len().min(self.buffer.len());
+///         if len == 0 {
+///             return None;
+///         }
+///
+///         let (block, rest) = self.storage.split_at(len);
+///         self.buffer[..len].copy_from_slice(block);
+///         self.storage = rest;
+///
+///         Some(&self.buffer[..len])
+///     }
+/// }
+/// ```
+///
+/// [`TextBox::draw_source`]: struct.TextBox.html#method.draw_source
+pub trait TextSource {
+    /// Returns the next chunk of text, or `None` if there is no more text.
+    fn next_chunk(&mut self) -> Option<&[u8]>;
+}
+
+impl<'a, 'b> TextSource for Iter<'b, &'a str> {
+    #[inline]
+    fn next_chunk(&mut self) -> Option<&[u8]> {
+        self.next().map(|chunk| chunk.as_bytes())
+    }
+}
+
+impl<'a, 'b> TextSource for Iter<'b, &'a [u8]> {
+    #[inline]
+    fn next_chunk(&mut self) -> Option<&[u8]> {
+        self.next().copied()
+    }
+}
+
+/// A text box that displays bytes which may not be valid UTF-8.
+///
+/// Raw data, like the receive buffer of a UART, can contain bytes that are not valid UTF-8.
+/// `LossyTextBox` validates the bytes while they are laid out, and replaces every invalid sequence
+/// with the replacement character, `"\u{fffd}"` (�), like `String::from_utf8_lossy` does, but
+/// without allocating a new string. The replacement character is part of the word it appears in,
+/// so words are wrapped the same way as if the bytes were converted to a string first. The font of
+/// the character style must contain the replacement glyph.
+///
+/// Create a `LossyTextBox` using [`TextBox::from_bytes_lossy`]. The bytes are drawn like the
+/// chunks of [`TextBox::draw_source`], so the same limitations apply to the style of the text box.
+/// Drawing returns the number of bytes that were drawn.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::TextBox;
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let received = b"OK\xff\r\n";
+///
+/// let mut display = MockDisplay::new();
+/// let drawn = TextBox::from_bytes_lossy(received, bounds, character_style)
+///     .draw(&mut display)
+///     .unwrap();
+///
+/// assert_eq!(drawn, received.len());
+/// ```
+///
+/// [`TextBox::from_bytes_lossy`]: struct.TextBox.html#method.from_bytes_lossy
+/// [`TextBox::draw_source`]: struct.TextBox.html#method.draw_source
+#[derive(Clone)]
+#[must_use]
+pub struct LossyTextBox<'a, S>
+where
+    S: TextRenderer,
+{
+    /// The bytes to be displayed.
+    pub bytes: &'a [u8],
+
+    /// The text box that the bytes are drawn into. Its text is not drawn.
+    pub text_box: TextBox<'a, S>,
+}
+
+impl<'a, F> Drawable for LossyTextBox<'a, F>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = usize;
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.text_box
+            .draw_chunks(&mut [self.bytes].iter(), display, true)
+    }
+}
+
+impl<'a, F> TextBox<'a, F>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Draws the text read from `source` instead of the text of the text box.
+    ///
+    /// The chunks of the source are drawn one after the other, like by
+    /// [`TextBoxAppender::append_source`], so only a single chunk, and the unfinished word at its
+    /// end, is kept in memory at any time. The border and the background fill of the text box are
+    /// drawn like by `draw`.
+    ///
+    /// Returns the number of bytes of text that were drawn. Drawing stops when the text doesn't
+    /// fit into the text box, or at the first invalid UTF-8 sequence.
+    ///
+    /// This method is only available for text boxes without plugins. Because the text is not
+    /// measured before it is drawn, it can only be left aligned and aligned to the top, it can't
+    /// be split into columns, and the background can't be filled behind the text only.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the text box is not left aligned, not aligned to the top, has
+    /// more than one column, or uses [`BackgroundFill::Text`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::TextBox;
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+    ///
+    /// let chunks = ["Hello, Wo", "rld!"];
+    ///
+    /// let mut display = MockDisplay::new();
+    /// let drawn = TextBox::new("", bounds, character_style)
+    ///     .draw_source(&mut chunks.iter(), &mut display)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(drawn, 13);
+    /// ```
+    ///
+    /// [`TextBoxAppender::append_source`]: struct.TextBoxAppender.html#method.append_source
+    /// [`BackgroundFill::Text`]: style/enum.BackgroundFill.html#variant.Text
+    #[inline]
+    pub fn draw_source<T, D>(&self, source: &mut T, display: &mut D) -> Result<usize, D::Error>
+    where
+        T: TextSource + ?Sized,
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        self.draw_chunks(source, display, false)
+    }
+
+    /// Creates a text box that displays `bytes`, replacing the invalid UTF-8 sequences.
+    ///
+    /// See [`LossyTextBox`] for more information.
+    ///
+    /// [`LossyTextBox`]: struct.LossyTextBox.html
+    #[inline]
+    pub fn from_bytes_lossy(
+        bytes: &'a [u8],
+        bounds: Rectangle,
+        character_style: F,
+    ) -> LossyTextBox<'a, F> {
+        LossyTextBox {
+            bytes,
+            text_box: TextBox::new("", bounds, character_style),
+        }
+    }
+
+    /// Draws the text read from `source`, like `draw_source`. If `lossy` is set, invalid UTF-8
+    /// sequences are replaced instead of ending the text.
+    fn draw_chunks<T, D>(
+        &self,
+        source: &mut T,
+        display: &mut D,
+        lossy: bool,
+    ) -> Result<usize, D::Error>
+    where
+        T: TextSource + ?Sized,
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        debug_assert!(
+            !matches!(self.style.background_fill, BackgroundFill::Text(_))
+                && self.style.columns.count <= 1,
+            "draw_source can't fill the background behind the text only, or draw columns"
+        );
+
+        self.fill_background(display, &self.top_cursor())?;
+        self.draw_border(display)?;
+
+        let mut appender = TextBoxAppender::new(
+            self.text_bounds(),
+            self.themed_character_style(),
+            self.style,
+        );
+        appender.append_chunks(source, display, lossy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        style::{BackgroundFill, TextBoxStyleBuilder},
+        utils::test::size_for,
+        TextBox, TextSource,
+    };
+
+    /// Text source that returns every chunk from the same buffer.
+    struct Generated {
+        buffer: [u8; 2],
+        remaining: u8,
+    }
+
+    impl TextSource for Generated {
+        fn next_chunk(&mut self) -> Option<&[u8]> {
+            if self.remaining == 0 {
+                return None;
+            }
+
+            self.remaining -= 1;
+            self.buffer = [b'a' + self.remaining, b' '];
+            Some(&self.buffer)
+        }
+    }
+
+    #[track_caller]
+    fn assert_drawn_like(source: &mut impl TextSource, text: &str, bounds: Rectangle) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let drawn = TextBox::new("", bounds, character_style)
+            .draw_source(source, &mut display)
+            .unwrap();
+        assert_eq!(drawn, text.len());
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[track_caller]
+    fn assert_lossy_like(bytes: &[u8], text: &str, bounds: Rectangle) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let drawn = TextBox::from_bytes_lossy(bytes, bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(drawn, bytes.len());
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(text, bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn source_is_drawn_like_the_whole_text() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 3));
+
+        let mut source = Generated {
+            buffer: [0; 2],
+            remaining: 5,
+        };
+
+        assert_drawn_like(&mut source, "e d c b a ", bounds);
+    }
+
+    #[test]
+    fn words_split_between_chunks_are_kept_together() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        assert_drawn_like(&mut ["abc h", "ello"].iter(), "abc hello", bounds);
+        assert_drawn_like(
+            &mut ["ab", "c", " he", "l", "lo"].iter(),
+            "abc hello",
+            bounds,
+        );
+    }
+
+    #[test]
+    fn characters_split_between_chunks_are_kept_together() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        let chunks: [&[u8]; 3] = [b"abc h\xc3", b"\xa9", b"llo"];
+        assert_drawn_like(&mut chunks.iter(), "abc h\u{e9}llo", bounds);
+    }
+
+    #[test]
+    fn long_words_are_carried_in_parts() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 16, 8));
+
+        let word = "abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0123456789";
+        let (start, end) = word.split_at(70);
+        assert_drawn_like(
+            &mut ["a ", start, end].iter(),
+            "a abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0123456789",
+            bounds,
+        );
+    }
+
+    #[test]
+    fn drawing_stops_at_the_chunk_that_doesnt_fit() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let mut source = ["ab", "\ncd", " ef", "gh"].iter();
+
+        let mut display = MockDisplay::new();
+        let drawn = TextBox::new("", bounds, character_style)
+            .draw_source(&mut source, &mut display)
+            .unwrap();
+
+        assert_eq!(drawn, 3);
+        assert_eq!(source.next(), Some(&"gh"));
+    }
+
+    #[test]
+    fn drawing_stops_at_invalid_utf8() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let chunks: [&[u8]; 2] = [b"ab c\xff", b"d"];
+
+        let mut display = MockDisplay::new();
+        let drawn = TextBox::new("", bounds, character_style)
+            .draw_source(&mut chunks.iter(), &mut display)
+            .unwrap();
+
+        assert_eq!(drawn, 4);
+    }
+
+    #[test]
+    fn border_and_background_are_drawn() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+        let style = TextBoxStyleBuilder::new()
+            .background_fill(BackgroundFill::Bounds(Rgb888::BLACK))
+            .build();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        TextBox::with_textbox_style("", bounds, character_style, style)
+            .draw_source(&mut ["ab"].iter(), &mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        TextBox::with_textbox_style("ab", bounds, character_style, style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn invalid_utf8_is_replaced() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 20, 1));
+
+        assert_lossy_like(
+            b"ab\xffc\xe2\x82\xac\xe2\x82",
+            "ab\u{fffd}c\u{20ac}\u{fffd}",
+            bounds,
+        );
+    }
+
+    #[test]
+    fn invalid_sequences_are_part_of_their_word() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 3));
+
+        assert_lossy_like(b"abc h\xffllo", "abc h\u{fffd}llo", bounds);
+        assert_lossy_like(b"abc h\xe2\x82llo", "abc h\u{fffd}llo", bounds);
+    }
+
+    #[test]
+    fn lossy_text_box_returns_the_number_of_drawn_bytes() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1));
+
+        let mut display = MockDisplay::new();
+        let drawn = TextBox::from_bytes_lossy(b"a\xff\xff cd\nef", bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        // "a\u{fffd}\u{fffd} cd\n" is drawn.
+        assert_eq!(drawn, 7);
+    }
+}
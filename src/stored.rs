@@ -0,0 +1,224 @@
+//! Text box that stores its text in a generic container.
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+use crate::{style::TextBoxStyle, TextBox};
+
+/// A text box that stores its text in any type that can be viewed as a `&str`.
+///
+/// [`TextBox`] borrows its text, so structures that keep a text box need a lifetime parameter,
+/// and the text has to be stored somewhere else. `StoredTextBox` keeps its text in a value of
+/// type `T`, like a `heapless::String<N>` or a `&'static str`, so widgets can own both the text
+/// and the styling without the heap.
+///
+/// Use [`as_text_box`] to add plugins or to access the rest of the `TextBox` API.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::StoredTextBox;
+///
+/// struct StatusBar {
+///     text_box: StoredTextBox<&'static str, MonoTextStyle<'static, BinaryColor>>,
+/// }
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut status = StatusBar {
+///     text_box: StoredTextBox::new("Idle", bounds, character_style),
+/// };
+/// status.text_box.set_text("Busy");
+///
+/// let mut display = MockDisplay::new();
+/// status.text_box.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: struct.TextBox.html
+/// [`as_text_box`]: #method.as_text_box
+#[derive(Clone, Debug, Hash)]
+#[must_use]
+pub struct StoredTextBox<T, S> {
+    text: T,
+    bounds: Rectangle,
+    character_style: S,
+    style: TextBoxStyle,
+}
+
+impl<T, S> StoredTextBox<T, S>
+where
+    T: AsRef<str>,
+    S: TextRenderer + CharacterStyle + Clone,
+    <S as TextRenderer>::Color: From<Rgb888>,
+{
+    /// Creates a new `StoredTextBox` instance with a given bounding `Rectangle`.
+    #[inline]
+    pub fn new(text: T, bounds: Rectangle, character_style: S) -> Self {
+        Self::with_textbox_style(text, bounds, character_style, TextBoxStyle::default())
+    }
+
+    /// Creates a new `StoredTextBox` instance with a given bounding `Rectangle` and a given
+    /// `TextBoxStyle`.
+    #[inline]
+    pub fn with_textbox_style(
+        text: T,
+        bounds: Rectangle,
+        character_style: S,
+        style: TextBoxStyle,
+    ) -> Self {
+        Self {
+            text,
+            bounds,
+            character_style,
+            style,
+        }
+    }
+
+    /// Returns the text.
+    #[inline]
+    pub fn text(&self) -> &str {
+        self.text.as_ref()
+    }
+
+    /// Returns a mutable reference to the container of the text.
+    #[inline]
+    pub fn text_mut(&mut self) -> &mut T {
+        &mut self.text
+    }
+
+    /// Replaces the text.
+    #[inline]
+    pub fn set_text(&mut self, text: T) -> &mut Self {
+        self.text = text;
+        self
+    }
+
+    /// Consumes the text box and returns its text container.
+    #[inline]
+    pub fn into_text(self) -> T {
+        self.text
+    }
+
+    /// Returns the bounding box the text box was created with.
+    #[inline]
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Changes the bounding box.
+    #[inline]
+    pub fn set_bounds(&mut self, bounds: Rectangle) -> &mut Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Returns the character style.
+    #[inline]
+    pub fn character_style(&self) -> &S {
+        &self.character_style
+    }
+
+    /// Changes the character style.
+    #[inline]
+    pub fn set_character_style(&mut self, character_style: S) -> &mut Self {
+        self.character_style = character_style;
+        self
+    }
+
+    /// Returns the text box style.
+    #[inline]
+    pub fn style(&self) -> &TextBoxStyle {
+        &self.style
+    }
+
+    /// Changes the text box style.
+    #[inline]
+    pub fn set_style(&mut self, style: TextBoxStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns a `TextBox` that borrows the text.
+    #[inline]
+    pub fn as_text_box(&self) -> TextBox<'_, S> {
+        TextBox::with_textbox_style(
+            self.text(),
+            self.bounds,
+            self.character_style.clone(),
+            self.style,
+        )
+    }
+}
+
+impl<T, S> Drawable for StoredTextBox<T, S>
+where
+    T: AsRef<str>,
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle + Clone,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    type Color = <S as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.as_text_box().draw(display).map(drop)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, StoredTextBox, TextBox};
+
+    /// Fixed size text container.
+    struct Digits([u8; 4]);
+
+    impl AsRef<str> for Digits {
+        fn as_ref(&self) -> &str {
+            core::str::from_utf8(&self.0).unwrap()
+        }
+    }
+
+    #[test]
+    fn stored_text_is_drawn() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 4, 1));
+
+        let mut text_box = StoredTextBox::new(Digits(*b"1234"), bounds, character_style);
+        text_box.text_mut().0[3] = b'5';
+        assert_eq!(text_box.text(), "1235");
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("1235", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
@@ -1,9 +1,13 @@
 //! Text box style builder.
-use embedded_graphics::text::LineHeight;
+use embedded_graphics::{geometry::Point, pixelcolor::Rgb888, text::LineHeight};
 
 use crate::{
     alignment::{HorizontalAlignment, VerticalAlignment},
-    style::{HeightMode, TabSize, TextBoxStyle, VerticalOverdraw},
+    style::{
+        Advance, BackgroundFill, Border, CarriageReturn, Columns, ControlCharacters, EmptyLines,
+        HeightMode, LayoutVersion, ParagraphIndent, ParagraphSpaces, Shadow, StyleContinuity,
+        TabSize, TextBoxStyle, VerticalOverdraw, WhiteSpace, WordBreak, Wrap, WrapMarker,
+    },
 };
 
 /// [`TextBoxStyle`] builder object.
@@ -15,6 +19,7 @@ pub struct TextBoxStyleBuilder {
     style: TextBoxStyle,
 }
 
+#[allow(clippy::new_without_default)]
 impl TextBoxStyleBuilder {
     /// Create a new builder object.
     #[inline]
@@ -33,6 +38,28 @@ impl TextBoxStyleBuilder {
                 line_height: LineHeight::Percent(100),
                 paragraph_spacing: 0,
                 tab_size: TabSize::Spaces(4),
+                word_spacing: 0,
+                word_break: WordBreak::default(),
+                hyphen_string: "-",
+                white_space: WhiteSpace::default(),
+                paragraph_spaces: ParagraphSpaces::default(),
+                wrap: Wrap::default(),
+                wrap_marker: WrapMarker::default(),
+                carriage_return: CarriageReturn::default(),
+                control_characters: ControlCharacters::default(),
+                empty_lines: EmptyLines::default(),
+                paragraph_indent: ParagraphIndent::default(),
+                underline_color: None,
+                max_chars_per_line: None,
+                outline_color: None,
+                advance: Advance::default(),
+                shadow: None,
+                background_fill: BackgroundFill::default(),
+                layout_version: LayoutVersion::default(),
+                border: None,
+                style_continuity: StyleContinuity::default(),
+                columns: Columns::default(),
+                baseline_grid: None,
             },
         }
     }
@@ -42,6 +69,11 @@ impl TextBoxStyleBuilder {
     /// The line height is defined as the vertical distance between the baseline of two adjacent lines
     /// of text.
     ///
+    /// [`LineHeight::Pixels`] sets an absolute distance. [`LineHeight::Percent`] sets the distance
+    /// relative to the line height of the font, so the spacing follows the font when it is
+    /// changed. Lines that use a different font, for example a font selected by a plugin, are
+    /// spaced relative to the tallest font in the line.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -51,7 +83,15 @@ impl TextBoxStyleBuilder {
     /// let style = TextBoxStyleBuilder::new()
     ///     .line_height(LineHeight::Pixels(12))
     ///     .build();
+    ///
+    /// // One and a half times the height of the font.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .line_height(LineHeight::Percent(150))
+    ///     .build();
     /// ```
+    ///
+    /// [`LineHeight::Pixels`]: https://docs.rs/embedded-graphics/0.7/embedded_graphics/text/enum.LineHeight.html#variant.Pixels
+    /// [`LineHeight::Percent`]: https://docs.rs/embedded-graphics/0.7/embedded_graphics/text/enum.LineHeight.html#variant.Percent
     #[inline]
     pub const fn line_height(mut self, line_height: LineHeight) -> Self {
         self.style.line_height = line_height;
@@ -113,6 +153,482 @@ impl TextBoxStyleBuilder {
         self
     }
 
+    /// Sets the word spacing.
+    ///
+    /// The given number of pixels is added to the width of every whitespace block, independent of
+    /// the width of the space character of the font. Negative values make the whitespace narrower,
+    /// but a whitespace block never has a negative width.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .word_spacing(2)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn word_spacing(mut self, word_spacing: i32) -> Self {
+        self.style.word_spacing = word_spacing;
+
+        self
+    }
+
+    /// Sets how words that don't fit into an empty line are broken.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{TextBoxStyleBuilder, WordBreak};
+    /// #
+    /// // Only break words after at least 8 characters.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .word_break(WordBreak::MinLength(8))
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn word_break(mut self, word_break: WordBreak) -> Self {
+        self.style.word_break = word_break;
+
+        self
+    }
+
+    /// Sets the string that is displayed when a word is hyphenated.
+    ///
+    /// The hyphen string is displayed at the end of the line when a soft hyphen break is taken,
+    /// or when a hyphenation plugin breaks a word. By default, `"-"` is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .hyphen_string("\u{2010}")
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn hyphen_string(mut self, hyphen_string: &'static str) -> Self {
+        self.style.hyphen_string = hyphen_string;
+
+        self
+    }
+
+    /// Sets how whitespace is displayed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{TextBoxStyleBuilder, WhiteSpace};
+    /// #
+    /// // Display the text as a single paragraph, ignoring extra whitespace.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .white_space(WhiteSpace::Collapse)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn white_space(mut self, white_space: WhiteSpace) -> Self {
+        self.style.white_space = white_space;
+
+        self
+    }
+
+    /// Sets whether spaces at the start and end of paragraphs are displayed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{alignment::HorizontalAlignment, style::{ParagraphSpaces, TextBoxStyleBuilder}};
+    /// #
+    /// // Keep the indentation of paragraphs in centered text.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .alignment(HorizontalAlignment::Center)
+    ///     .paragraph_spaces(ParagraphSpaces::Preserve)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn paragraph_spaces(mut self, paragraph_spaces: ParagraphSpaces) -> Self {
+        self.style.paragraph_spaces = paragraph_spaces;
+
+        self
+    }
+
+    /// Sets how lines that are wider than the text box are handled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{TextBoxStyleBuilder, Wrap};
+    /// #
+    /// // Display log lines without wrapping, and mark the truncated lines.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .wrap(Wrap::Ellipsis("..."))
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn wrap(mut self, wrap: Wrap) -> Self {
+        self.style.wrap = wrap;
+
+        self
+    }
+
+    /// Sets the color of underlines that are turned on by text style changes.
+    ///
+    /// By default, text is underlined using the text color. This option allows, for example,
+    /// underlining links in a different color than their text. The color is converted to the color
+    /// type of the character style.
+    ///
+    /// The underline color can also be changed by the `\x1b[58;...m` ANSI sequence.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// # use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .underline_color(Rgb888::BLUE)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn underline_color(mut self, color: Rgb888) -> Self {
+        self.style.underline_color = Some(color);
+
+        self
+    }
+
+    /// Sets the maximum number of characters in a line.
+    ///
+    /// Lines are wrapped when they would be wider than the text box, or when they would contain
+    /// more characters than `max`, whichever happens first. This can be used to mirror the output
+    /// of a terminal, or to display text of protocols that limit the length of lines. Whitespace
+    /// and the hyphen string of wrapped words also count as characters.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// #
+    /// // Wrap lines like an 80 column terminal.
+    /// let style = TextBoxStyleBuilder::new().max_chars_per_line(80).build();
+    /// ```
+    #[inline]
+    pub const fn max_chars_per_line(mut self, max: u32) -> Self {
+        self.style.max_chars_per_line = Some(max);
+
+        self
+    }
+
+    /// Sets the color of the outline drawn around the glyphs.
+    ///
+    /// Every glyph is first drawn in the outline color, shifted by 1 pixel in every direction,
+    /// then the glyph itself is drawn on top. This keeps text readable over images and dithered
+    /// backgrounds. The color is converted to the color type of the character style.
+    ///
+    /// Whitespace, underlines and strikethrough are not outlined. The background color of the
+    /// character style is drawn over the outline, so outlines are best used with a transparent
+    /// background. The outline is clipped to the bounding box and to the height of the line, so
+    /// it may be cut off for glyphs that touch the top or bottom edge of their line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// # use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .outline_color(Rgb888::BLACK)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn outline_color(mut self, color: Rgb888) -> Self {
+        self.style.outline_color = Some(color);
+
+        self
+    }
+
+    /// Sets how the position of text is advanced after drawing a piece of text.
+    ///
+    /// Use [`Advance::Rendered`] with character styles that draw strings with a different width
+    /// than what they measure, to keep the following text, whitespace and decorations drawn by
+    /// plugins next to the drawn text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{Advance, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .advance(Advance::Rendered)
+    ///     .build();
+    /// ```
+    ///
+    /// [`Advance::Rendered`]: enum.Advance.html#variant.Rendered
+    #[inline]
+    pub const fn advance(mut self, advance: Advance) -> Self {
+        self.style.advance = advance;
+
+        self
+    }
+
+    /// Draws a shadow beneath the text.
+    ///
+    /// The glyphs of every line are first drawn in `color`, displaced by `offset`, then the text
+    /// is drawn on top, using the same line layout. The color is converted to the color type of
+    /// the character style.
+    ///
+    /// Only glyphs cast a shadow: whitespace, backgrounds, underlines, strikethrough and elements
+    /// drawn by plugins don't. The shadow is clipped to the bounding box and to the height of the
+    /// line, so shadows with a large vertical offset may be cut off.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// # use embedded_graphics::{pixelcolor::{Rgb888, RgbColor}, prelude::Point};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .shadow(Point::new(1, 1), Rgb888::BLACK)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn shadow(mut self, offset: Point, color: Rgb888) -> Self {
+        self.style.shadow = Some(Shadow { offset, color });
+
+        self
+    }
+
+    /// Sets which part of the text box is filled with a background color.
+    ///
+    /// The fill is drawn before the text, so the background color of the character style, if
+    /// set, is drawn over it. The color is converted to the color type of the character style.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{BackgroundFill, TextBoxStyleBuilder};
+    /// # use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .background_fill(BackgroundFill::Bounds(Rgb888::BLUE))
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn background_fill(mut self, background_fill: BackgroundFill) -> Self {
+        self.style.background_fill = background_fill;
+
+        self
+    }
+
+    /// Sets the version of the line layout algorithm.
+    ///
+    /// Select a numbered version to keep the layout of the text unchanged when the crate is
+    /// upgraded to a new minor release. See [`LayoutVersion`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{LayoutVersion, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .layout_version(LayoutVersion::V1)
+    ///     .build();
+    /// ```
+    ///
+    /// [`LayoutVersion`]: enum.LayoutVersion.html
+    #[inline]
+    pub const fn layout_version(mut self, layout_version: LayoutVersion) -> Self {
+        self.style.layout_version = layout_version;
+
+        self
+    }
+
+    /// Sets the border drawn around the bounding box.
+    ///
+    /// The border is drawn after the background fill and before the text. With a
+    /// [`BackgroundFill::Bounds`] fill, the corners of the fill are rounded like the corners of
+    /// the border.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{Border, TextBoxStyleBuilder};
+    /// # use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .border(Border::new(Rgb888::WHITE, 1).corner_radius(3))
+    ///     .build();
+    /// ```
+    ///
+    /// [`BackgroundFill::Bounds`]: enum.BackgroundFill.html#variant.Bounds
+    #[inline]
+    pub const fn border(mut self, border: Border) -> Self {
+        self.style.border = Some(border);
+
+        self
+    }
+
+    /// Sets the marker that is displayed where lines are wrapped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{TextBoxStyleBuilder, WrapMarker};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .wrap_marker(WrapMarker::End("\\"))
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn wrap_marker(mut self, wrap_marker: WrapMarker) -> Self {
+        self.style.wrap_marker = wrap_marker;
+
+        self
+    }
+
+    /// Sets how carriage return characters are interpreted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{CarriageReturn, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .carriage_return(CarriageReturn::NewLine)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn carriage_return(mut self, carriage_return: CarriageReturn) -> Self {
+        self.style.carriage_return = carriage_return;
+
+        self
+    }
+
+    /// Sets how control characters are displayed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{ControlCharacters, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .control_characters(ControlCharacters::Visualize)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn control_characters(mut self, control_characters: ControlCharacters) -> Self {
+        self.style.control_characters = control_characters;
+
+        self
+    }
+
+    /// Sets how consecutive line breaks are displayed.
+    ///
+    /// Line breaks are consecutive if only other line breaks are between them. Carriage returns
+    /// that are displayed as line breaks or ignored count as line breaks.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{EmptyLines, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .empty_lines(EmptyLines::Collapse)
+    ///     .paragraph_spacing(4)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn empty_lines(mut self, empty_lines: EmptyLines) -> Self {
+        self.style.empty_lines = empty_lines;
+
+        self
+    }
+
+    /// Sets the indentation of the first line of paragraphs.
+    ///
+    /// Paragraphs are started by line break characters. Lines that are started by carriage
+    /// returns or by wrapping the text are not indented.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{ParagraphIndent, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .paragraph_indent(ParagraphIndent::AfterFirst(12))
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn paragraph_indent(mut self, paragraph_indent: ParagraphIndent) -> Self {
+        self.style.paragraph_indent = paragraph_indent;
+
+        self
+    }
+
+    /// Sets whether text style changes are carried over to the next paragraph.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::{StyleContinuity, TextBoxStyleBuilder};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .style_continuity(StyleContinuity::ResetParagraphs)
+    ///     .build();
+    /// ```
+    #[inline]
+    pub const fn style_continuity(mut self, style_continuity: StyleContinuity) -> Self {
+        self.style.style_continuity = style_continuity;
+
+        self
+    }
+
+    /// Flows the text into `count` columns of equal width, separated by `gap` pixels.
+    ///
+    /// The text continues at the top of the next column when a column is full, and the text that
+    /// doesn't fit into the last column is returned by `draw`. Every column is filled from the
+    /// top, so the vertical alignment is ignored. The border and the background are drawn around
+    /// the whole text box, and [`BackgroundFill::Text`] fills the whole bounding box. Height
+    /// modes that fit the height of the text box to the text measure the text as a single column.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// #
+    /// let style = TextBoxStyleBuilder::new().columns(3, 8).build();
+    /// ```
+    ///
+    /// [`BackgroundFill::Text`]: enum.BackgroundFill.html#variant.Text
+    #[inline]
+    pub const fn columns(mut self, count: u32, gap: u32) -> Self {
+        self.style.columns = Columns::new(count, gap);
+
+        self
+    }
+
+    /// Snaps the lines to a grid of horizontal lines that are `spacing` pixels apart.
+    ///
+    /// Every line is moved down to the next multiple of `spacing` in display coordinates, so the
+    /// lines of text boxes with different origins stay aligned, as long as they use the same
+    /// font and grid. Measuring the height of the text ignores the grid, so text boxes that fit
+    /// their height to the text may cut off the last line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// #
+    /// let style = TextBoxStyleBuilder::new().baseline_grid(12).build();
+    /// ```
+    #[inline]
+    pub const fn baseline_grid(mut self, spacing: u32) -> Self {
+        self.style.baseline_grid = Some(spacing);
+
+        self
+    }
+
     /// Builds the [`TextBoxStyle`].
     ///
     /// [`TextBoxStyle`]: struct.TextBoxStyle.html
@@ -192,6 +192,57 @@ pub enum HeightMode {
     /// assert_eq!(size, Size::new(60, 18));
     /// ```
     ShrinkToText(VerticalOverdraw),
+
+    /// Sets the height of the [`TextBox`] to fit the text, but not taller than `max_height`.
+    ///
+    /// The [`TextBox`] grows and shrinks with the text, like it does with [`FitToText`]. If the
+    /// text is taller than `max_height`, the height is limited to `max_height`, and the text is
+    /// displayed using the `overdraw` option.
+    ///
+    /// # Example: the height of the [`TextBox`] is limited.
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{
+    ///     style::{HeightMode, VerticalOverdraw, TextBoxStyleBuilder},
+    ///     TextBox,
+    /// };
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    ///
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .height_mode(HeightMode::FitToTextLimited {
+    ///         max_height: 27,
+    ///         overdraw: VerticalOverdraw::FullRowsOnly,
+    ///     })
+    ///     .build();
+    ///
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 0));
+    ///
+    /// // The TextBox grows with the text...
+    /// let text_box =
+    ///     TextBox::with_textbox_style("Two lines\nof text", bounds, character_style, style);
+    /// assert_eq!(text_box.bounding_box().size, Size::new(60, 18));
+    ///
+    /// // ... up to the limit.
+    /// let text_box =
+    ///     TextBox::with_textbox_style("Four\nlines\nof\ntext", bounds, character_style, style);
+    /// assert_eq!(text_box.bounding_box().size, Size::new(60, 27));
+    /// ```
+    ///
+    /// [`FitToText`]: #variant.FitToText
+    FitToTextLimited {
+        /// The maximum height of the [`TextBox`].
+        max_height: u32,
+
+        /// How to display the text when it's taller than `max_height`.
+        overdraw: VerticalOverdraw,
+    },
 }
 
 impl HeightMode {
@@ -212,6 +263,9 @@ impl HeightMode {
             HeightMode::ShrinkToText(_) => {
                 text_box.fit_height_limited(text_box.bounding_box().size.height);
             }
+            HeightMode::FitToTextLimited { max_height, .. } => {
+                text_box.fit_height_limited(max_height);
+            }
         }
     }
 
@@ -221,11 +275,16 @@ impl HeightMode {
     /// partial lines. For a partial line, this function calculates, which rows of each character
     /// should be displayed.
     pub(crate) fn calculate_displayed_row_range(self, cursor: &Cursor) -> Range<i32> {
-        let overdraw = match self {
-            HeightMode::Exact(overdraw) | HeightMode::ShrinkToText(overdraw) => overdraw,
-            HeightMode::FitToText => VerticalOverdraw::Visible,
-        };
+        self.overdraw().calculate_displayed_row_range(cursor)
+    }
 
-        overdraw.calculate_displayed_row_range(cursor)
+    /// Returns the vertical overdraw option that is used by the height mode.
+    pub(crate) fn overdraw(self) -> VerticalOverdraw {
+        match self {
+            HeightMode::Exact(overdraw)
+            | HeightMode::ShrinkToText(overdraw)
+            | HeightMode::FitToTextLimited { overdraw, .. } => overdraw,
+            HeightMode::FitToText => VerticalOverdraw::Visible,
+        }
     }
 }
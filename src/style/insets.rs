@@ -0,0 +1,67 @@
+//! Padding around the text.
+use az::SaturatingAs;
+use embedded_graphics::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+};
+
+/// Distances between the edges of a rectangle and an area inside it, in pixels.
+///
+/// See [`TextBox::with_padding`].
+///
+/// [`TextBox::with_padding`]: ../struct.TextBox.html#method.with_padding
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Insets {
+    /// The distance from the top edge.
+    pub top: u32,
+
+    /// The distance from the right edge.
+    pub right: u32,
+
+    /// The distance from the bottom edge.
+    pub bottom: u32,
+
+    /// The distance from the left edge.
+    pub left: u32,
+}
+
+impl Insets {
+    /// Creates new insets, in the same order as CSS does.
+    #[inline]
+    pub const fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Creates insets that are equal on every side.
+    #[inline]
+    pub const fn uniform(inset: u32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    /// Returns the sum of the left and right insets.
+    pub(crate) fn horizontal(&self) -> u32 {
+        self.left.saturating_add(self.right)
+    }
+
+    /// Returns the sum of the top and bottom insets.
+    pub(crate) fn vertical(&self) -> u32 {
+        self.top.saturating_add(self.bottom)
+    }
+
+    /// Returns the area inside `rectangle`. The area is empty if the insets are larger than the
+    /// rectangle.
+    pub(crate) fn shrink(&self, rectangle: Rectangle) -> Rectangle {
+        Rectangle::new(
+            rectangle.top_left + Point::new(self.left.saturating_as(), self.top.saturating_as()),
+            Size::new(
+                rectangle.size.width.saturating_sub(self.horizontal()),
+                rectangle.size.height.saturating_sub(self.vertical()),
+            ),
+        )
+    }
+}
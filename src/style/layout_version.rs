@@ -0,0 +1,195 @@
+//! Layout versions.
+
+/// Selects the version of the line layout algorithm.
+///
+/// The line layout algorithm decides where lines are wrapped, how words are hyphenated or broken,
+/// and where text is placed by the alignment. Improvements and fixes to the algorithm can move
+/// text by a few pixels, or wrap a line differently, which is unwanted if the screens of a device
+/// were validated pixel by pixel.
+///
+/// Selecting a numbered version opts into a stable layout: the layout of a numbered version
+/// doesn't change in later minor and patch releases of the crate, so the crate can be upgraded
+/// without validating every screen again. Changes to the layout are only applied to
+/// [`LayoutVersion::Latest`], and are introduced as a new numbered version. Numbered versions are
+/// covered by golden layout tests.
+///
+/// Rendering bugs that don't affect the layout, for example the colors of decorations, are fixed
+/// in every version.
+///
+/// [`LayoutVersion::Latest`]: enum.LayoutVersion.html#variant.Latest
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LayoutVersion {
+    /// The latest layout algorithm, which may change in minor releases. This is the default.
+    Latest,
+
+    /// The layout algorithm of the 0.5 releases.
+    V1,
+}
+
+impl LayoutVersion {
+    /// Returns the default layout version.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Latest
+    }
+}
+
+/// Golden layouts of the numbered versions.
+///
+/// These tests must not be changed. If a change in the layout algorithm makes them fail, the
+/// previous behavior must be kept for the failing version.
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        Drawable,
+    };
+
+    use crate::{
+        alignment::HorizontalAlignment,
+        style::{LayoutVersion, TabSize, TextBoxStyleBuilder},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_v1(alignment: HorizontalAlignment, text: &str, size: Size, pattern: &[&str]) {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .layout_version(LayoutVersion::V1)
+            .alignment(alignment)
+            .tab_size(TabSize::Spaces(2))
+            .build();
+
+        let mut display = MockDisplay::new();
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn v1_wrapping() {
+        assert_v1(
+            HorizontalAlignment::Left,
+            "ab cd ef\u{AD}gh\tij",
+            size_for(&FONT_6X9, 4, 3),
+            &[
+                "                       ",
+                "       #               ",
+                "       #               ",
+                "  ###  ###             ",
+                " #  #  #  #            ",
+                " #  #  #  #            ",
+                "  ###  ###             ",
+                "                       ",
+                "                       ",
+                "                       ",
+                "          #            ",
+                "          #            ",
+                "  ###   ###            ",
+                " #     #  #            ",
+                " #     #  #            ",
+                "  ###   ###            ",
+                "                       ",
+                "                       ",
+                "                       ",
+                "         #         #   ",
+                "        # #        #   ",
+                "  ##    #     ##   ### ",
+                " # ##  ###   #  #  #  #",
+                " ##     #    #  #  #  #",
+                "  ###   #     ###  #  #",
+                "                #      ",
+                "              ##       ",
+            ],
+        );
+    }
+
+    #[test]
+    fn v1_soft_hyphen_wrapping() {
+        // The hyphen that doesn't fit is carried over to the next line.
+        assert_v1(
+            HorizontalAlignment::Left,
+            "ab\u{AD}cd",
+            size_for(&FONT_6X9, 2, 2),
+            &[
+                "            ",
+                "       #    ",
+                "       #    ",
+                "  ###  ###  ",
+                " #  #  #  # ",
+                " #  #  #  # ",
+                "  ###  ###  ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "            ",
+                "        ### ",
+                "#####  #    ",
+                "       #    ",
+                "        ### ",
+            ],
+        );
+    }
+
+    #[test]
+    fn v1_center_alignment() {
+        assert_v1(
+            HorizontalAlignment::Center,
+            "ab c",
+            size_for(&FONT_6X9, 5, 1),
+            &[
+                "                          ",
+                "          #               ",
+                "          #               ",
+                "     ###  ###          ###",
+                "    #  #  #  #        #   ",
+                "    #  #  #  #        #   ",
+                "     ###  ###          ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn v1_justified_alignment() {
+        assert_v1(
+            HorizontalAlignment::Justified,
+            "ab c de",
+            size_for(&FONT_6X9, 5, 2),
+            &[
+                "                             ",
+                "       #                     ",
+                "       #                     ",
+                "  ###  ###                ###",
+                " #  #  #  #              #   ",
+                " #  #  #  #              #   ",
+                "  ###  ###                ###",
+                "                             ",
+                "                             ",
+                "                             ",
+                "    #                        ",
+                "    #                        ",
+                "  ###   ##                   ",
+                " #  #  # ##                  ",
+                " #  #  ##                    ",
+                "  ###   ###                  ",
+            ],
+        );
+    }
+}
@@ -27,7 +27,7 @@
 //! escape codes, only describes the supported subset.
 //!
 //! > *Note:* if `embedded-text` fails to parse an escape sequence, it will ignore the `\x1b` character
-//! and display the rest as normal text.
+//! > and display the rest as normal text.
 //!
 //! All escape sequences start with the `\x1b[` sequence, where `\x1b` is the ASCII `escape`
 //! character. `embedded-text` supports a subset of the `SGR` parameters, which are numeric codes
@@ -100,21 +100,40 @@
 //! The following SGR sequences are supported:
 //!
 //!  * `\x1b[0m`: Reset everything
+//!  * `\x1b[1m`: Bold text. Standard colors are replaced by their bright variants, other colors
+//!    are brightened.
+//!  * `\x1b[2m`: Faint text. The text color is dimmed.
+//!  * `\x1b[22m`: Neither bold nor faint text
 //!  * `\x1b[4m`: Underlined text
 //!  * `\x1b[24m`: Turn off text underline
+//!  * `\x1b[7m`: Reverse video. The text and background colors are swapped.
+//!  * `\x1b[27m`: Turn off reverse video
+//!  * `\x1b[58;5;<n>m`, `\x1b[58;2;<r>;<g>;<b>m`: Change the underline color, using the 8 bit or
+//!    24 bit color formats described above. The color is used when underlining is turned on.
+//!  * `\x1b[59m`: Reset the underline color to the default
 //!  * `\x1b[9m`: Crossed out/strikethrough text
 //!  * `\x1b[29m`: Turn off strikethrough
 //!  * `\x1b[39m`: Reset text color
 //!  * `\x1b[49m`: Reset background color
 //!
+//! Bold and faint text are emulated by changing the text color, so they only have an effect after
+//! the text color is set by an escape sequence. Similarly, reverse video swaps the colors set by
+//! escape sequences, and colors that weren't set are swapped as transparent.
+//!
+//! Multiple attributes can be combined in a single sequence by separating them with `;`, e.g.
+//! `\x1b[1;4;32m` sets bold, underlined, green text. Due to limitations of the escape sequence
+//! parser, a sequence must consist of 1, 2, 3 or 5 numbers.
+//!
 //! Reset style options to default
 //! ------------------------------
 //!
 //! `embedded-text` supports the `Reset all` (`\x1b[0m`), `Default text color` (`\x1b[39m`) and
-//! `Default background color` (`\x1b[49m`) codes. These codes can be used to reset colors to
-//! *transparent* (i.e. no pixels drawn for text or background).
+//! `Default background color` (`\x1b[49m`) codes. The default color codes can be used to reset
+//! colors to *transparent* (i.e. no pixels drawn for text or background).
 //!
-//! In addition, `Reset all` turns off the underlined and crossed out styles.
+//! `Reset all` restores the character style of the text box, including its colors and decorations.
+//! If the character style of the text box is changed after the text box is created, `Reset all`
+//! restores the changed style.
 //!
 //! Other supported ANSI escape codes
 //! ---------------------------------
@@ -128,6 +147,19 @@
 //!    avoid this, make sure to reset the background color before moving the cursor!
 //!  - Move the cursor backward `<n>` characters: `\x1b[<n>D`. This command will stop at the start
 //!    of line.
+//!  - Move the cursor up or down `<n>` lines: `\x1b[<n>A` and `\x1b[<n>B`. The horizontal position
+//!    of the cursor is kept. The cursor can't be moved above the first line of the text.
+//!  - Move the cursor to row `<r>` and column `<c>`: `\x1b[<r>;<c>H`. Rows and columns are
+//!    numbered from 1, starting at the first line of the text.
+//!
+//!  - Erase the rest of the line: `\x1b[K`. The line is filled with the background color, starting
+//!    at the cursor. The cursor is not moved.
+//!  - Erase the display: `\x1b[2J`. The text box is filled with the background color. The cursor
+//!    is not moved.
+//!
+//! Moving the cursor to a different line or erasing the display ends the current line, similar to
+//! a carriage return. Cursor movements are not taken into account when the height of the text is
+//! measured.
 //!
 //! [`TextBox`]: ../struct.TextBox.html
 //! [`TextBoxStyle`]: struct.TextBoxStyle.html
@@ -137,29 +169,49 @@
 
 mod builder;
 mod height_mode;
-mod vertical_overdraw;
+mod insets;
+mod layout_version;
+mod ranges;
+mod theme;
+pub(crate) mod vertical_overdraw;
 
 use core::convert::Infallible;
 
 use crate::{
     alignment::{HorizontalAlignment, VerticalAlignment},
-    parser::{Parser, SPEC_CHAR_NBSP},
+    parser::{ChangeTextStyle, Parser, SPEC_CHAR_NBSP},
     plugin::{NoPlugin, PluginMarker as Plugin, PluginWrapper, ProcessingState},
     rendering::{
         cursor::LineCursor,
-        line_iter::{ElementHandler, LineElementParser, LineEndType},
+        font_style,
+        layout::LineLayout,
+        line_iter::{
+            ElementHandler, ElementRecorder, LineElementParser, LineElements, LineEndType,
+        },
         space_config::SpaceConfig,
     },
     utils::str_width,
+    TextBox,
 };
 use az::SaturatingAs;
 use embedded_graphics::{
+    geometry::{Point, Size},
     pixelcolor::Rgb888,
-    text::{renderer::TextRenderer, LineHeight},
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        LineHeight,
+    },
 };
 
 pub use self::{
-    builder::TextBoxStyleBuilder, height_mode::HeightMode, vertical_overdraw::VerticalOverdraw,
+    builder::TextBoxStyleBuilder,
+    height_mode::HeightMode,
+    insets::Insets,
+    layout_version::LayoutVersion,
+    ranges::{StyleDelta, StyledRanges},
+    theme::Theme,
+    vertical_overdraw::VerticalOverdraw,
 };
 
 /// Tab size helper
@@ -192,6 +244,449 @@ impl TabSize {
     }
 }
 
+/// Controls how words that don't fit into an empty line are broken.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum WordBreak {
+    /// Overlong words are broken at any character. This is the default.
+    Anywhere,
+
+    /// Words are only broken if at least the given number of characters fit into the line before
+    /// the break.
+    ///
+    /// The rest of a broken word may be shorter. Words that can't be broken overflow the line and
+    /// are clipped.
+    MinLength(u16),
+
+    /// Words are never broken, they overflow the line and are clipped instead.
+    Never,
+}
+
+impl WordBreak {
+    /// Returns the default word breaking mode.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Anywhere
+    }
+
+    /// Returns whether a word may be broken after `part`, the part of it that fits into the line.
+    pub(crate) fn can_break(self, part: &str) -> bool {
+        match self {
+            WordBreak::Anywhere => true,
+            WordBreak::MinLength(n) => part.chars().nth(n.saturating_sub(1) as usize).is_some(),
+            WordBreak::Never => false,
+        }
+    }
+}
+
+/// Controls how whitespace in the text is displayed, similar to the CSS `white-space` property.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum WhiteSpace {
+    /// Consecutive spaces and line breaks are collapsed into a single space, and spaces at the
+    /// beginning of lines are removed.
+    Collapse,
+
+    /// Spaces are displayed as they appear in the text. This is the default.
+    ///
+    /// Spaces at the beginning of lines are only displayed if the text is left aligned.
+    Preserve,
+
+    /// Consecutive spaces are collapsed into a single space, and spaces at the beginning of lines
+    /// are removed. Line breaks are kept.
+    PreserveBreaks,
+}
+
+impl WhiteSpace {
+    /// Returns the default whitespace mode.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Preserve
+    }
+
+    /// Returns whether consecutive spaces are collapsed into a single space.
+    pub(crate) fn collapses_spaces(self) -> bool {
+        self != WhiteSpace::Preserve
+    }
+
+    /// Returns whether line breaks are collapsed into spaces.
+    pub(crate) fn collapses_breaks(self) -> bool {
+        self == WhiteSpace::Collapse
+    }
+}
+
+/// Controls whether spaces at the start and end of paragraphs are displayed.
+///
+/// Paragraphs are separated by newline characters. Spaces at the start of lines that are started
+/// by wrapping the text are not affected. Collapsed whitespace is never displayed at the start of
+/// a paragraph.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ParagraphSpaces {
+    /// Spaces at the start of a paragraph are only displayed if the text is left aligned. Spaces
+    /// at the end of a paragraph are not displayed. This is the default.
+    Auto,
+
+    /// Spaces at the start and end of paragraphs are displayed, regardless of the alignment.
+    ///
+    /// Displayed spaces take part in aligning the line, so trailing spaces move right aligned
+    /// text to the left.
+    Preserve,
+
+    /// Spaces at the start and end of paragraphs are not displayed.
+    Trim,
+}
+
+impl ParagraphSpaces {
+    /// Returns the default paragraph space handling.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Auto
+    }
+}
+
+/// Controls how lines that are wider than the text box are handled.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Wrap {
+    /// Lines are wrapped at word boundaries. This is the default.
+    Word,
+
+    /// Lines are only broken at line break characters. Content that doesn't fit into the line is
+    /// clipped.
+    None,
+
+    /// Lines are only broken at line break characters. Lines that don't fit end with the given
+    /// string, for example `"..."`.
+    Ellipsis(&'static str),
+}
+
+impl Wrap {
+    /// Returns the default wrapping mode.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Word
+    }
+}
+
+/// A marker that is displayed where a line is wrapped.
+///
+/// Only line breaks inserted by [`Wrap::Word`] are marked, line break characters in the text are
+/// not.
+///
+/// [`Wrap::Word`]: enum.Wrap.html#variant.Word
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum WrapMarker {
+    /// Wrapped lines are not marked. This is the default.
+    None,
+
+    /// The string is displayed at the start of every line that continues a wrapped line, for
+    /// example `"\u{21aa}"`.
+    Start(&'static str),
+
+    /// The string is displayed at the end of every wrapped line, after the whitespace that fits
+    /// into the line, for example `"\\"`. Space for the marker is reserved on lines that don't
+    /// fit into the text box.
+    End(&'static str),
+}
+
+impl WrapMarker {
+    /// Returns the default wrap marker.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Controls how carriage return (`\r`) characters are interpreted.
+///
+/// Text from different sources, for example logs of different systems, separates lines with
+/// `\r\n`, `\n` or a single `\r`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum CarriageReturn {
+    /// The cursor moves back to the start of the line, and the following text is drawn over the
+    /// line. This is the default.
+    Overprint,
+
+    /// A carriage return starts a new paragraph, like a newline character. A carriage return that
+    /// is followed by a newline character only starts a single paragraph.
+    NewLine,
+
+    /// Carriage returns are removed from the text.
+    Ignore,
+}
+
+impl CarriageReturn {
+    /// Returns the default carriage return handling.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Overprint
+    }
+}
+
+/// The indentation of the first line of paragraphs.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ParagraphIndent {
+    /// Paragraphs are not indented. This is the default.
+    None,
+
+    /// The first line of every paragraph is indented by the given number of pixels.
+    Every(u32),
+
+    /// The first line of every paragraph, except the first paragraph of the text, is indented by
+    /// the given number of pixels, like in books.
+    AfterFirst(u32),
+}
+
+impl ParagraphIndent {
+    /// Returns the default paragraph indentation.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::None
+    }
+
+    /// Returns the indentation of the first line of the `paragraph`th paragraph.
+    pub(crate) fn first_line(self, paragraph: usize) -> u32 {
+        match self {
+            Self::Every(indent) => indent,
+            Self::AfterFirst(indent) if paragraph > 0 => indent,
+            _ => 0,
+        }
+    }
+}
+
+/// Controls how consecutive line breaks are displayed.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum EmptyLines {
+    /// Every line break starts a new line, so consecutive line breaks display empty lines. This
+    /// is the default.
+    Display,
+
+    /// Consecutive line breaks are displayed as a single line break, so paragraphs are only
+    /// separated by the paragraph spacing. This matches how markdown-like sources separate
+    /// paragraphs with empty lines.
+    Collapse,
+}
+
+impl EmptyLines {
+    /// Returns the default empty line handling.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Display
+    }
+}
+
+/// Controls how control characters in the text are displayed.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ControlCharacters {
+    /// Tabs, line breaks and, with the `ansi` feature, escape sequences are interpreted. Other
+    /// control characters are drawn by the character style. This is the default.
+    Interpret,
+
+    /// Every control character, except the newline character, is displayed as a replacement
+    /// glyph instead of being interpreted. ASCII control characters are displayed as the
+    /// matching symbol of the Unicode Control Pictures block, for example `"\u{2400}"` (␀) for
+    /// `'\0'` and `"\u{2409}"` (␉) for a tab. Other control characters are displayed as
+    /// `"\u{fffd}"` (�).
+    ///
+    /// This mode is meant for displaying raw data, for example protocol traffic. Raw bytes that
+    /// are not valid UTF-8 can be converted to a string using `String::from_utf8_lossy`, which
+    /// also replaces the invalid sequences with `"\u{fffd}"`. The font of the character style
+    /// must contain the replacement glyphs.
+    Visualize,
+}
+
+impl ControlCharacters {
+    /// Returns the default control character handling.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Interpret
+    }
+}
+
+/// Controls where text is drawn after a piece of text on the same line.
+///
+/// The layout of a line is calculated from the widths the character style reports for strings.
+/// Some [`TextRenderer`] implementations, for example renderers that combine ligatures or apply
+/// kerning, draw strings narrower or wider than what the measurement predicts, which makes the
+/// following text drift away from its calculated position.
+///
+/// [`TextRenderer`]: https://docs.rs/embedded-graphics/latest/embedded_graphics/text/renderer/trait.TextRenderer.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Advance {
+    /// The next element is drawn at the position calculated from the measured widths. This is
+    /// the default.
+    Measured,
+
+    /// The next element is drawn at the position returned by the renderer after drawing the
+    /// previous one. Line wrapping and alignment still use the measured widths.
+    Rendered,
+}
+
+impl Advance {
+    /// Returns the default advance mode.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Measured
+    }
+}
+
+/// Controls whether text style changes are carried over to the next paragraph.
+///
+/// Text style changes include ANSI sequences, markup and changes made by plugins. Applications
+/// that display a text page by page can use [`TextBox::character_style_at`] to find the style at
+/// the start of the next page.
+///
+/// [`TextBox::character_style_at`]: ../struct.TextBox.html#method.character_style_at
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum StyleContinuity {
+    /// Text style changes stay in effect until they are changed again, like in a terminal. This
+    /// is the default.
+    Continuous,
+
+    /// The character style, font and vertical offset are reset at the start of every paragraph,
+    /// that is after every newline character.
+    ResetParagraphs,
+}
+
+impl StyleContinuity {
+    /// Returns the default style continuity.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::Continuous
+    }
+}
+
+/// Controls which part of a [`TextBox`] is filled with a background color before the text is
+/// drawn.
+///
+/// The background color of the character style only covers the cells of the glyphs and
+/// whitespace. The background fill also covers the gaps left by alignment, short lines and line
+/// spacing.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum BackgroundFill {
+    /// Nothing is filled. This is the default.
+    None,
+
+    /// The whole bounding box is filled with the given color.
+    Bounds(Rgb888),
+
+    /// The full width of the bounding box is filled with the given color, from the top of the
+    /// first line to the bottom of the last line of text.
+    Text(Rgb888),
+}
+
+impl BackgroundFill {
+    /// Returns the default background fill.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::None
+    }
+
+    /// Returns the fill color, if the background is filled.
+    #[cfg(feature = "buffered")]
+    pub(crate) fn color(self) -> Option<Rgb888> {
+        match self {
+            BackgroundFill::None => None,
+            BackgroundFill::Bounds(color) | BackgroundFill::Text(color) => Some(color),
+        }
+    }
+}
+
+/// A shadow drawn beneath the text.
+///
+/// The shadow is a copy of the glyphs, drawn in the shadow color, displaced by `offset`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Shadow {
+    /// The displacement of the shadow relative to the text.
+    pub offset: Point,
+
+    /// The color of the shadow. The color is converted to the color type of the character style.
+    pub color: Rgb888,
+}
+
+/// A border drawn around the bounding box of a [`TextBox`].
+///
+/// The border is drawn inside the bounding box, so it follows the size of text boxes that fit
+/// their height to the text. The border doesn't move the text, use [`TextBox::with_padding`] to
+/// keep the text away from it.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+/// [`TextBox::with_padding`]: ../struct.TextBox.html#method.with_padding
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Border {
+    /// The color of the border. The color is converted to the color type of the character style.
+    pub color: Rgb888,
+
+    /// The thickness of the border, in pixels.
+    pub thickness: u32,
+
+    /// The radius of the corners, in pixels. A radius of 0 draws square corners.
+    pub corner_radius: u32,
+}
+
+impl Border {
+    /// Creates a new border with square corners.
+    #[inline]
+    pub const fn new(color: Rgb888, thickness: u32) -> Self {
+        Self {
+            color,
+            thickness,
+            corner_radius: 0,
+        }
+    }
+
+    /// Sets the radius of the corners.
+    #[inline]
+    pub const fn corner_radius(mut self, corner_radius: u32) -> Self {
+        self.corner_radius = corner_radius;
+
+        self
+    }
+}
+
+/// The columns the text of a [`TextBox`] flows into.
+///
+/// The text area is divided into `count` columns of equal width, separated by `gap` pixels. The
+/// text continues at the top of the next column when a column is full.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Columns {
+    /// The number of columns.
+    pub count: u32,
+
+    /// The distance between columns, in pixels.
+    pub gap: u32,
+}
+
+impl Columns {
+    /// Creates a new column layout.
+    #[inline]
+    pub const fn new(count: u32, gap: u32) -> Self {
+        Self { count, gap }
+    }
+
+    /// Returns the default column layout, which is a single column.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::new(1, 0)
+    }
+
+    /// Returns the areas of the columns inside `bounds`, from left to right.
+    pub(crate) fn areas(self, bounds: Rectangle) -> impl Iterator<Item = Rectangle> {
+        let count = self.count.max(1);
+        let gaps = self.gap.saturating_mul(count - 1);
+        let width = bounds.size.width.saturating_sub(gaps) / count;
+        let pitch = width.saturating_add(self.gap).saturating_as::<i32>();
+
+        (0..count).map(move |column| {
+            Rectangle::new(
+                bounds.top_left + Point::new(pitch * column.saturating_as::<i32>(), 0),
+                Size::new(width, bounds.size.height),
+            )
+        })
+    }
+}
+
 /// Styling options of a [`TextBox`].
 ///
 /// `TextBoxStyle` contains the font, foreground and background `PixelColor`, line spacing,
@@ -229,6 +724,76 @@ pub struct TextBoxStyle {
 
     /// Desired column width for tabs
     pub tab_size: TabSize,
+
+    /// Pixels added to the width of every whitespace block.
+    pub word_spacing: i32,
+
+    /// How overlong words are broken.
+    pub word_break: WordBreak,
+
+    /// The string displayed at the end of a line when a word is hyphenated.
+    pub hyphen_string: &'static str,
+
+    /// How whitespace is displayed.
+    pub white_space: WhiteSpace,
+
+    /// Whether spaces at the start and end of paragraphs are displayed.
+    pub paragraph_spaces: ParagraphSpaces,
+
+    /// How lines that are wider than the text box are handled.
+    pub wrap: Wrap,
+
+    /// The marker displayed where lines are wrapped.
+    pub wrap_marker: WrapMarker,
+
+    /// How carriage return characters are interpreted.
+    pub carriage_return: CarriageReturn,
+
+    /// How control characters are displayed.
+    pub control_characters: ControlCharacters,
+
+    /// How consecutive line breaks are displayed.
+    pub empty_lines: EmptyLines,
+
+    /// The indentation of the first line of paragraphs.
+    pub paragraph_indent: ParagraphIndent,
+
+    /// The color of underlines that are turned on by text style changes. `None` means the text
+    /// color.
+    pub underline_color: Option<Rgb888>,
+
+    /// The maximum number of characters in a line. `None` means that only the width of the text
+    /// box limits the lines.
+    pub max_chars_per_line: Option<u32>,
+
+    /// The color of the 1 pixel wide outline drawn around every glyph. `None` means that no
+    /// outline is drawn.
+    pub outline_color: Option<Rgb888>,
+
+    /// How the position of text is advanced after drawing a piece of text.
+    pub advance: Advance,
+
+    /// The shadow drawn beneath the text. `None` means that no shadow is drawn.
+    pub shadow: Option<Shadow>,
+
+    /// The part of the text box that is filled with a background color before drawing the text.
+    pub background_fill: BackgroundFill,
+
+    /// The version of the line layout algorithm.
+    pub layout_version: LayoutVersion,
+
+    /// The border drawn around the bounding box. `None` means that no border is drawn.
+    pub border: Option<Border>,
+
+    /// Whether text style changes are carried over to the next paragraph.
+    pub style_continuity: StyleContinuity,
+
+    /// The columns the text flows into.
+    pub columns: Columns,
+
+    /// The spacing of the grid the lines are snapped to, in pixels. `None` means that lines are
+    /// not snapped.
+    pub baseline_grid: Option<u32>,
 }
 
 impl TextBoxStyle {
@@ -254,7 +819,7 @@ impl TextBoxStyle {
 }
 
 /// Information about a line.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 #[must_use]
 pub(crate) struct LineMeasurement {
     /// Maximum line width in pixels.
@@ -271,18 +836,61 @@ pub(crate) struct LineMeasurement {
 
     /// Number of spaces in the current line.
     pub space_count: u32,
+
+    /// Number of words in the current line.
+    pub word_count: u32,
+
+    /// The height of the tallest font used in the line.
+    pub line_height: u32,
+
+    /// Whether a word had to be broken at the end of the line.
+    pub word_broken: bool,
+}
+
+/// The measurements of a text.
+///
+/// See [`TextBoxStyle::measure_text`].
+///
+/// [`TextBoxStyle::measure_text`]: struct.TextBoxStyle.html#method.measure_text
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TextMetrics {
+    /// The number of displayed lines.
+    ///
+    /// Lines that are drawn over each other, for example by a carriage return, are counted once.
+    pub line_count: u32,
+
+    /// The height of the text in pixels.
+    pub height: u32,
+
+    /// The width of the widest line in pixels.
+    pub width: u32,
+
+    /// Whether a word had to be broken because it didn't fit into a line.
+    pub word_broken: bool,
 }
 
 struct MeasureLineElementHandler<'a, S> {
     style: &'a S,
+    default_style: &'a S,
+    fonts: &'a [S],
+    font: Option<usize>,
+    line_height: Option<u32>,
     right: u32,
     max_line_width: u32,
     pos: u32,
     space_count: u32,
     partial_space_count: u32,
+    word_count: u32,
+    in_word: bool,
+    word_broken: bool,
 }
 
-impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
+impl<'a, S> ElementHandler<'_> for MeasureLineElementHandler<'a, S>
+where
+    S: TextRenderer,
+    S::Color: From<Rgb888>,
+{
     type Error = Infallible;
     type Color = S::Color;
 
@@ -290,8 +898,13 @@ impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
         str_width(self.style, st)
     }
 
-    fn whitespace(&mut self, st: &str, _count: u32, width: u32) -> Result<(), Self::Error> {
+    fn whitespace(&mut self, st: &str, count: u32, width: u32) -> Result<(), Self::Error> {
+        if count > 0 {
+            // Displayed whitespace is a part of the line, even if no word follows it.
+            self.right = self.right.max(self.pos + width);
+        }
         self.pos += width;
+        self.in_word = false;
 
         self.partial_space_count += st
             .chars()
@@ -303,6 +916,33 @@ impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
     }
 
     fn printed_characters(&mut self, _: &str, width: u32) -> Result<(), Self::Error> {
+        self.line_height = self.line_height.max(Some(self.style.line_height()));
+
+        if !self.in_word {
+            self.in_word = true;
+            self.word_count += 1;
+        }
+
+        self.right = self.right.max(self.pos + width);
+        self.pos += width;
+        self.space_count = self.partial_space_count;
+        Ok(())
+    }
+
+    fn change_text_style(
+        &mut self,
+        change: ChangeTextStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        if let Some(font) = change.selected_font(self.fonts.len()) {
+            self.font = font;
+            self.style = font_style(self.default_style, self.fonts, font);
+        }
+
+        Ok(())
+    }
+
+    fn custom(&mut self, _id: u32, width: u32) -> Result<(), Self::Error> {
+        self.in_word = false;
         self.right = self.right.max(self.pos + width);
         self.pos += width;
         self.space_count = self.partial_space_count;
@@ -310,12 +950,18 @@ impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
     }
 
     fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.in_word = false;
         self.pos = (self.pos.saturating_as::<i32>() + by)
             .max(0)
             .min(self.max_line_width.saturating_as()) as u32;
 
         Ok(())
     }
+
+    fn word_broken(&mut self) -> Result<(), Self::Error> {
+        self.word_broken = true;
+        Ok(())
+    }
 }
 
 impl TextBoxStyle {
@@ -339,33 +985,112 @@ impl TextBoxStyle {
         M: Plugin<'a, S::Color>,
         S::Color: From<Rgb888>,
     {
+        self.measure_line_with_fonts(
+            plugin,
+            character_style,
+            &[],
+            &mut None,
+            parser,
+            max_line_width,
+        )
+    }
+
+    /// Measures a single line of text that may switch between the fonts of a text box.
+    ///
+    /// `font` is the font that is selected at the start of the line, and it's updated to the font
+    /// that is selected at the end of the line.
+    pub(crate) fn measure_line_with_fonts<'a, S, M>(
+        &self,
+        plugin: &PluginWrapper<'a, M, S::Color>,
+        default_style: &S,
+        fonts: &[S],
+        font: &mut Option<usize>,
+        parser: &mut Parser<'a, S::Color>,
+        max_line_width: u32,
+    ) -> LineMeasurement
+    where
+        S: TextRenderer,
+        M: Plugin<'a, S::Color>,
+        S::Color: From<Rgb888>,
+    {
+        let character_style = font_style(default_style, fonts, *font);
         let cursor = LineCursor::new(max_line_width, self.tab_size.into_pixels(character_style));
 
+        self.measure_line_elements(plugin, default_style, fonts, font, parser, cursor, None)
+            .0
+    }
+
+    /// Measures a single line of text, starting at `cursor`.
+    ///
+    /// If `elements` is given, the elements of the line are recorded into it. Returns the
+    /// measurement, and the cursor after the last element of the line.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn measure_line_elements<'a, S, M>(
+        &self,
+        plugin: &PluginWrapper<'a, M, S::Color>,
+        default_style: &S,
+        fonts: &[S],
+        font: &mut Option<usize>,
+        parser: &mut Parser<'a, S::Color>,
+        cursor: LineCursor,
+        elements: Option<&mut LineElements<'a, S::Color>>,
+    ) -> (LineMeasurement, LineCursor)
+    where
+        S: TextRenderer,
+        M: Plugin<'a, S::Color>,
+        S::Color: From<Rgb888>,
+    {
+        let character_style = font_style(default_style, fonts, *font);
+        let max_line_width = cursor.line_width();
+
         let mut iter = LineElementParser::new(
             parser,
             plugin,
             cursor,
-            SpaceConfig::new(str_width(character_style, " "), None),
-            self.alignment,
+            SpaceConfig::new(str_width(character_style, " "), None)
+                .with_word_spacing(self.word_spacing),
+            self,
         );
 
         let mut handler = MeasureLineElementHandler {
             style: character_style,
+            default_style,
+            fonts,
+            font: *font,
+            line_height: None,
             right: 0,
             pos: 0,
             max_line_width,
             space_count: 0,
             partial_space_count: 0,
+            word_count: 0,
+            in_word: false,
+            word_broken: false,
         };
-        let last_token = iter.process(&mut handler).unwrap();
+        let last_token = match elements {
+            Some(elements) => iter.process(&mut ElementRecorder {
+                handler: &mut handler,
+                elements,
+            }),
+            None => iter.process(&mut handler),
+        }
+        .unwrap();
+        *font = handler.font;
 
-        LineMeasurement {
+        let measurement = LineMeasurement {
             max_line_width,
             width: handler.right,
             space_count: handler.space_count,
+            word_count: handler.word_count,
             last_line: matches!(last_token, LineEndType::NewLine | LineEndType::EndOfText),
             line_end_type: last_token,
-        }
+            line_height: handler
+                .line_height
+                .unwrap_or_else(|| handler.style.line_height()),
+            word_broken: handler.word_broken,
+        };
+
+        (measurement, iter.cursor)
     }
 
     /// Measures text height when rendered using a given width.
@@ -406,58 +1131,302 @@ impl TextBoxStyle {
     #[inline]
     #[must_use]
     pub fn measure_text_height<S>(&self, character_style: &S, text: &str, max_width: u32) -> u32
+    where
+        S: TextRenderer,
+        S::Color: From<Rgb888>,
+    {
+        self.measure_text(character_style, text, max_width).height
+    }
+
+    /// Measures text when rendered using a given width.
+    ///
+    /// Returns the number of lines, the height of the text, the width of the widest line, and
+    /// whether a word had to be broken. The width can be used to size or center a text box
+    /// that is fitted to its text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyleBuilder;
+    /// # use embedded_graphics::{
+    /// #     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    /// #     pixelcolor::BinaryColor,
+    /// # };
+    /// #
+    /// let character_style = MonoTextStyleBuilder::new()
+    ///     .font(&FONT_6X9)
+    ///     .text_color(BinaryColor::On)
+    ///     .build();
+    /// let style = TextBoxStyleBuilder::new().build();
+    ///
+    /// let metrics = style.measure_text(&character_style, "Hello, World!", 60);
+    ///
+    /// // |Hello,    |
+    /// // |World!    |
+    ///
+    /// assert_eq!(metrics.line_count, 2);
+    /// assert_eq!(metrics.height, 2 * 9);
+    /// assert_eq!(metrics.width, 6 * 6);
+    /// assert!(!metrics.word_broken);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn measure_text<S>(&self, character_style: &S, text: &str, max_width: u32) -> TextMetrics
     where
         S: TextRenderer,
         S::Color: From<Rgb888>,
     {
         let plugin = PluginWrapper::new(NoPlugin::new());
-        self.measure_text_height_impl(plugin, character_style, text, max_width)
+        self.measure_text_impl(plugin, character_style, &[], text, max_width)
+    }
+
+    /// Returns whether the text fits into an area of the given size.
+    ///
+    /// The text fits if it's not taller than `size`, and none of its lines are wider than
+    /// `size`. Words that are broken into multiple lines still fit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyle;
+    /// # use embedded_graphics::{
+    /// #     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    /// #     pixelcolor::BinaryColor,
+    /// #     prelude::*,
+    /// # };
+    /// #
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyle::default();
+    ///
+    /// assert!(style.fits(&character_style, "Hello, World!", Size::new(60, 18)));
+    /// assert!(!style.fits(&character_style, "Hello, World!", Size::new(60, 9)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn fits<S>(&self, character_style: &S, text: &str, size: Size) -> bool
+    where
+        S: TextRenderer,
+        S::Color: From<Rgb888>,
+    {
+        let metrics = self.measure_text(character_style, text, size.width);
+
+        metrics.height <= size.height && metrics.width <= size.width
+    }
+
+    /// Returns the largest character style that the text fits into an area of the given size
+    /// with.
+    ///
+    /// `character_styles` must be ordered by increasing font size, so that the text fits with a
+    /// style if it fits with the next one. The styles are tried using a binary search. Returns
+    /// `None` if the text doesn't fit with any of the styles. See [`fits`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyle;
+    /// # use embedded_graphics::{
+    /// #     mono_font::{
+    /// #         ascii::{FONT_10X20, FONT_6X9, FONT_8X13},
+    /// #         MonoTextStyle,
+    /// #     },
+    /// #     pixelcolor::BinaryColor,
+    /// #     prelude::*,
+    /// # };
+    /// #
+    /// let character_styles = [
+    ///     MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+    ///     MonoTextStyle::new(&FONT_8X13, BinaryColor::On),
+    ///     MonoTextStyle::new(&FONT_10X20, BinaryColor::On),
+    /// ];
+    /// let style = TextBoxStyle::default();
+    ///
+    /// let label = style.largest_fitting(&character_styles, "Hello", Size::new(48, 16));
+    /// assert_eq!(label, Some(&character_styles[1]));
+    /// ```
+    ///
+    /// [`fits`]: #method.fits
+    #[inline]
+    pub fn largest_fitting<'s, S>(
+        &self,
+        character_styles: &'s [S],
+        text: &str,
+        size: Size,
+    ) -> Option<&'s S>
+    where
+        S: TextRenderer,
+        S::Color: From<Rgb888>,
+    {
+        // The text fits with every style before `low`, and doesn't fit with any style from `high`.
+        let mut low = 0;
+        let mut high = character_styles.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.fits(&character_styles[mid], text, size) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        low.checked_sub(1).map(|index| &character_styles[index])
+    }
+
+    /// Returns the longest part of the text, from its beginning, whose lines fit completely into
+    /// an area of the given size.
+    ///
+    /// The text is laid out the same way as a text box of the given size lays it out, starting at
+    /// the top of the area. The returned text ends where the first line that doesn't fit starts,
+    /// so the rest of the text can be displayed on the next page.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::style::TextBoxStyle;
+    /// # use embedded_graphics::{
+    /// #     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    /// #     pixelcolor::BinaryColor,
+    /// #     prelude::*,
+    /// # };
+    /// #
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyle::default();
+    ///
+    /// let text = "Hello, World! How are you?";
+    /// let shown = style.fit_text(&character_style, text, Size::new(60, 20));
+    ///
+    /// assert_eq!(shown, "Hello, World! How ");
+    /// assert_eq!(&text[shown.len()..], "are you?");
+    /// ```
+    #[inline]
+    pub fn fit_text<'t, S>(&self, character_style: &S, text: &'t str, size: Size) -> &'t str
+    where
+        S: TextRenderer + CharacterStyle,
+        <S as TextRenderer>::Color: From<Rgb888>,
+    {
+        let style = TextBoxStyle {
+            vertical_alignment: VerticalAlignment::Top,
+            height_mode: HeightMode::Exact(VerticalOverdraw::FullRowsOnly),
+            ..*self
+        };
+        let text_box = TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size),
+            character_style.clone(),
+            style,
+        );
+
+        let end = LineLayout::new(&text_box, text)
+            .take_while(|line| line.in_bounds)
+            .last()
+            .map_or(0, |line| line.offset + line.text.len());
+
+        &text[..end]
     }
 
-    pub(crate) fn measure_text_height_impl<'a, S, M>(
+    pub(crate) fn measure_text_impl<'a, S, M>(
         &self,
         plugin: PluginWrapper<'a, M, S::Color>,
         character_style: &S,
+        fonts: &[S],
         text: &'a str,
         max_width: u32,
-    ) -> u32
+    ) -> TextMetrics
     where
         S: TextRenderer,
         M: Plugin<'a, S::Color>,
         S::Color: From<Rgb888>,
     {
-        let mut parser = Parser::parse(text);
+        self.measure_text_with_widths(plugin, character_style, fonts, text, |_, _| max_width)
+    }
+
+    /// Measures text with lines of different widths.
+    ///
+    /// `line_width` is called with the index of every line and its vertical offset from the first
+    /// line, and returns the width the line is wrapped at.
+    pub(crate) fn measure_text_with_widths<'a, S, M>(
+        &self,
+        plugin: PluginWrapper<'a, M, S::Color>,
+        character_style: &S,
+        fonts: &[S],
+        text: &'a str,
+        mut line_width: impl FnMut(u32, u32) -> u32,
+    ) -> TextMetrics
+    where
+        S: TextRenderer,
+        M: Plugin<'a, S::Color>,
+        S::Color: From<Rgb888>,
+    {
+        let mut parser = Parser::parse(text).with_style(self);
+        let mut line_count = 1;
+        let mut width = 0;
+        let mut word_broken = false;
         let mut closed_paragraphs: u32 = 0;
-        let line_height = self.line_height.to_absolute(character_style.line_height());
-        let last_line_height = character_style.line_height();
-        let mut height = last_line_height;
         let mut paragraph_ended = false;
+        let mut font = None;
+
+        // The vertical position of the current row of text, and the height of its tallest line.
+        let mut top = 0;
+        let mut row_height = 0;
 
         plugin.set_state(ProcessingState::Measure);
 
         let mut prev_end = LineEndType::EndOfText;
+        let mut line_index = 0;
 
         loop {
             plugin.new_line();
-            let lm = self.measure_line(&plugin, character_style, &mut parser, max_width);
+
+            // Lines after a line break are placed in a new row when they are drawn.
+            let row_top = if prev_end == LineEndType::LineBreak {
+                top + self.line_height.to_absolute(row_height)
+            } else {
+                top
+            };
+            let paragraphs = closed_paragraphs + u32::from(paragraph_ended);
+            let max_width = line_width(line_index, row_top + paragraphs * self.paragraph_spacing);
+            line_index += 1;
+
+            let lm = self.measure_line_with_fonts(
+                &plugin,
+                character_style,
+                fonts,
+                &mut font,
+                &mut parser,
+                max_width,
+            );
 
             if paragraph_ended {
                 closed_paragraphs += 1;
             }
             paragraph_ended = lm.last_line;
+            width = width.max(lm.width);
+            word_broken |= lm.word_broken;
 
             if prev_end == LineEndType::LineBreak && lm.width != 0 {
-                height += line_height;
+                top += self.line_height.to_absolute(row_height);
+                row_height = 0;
+                line_count += 1;
             }
+            row_height = row_height.max(lm.line_height);
 
             match lm.line_end_type {
                 LineEndType::CarriageReturn => {}
                 LineEndType::LineBreak => {}
+                // Cursor movements don't change the height of the text.
+                #[cfg(feature = "ansi")]
+                LineEndType::MoveCursor(_) | LineEndType::EraseDisplay(_) => {}
                 LineEndType::NewLine => {
-                    height += line_height;
+                    top += self.line_height.to_absolute(row_height);
+                    row_height = 0;
+                    line_count += 1;
                 }
                 LineEndType::EndOfText => {
-                    return height + closed_paragraphs * self.paragraph_spacing;
+                    return TextMetrics {
+                        line_count,
+                        height: top + row_height + closed_paragraphs * self.paragraph_spacing,
+                        width,
+                        word_broken,
+                    };
                 }
             }
             prev_end = lm.line_end_type;
@@ -474,8 +1443,12 @@ mod test {
         style::{builder::TextBoxStyleBuilder, TextBoxStyle},
     };
     use embedded_graphics::{
-        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        mono_font::{
+            ascii::{FONT_10X20, FONT_6X9, FONT_8X13},
+            MonoTextStyleBuilder,
+        },
         pixelcolor::BinaryColor,
+        prelude::Size,
         text::{renderer::TextRenderer, LineHeight},
     };
 
@@ -542,6 +1515,93 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_measure_text() {
+        let data = [
+            // (text; max width in characters; (lines, width in characters, word broken))
+            ("", 5, (1, 0, false)),
+            ("word\nlonger", 50, (2, 6, false)),
+            ("some words", 5, (2, 5, false)),
+            ("verylongword", 5, (3, 5, true)),
+            ("Longer\rcr", 50, (1, 6, false)),
+        ];
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyle::default();
+
+        for (text, width, (lines, chars, broken)) in data.iter() {
+            let metrics = style.measure_text(&character_style, text, *width * 6);
+            assert_eq!(
+                (metrics.line_count, metrics.width, metrics.word_broken),
+                (*lines, *chars * 6, *broken),
+                "{:?}",
+                text
+            );
+            assert_eq!(metrics.height, *lines * 9);
+        }
+    }
+
+    #[test]
+    fn largest_fitting() {
+        let character_styles = [
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .text_color(BinaryColor::On)
+                .build(),
+            MonoTextStyleBuilder::new()
+                .font(&FONT_8X13)
+                .text_color(BinaryColor::On)
+                .build(),
+            MonoTextStyleBuilder::new()
+                .font(&FONT_10X20)
+                .text_color(BinaryColor::On)
+                .build(),
+        ];
+        let style = TextBoxStyle::default();
+
+        let largest = |text, width, height| {
+            style
+                .largest_fitting(&character_styles, text, Size::new(width, height))
+                .map(|character_style| character_style.font.character_size.width)
+        };
+
+        assert_eq!(largest("ab", 100, 100), Some(10));
+        assert_eq!(largest("ab", 16, 13), Some(8));
+        assert_eq!(largest("ab", 12, 9), Some(6));
+        assert_eq!(largest("ab", 12, 8), None);
+        assert_eq!(largest("ab cd", 30, 30), Some(8));
+        assert_eq!(largest("", 0, 9), Some(6));
+    }
+
+    #[test]
+    fn fit_text() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let fit = |style: TextBoxStyle, text, lines: u32| {
+            style.fit_text(&character_style, text, Size::new(30, lines * 9))
+        };
+
+        let style = TextBoxStyle::default();
+        assert_eq!(fit(style, "", 1), "");
+        assert_eq!(fit(style, "ab cd", 0), "");
+        assert_eq!(fit(style, "ab\ncd\nef", 2), "ab\ncd\n");
+        assert_eq!(fit(style, "abc defg hij", 2), "abc defg ");
+        assert_eq!(fit(style, "abc defg hij", 3), "abc defg hij");
+
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(VerticalAlignment::Bottom)
+            .paragraph_spacing(9)
+            .build();
+        assert_eq!(fit(style, "ab\ncd\nef", 2), "ab\n");
+    }
+
     #[test]
     fn test_measure_height_ignored_spaces() {
         let data = [
@@ -586,9 +1646,9 @@ mod test {
 
         let mut text = Parser::parse("123 45 67");
 
-        let mut plugin = PluginWrapper::new(NoPlugin::new());
+        let plugin = PluginWrapper::new(NoPlugin::new());
         let lm = style.measure_line(
-            &mut plugin,
+            &plugin,
             &character_style,
             &mut text,
             6 * FONT_6X9.character_size.width,
@@ -610,9 +1670,9 @@ mod test {
 
         let mut text = Parser::parse("123\x1b[2D");
 
-        let mut plugin = PluginWrapper::new(NoPlugin::new());
+        let plugin = PluginWrapper::new(NoPlugin::new());
         let lm = style.measure_line(
-            &mut plugin,
+            &plugin,
             &character_style,
             &mut text,
             5 * FONT_6X9.character_size.width,
@@ -623,9 +1683,9 @@ mod test {
         // continuation after rewind extends the line.
         let mut text = Parser::parse("123\x1b[2D456");
 
-        let mut plugin = PluginWrapper::new(NoPlugin::new());
+        let plugin = PluginWrapper::new(NoPlugin::new());
         let lm = style.measure_line(
-            &mut plugin,
+            &plugin,
             &character_style,
             &mut text,
             5 * FONT_6X9.character_size.width,
@@ -646,9 +1706,9 @@ mod test {
 
         let mut text = Parser::parse("123\u{A0}45");
 
-        let mut plugin = PluginWrapper::new(NoPlugin::new());
+        let plugin = PluginWrapper::new(NoPlugin::new());
         let lm = style.measure_line(
-            &mut plugin,
+            &plugin,
             &character_style,
             &mut text,
             5 * FONT_6X9.character_size.width,
@@ -714,9 +1774,9 @@ mod test {
             .line_height(LineHeight::Pixels(11))
             .build();
 
-        let mut plugin = PluginWrapper::new(NoPlugin::new());
+        let plugin = PluginWrapper::new(NoPlugin::new());
         let lm = style.measure_line(
-            &mut plugin,
+            &plugin,
             &character_style,
             &mut Parser::parse("soft\u{AD}hyphen"),
             50,
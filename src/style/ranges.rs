@@ -0,0 +1,256 @@
+//! Style changes applied to ranges of the text.
+use core::{iter::once, ops::Range};
+
+use embedded_graphics::{
+    prelude::PixelColor,
+    text::{renderer::CharacterStyle, DecorationColor},
+};
+
+/// Changes to the character style, applied to a range of text by [`StyledRanges`].
+///
+/// Properties that are not set keep their value from the current character style.
+///
+/// [`StyledRanges`]: struct.StyledRanges.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StyleDelta<C> {
+    text_color: Option<C>,
+    underline: Option<DecorationColor<C>>,
+    strikethrough: Option<DecorationColor<C>>,
+}
+
+impl<C> StyleDelta<C> {
+    /// Creates a style delta that doesn't change anything.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            text_color: None,
+            underline: None,
+            strikethrough: None,
+        }
+    }
+
+    /// Sets the text color.
+    #[inline]
+    pub fn text_color(self, color: C) -> Self {
+        Self {
+            text_color: Some(color),
+            ..self
+        }
+    }
+
+    /// Sets the underline color. `DecorationColor::None` turns underlining off.
+    #[inline]
+    pub fn underline(self, color: DecorationColor<C>) -> Self {
+        Self {
+            underline: Some(color),
+            ..self
+        }
+    }
+
+    /// Sets the strikethrough color. `DecorationColor::None` turns strikethrough off.
+    #[inline]
+    pub fn strikethrough(self, color: DecorationColor<C>) -> Self {
+        Self {
+            strikethrough: Some(color),
+            ..self
+        }
+    }
+}
+
+impl<C> Default for StyleDelta<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> StyleDelta<C>
+where
+    C: PixelColor,
+{
+    /// Overrides the properties of `self` with the properties set in `other`.
+    fn merge(self, other: &Self) -> Self {
+        Self {
+            text_color: other.text_color.or(self.text_color),
+            underline: other.underline.or(self.underline),
+            strikethrough: other.strikethrough.or(self.strikethrough),
+        }
+    }
+
+    /// Applies the changes to a character style.
+    pub(crate) fn apply<S: CharacterStyle<Color = C>>(&self, style: &mut S) {
+        if let Some(color) = self.text_color {
+            style.set_text_color(Some(color));
+        }
+        if let Some(color) = self.underline {
+            style.set_underline_color(color);
+        }
+        if let Some(color) = self.strikethrough {
+            style.set_strikethrough_color(color);
+        }
+    }
+}
+
+/// Styles applied to ranges of the text of a text box.
+///
+/// Every range is a pair of a byte range in the text of the text box, and the changes to the
+/// character style that should be used to draw that part of the text. Ranges may overlap, in
+/// which case the properties set by later ranges take precedence. Range boundaries should be
+/// on character boundaries, other boundaries are moved to the next character boundary.
+///
+/// Styled ranges only change colors and decorations, so they don't affect the layout of the
+/// text. This makes them useful to highlight search results or a selection without modifying the
+/// text or writing a plugin. See [`TextBox::set_styled_ranges`].
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+///     text::DecorationColor,
+/// };
+/// use embedded_text::{
+///     style::{StyleDelta, StyledRanges},
+///     TextBox,
+/// };
+///
+/// let ranges = [
+///     (0..5, StyleDelta::new().text_color(Rgb888::RED)),
+///     (6..11, StyleDelta::new().underline(DecorationColor::TextColor)),
+/// ];
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 18));
+///
+/// let mut text_box = TextBox::new("Hello World", bounds, character_style);
+/// text_box.set_styled_ranges(StyledRanges::new(&ranges));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox::set_styled_ranges`]: ../struct.TextBox.html#method.set_styled_ranges
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StyledRanges<'r, C> {
+    ranges: &'r [(Range<usize>, StyleDelta<C>)],
+}
+
+impl<'r, C> StyledRanges<'r, C> {
+    /// Creates a new object from `(byte range, style delta)` pairs.
+    #[inline]
+    pub const fn new(ranges: &'r [(Range<usize>, StyleDelta<C>)]) -> Self {
+        Self { ranges }
+    }
+}
+
+impl<'r, C> StyledRanges<'r, C>
+where
+    C: PixelColor,
+{
+    /// Returns the combined style changes of the ranges that contain `offset`.
+    pub(crate) fn delta_at(&self, offset: usize) -> StyleDelta<C> {
+        self.ranges
+            .iter()
+            .filter(|(range, _)| range.contains(&offset))
+            .fold(StyleDelta::new(), |delta, (_, other)| delta.merge(other))
+    }
+
+    /// Returns the first range boundary after `offset`.
+    pub(crate) fn next_boundary(&self, offset: usize) -> Option<usize> {
+        self.ranges
+            .iter()
+            .flat_map(|(range, _)| once(range.start).chain(once(range.end)))
+            .filter(|boundary| *boundary > offset)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        text::DecorationColor,
+        Drawable,
+    };
+
+    use crate::{
+        style::{StyleDelta, StyledRanges},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    #[track_caller]
+    fn assert_rendered(
+        text: &str,
+        size: Size,
+        ranges: &[(core::ops::Range<usize>, StyleDelta<BinaryColor>)],
+        pattern: &[&str],
+    ) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let mut text_box = TextBox::new(text, Rectangle::new(Point::zero(), size), character_style);
+        text_box.set_styled_ranges(StyledRanges::new(ranges));
+        text_box.draw(&mut display).unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn ranges_split_words() {
+        assert_rendered(
+            "ab cd",
+            size_for(&FONT_6X9, 5, 1),
+            &[(1..4, StyleDelta::new().text_color(BinaryColor::Off))],
+            &[
+                "                             ",
+                "       .                    #",
+                "       .                    #",
+                "  ###  ...          ...   ###",
+                " #  #  .  .        .     #  #",
+                " #  #  .  .        .     #  #",
+                "  ###  ...          ...   ###",
+            ],
+        );
+    }
+
+    #[test]
+    fn later_ranges_take_precedence() {
+        assert_rendered(
+            "ab cd",
+            size_for(&FONT_6X9, 5, 1),
+            &[
+                (0..5, StyleDelta::new().text_color(BinaryColor::Off)),
+                (
+                    2..4,
+                    StyleDelta::new()
+                        .text_color(BinaryColor::On)
+                        .underline(DecorationColor::TextColor),
+                ),
+            ],
+            &[
+                "                             ",
+                "       .                    .",
+                "       .                    .",
+                "  ...  ...          ###   ...",
+                " .  .  .  .        #     .  .",
+                " .  .  .  .        #     .  .",
+                "  ...  ...          ###   ...",
+                "                             ",
+                "            ############     ",
+            ],
+        );
+    }
+}
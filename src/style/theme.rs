@@ -0,0 +1,207 @@
+//! Colors that are shared by multiple text boxes.
+use core::{
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use embedded_graphics::{
+    pixelcolor::{Rgb888, RgbColor},
+    text::renderer::CharacterStyle,
+};
+
+/// Marks a stored color as set.
+const COLOR_SET: u32 = 1 << 24;
+
+/// Encodes an optional color into a single integer.
+fn encode(color: Option<Rgb888>) -> u32 {
+    color.map_or(0, |color| {
+        COLOR_SET | u32::from(color.r()) << 16 | u32::from(color.g()) << 8 | u32::from(color.b())
+    })
+}
+
+/// Decodes an optional color that was encoded by `encode`.
+fn decode(value: u32) -> Option<Rgb888> {
+    if value & COLOR_SET == 0 {
+        None
+    } else {
+        Some(Rgb888::new(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ))
+    }
+}
+
+/// Text and background colors that are applied to text boxes when they are drawn.
+///
+/// A theme is shared by any number of text boxes using [`TextBox::set_theme`]. The colors of the
+/// theme are read every time a text box is drawn, and they replace the colors of its character
+/// style. Changing the colors of a theme, for example to switch between a day and a night mode,
+/// updates every text box that uses it on the next frame, without changing their styles.
+///
+/// Colors that are not set in the theme are taken from the character style of the text box. Text
+/// style changes in the text, like ANSI sequences, are applied over the colors of the theme.
+///
+/// A theme can be stored in a `static` variable, and its colors can be changed through a shared
+/// reference.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{style::Theme, TextBox};
+///
+/// static THEME: Theme = Theme::new();
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::BLACK);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut text_box = TextBox::new("Hello", bounds, character_style);
+/// text_box.set_theme(&THEME);
+///
+/// // Switch to night mode.
+/// THEME.set_text_color(Some(Rgb888::WHITE));
+/// THEME.set_background_color(Some(Rgb888::BLACK));
+///
+/// let mut display = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox::set_theme`]: ../struct.TextBox.html#method.set_theme
+#[derive(Debug, Default)]
+pub struct Theme {
+    text_color: AtomicU32,
+    background_color: AtomicU32,
+}
+
+impl Theme {
+    /// Creates a new theme that doesn't change any colors.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            text_color: AtomicU32::new(0),
+            background_color: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the text color of the theme.
+    #[inline]
+    pub fn text_color(&self) -> Option<Rgb888> {
+        decode(self.text_color.load(Ordering::Relaxed))
+    }
+
+    /// Sets the text color of the theme.
+    ///
+    /// `None` keeps the text color of the character style of the text boxes.
+    #[inline]
+    pub fn set_text_color(&self, color: Option<Rgb888>) {
+        self.text_color.store(encode(color), Ordering::Relaxed);
+    }
+
+    /// Returns the background color of the theme.
+    #[inline]
+    pub fn background_color(&self) -> Option<Rgb888> {
+        decode(self.background_color.load(Ordering::Relaxed))
+    }
+
+    /// Sets the background color of the theme.
+    ///
+    /// `None` keeps the background color of the character style of the text boxes.
+    #[inline]
+    pub fn set_background_color(&self, color: Option<Rgb888>) {
+        self.background_color
+            .store(encode(color), Ordering::Relaxed);
+    }
+
+    /// Applies the colors of the theme to a character style.
+    pub(crate) fn apply<S>(&self, style: &mut S)
+    where
+        S: CharacterStyle,
+        S::Color: From<Rgb888>,
+    {
+        if let Some(color) = self.text_color() {
+            style.set_text_color(Some(color.into()));
+        }
+        if let Some(color) = self.background_color() {
+            style.set_background_color(Some(color.into()));
+        }
+    }
+}
+
+impl Hash for Theme {
+    /// Hashes the current colors of the theme.
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text_color().hash(state);
+        self.background_color().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{style::Theme, utils::test::size_for, TextBox};
+
+    #[test]
+    fn colors_are_stored() {
+        let theme = Theme::new();
+        assert_eq!(theme.text_color(), None);
+
+        theme.set_text_color(Some(Rgb888::new(1, 2, 3)));
+        theme.set_background_color(Some(Rgb888::BLACK));
+        assert_eq!(theme.text_color(), Some(Rgb888::new(1, 2, 3)));
+        assert_eq!(theme.background_color(), Some(Rgb888::BLACK));
+
+        theme.set_text_color(None);
+        assert_eq!(theme.text_color(), None);
+    }
+
+    #[test]
+    fn theme_is_applied_when_drawing() {
+        static THEME: Theme = Theme::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let mut text_box = TextBox::new(
+            "a",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1)),
+            character_style,
+        );
+        text_box.set_theme(&THEME);
+
+        let draw = || {
+            let mut display = MockDisplay::new();
+            text_box.draw(&mut display).unwrap();
+            display
+        };
+
+        let day = draw();
+        THEME.set_text_color(Some(Rgb888::BLACK));
+        THEME.set_background_color(Some(Rgb888::WHITE));
+        let night = draw();
+
+        day.assert_pattern(&[
+            "      ", "      ", "      ", "  ### ", " #  # ", " #  # ", "  ### ",
+        ]);
+        night.assert_pattern(&[
+            "######", "######", "######", "##...#", "#.##.#", "#.##.#", "##...#", "######",
+            "######",
+        ]);
+    }
+}
@@ -1,9 +1,18 @@
 //! Vertical overdraw options.
 use crate::rendering::cursor::Cursor;
-use core::ops::Range;
+use core::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
 
 /// Vertical overdraw options used by height modes that don't conform exactly to the text size.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Copy, Clone, Eq, Debug)]
 pub enum VerticalOverdraw {
     /// Only render full rows of text.
     FullRowsOnly,
@@ -11,6 +20,72 @@ pub enum VerticalOverdraw {
     Hidden,
     /// Display text even if it's outside the bounding box.
     Visible,
+    /// Render partially visible rows inside the bounding box, and only draw the pixels of them
+    /// that the mask function selects.
+    ///
+    /// The mask function is called with the position of a pixel, relative to the top left corner
+    /// of the visible part of the line, and the size of the visible part. It returns whether the
+    /// pixel is drawn. Lines that are fully visible are not masked. This can be used to fade out
+    /// the last line, to indicate that the text continues below.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    ///     primitives::Rectangle,
+    /// };
+    /// use embedded_text::{
+    ///     style::{HeightMode, TextBoxStyleBuilder, VerticalOverdraw},
+    ///     TextBox,
+    /// };
+    ///
+    /// // Draws every second pixel of the partially visible line.
+    /// fn dither(point: Point, _visible: Size) -> bool {
+    ///     (point.x + point.y) % 2 == 0
+    /// }
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .height_mode(HeightMode::Exact(VerticalOverdraw::Masked(dither)))
+    ///     .build();
+    ///
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(30, 14));
+    ///
+    /// let mut display = MockDisplay::new();
+    /// TextBox::with_textbox_style("Some\nmore\ntext", bounds, character_style, style)
+    ///     .draw(&mut display)
+    ///     .unwrap();
+    /// ```
+    Masked(fn(Point, Size) -> bool),
+}
+
+impl PartialEq for VerticalOverdraw {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (VerticalOverdraw::FullRowsOnly, VerticalOverdraw::FullRowsOnly)
+            | (VerticalOverdraw::Hidden, VerticalOverdraw::Hidden)
+            | (VerticalOverdraw::Visible, VerticalOverdraw::Visible) => true,
+            (VerticalOverdraw::Masked(a), VerticalOverdraw::Masked(b)) => {
+                *a as usize == *b as usize
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Hash for VerticalOverdraw {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        if let VerticalOverdraw::Masked(mask) = self {
+            (*mask as usize).hash(state);
+        }
+    }
 }
 
 impl VerticalOverdraw {
@@ -25,7 +100,7 @@ impl VerticalOverdraw {
                 }
             }
 
-            VerticalOverdraw::Hidden => {
+            VerticalOverdraw::Hidden | VerticalOverdraw::Masked(_) => {
                 let offset_top = (cursor.top_left().y - cursor.y).max(0);
                 let offset_bottom =
                     (cursor.bottom_right().y - cursor.y + 1).min(cursor.line_height());
@@ -36,6 +111,96 @@ impl VerticalOverdraw {
             VerticalOverdraw::Visible => 0..cursor.line_height(),
         }
     }
+
+    /// Returns the mask of a line, if only a part of the line is displayed in `area`.
+    pub(crate) fn row_mask(
+        self,
+        cursor: &Cursor,
+        displayed_rows: &Range<i32>,
+        area: Rectangle,
+    ) -> Option<RowMask> {
+        match self {
+            VerticalOverdraw::Masked(mask)
+                if displayed_rows.start < displayed_rows.end
+                    && displayed_rows.clone().count() < cursor.line_height() as usize =>
+            {
+                Some(RowMask { mask, area })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The mask of a partially displayed line.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct RowMask {
+    mask: fn(Point, Size) -> bool,
+    area: Rectangle,
+}
+
+/// Draw target adapter that only draws the pixels that are selected by a row mask.
+pub(crate) struct Masked<'d, D> {
+    target: &'d mut D,
+    mask: Option<RowMask>,
+}
+
+impl<'d, D> Masked<'d, D> {
+    /// Applies `mask` to the pixels that are drawn into `target`.
+    pub fn new(target: &'d mut D, mask: Option<RowMask>) -> Self {
+        Self { target, mask }
+    }
+}
+
+impl<'d, D> Dimensions for Masked<'d, D>
+where
+    D: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<'d, D> DrawTarget for Masked<'d, D>
+where
+    D: DrawTarget,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        match self.mask {
+            Some(RowMask { mask, area }) => self.target.draw_iter(
+                pixels
+                    .into_iter()
+                    .filter(|Pixel(point, _)| mask(*point - area.top_left, area.size)),
+            ),
+            None => self.target.draw_iter(pixels),
+        }
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        match self.mask {
+            Some(_) => self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            ),
+            None => self.target.fill_contiguous(area, colors),
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        match self.mask {
+            Some(_) => self.fill_contiguous(area, core::iter::repeat(color)),
+            None => self.target.fill_solid(area, color),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -167,4 +332,50 @@ mod test {
             "#.#.#..#..#..#.....#..#.",
         ]);
     }
+
+    #[test]
+    fn masked_only_draws_selected_pixels_of_partial_rows() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(HeightMode::Exact(VerticalOverdraw::Masked(
+                |point, size| {
+                    assert_eq!(size, Size::new(12, 5));
+                    point.x % 2 == 0
+                },
+            )))
+            .build();
+
+        TextBox::with_textbox_style(
+            "ab\ncd",
+            Rectangle::new(Point::zero(), Size::new(12, 14)),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "............",
+            ".......#....",
+            ".......#....",
+            "..###..###..",
+            ".#..#..#..#.",
+            ".#..#..#..#.",
+            "..###..###..",
+            "............",
+            "............",
+            ". . . . . . ",
+            ". . . . . # ",
+            ". . . . . # ",
+            ". # # . # # ",
+            ". . . . . # ",
+        ]);
+    }
 }
@@ -26,6 +26,31 @@ pub fn str_width(renderer: &impl TextRenderer, s: &str) -> u32 {
     width(s) - nbsp_count * (width("\u{a0}").saturating_sub(width(" ")))
 }
 
+/// Returns the byte offset of `st` in `text`, if `st` is a part of `text`.
+pub fn offset_in(text: &str, st: &str) -> Option<usize> {
+    let start = (st.as_ptr() as usize).checked_sub(text.as_ptr() as usize)?;
+
+    if start + st.len() <= text.len() {
+        Some(start)
+    } else {
+        None
+    }
+}
+
+/// Copies the longest prefix of `s` that fits into `buffer` and ends at a character boundary.
+///
+/// Returns the number of copied bytes.
+pub fn copy_truncated(buffer: &mut [u8], s: &str) -> usize {
+    let len = (0..=s.len().min(buffer.len()))
+        .rev()
+        .find(|len| s.is_char_boundary(*len))
+        .unwrap_or(0);
+
+    buffer[..len].copy_from_slice(&s.as_bytes()[..len]);
+
+    len
+}
+
 #[cfg(test)]
 pub mod test {
     use embedded_graphics::{
@@ -0,0 +1,355 @@
+//! Text box with vertical text.
+
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Dimensions, Point},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+    Drawable,
+};
+
+use crate::{
+    grapheme::graphemes,
+    parser::{Parser, Token},
+    style::TextBoxStyle,
+    utils::{offset_in, str_width},
+};
+
+/// The order of the columns of a [`VerticalTextBox`].
+///
+/// [`VerticalTextBox`]: struct.VerticalTextBox.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ColumnOrder {
+    /// The first column is at the right edge of the bounding box, like in vertical CJK text. This
+    /// is the default.
+    RightToLeft,
+
+    /// The first column is at the left edge of the bounding box.
+    LeftToRight,
+}
+
+impl ColumnOrder {
+    /// Returns the default column order.
+    #[inline]
+    pub const fn default() -> Self {
+        Self::RightToLeft
+    }
+}
+
+/// A text box that lays out the text in vertical columns.
+///
+/// The characters of a column flow from the top to the bottom of the bounding box. Columns
+/// advance from right to left, or from left to right, depending on the [`ColumnOrder`]. Vertical
+/// text is meant for narrow side labels and vertical CJK signage.
+///
+/// Every character is drawn in a cell that is as high as the line height of the character style.
+/// Every column is as wide as the widest character of the text, and characters are centered in
+/// their columns. The distance between columns is calculated from the column width and the line
+/// height of the text box style. The text is tokenized like in a [`TextBox`], so newline
+/// characters start a new column, and the carriage return and control character settings of the
+/// text box style apply. Columns that are full are wrapped at any character, and spaces at the
+/// top of a wrapped column are not drawn. Tabs are drawn as a single space, and other style
+/// settings are not supported.
+///
+/// Drawing returns the part of the text that doesn't fit into the bounding box.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{ColumnOrder, VerticalTextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(12, 45));
+///
+/// let mut display = MockDisplay::new();
+/// VerticalTextBox::new("EXIT\nRUN", bounds, character_style)
+///     .with_column_order(ColumnOrder::LeftToRight)
+///     .draw(&mut display)
+///     .unwrap();
+/// ```
+///
+/// [`ColumnOrder`]: enum.ColumnOrder.html
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Clone, Debug, Hash)]
+#[must_use]
+pub struct VerticalTextBox<'a, S> {
+    /// The text to be displayed.
+    pub text: &'a str,
+
+    /// The bounding box.
+    pub bounds: Rectangle,
+
+    /// The character style.
+    pub character_style: S,
+
+    /// The text box style.
+    pub style: TextBoxStyle,
+
+    /// The order of the columns.
+    pub column_order: ColumnOrder,
+}
+
+impl<'a, S> VerticalTextBox<'a, S>
+where
+    S: TextRenderer,
+{
+    /// Creates a new vertical text box.
+    #[inline]
+    pub fn new(text: &'a str, bounds: Rectangle, character_style: S) -> Self {
+        Self::with_textbox_style(text, bounds, character_style, TextBoxStyle::default())
+    }
+
+    /// Creates a new vertical text box with the given text box style.
+    #[inline]
+    pub fn with_textbox_style(
+        text: &'a str,
+        bounds: Rectangle,
+        character_style: S,
+        style: TextBoxStyle,
+    ) -> Self {
+        Self {
+            text,
+            bounds,
+            character_style,
+            style,
+            column_order: ColumnOrder::default(),
+        }
+    }
+
+    /// Sets the order of the columns.
+    #[inline]
+    pub fn with_column_order(mut self, column_order: ColumnOrder) -> Self {
+        self.column_order = column_order;
+        self
+    }
+
+    /// Returns the width of the widest character of the text.
+    fn column_width(&self) -> u32 {
+        let parser = Parser::<S::Color>::parse(self.text).with_style(&self.style);
+
+        let mut width = 0;
+        for token in parser {
+            if let Token::Word(word) = token {
+                for (_, grapheme) in graphemes(word) {
+                    width = width.max(str_width(&self.character_style, grapheme));
+                }
+            }
+        }
+
+        width
+    }
+}
+
+impl<S> Dimensions for VerticalTextBox<'_, S> {
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+/// Tracks the position of the next character.
+struct ColumnWriter<'s, S> {
+    style: &'s S,
+    bounds: Rectangle,
+    column_order: ColumnOrder,
+    column_width: u32,
+    column_pitch: i32,
+    row_height: i32,
+    column: i32,
+    y: i32,
+}
+
+impl<S> ColumnWriter<'_, S>
+where
+    S: TextRenderer,
+{
+    /// Returns the horizontal position of the current column.
+    fn x(&self) -> i32 {
+        let offset = self.column * self.column_pitch;
+        match self.column_order {
+            ColumnOrder::LeftToRight => self.bounds.top_left.x + offset,
+            ColumnOrder::RightToLeft => {
+                self.bounds.top_left.x + self.bounds.size.width.saturating_as::<i32>()
+                    - self.column_width.saturating_as::<i32>()
+                    - offset
+            }
+        }
+    }
+
+    /// Returns whether the current column fits into the bounding box.
+    fn column_fits(&self) -> bool {
+        let x = self.x() - self.bounds.top_left.x;
+        x >= 0
+            && x + self.column_width.saturating_as::<i32>()
+                <= self.bounds.size.width.saturating_as::<i32>()
+    }
+
+    /// Returns whether the current cell fits into the column.
+    fn cell_fits(&self) -> bool {
+        self.y + self.row_height
+            <= self.bounds.top_left.y + self.bounds.size.height.saturating_as::<i32>()
+    }
+
+    /// Leaves `n` empty cells, unless the current cell is at the top of its column.
+    fn skip_cells(&mut self, n: u32) {
+        if self.y != self.bounds.top_left.y {
+            self.y += self.row_height * n.saturating_as::<i32>();
+        }
+    }
+
+    /// Moves to the top of the next column. Returns `false` if the column doesn't fit into the
+    /// bounding box.
+    fn new_column(&mut self) -> bool {
+        self.column += 1;
+        self.y = self.bounds.top_left.y;
+        self.column_fits() && self.cell_fits()
+    }
+
+    /// Moves to the next column if the current column is full. Returns `false` if there is no
+    /// room for the next character.
+    fn make_room(&mut self) -> bool {
+        self.cell_fits() || self.new_column()
+    }
+
+    fn draw_char<D>(&mut self, grapheme: &str, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = S::Color>,
+    {
+        let width = str_width(self.style, grapheme);
+        let x = self.x() + (self.column_width.saturating_sub(width) / 2).saturating_as::<i32>();
+
+        self.style
+            .draw_string(grapheme, Point::new(x, self.y), Baseline::Top, display)?;
+        self.y += self.row_height;
+
+        Ok(())
+    }
+}
+
+impl<'a, S> Drawable for VerticalTextBox<'a, S>
+where
+    S: TextRenderer,
+{
+    type Color = S::Color;
+    type Output = &'a str;
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<&'a str, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut display = display.clipped(&self.bounds);
+
+        let column_width = self.column_width();
+        let mut writer = ColumnWriter {
+            style: &self.character_style,
+            bounds: self.bounds,
+            column_order: self.column_order,
+            column_width,
+            column_pitch: self
+                .style
+                .line_height
+                .to_absolute(column_width)
+                .saturating_as::<i32>()
+                .max(1),
+            row_height: self.character_style.line_height().saturating_as(),
+            column: 0,
+            y: self.bounds.top_left.y,
+        };
+
+        if !writer.column_fits() || !writer.cell_fits() {
+            return Ok(self.text);
+        }
+
+        let mut parser = Parser::<S::Color>::parse(self.text).with_style(&self.style);
+        loop {
+            let start = self.text.len() - parser.as_str().len();
+            let token = match parser.next() {
+                Some(token) => token,
+                None => return Ok(""),
+            };
+
+            match token {
+                Token::Word(word) => {
+                    // Words generated by the parser, like control pictures, are not split.
+                    let word_start = offset_in(self.text, word);
+                    for (idx, grapheme) in graphemes(word) {
+                        if !writer.make_room() {
+                            let offset = word_start.map_or(start, |word_start| word_start + idx);
+                            return Ok(&self.text[offset..]);
+                        }
+                        writer.draw_char(grapheme, &mut display)?;
+                    }
+                }
+
+                Token::Whitespace(n, _) => writer.skip_cells(n),
+                Token::Tab => writer.skip_cells(1),
+
+                Token::NewLine if !writer.new_column() => {
+                    return Ok(&self.text[self.text.len() - parser.as_str().len()..]);
+                }
+
+                Token::CarriageReturn => writer.y = self.bounds.top_left.y,
+
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+        text::{Baseline, Text},
+    };
+
+    use crate::{utils::test::size_for, ColumnOrder, VerticalTextBox};
+
+    #[test]
+    fn characters_flow_in_columns() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 2));
+
+        for &(order, columns) in &[
+            (ColumnOrder::RightToLeft, [12, 6, 0]),
+            (ColumnOrder::LeftToRight, [0, 6, 12]),
+        ] {
+            let mut display = MockDisplay::new();
+            let rest = VerticalTextBox::new("ab\ncd efg", bounds, character_style)
+                .with_column_order(order)
+                .draw(&mut display)
+                .unwrap();
+            assert_eq!(rest, "g");
+
+            let mut expected = MockDisplay::new();
+            for &(c, column, row) in &[
+                ("a", 0, 0),
+                ("b", 0, 1),
+                ("c", 1, 0),
+                ("d", 1, 1),
+                ("e", 2, 0),
+                ("f", 2, 1),
+            ] {
+                let position = Point::new(columns[column], row * 9);
+                Text::with_baseline(c, position, character_style, Baseline::Top)
+                    .draw(&mut expected)
+                    .unwrap();
+            }
+
+            display.assert_eq(&expected);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Rolling counter animation.
+use core::{iter::Peekable, str::CharIndices};
+
+use crate::{
+    plugin::{NoPlugin, PluginMarker as Plugin},
+    rendering::layout::{LaidOutLine, LineLayout},
+    utils::str_width,
+    TextBox,
+};
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Point, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline,
+    },
+    Drawable,
+};
+
+/// A text box that animates changed characters by rolling them vertically.
+///
+/// `RollingCounter` draws the text of the wrapped [`TextBox`]. Characters that differ from the
+/// `previous` text slide upwards inside their character cells: the old character leaves the cell
+/// at the top while the new one enters from the bottom. Unchanged characters are drawn in place.
+///
+/// The animation is split into `frames` steps. Advance it by calling [`next_frame`] and drawing
+/// the counter again, until [`is_finished`] returns `true`.
+///
+/// Characters are positioned by the text box layout, so alignment and wrapping options apply.
+/// The counter is intended for short, single style texts like numbers and clocks: escape sequences
+/// and whitespace stretching are not taken into account when placing the characters.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{widget::RollingCounter, TextBox};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(30, 9));
+///
+/// let mut counter = RollingCounter::new("12:30", TextBox::new("12:31", bounds, character_style), 3);
+///
+/// while !counter.is_finished() {
+///     counter.next_frame();
+///
+///     let mut display = MockDisplay::new();
+///     counter.draw(&mut display).unwrap();
+/// }
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+/// [`next_frame`]: #method.next_frame
+/// [`is_finished`]: #method.is_finished
+#[derive(Clone)]
+#[must_use]
+pub struct RollingCounter<'a, S, M = NoPlugin<<S as TextRenderer>::Color>>
+where
+    S: TextRenderer,
+{
+    /// The text the animation starts from.
+    pub previous: &'a str,
+
+    /// The text box that holds the new text and the layout information.
+    pub text_box: TextBox<'a, S, M>,
+
+    /// The current frame of the animation.
+    pub frame: u32,
+
+    /// The number of frames the animation takes.
+    pub frames: u32,
+}
+
+impl<'a, S, M> RollingCounter<'a, S, M>
+where
+    S: TextRenderer,
+{
+    /// Creates a new rolling counter that animates from `previous` to the text of `text_box`
+    /// in `frames` steps.
+    #[inline]
+    pub fn new(previous: &'a str, text_box: TextBox<'a, S, M>, frames: u32) -> Self {
+        Self {
+            previous,
+            text_box,
+            frame: 0,
+            frames,
+        }
+    }
+
+    /// Advances the animation by one frame.
+    #[inline]
+    pub fn next_frame(&mut self) -> &mut Self {
+        self.frame = self.frame.saturating_add(1).min(self.frames);
+        self
+    }
+
+    /// Returns whether the animation has reached its last frame.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    fn offset(&self, line_height: u32) -> i32 {
+        if self.is_finished() {
+            line_height.saturating_as()
+        } else {
+            (line_height * self.frame / self.frames).saturating_as()
+        }
+    }
+}
+
+/// Iterates over the printable characters of a line and their positions.
+struct Cells<'a, 'b, S> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    style: &'b S,
+    origin: Point,
+    x: u32,
+}
+
+impl<'a, 'b, S> Cells<'a, 'b, S> {
+    fn new(line: LaidOutLine<'a>, style: &'b S) -> Self {
+        Self {
+            text: line.text,
+            chars: line.text.char_indices(),
+            style,
+            origin: line.position,
+            x: 0,
+        }
+    }
+}
+
+impl<'a, 'b, S> Iterator for Cells<'a, 'b, S>
+where
+    S: TextRenderer,
+{
+    type Item = (Point, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, c) = self.chars.next()?;
+            if c.is_control() {
+                continue;
+            }
+
+            let st = &self.text[idx..idx + c.len_utf8()];
+            let position = self.origin + Point::new(self.x.saturating_as(), 0);
+            self.x += str_width(self.style, st);
+
+            return Some((position, st));
+        }
+    }
+}
+
+/// A previous and current character at the same position. Either may be missing.
+type CellPair<'a> = (Option<(Point, &'a str)>, Option<(Point, &'a str)>);
+
+fn next_pair<'a, I>(previous: &mut Peekable<I>, current: &mut Peekable<I>) -> Option<CellPair<'a>>
+where
+    I: Iterator<Item = (Point, &'a str)>,
+{
+    match (previous.peek(), current.peek()) {
+        (None, None) => None,
+        (Some((p, _)), Some((c, _))) if p.x == c.x => Some((previous.next(), current.next())),
+        (Some((p, _)), Some((c, _))) if p.x < c.x => Some((previous.next(), None)),
+        (Some(_), None) => Some((previous.next(), None)),
+        _ => Some((None, current.next())),
+    }
+}
+
+impl<'a, F, M> Drawable for RollingCounter<'a, F, M>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb888>,
+    M: Plugin<'a, <F as TextRenderer>::Color> + Plugin<'a, <F as CharacterStyle>::Color>,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let style = &self.text_box.character_style;
+        let line_height = style.line_height();
+        let offset = self.offset(line_height);
+
+        let mut display = display.clipped(&self.text_box.bounds);
+
+        let mut previous_lines = LineLayout::new(&self.text_box, self.previous);
+        let mut current_lines = LineLayout::new(&self.text_box, self.text_box.text);
+
+        loop {
+            let (previous_line, current_line) = (previous_lines.next(), current_lines.next());
+            if previous_line.is_none() && current_line.is_none() {
+                return Ok(());
+            }
+
+            let mut previous = previous_line
+                .map(|line| Cells::new(line, style))
+                .into_iter()
+                .flatten()
+                .peekable();
+            let mut current = current_line
+                .map(|line| Cells::new(line, style))
+                .into_iter()
+                .flatten()
+                .peekable();
+
+            while let Some(pair) = next_pair(&mut previous, &mut current) {
+                match pair {
+                    (Some((_, old)), Some((position, new))) if old == new => {
+                        style.draw_string(new, position, Baseline::Top, &mut display)?;
+                    }
+
+                    (old, new) => {
+                        let position = new.or(old).map(|(position, _)| position).unwrap();
+                        let width = old
+                            .map(|(_, st)| str_width(style, st))
+                            .max(new.map(|(_, st)| str_width(style, st)))
+                            .unwrap_or(0);
+
+                        let mut cell = display
+                            .clipped(&Rectangle::new(position, Size::new(width, line_height)));
+
+                        if let Some((position, old)) = old {
+                            let position = position - Point::new(0, offset);
+                            style.draw_string(old, position, Baseline::Top, &mut cell)?;
+                        }
+
+                        if let Some((position, new)) = new {
+                            let position = position
+                                + Point::new(0, line_height.saturating_as::<i32>() - offset);
+                            style.draw_string(new, position, Baseline::Top, &mut cell)?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{utils::test::size_for, widget::RollingCounter, TextBox};
+
+    #[track_caller]
+    fn assert_frame(previous: &str, current: &str, frame: u32, pattern: &[&str]) {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let mut counter = RollingCounter::new(
+            previous,
+            TextBox::new(
+                current,
+                Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+                character_style,
+            ),
+            3,
+        );
+        counter.frame = frame;
+
+        let mut display = MockDisplay::new();
+        counter.draw(&mut display).unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn first_frame_shows_previous_text() {
+        assert_frame(
+            "11",
+            "12",
+            0,
+            &[
+                "            ",
+                "  #     #   ",
+                " ##    ##   ",
+                "  #     #   ",
+                "  #     #   ",
+                "  #     #   ",
+                " ###   ###  ",
+            ],
+        );
+    }
+
+    #[test]
+    fn changed_characters_roll() {
+        assert_frame(
+            "11",
+            "12",
+            1,
+            &[
+                "        #   ",
+                "  #     #   ",
+                " ##     #   ",
+                "  #    ###  ",
+                "  #         ",
+                "  #         ",
+                " ###        ",
+                "        ##  ",
+                "       #  # ",
+            ],
+        );
+    }
+
+    #[test]
+    fn last_frame_shows_current_text() {
+        assert_frame(
+            "11",
+            "12",
+            3,
+            &[
+                "            ",
+                "  #     ##  ",
+                " ##    #  # ",
+                "  #       # ",
+                "  #      #  ",
+                "  #     #   ",
+                " ###   #### ",
+            ],
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Single line label with cell based partial redraws.
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    mono_font::MonoTextStyle,
+    prelude::PixelColor,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+/// Marks a cell whose displayed contents are unknown.
+const INVALID_CELL: char = '\0';
+
+/// A single line label for short, frequently changing texts.
+///
+/// `Label` is a fast path for texts like clocks, counters and sensor readouts, that are updated
+/// every frame. Because monospaced fonts place every character in a cell of the same size, the
+/// label can compute the position of each character directly and skip the general text box
+/// layout machinery. The label remembers the displayed characters in a caller-provided buffer and
+/// [`update`] only redraws the cells that changed.
+///
+/// The length of the buffer determines the number of cells. Text that does not fit is truncated,
+/// and cells beyond the end of a shorter text are cleared by drawing spaces.
+///
+/// *Note:* set a background color in the character style. Without a background color, changed
+/// cells are not cleared before the new character is drawn.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::widget::Label;
+///
+/// let character_style = MonoTextStyleBuilder::new()
+///     .font(&FONT_6X9)
+///     .text_color(BinaryColor::On)
+///     .background_color(BinaryColor::Off)
+///     .build();
+///
+/// let mut cells = ['\0'; 5];
+/// let mut label = Label::new(Point::zero(), character_style, &mut cells);
+///
+/// let mut display = MockDisplay::new();
+/// display.set_allow_overdraw(true);
+///
+/// // The first update draws every cell.
+/// label.update("12:30", &mut display).unwrap();
+///
+/// // Later updates only redraw the last cell.
+/// label.update("12:31", &mut display).unwrap();
+/// ```
+///
+/// [`update`]: #method.update
+#[derive(Debug)]
+pub struct Label<'a, 'b, C>
+where
+    C: PixelColor,
+{
+    position: Point,
+    character_style: MonoTextStyle<'a, C>,
+    cells: &'b mut [char],
+    advance: u32,
+}
+
+impl<'a, 'b, C> Label<'a, 'b, C>
+where
+    C: PixelColor,
+{
+    /// Creates a new label at the given position.
+    ///
+    /// The label has as many cells as the length of `cells`.
+    #[inline]
+    pub fn new(
+        position: Point,
+        character_style: MonoTextStyle<'a, C>,
+        cells: &'b mut [char],
+    ) -> Self {
+        let advance =
+            character_style.font.character_size.width + character_style.font.character_spacing;
+
+        let mut label = Self {
+            position,
+            character_style,
+            cells,
+            advance,
+        };
+        label.invalidate();
+
+        label
+    }
+
+    /// Returns the bounding box of the cell at the given index.
+    #[inline]
+    pub fn cell(&self, index: usize) -> Rectangle {
+        Rectangle::new(
+            self.position + Point::new((index as u32 * self.advance).saturating_as(), 0),
+            Size::new(
+                self.character_style.font.character_size.width,
+                self.character_style.line_height(),
+            ),
+        )
+    }
+
+    /// Returns the bounding box of the whole label.
+    #[inline]
+    pub fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(
+            self.position,
+            Size::new(
+                self.cells.len() as u32 * self.advance,
+                self.character_style.line_height(),
+            ),
+        )
+    }
+
+    /// Marks every cell as changed, so the next [`update`] redraws the whole label.
+    ///
+    /// [`update`]: #method.update
+    #[inline]
+    pub fn invalidate(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = INVALID_CELL;
+        }
+    }
+
+    /// Displays `text`, redrawing only the cells whose character changed.
+    #[inline]
+    pub fn update<D>(&mut self, text: &str, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let mut chars = text.chars();
+
+        for index in 0..self.cells.len() {
+            let c = chars.next().unwrap_or(' ');
+            if self.cells[index] == c {
+                continue;
+            }
+
+            let mut buffer = [0; 4];
+            let position = self.cell(index).top_left;
+            self.character_style.draw_string(
+                c.encode_utf8(&mut buffer),
+                position,
+                Baseline::Top,
+                display,
+            )?;
+
+            self.cells[index] = c;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+    };
+
+    use crate::widget::Label;
+
+    #[test]
+    fn only_changed_cells_are_redrawn() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let mut cells = ['\0'; 2];
+        let mut label = Label::new(Point::zero(), character_style, &mut cells);
+
+        let mut display = MockDisplay::new();
+        label.update("11", &mut display).unwrap();
+
+        let mut display = MockDisplay::new();
+        label.update("12", &mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      ......",
+            "      ..##..",
+            "      .#..#.",
+            "      ....#.",
+            "      ...#..",
+            "      ..#...",
+            "      .####.",
+            "      ......",
+            "      ......",
+        ]);
+    }
+
+    #[test]
+    fn shorter_text_clears_cells() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let mut cells = ['\0'; 2];
+        let mut label = Label::new(Point::zero(), character_style, &mut cells);
+
+        let mut display = MockDisplay::new();
+        label.update("11", &mut display).unwrap();
+
+        let mut display = MockDisplay::new();
+        label.update("1", &mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ......",
+        ]);
+    }
+
+    #[test]
+    fn invalidate_redraws_everything() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let mut cells = ['\0'; 1];
+        let mut label = Label::new(Point::zero(), character_style, &mut cells);
+
+        let mut display = MockDisplay::new();
+        label.update("1", &mut display).unwrap();
+
+        label.invalidate();
+
+        let mut display = MockDisplay::new();
+        label.update("1", &mut display).unwrap();
+        assert_eq!(display.affected_area(), label.bounding_box());
+    }
+}
@@ -0,0 +1,13 @@
+//! Ready-made components built on top of the text box layout engine.
+//!
+//! The components in this module cover common use cases where a plain [`TextBox`] is either too
+//! heavy or lacks a needed feature, like animating changes of the displayed text.
+//!
+//! [`TextBox`]: ../struct.TextBox.html
+mod counter;
+mod label;
+mod table;
+
+pub use counter::RollingCounter;
+pub use label::Label;
+pub use table::TextTable;
@@ -0,0 +1,288 @@
+//! Tables of text.
+use az::SaturatingAs;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+use crate::{
+    style::{HeightMode, Insets, TextBoxStyle},
+    TextBox,
+};
+
+/// A table of text cells, arranged in rows and columns.
+///
+/// Every cell is drawn as a [`TextBox`] that is as wide as its column and as high as the highest
+/// cell of its row, so long texts wrap inside their cell and make the row taller. The text box
+/// style applies to every cell, except that the height of the cells is not changed by the height
+/// mode. Cells are kept away from their edges by the padding.
+///
+/// Rows may have fewer cells than there are columns, the missing cells are left empty. Cells that
+/// don't have a column are not drawn.
+///
+/// If separators are enabled, 1 pixel wide lines are drawn between the rows and the columns.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::{BinaryColor, Rgb888},
+///     prelude::*,
+/// };
+/// use embedded_text::{style::Insets, widget::TextTable};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+///
+/// let rows: [&[&str]; 2] = [&["Temp", "21C"], &["Hum.", "40%"]];
+/// let column_widths = [30, 24];
+///
+/// let table = TextTable::new(&rows, &column_widths, Point::zero(), character_style)
+///     .with_padding(Insets::new(0, 0, 0, 1))
+///     .with_separators(Rgb888::WHITE);
+///
+/// let mut display = MockDisplay::new();
+/// table.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct TextTable<'a, S> {
+    /// The cells of the table, row by row.
+    pub rows: &'a [&'a [&'a str]],
+
+    /// The widths of the columns, in pixels.
+    pub column_widths: &'a [u32],
+
+    /// The top left corner of the table.
+    pub position: Point,
+
+    /// The character style.
+    pub character_style: S,
+
+    /// The text box style of the cells.
+    pub style: TextBoxStyle,
+
+    /// The padding inside every cell.
+    pub padding: Insets,
+
+    /// The color of the separator lines. `None` means that no separators are drawn. The color is
+    /// converted to the color type of the character style.
+    pub separator_color: Option<Rgb888>,
+}
+
+impl<'a, S> TextTable<'a, S>
+where
+    S: TextRenderer,
+    S::Color: From<Rgb888>,
+{
+    /// Creates a new table without padding and separators.
+    #[inline]
+    pub fn new(
+        rows: &'a [&'a [&'a str]],
+        column_widths: &'a [u32],
+        position: Point,
+        character_style: S,
+    ) -> Self {
+        Self {
+            rows,
+            column_widths,
+            position,
+            character_style,
+            style: TextBoxStyle::default(),
+            padding: Insets::default(),
+            separator_color: None,
+        }
+    }
+
+    /// Sets the text box style of the cells.
+    #[inline]
+    pub fn with_textbox_style(mut self, mut style: TextBoxStyle) -> Self {
+        style.height_mode = HeightMode::Exact(style.height_mode.overdraw());
+        self.style = style;
+        self
+    }
+
+    /// Sets the padding inside every cell.
+    #[inline]
+    pub fn with_padding(mut self, padding: Insets) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Draws separator lines between the rows and the columns.
+    #[inline]
+    pub fn with_separators(mut self, color: Rgb888) -> Self {
+        self.separator_color = Some(color);
+        self
+    }
+
+    /// Returns the width of the separator lines.
+    fn separator_width(&self) -> u32 {
+        match self.separator_color {
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    /// Returns the height of `row`, including the padding.
+    #[inline]
+    pub fn row_height(&self, row: &[&str]) -> u32 {
+        let text_width = |column_width: u32| column_width.saturating_sub(self.padding.horizontal());
+
+        self.column_widths
+            .iter()
+            .zip(row.iter())
+            .map(|(&width, cell)| {
+                self.style
+                    .measure_text_height(&self.character_style, cell, text_width(width))
+            })
+            .max()
+            .unwrap_or(0)
+            .saturating_add(self.padding.vertical())
+    }
+
+    /// Returns the sum of the sizes, and the separators between them.
+    fn total(&self, sizes: impl Iterator<Item = u32>) -> u32 {
+        sizes.enumerate().fold(0_u32, |total, (idx, size)| {
+            let separator = if idx == 0 { 0 } else { self.separator_width() };
+            total.saturating_add(separator).saturating_add(size)
+        })
+    }
+}
+
+impl<S> Dimensions for TextTable<'_, S>
+where
+    S: TextRenderer,
+    S::Color: From<Rgb888>,
+{
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        let width = self.total(self.column_widths.iter().copied());
+        let height = self.total(self.rows.iter().map(|row| self.row_height(row)));
+
+        Rectangle::new(self.position, Size::new(width, height))
+    }
+}
+
+impl<'a, S> Drawable for TextTable<'a, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    type Color = <S as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let separator = self.separator_width();
+        let table_width = self.bounding_box().size.width;
+
+        let mut y = self.position.y;
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            if row_idx > 0 {
+                if let Some(color) = self.separator_color {
+                    let line = Rectangle::new(
+                        Point::new(self.position.x, y),
+                        Size::new(table_width, separator),
+                    );
+                    display.fill_solid(&line, color.into())?;
+                }
+                y += separator.saturating_as::<i32>();
+            }
+
+            let height = self.row_height(row);
+
+            let mut x = self.position.x;
+            for (column_idx, &width) in self.column_widths.iter().enumerate() {
+                if column_idx > 0 {
+                    if let Some(color) = self.separator_color {
+                        let line = Rectangle::new(Point::new(x, y), Size::new(separator, height));
+                        display.fill_solid(&line, color.into())?;
+                    }
+                    x += separator.saturating_as::<i32>();
+                }
+
+                if let Some(cell) = row.get(column_idx) {
+                    let bounds = Rectangle::new(Point::new(x, y), Size::new(width, height));
+                    TextBox::with_textbox_style(
+                        cell,
+                        bounds,
+                        self.character_style.clone(),
+                        self.style,
+                    )
+                    .with_padding(self.padding)
+                    .draw(display)?;
+                }
+
+                x += width.saturating_as::<i32>();
+            }
+
+            y += height.saturating_as::<i32>();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{widget::TextTable, TextBox};
+
+    #[test]
+    fn cells_are_laid_out_in_rows_and_columns() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+
+        let rows: [&[&str]; 2] = [&["ab", "c"], &["d", "e f"]];
+        let table = TextTable::new(&rows, &[12, 6], Point::zero(), character_style)
+            .with_separators(Rgb888::WHITE);
+
+        assert_eq!(
+            table.bounding_box(),
+            Rectangle::new(Point::zero(), Size::new(19, 28))
+        );
+
+        let mut display = MockDisplay::new();
+        table.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        for &(text, top_left, size) in &[
+            ("ab", Point::new(0, 0), Size::new(12, 9)),
+            ("c", Point::new(13, 0), Size::new(6, 9)),
+            ("d", Point::new(0, 10), Size::new(12, 18)),
+            ("e\nf", Point::new(13, 10), Size::new(6, 18)),
+        ] {
+            TextBox::new(text, Rectangle::new(top_left, size), character_style)
+                .draw(&mut expected)
+                .unwrap();
+        }
+        for &(top_left, size) in &[
+            (Point::new(12, 0), Size::new(1, 9)),
+            (Point::new(0, 9), Size::new(19, 1)),
+            (Point::new(12, 10), Size::new(1, 18)),
+        ] {
+            expected
+                .fill_solid(&Rectangle::new(top_left, size), BinaryColor::On)
+                .unwrap();
+        }
+
+        display.assert_eq(&expected);
+    }
+}
@@ -0,0 +1,190 @@
+//! Text box that formats its text into a buffer.
+
+use core::fmt::{self, Write};
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+use crate::{style::TextBoxStyle, utils::copy_truncated, TextBox};
+
+/// A text box that can be written to using the `write!` macro.
+///
+/// Displaying formatted values, like sensor readings, requires formatting them into a string
+/// first. `TextBoxWriter` implements `core::fmt::Write` by storing the written text in a
+/// caller-provided byte buffer, and draws the stored text as a [`TextBox`], so no string type
+/// from an other crate is needed.
+///
+/// Text that doesn't fit into the buffer is truncated at a character boundary, and the write
+/// returns `fmt::Error`. The text that was already stored is still drawn.
+///
+/// # Example
+///
+/// ```rust
+/// use core::fmt::Write;
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+///     primitives::Rectangle,
+/// };
+/// use embedded_text::{style::TextBoxStyle, TextBoxWriter};
+///
+/// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+/// let bounds = Rectangle::new(Point::zero(), Size::new(60, 9));
+///
+/// let mut buffer = [0; 16];
+/// let mut writer =
+///     TextBoxWriter::new(&mut buffer, bounds, character_style, TextBoxStyle::default());
+///
+/// write!(writer, "Temp: {:.1}C", 21.53).unwrap();
+/// assert_eq!(writer.text(), "Temp: 21.5C");
+///
+/// let mut display = MockDisplay::new();
+/// writer.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: struct.TextBox.html
+#[derive(Debug)]
+pub struct TextBoxWriter<'b, S> {
+    buffer: &'b mut [u8],
+    len: usize,
+    bounds: Rectangle,
+    character_style: S,
+    style: TextBoxStyle,
+}
+
+impl<'b, S> TextBoxWriter<'b, S> {
+    /// Creates a new, empty writer that stores its text in `buffer`.
+    ///
+    /// The length of `buffer` limits the length of the text in bytes.
+    #[inline]
+    pub fn new(
+        buffer: &'b mut [u8],
+        bounds: Rectangle,
+        character_style: S,
+        style: TextBoxStyle,
+    ) -> Self {
+        Self {
+            buffer,
+            len: 0,
+            bounds,
+            character_style,
+            style,
+        }
+    }
+
+    /// Returns the text that was written since the last [`clear`].
+    ///
+    /// [`clear`]: #method.clear
+    #[inline]
+    pub fn text(&self) -> &str {
+        // Only whole characters are copied into the buffer.
+        core::str::from_utf8(&self.buffer[..self.len]).unwrap_or_default()
+    }
+
+    /// Removes the stored text.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<'b, S> TextBoxWriter<'b, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    /// Returns a text box that displays the stored text.
+    #[inline]
+    pub fn text_box(&self) -> TextBox<'_, S> {
+        TextBox::with_textbox_style(
+            self.text(),
+            self.bounds,
+            self.character_style.clone(),
+            self.style,
+        )
+    }
+}
+
+impl<'b, S> Write for TextBoxWriter<'b, S> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = copy_truncated(&mut self.buffer[self.len..], s);
+        self.len += len;
+
+        if len == s.len() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+impl<'b, S> Drawable for TextBoxWriter<'b, S>
+where
+    S: TextRenderer<Color = <S as CharacterStyle>::Color> + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb888>,
+{
+    type Color = <S as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.text_box().draw(display).map(drop)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::fmt::Write;
+
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{style::TextBoxStyle, utils::test::size_for, TextBox, TextBoxWriter};
+
+    #[test]
+    fn text_that_doesnt_fit_is_truncated() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 1));
+
+        let mut buffer = [0; 5];
+        let mut writer = TextBoxWriter::new(
+            &mut buffer,
+            bounds,
+            character_style,
+            TextBoxStyle::default(),
+        );
+
+        assert!(write!(writer, "{}°", 1234).is_err());
+        assert_eq!(writer.text(), "1234");
+
+        writer.clear();
+        assert!(write!(writer, "{}°", 12).is_ok());
+        assert_eq!(writer.text(), "12°");
+
+        let mut display = MockDisplay::new();
+        writer.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("12°", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}